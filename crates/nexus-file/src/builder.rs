@@ -0,0 +1,114 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+use nexus::ErrorId;
+
+use crate::error::FileError;
+use crate::recover;
+use crate::store::FileStore;
+
+/// Size past which the active segment is sealed and a new one started,
+/// unless [`segment_size`](FileStoreBuilder::segment_size) says otherwise.
+pub const DEFAULT_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The file in the store directory an open store holds locked.
+const LOCK_FILE: &str = "LOCK";
+
+/// When appends are synced to disk.
+///
+/// Either way a crash of the *process* never loses an acknowledged append —
+/// the bytes are in the OS page cache. The difference is a power loss or OS
+/// crash, after which recovery truncates whatever did not reach the disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FsyncPolicy {
+    /// Sync before every append returns: an acknowledged append survives
+    /// power loss.
+    #[default]
+    Always,
+    /// Sync after every `n`th append. A power loss may drop up to the last
+    /// `n - 1` acknowledged appends.
+    EveryAppends(NonZeroU32),
+    /// Leave syncing to the OS and to [`FileStore::sync`].
+    Never,
+}
+
+/// Builder for [`FileStore`].
+///
+/// ```ignore
+/// let store = FileStore::builder("/var/lib/app/events")
+///     .segment_size(16 * 1024 * 1024)
+///     .fsync(FsyncPolicy::EveryAppends(NonZeroU32::new(32).unwrap()))
+///     .open()?;
+/// ```
+///
+/// Opening creates the directory if absent, locks it, repairs a torn tail
+/// left by a crash, and rebuilds the stream index from the segments.
+#[derive(Debug, Clone)]
+pub struct FileStoreBuilder {
+    dir: PathBuf,
+    segment_size: u64,
+    fsync: FsyncPolicy,
+}
+
+impl FileStoreBuilder {
+    /// Create a new builder targeting the given directory.
+    pub(crate) fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            segment_size: DEFAULT_SEGMENT_SIZE,
+            fsync: FsyncPolicy::default(),
+        }
+    }
+
+    /// Seal the active segment once an append would take it past `bytes`.
+    /// An append is never split across segments, so one larger than this
+    /// gets a segment of its own. Defaults to [`DEFAULT_SEGMENT_SIZE`].
+    #[must_use]
+    pub const fn segment_size(mut self, bytes: u64) -> Self {
+        self.segment_size = bytes;
+        self
+    }
+
+    /// When appends reach the disk. Defaults to [`FsyncPolicy::Always`].
+    #[must_use]
+    pub const fn fsync(mut self, policy: FsyncPolicy) -> Self {
+        self.fsync = policy;
+        self
+    }
+
+    /// Open (or create) the store directory and recover its log.
+    ///
+    /// Blocks on file I/O — call it at startup, or from `spawn_blocking`.
+    ///
+    /// # Errors
+    ///
+    /// [`FileError::Locked`] if another store has the directory open;
+    /// [`FileError::Corrupt`] if a sealed segment is damaged or a stream's
+    /// versions are out of sequence; [`FileError::Io`] for anything the
+    /// filesystem refuses.
+    pub fn open(self) -> Result<FileStore, FileError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let lock = lock_dir(&self.dir)?;
+        let recovered = recover::recover(&self.dir)?;
+        FileStore::assemble(self.dir, lock, recovered, self.segment_size, self.fsync)
+    }
+}
+
+/// Take the directory's exclusive lock: two stores appending to one log
+/// would interleave their records.
+fn lock_dir(dir: &Path) -> Result<File, FileError> {
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(LOCK_FILE))?;
+    match lock.try_lock() {
+        Ok(()) => Ok(lock),
+        Err(TryLockError::WouldBlock) => Err(FileError::Locked {
+            dir: ErrorId::from_display(&dir.display()),
+        }),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
@@ -0,0 +1,95 @@
+use nexus::ErrorId;
+use nexus_store::envelope::EnvelopeError;
+
+/// Errors produced by the segmented file event store adapter.
+///
+/// Distinct failure domains (CLAUDE rule 3); diagnostic fields use
+/// [`ErrorId`] to stay allocation-light on error paths.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum FileError {
+    /// A filesystem read, write, sync, or directory error.
+    #[error("file store i/o error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A record that must be intact is not: a bad checksum, a truncated or
+    /// malformed record in a sealed segment, or a version out of sequence.
+    ///
+    /// Only the tail of the active segment is repaired on open — a torn
+    /// write there is expected after a crash. Damage anywhere else is
+    /// reported, never silently dropped.
+    #[error("corrupt record in segment {segment} at offset {offset}: {reason}")]
+    Corrupt {
+        segment: u64,
+        offset: u64,
+        reason: ErrorId<128>,
+    },
+
+    /// A persisted frame decoded into bytes that fail envelope validation.
+    #[error("envelope integrity error in stream '{stream_id}' at version {version}")]
+    EnvelopeCorrupt {
+        stream_id: ErrorId,
+        version: u64,
+        #[source]
+        source: EnvelopeError,
+    },
+
+    /// Invalid input on the write path (e.g. a stream id too long for the
+    /// record header). Fires before anything is written.
+    #[error("invalid input for stream '{stream_id}' at version {version}: {reason}")]
+    InvalidInput {
+        stream_id: ErrorId,
+        version: u64,
+        reason: ErrorId<128>,
+    },
+
+    /// Building the canonical wire frame for an envelope failed.
+    #[error("wire frame build failed in stream '{stream_id}' at version {version}: {reason}")]
+    Frame {
+        stream_id: ErrorId,
+        version: u64,
+        reason: ErrorId<128>,
+    },
+
+    /// Another store — in this process or another — holds the directory.
+    #[error("store directory '{dir}' is locked by another store")]
+    Locked { dir: ErrorId<128> },
+
+    /// An earlier append failed and could not be cut back off the active
+    /// segment, or a new segment could not be made durable, so no further
+    /// append is accepted. Reopening the store recovers the log from what
+    /// reached the disk.
+    #[error(
+        "an earlier write failure left the log unsafe to append to; reopen the store to recover"
+    )]
+    Poisoned,
+
+    /// The blocking task running file I/O panicked or was cancelled.
+    #[error("file store worker task failed: {0}")]
+    Worker(#[source] tokio::task::JoinError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify `FileError` satisfies the bounds required by `RawEventStore::Error`.
+    #[test]
+    fn satisfies_error_send_sync_static() {
+        fn assert_bounds<T: std::error::Error + Send + Sync + 'static>() {}
+        assert_bounds::<FileError>();
+    }
+
+    #[test]
+    fn corrupt_display_names_segment_and_offset() {
+        let err = FileError::Corrupt {
+            segment: 3,
+            offset: 4096,
+            reason: ErrorId::from_display(&"checksum mismatch"),
+        };
+        assert_eq!(
+            err.to_string(),
+            "corrupt record in segment 3 at offset 4096: checksum mismatch"
+        );
+    }
+}
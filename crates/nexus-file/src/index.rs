@@ -0,0 +1,160 @@
+//! The sparse per-stream index, held in memory and rebuilt from the
+//! segments on every open.
+//!
+//! For each stream it keeps the head version, the position of the last
+//! record, and a **mark** — `(version, position)` — every [`MARK_INTERVAL`]
+//! versions. A `read_stream` seeks to the nearest mark at or before its start
+//! version and scans forward from there, skipping other streams' records, so
+//! the index stays a small fraction of the log while no read starts more
+//! than one interval before its first event.
+//!
+//! The segments are the only source of truth: losing the index loses
+//! nothing, which is why it is never written to disk.
+
+use std::collections::HashMap;
+
+use crate::position::FilePos;
+
+/// Versions between two marks of one stream.
+pub const MARK_INTERVAL: u64 = 16;
+
+#[derive(Debug)]
+struct StreamEntry {
+    head: u64,
+    last: FilePos,
+    /// `(version, position)`, ascending; the first is always version 1.
+    marks: Vec<(u64, FilePos)>,
+}
+
+/// Where a `read_stream` starts scanning, and the last record it can match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seek {
+    pub start: FilePos,
+    pub last: FilePos,
+}
+
+#[derive(Debug, Default)]
+pub struct Index {
+    streams: HashMap<Vec<u8>, StreamEntry>,
+}
+
+impl Index {
+    /// The stream's head version; 0 for a stream with no events.
+    pub fn head(&self, id: &[u8]) -> u64 {
+        self.streams.get(id).map_or(0, |entry| entry.head)
+    }
+
+    /// Note that `version` of stream `id` was committed at `pos`.
+    ///
+    /// Returns `false`, changing nothing, unless `version` is the stream's
+    /// next one.
+    pub fn record(&mut self, id: &[u8], version: u64, pos: FilePos) -> bool {
+        if version != self.head(id).wrapping_add(1) {
+            return false;
+        }
+        let is_mark = (version - 1).is_multiple_of(MARK_INTERVAL);
+        match self.streams.get_mut(id) {
+            Some(entry) => {
+                entry.head = version;
+                entry.last = pos;
+                if is_mark {
+                    entry.marks.push((version, pos));
+                }
+            }
+            None => {
+                self.streams.insert(
+                    id.to_vec(),
+                    StreamEntry {
+                        head: version,
+                        last: pos,
+                        marks: vec![(version, pos)],
+                    },
+                );
+            }
+        }
+        true
+    }
+
+    /// Where to read stream `id` from version `from` on; `None` if it has
+    /// no event at or after `from`.
+    pub fn seek(&self, id: &[u8], from: u64) -> Option<Seek> {
+        let entry = self.streams.get(id)?;
+        if from > entry.head {
+            return None;
+        }
+        let after = entry.marks.partition_point(|(version, _)| *version <= from);
+        let (_, start) = entry.marks[after.saturating_sub(1)];
+        Some(Seek {
+            start,
+            last: entry.last,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(offset: u64) -> FilePos {
+        FilePos::new(1, offset)
+    }
+
+    fn index_with(versions: u64) -> Index {
+        let mut index = Index::default();
+        for v in 1..=versions {
+            assert!(index.record(b"s", v, pos(v * 100)));
+        }
+        index
+    }
+
+    #[test]
+    fn record_accepts_only_the_next_version() {
+        let mut index = index_with(2);
+        assert!(!index.record(b"s", 2, pos(999)));
+        assert!(!index.record(b"s", 4, pos(999)));
+        assert!(!index.record(b"t", 2, pos(999)));
+        assert_eq!(index.head(b"s"), 2);
+        assert_eq!(index.head(b"t"), 0);
+    }
+
+    #[test]
+    fn seek_starts_at_the_nearest_mark_at_or_before_from() {
+        let index = index_with(40);
+        let last = pos(4000);
+        assert_eq!(
+            index.seek(b"s", 1),
+            Some(Seek {
+                start: pos(100),
+                last
+            })
+        );
+        assert_eq!(
+            index.seek(b"s", 16),
+            Some(Seek {
+                start: pos(100),
+                last
+            })
+        );
+        assert_eq!(
+            index.seek(b"s", 17),
+            Some(Seek {
+                start: pos(1700),
+                last
+            })
+        );
+        assert_eq!(
+            index.seek(b"s", 40),
+            Some(Seek {
+                start: pos(3300),
+                last
+            })
+        );
+    }
+
+    #[test]
+    fn seek_past_the_head_or_into_an_unknown_stream_is_none() {
+        let index = index_with(3);
+        assert_eq!(index.seek(b"s", 4), None);
+        assert_eq!(index.seek(b"t", 1), None);
+    }
+}
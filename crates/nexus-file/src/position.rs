@@ -0,0 +1,67 @@
+//! `FilePos` — the file adapter's [`AllPosition`](nexus_store::AllPosition).
+//!
+//! A record's address in the log: the segment it lives in and its byte
+//! offset there. Segments are numbered in the order they are created and
+//! written strictly front to back, so ordering positions by
+//! `(segment, offset)` is exactly the order records were committed in — a
+//! gap-safe `$all` resume position with no counter to maintain.
+//!
+//! Positions are **monotonic but not dense**: consecutive records differ by
+//! their encoded length, and a roll to a new segment restarts the offset at
+//! 0. Consumers compare positions; they never do arithmetic on them.
+
+use core::fmt;
+
+/// The address of one record: `(segment, byte offset)`. See the module docs.
+///
+/// Field order is significant — the derived `Ord` compares `segment` first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FilePos {
+    segment: u64,
+    offset: u64,
+}
+
+impl FilePos {
+    /// A position from its parts.
+    #[must_use]
+    pub const fn new(segment: u64, offset: u64) -> Self {
+        Self { segment, offset }
+    }
+
+    /// The number of the segment holding the record.
+    #[must_use]
+    pub const fn segment(self) -> u64 {
+        self.segment
+    }
+
+    /// The record's byte offset within its segment.
+    #[must_use]
+    pub const fn offset(self) -> u64 {
+        self.offset
+    }
+}
+
+impl fmt::Display for FilePos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.segment, self.offset)
+    }
+}
+
+/// The file adapter's `$all` resume position is a record's address.
+impl nexus_store::AllPosition for FilePos {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_by_segment_before_offset() {
+        assert!(FilePos::new(1, 900) < FilePos::new(2, 0));
+        assert!(FilePos::new(2, 0) < FilePos::new(2, 40));
+    }
+
+    #[test]
+    fn displays_as_segment_colon_offset() {
+        assert_eq!(FilePos::new(7, 128).to_string(), "7:128");
+    }
+}
@@ -0,0 +1,187 @@
+//! The on-disk record — pure encode/decode, no I/O.
+//!
+//! Every event is one record, little-endian throughout:
+//!
+//! ```text
+//! [u32 body_len][u32 crc32c(body)]                      -- 8-byte header
+//! [u8 flags][u16 stream_id_len][u64 version]            -- body
+//! [stream_id bytes][frame bytes from wire::encode_frame]
+//! ```
+//!
+//! The frame runs to the end of the body, so its length is implied. The
+//! checksum covers the whole body: a record whose bytes do not match it was
+//! torn by a crash mid-write or damaged since.
+//!
+//! `flags` bit 0 marks the **last record of an append**. An append is one
+//! contiguous run of records inside one segment; recovery keeps a run only
+//! once it has seen that run's marked record, which makes multi-event
+//! appends all-or-nothing across a crash.
+
+/// Length of the `[body_len][crc]` record header.
+pub const HEADER_LEN: usize = 8;
+
+/// Length of the fixed body prefix: flags, stream id length, version.
+const BODY_PREFIX_LEN: usize = 11;
+
+/// `flags` bit set on the record that completes an append.
+const ENDS_APPEND: u8 = 0b1;
+
+/// Why a record could not be encoded or decoded. The caller attaches the
+/// stream or segment context its own error needs.
+pub type CodecError = &'static str;
+
+/// A decoded record body, borrowing from the buffer it was read into.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RecordBody<'a> {
+    pub ends_append: bool,
+    pub stream_id: &'a [u8],
+    pub version: u64,
+    pub frame: &'a [u8],
+}
+
+/// The `(body_len, crc)` pair from a record header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordHeader {
+    pub body_len: u32,
+    pub crc: u32,
+}
+
+impl RecordHeader {
+    /// Split a header into its two fields.
+    pub const fn parse(bytes: [u8; HEADER_LEN]) -> Self {
+        let [l0, l1, l2, l3, c0, c1, c2, c3] = bytes;
+        Self {
+            body_len: u32::from_le_bytes([l0, l1, l2, l3]),
+            crc: u32::from_le_bytes([c0, c1, c2, c3]),
+        }
+    }
+
+    /// Header plus body length — how far the record extends.
+    pub fn record_len(self) -> u64 {
+        u64::from(self.body_len) + 8
+    }
+}
+
+/// Append one encoded record to `buf`.
+///
+/// # Errors
+///
+/// The stream id is longer than `u16::MAX` bytes, or the body would not fit
+/// the `u32` length field.
+pub fn encode(
+    buf: &mut Vec<u8>,
+    ends_append: bool,
+    stream_id: &[u8],
+    version: u64,
+    frame: &[u8],
+) -> Result<(), CodecError> {
+    let id_len = u16::try_from(stream_id.len()).map_err(|_| "stream id exceeds u16::MAX bytes")?;
+    let body_len = BODY_PREFIX_LEN
+        .checked_add(stream_id.len())
+        .and_then(|n| n.checked_add(frame.len()))
+        .and_then(|n| u32::try_from(n).ok())
+        .ok_or("record exceeds u32::MAX bytes")?;
+
+    let header_at = buf.len();
+    buf.extend_from_slice(&body_len.to_le_bytes());
+    buf.extend_from_slice(&[0; 4]); // crc, filled in below
+    let body_at = buf.len();
+    buf.push(if ends_append { ENDS_APPEND } else { 0 });
+    buf.extend_from_slice(&id_len.to_le_bytes());
+    buf.extend_from_slice(&version.to_le_bytes());
+    buf.extend_from_slice(stream_id);
+    buf.extend_from_slice(frame);
+
+    let crc = crc32c::crc32c(&buf[body_at..]);
+    buf[header_at + 4..body_at].copy_from_slice(&crc.to_le_bytes());
+    Ok(())
+}
+
+/// Check `body` against its header checksum and split it into fields.
+///
+/// # Errors
+///
+/// The checksum does not match, or the body is too short for its own
+/// stream id length.
+pub fn decode(header: RecordHeader, body: &[u8]) -> Result<RecordBody<'_>, CodecError> {
+    if crc32c::crc32c(body) != header.crc {
+        return Err("checksum mismatch");
+    }
+    let (prefix, rest) = body
+        .split_first_chunk::<BODY_PREFIX_LEN>()
+        .ok_or("body shorter than its fixed prefix")?;
+    let [flags, i0, i1, v @ ..] = *prefix;
+    let id_len = usize::from(u16::from_le_bytes([i0, i1]));
+    if rest.len() < id_len {
+        return Err("stream id runs past the body");
+    }
+    let (stream_id, frame) = rest.split_at(id_len);
+    Ok(RecordBody {
+        ends_append: flags & ENDS_APPEND != 0,
+        stream_id,
+        version: u64::from_le_bytes(v),
+        frame,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    fn split(buf: &[u8]) -> (RecordHeader, &[u8]) {
+        let (header, body) = buf.split_first_chunk::<HEADER_LEN>().unwrap();
+        (RecordHeader::parse(*header), body)
+    }
+
+    #[test]
+    fn round_trips_every_field() {
+        let mut buf = Vec::new();
+        encode(&mut buf, true, b"order-7", 42, b"frame-bytes").unwrap();
+        let (header, body) = split(&buf);
+        assert_eq!(header.record_len(), u64::try_from(buf.len()).unwrap());
+        assert_eq!(
+            decode(header, body).unwrap(),
+            RecordBody {
+                ends_append: true,
+                stream_id: b"order-7",
+                version: 42,
+                frame: b"frame-bytes",
+            }
+        );
+    }
+
+    #[test]
+    fn records_concatenate_and_keep_their_own_flags() {
+        let mut buf = Vec::new();
+        encode(&mut buf, false, b"a", 1, b"x").unwrap();
+        let first_len = buf.len();
+        encode(&mut buf, true, b"a", 2, b"y").unwrap();
+        let (h1, rest) = split(&buf);
+        assert!(
+            !decode(h1, &rest[..first_len - HEADER_LEN])
+                .unwrap()
+                .ends_append
+        );
+        let (h2, body2) = split(&buf[first_len..]);
+        assert!(decode(h2, body2).unwrap().ends_append);
+    }
+
+    #[test]
+    fn a_flipped_bit_fails_the_checksum() {
+        let mut buf = Vec::new();
+        encode(&mut buf, true, b"a", 1, b"payload").unwrap();
+        let last = buf.len() - 1;
+        buf[last] ^= 0x01;
+        let (header, body) = split(&buf);
+        assert_eq!(decode(header, body), Err("checksum mismatch"));
+    }
+
+    #[test]
+    fn an_overlong_stream_id_is_refused() {
+        let mut buf = Vec::new();
+        let id = vec![b'x'; usize::from(u16::MAX) + 1];
+        assert!(encode(&mut buf, true, &id, 1, b"").is_err());
+        assert!(buf.is_empty(), "nothing written for a refused record");
+    }
+}
@@ -0,0 +1,113 @@
+//! Crash recovery: scan every segment on open, repair the active segment's
+//! tail, and rebuild the stream index.
+//!
+//! A crash can leave the active segment ending in a torn record (a short
+//! write, or bytes that never reached the disk) or in the first records of
+//! an append whose last record never landed. Both are cut off at the end of
+//! the last whole append, then the truncation is synced — the append was
+//! never acknowledged, so dropping it loses nothing a caller was promised.
+//!
+//! Sealed segments were synced before the next one was created, so the same
+//! damage there is not a crash artifact: it is reported as
+//! [`FileError::Corrupt`] and the store does not open.
+
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+use nexus::ErrorId;
+
+use crate::error::FileError;
+use crate::index::Index;
+use crate::position::FilePos;
+use crate::segment::{self, Next, Segment, SegmentReader};
+
+/// The log as recovery found it: every segment with its committed length
+/// (the last is the active one) and the rebuilt index.
+#[derive(Debug)]
+pub struct Recovered {
+    pub segments: Vec<Segment>,
+    pub index: Index,
+}
+
+/// Recover the log in `dir`, creating its first segment if it has none.
+pub fn recover(dir: &Path) -> Result<Recovered, FileError> {
+    let mut ids = segment::list(dir)?;
+    if ids.is_empty() {
+        File::create_new(segment::path(dir, 1))?;
+        segment::sync_dir(dir)?;
+        ids.push(1);
+    }
+
+    let mut index = Index::default();
+    let mut segments = Vec::with_capacity(ids.len());
+    let last = ids.len() - 1;
+    for (i, id) in ids.into_iter().enumerate() {
+        let len = scan(dir, id, i == last, &mut index)?;
+        segments.push(Segment { id, len });
+    }
+    Ok(Recovered { segments, index })
+}
+
+/// One record of an append not yet known to be whole.
+struct Staged {
+    stream_id: Vec<u8>,
+    version: u64,
+    pos: FilePos,
+}
+
+/// Index segment `id`'s whole appends and return its committed length,
+/// truncating anything after it when `active`.
+fn scan(dir: &Path, id: u64, active: bool, index: &mut Index) -> Result<u64, FileError> {
+    let len = std::fs::metadata(segment::path(dir, id))?.len();
+    let mut reader = SegmentReader::open(dir, id, 0, len)?;
+    let mut staged: Vec<Staged> = Vec::new();
+    let mut committed = 0;
+    let damage = loop {
+        match reader.next()? {
+            Next::End => break "append cut short",
+            Next::Torn { reason, .. } => break reason,
+            Next::Record { pos, end, body } => {
+                staged.push(Staged {
+                    stream_id: body.stream_id.to_vec(),
+                    version: body.version,
+                    pos,
+                });
+                if body.ends_append {
+                    commit(index, std::mem::take(&mut staged))?;
+                    committed = end;
+                }
+            }
+        }
+    };
+
+    if committed < len {
+        if !active {
+            return Err(corrupt(FilePos::new(id, committed), damage));
+        }
+        let file = OpenOptions::new()
+            .write(true)
+            .open(segment::path(dir, id))?;
+        file.set_len(committed)?;
+        file.sync_all()?;
+    }
+    Ok(committed)
+}
+
+/// Index a whole append's records, checking each stream's versions run on
+/// from its head.
+fn commit(index: &mut Index, records: Vec<Staged>) -> Result<(), FileError> {
+    for r in records {
+        if !index.record(&r.stream_id, r.version, r.pos) {
+            return Err(corrupt(r.pos, "version out of sequence for its stream"));
+        }
+    }
+    Ok(())
+}
+
+pub fn corrupt(pos: FilePos, reason: &str) -> FileError {
+    FileError::Corrupt {
+        segment: pos.segment(),
+        offset: pos.offset(),
+        reason: ErrorId::from_display(&reason),
+    }
+}
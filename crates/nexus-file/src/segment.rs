@@ -0,0 +1,154 @@
+//! Segment files: naming, listing, and a bounded sequential record reader.
+//!
+//! A segment is `<20-digit number>.seg`, so a directory listing sorts in
+//! log order. Only the highest-numbered segment is ever written; every other
+//! one is sealed and never changes again.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::position::FilePos;
+use crate::record::{self, HEADER_LEN, RecordBody, RecordHeader};
+
+const EXTENSION: &str = "seg";
+
+/// A segment's number and the length of its committed records. The length
+/// is the read bound: bytes past it belong to an append still in flight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub id: u64,
+    pub len: u64,
+}
+
+/// The file holding segment `id`.
+pub fn path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{id:020}.{EXTENSION}"))
+}
+
+/// The numbers of every segment in `dir`, ascending. Other files are
+/// ignored.
+pub fn list(dir: &Path) -> io::Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        let parsed = name
+            .to_str()
+            .and_then(|n| n.strip_suffix(EXTENSION))
+            .and_then(|n| n.strip_suffix('.'))
+            .and_then(|n| n.parse::<u64>().ok());
+        if let Some(id) = parsed {
+            ids.push(id);
+        }
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+/// Make a segment's creation durable: syncing its bytes does not sync the
+/// directory entry that names it.
+pub fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir; // directories cannot be opened for sync here
+    Ok(())
+}
+
+/// What [`SegmentReader::next`] found.
+#[derive(Debug)]
+pub enum Next<'a> {
+    /// A whole record whose checksum matches. `end` is the offset just past
+    /// it.
+    Record {
+        pos: FilePos,
+        end: u64,
+        body: RecordBody<'a>,
+    },
+    /// The bound was reached exactly on a record boundary.
+    End,
+    /// The bytes at `offset` are not a whole, intact record.
+    Torn {
+        offset: u64,
+        reason: record::CodecError,
+    },
+}
+
+/// Reads records front to back from one segment, up to a fixed bound.
+///
+/// The bound is the committed length the caller saw, never the file's live
+/// length, so an append racing the read is not observed half-written.
+pub struct SegmentReader {
+    file: BufReader<File>,
+    segment: u64,
+    offset: u64,
+    end: u64,
+    body: Vec<u8>,
+}
+
+impl SegmentReader {
+    /// Open segment `id` in `dir` to read records in `[start, end)`. `start`
+    /// must be a record boundary.
+    pub fn open(dir: &Path, id: u64, start: u64, end: u64) -> io::Result<Self> {
+        let mut file = File::open(path(dir, id))?;
+        file.seek(SeekFrom::Start(start))?;
+        Ok(Self {
+            file: BufReader::new(file),
+            segment: id,
+            offset: start,
+            end,
+            body: Vec::new(),
+        })
+    }
+
+    /// The next record, or why there is none.
+    ///
+    /// # Errors
+    ///
+    /// Only for a failed read; damaged bytes are [`Next::Torn`], which the
+    /// caller either repairs or reports.
+    pub fn next(&mut self) -> io::Result<Next<'_>> {
+        let remaining = self.end.saturating_sub(self.offset);
+        if remaining == 0 {
+            return Ok(Next::End);
+        }
+        if remaining < 8 {
+            return Ok(self.torn("header cut short"));
+        }
+        let mut raw = [0; HEADER_LEN];
+        self.file.read_exact(&mut raw)?;
+        let header = RecordHeader::parse(raw);
+        if header.record_len() > remaining {
+            return Ok(self.torn("body cut short"));
+        }
+        self.body.clear();
+        self.body.resize(
+            usize::try_from(header.body_len).map_err(io::Error::other)?,
+            0,
+        );
+        self.file.read_exact(&mut self.body)?;
+
+        let pos = FilePos::new(self.segment, self.offset);
+        match record::decode(header, &self.body) {
+            Ok(body) => {
+                self.offset += header.record_len();
+                Ok(Next::Record {
+                    pos,
+                    end: self.offset,
+                    body,
+                })
+            }
+            Err(reason) => Ok(Next::Torn {
+                offset: pos.offset(),
+                reason,
+            }),
+        }
+    }
+
+    const fn torn(&self, reason: record::CodecError) -> Next<'static> {
+        Next::Torn {
+            offset: self.offset,
+            reason,
+        }
+    }
+}
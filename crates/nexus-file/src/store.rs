@@ -0,0 +1,832 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use aligned_vec::{AVec, ConstAlign};
+use bytes::Bytes;
+use futures::{StreamExt, TryStreamExt};
+use nexus::{ErrorId, Version};
+use nexus_store::PendingEnvelope;
+use nexus_store::StreamKey;
+use nexus_store::batch::BatchSize;
+use nexus_store::envelope::PersistedEnvelope;
+use nexus_store::error::AppendError;
+use nexus_store::notify::{NotifyError, StreamNotifiers, WakeReg};
+use nexus_store::store::RawEventStore;
+use nexus_store::wake::WakeSource;
+use nexus_store::wire::{self, PAYLOAD_ALIGN};
+use parking_lot::Mutex;
+
+use crate::builder::{FileStoreBuilder, FsyncPolicy};
+use crate::error::FileError;
+use crate::index::{Index, Seek};
+use crate::position::FilePos;
+use crate::record::{self, RecordBody};
+use crate::recover::{Recovered, corrupt};
+use crate::segment::{self, Next, Segment, SegmentReader};
+
+/// What reads see of the log: every segment with its committed length, and
+/// the index over them.
+///
+/// Behind its own lock, held only for in-memory updates and copies — never
+/// across file I/O — so a read opening on the async executor never waits on
+/// an append's write or fsync. An append updates it in one step after its
+/// bytes are written, so a read never sees a half-indexed append.
+struct View {
+    /// Every segment with its committed length; the last is the active one.
+    segments: Vec<Segment>,
+    index: Index,
+}
+
+impl View {
+    fn active_segment(&self) -> Segment {
+        // `recover` always leaves at least one segment.
+        self.segments
+            .last()
+            .copied()
+            .unwrap_or(Segment { id: 1, len: 0 })
+    }
+
+    /// Index a written append's `(version, position)` records and extend
+    /// the active segment's committed length by the `written` bytes.
+    fn commit(
+        &mut self,
+        id: &StreamKey,
+        records: impl Iterator<Item = (u64, FilePos)>,
+        written: u64,
+    ) {
+        for (version, pos) in records {
+            self.index.record(id.as_bytes(), version, pos);
+        }
+        if let Some(last) = self.segments.last_mut() {
+            last.len += written;
+        }
+    }
+
+    /// The segments from number `first` on, with their committed lengths.
+    fn segments_from(&self, first: u64) -> Vec<Segment> {
+        self.segments
+            .iter()
+            .filter(|seg| seg.id >= first)
+            .copied()
+            .collect()
+    }
+
+    /// Where stream `id` is read from `from` on, and the segments that
+    /// read spans. `None` if there is nothing to read.
+    fn pin_stream(&self, id: &StreamKey, from: Version) -> Option<(Seek, Vec<Segment>)> {
+        let seek = self.index.seek(id.as_bytes(), from.as_u64())?;
+        let segments = self
+            .segments_from(seek.start.segment())
+            .into_iter()
+            .take_while(|seg| seg.id <= seek.last.segment())
+            .collect();
+        Some((seek, segments))
+    }
+}
+
+/// The writable end of the log.
+///
+/// Its lock serializes appends: an append's version check, its write, and
+/// its [`View`] update are one step no other append can interleave with.
+/// Only appends change the view, so the heads it reads stay current for the
+/// whole step.
+struct Log {
+    /// The active (last) segment, opened for appending.
+    active: File,
+    view: Arc<Mutex<View>>,
+    segment_size: u64,
+    fsync: FsyncPolicy,
+    /// Appends written since the last sync, for [`FsyncPolicy::EveryAppends`].
+    unsynced: u32,
+    /// Set when a failed append could not be cut back off the active
+    /// segment, or a new segment's directory entry could not be synced.
+    /// Every later append fails: it would land after the partial one, at
+    /// offsets the index does not know, or in a file a crash may drop.
+    poisoned: bool,
+}
+
+impl Log {
+    /// Seal the active segment and start the next one.
+    fn roll(&mut self, dir: &Path) -> Result<(), FileError> {
+        self.roll_with(dir, segment::sync_dir)
+    }
+
+    /// [`roll`](Self::roll), with the directory sync passed in so tests can
+    /// make it fail.
+    ///
+    /// Once the next segment is created the writer appends to it, so the
+    /// view gains it at once. If the directory entry then cannot be synced,
+    /// the log is poisoned: an append there could vanish in a crash after
+    /// being acknowledged.
+    fn roll_with(
+        &mut self,
+        dir: &Path,
+        sync_dir: fn(&Path) -> std::io::Result<()>,
+    ) -> Result<(), FileError> {
+        self.active.sync_all()?;
+        self.unsynced = 0;
+        let id = self.view.lock().active_segment().id.wrapping_add(1);
+        self.active = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(segment::path(dir, id))?;
+        self.view.lock().segments.push(Segment { id, len: 0 });
+        if let Err(e) = sync_dir(dir) {
+            self.poisoned = true;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Write one encoded append to the end of the active segment (committed
+    /// length `len`), syncing per the [`FsyncPolicy`]. A failed write or
+    /// sync is cut back off, so the segment never keeps an append it did not
+    /// acknowledge; if even that fails, the log is poisoned.
+    fn write(&mut self, len: u64, bytes: &[u8]) -> Result<(), FileError> {
+        if let Err(e) = self
+            .active
+            .write_all(bytes)
+            .and_then(|()| self.sync_per_policy())
+        {
+            if self.active.set_len(len).is_err() {
+                self.poisoned = true;
+            }
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn sync_per_policy(&mut self) -> std::io::Result<()> {
+        match self.fsync {
+            FsyncPolicy::Always => self.sync(),
+            FsyncPolicy::EveryAppends(n) => {
+                self.unsynced = self.unsynced.saturating_add(1);
+                if self.unsynced >= n.get() {
+                    self.sync()?;
+                }
+                Ok(())
+            }
+            FsyncPolicy::Never => Ok(()),
+        }
+    }
+
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.active.sync_data()?;
+        self.unsynced = 0;
+        Ok(())
+    }
+
+    /// The whole of `append` under the lock: check the versions, encode,
+    /// roll if the segment is full, write, index.
+    fn append(
+        &mut self,
+        dir: &Path,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+    ) -> Result<(), AppendError<FileError>> {
+        if self.poisoned {
+            return Err(AppendError::Store(FileError::Poisoned));
+        }
+        let head = self.view.lock().index.head(id.as_bytes());
+        check_versions(head, expected_version, envelopes, id)?;
+        if envelopes.is_empty() {
+            return Ok(()); // version checked; nothing to write
+        }
+        let (bytes, offsets) = encode_append(id, envelopes).map_err(AppendError::Store)?;
+
+        let written = u64::try_from(bytes.len()).unwrap_or(u64::MAX);
+        let active = self.view.lock().active_segment();
+        if active.len > 0 && active.len.saturating_add(written) > self.segment_size {
+            self.roll(dir).map_err(AppendError::Store)?;
+        }
+        let Segment { id: seg, len } = self.view.lock().active_segment();
+        self.write(len, &bytes).map_err(AppendError::Store)?;
+
+        let records = envelopes
+            .iter()
+            .zip(offsets)
+            .map(|(env, offset)| (env.version().as_u64(), FilePos::new(seg, len + offset)));
+        self.view.lock().commit(id, records, written);
+        Ok(())
+    }
+}
+
+impl Drop for Log {
+    /// Hand what an [`FsyncPolicy::EveryAppends`] store has not synced yet
+    /// to the disk on a clean shutdown. Errors have nowhere to go.
+    fn drop(&mut self) {
+        if self.unsynced > 0 {
+            let _ = self.active.sync_data();
+        }
+    }
+}
+
+/// Shared, `Arc`-owned interior of a [`FileStore`].
+struct Inner {
+    dir: PathBuf,
+    log: Mutex<Log>,
+    /// The same [`View`] the log updates; reads lock only this.
+    view: Arc<Mutex<View>>,
+    /// In-process wake registry, woken after every append.
+    notifiers: Arc<StreamNotifiers>,
+    /// Holds the directory's exclusive lock for the store's lifetime.
+    _lock: File,
+}
+
+/// Segmented append-only file event store.
+///
+/// Implements [`RawEventStore`] and [`WakeSource`]. Constructed via
+/// [`builder`](Self::builder).
+///
+/// File I/O is blocking, so appends and read pages run on tokio's blocking
+/// pool (`spawn_blocking`) and never stall the async executor.
+///
+/// Clone is cheap: it is a single `Arc` bump over the shared `Inner`. All
+/// clones share one log, one index and one wake registry.
+///
+/// Reads page lazily: `read_stream` and `read_all` hold at most
+/// [`batch_size`](Self::batch_size) events at a time, and see exactly the
+/// appends committed when they opened.
+#[derive(Clone)]
+pub struct FileStore {
+    inner: Arc<Inner>,
+    batch_size: BatchSize,
+}
+
+impl FileStore {
+    /// Start building a store backed by the segment directory `dir`.
+    pub fn builder(dir: impl AsRef<Path>) -> FileStoreBuilder {
+        FileStoreBuilder::new(dir)
+    }
+
+    /// Wrap a recovered log. Called by the [`builder`](crate::FileStoreBuilder).
+    pub(crate) fn assemble(
+        dir: PathBuf,
+        lock: File,
+        recovered: Recovered,
+        segment_size: u64,
+        fsync: FsyncPolicy,
+    ) -> Result<Self, FileError> {
+        let Recovered { segments, index } = recovered;
+        let recovered_view = View { segments, index };
+        let active = OpenOptions::new()
+            .append(true)
+            .open(segment::path(&dir, recovered_view.active_segment().id))?;
+        let view = Arc::new(Mutex::new(recovered_view));
+        Ok(Self {
+            inner: Arc::new(Inner {
+                dir,
+                log: Mutex::new(Log {
+                    active,
+                    view: Arc::clone(&view),
+                    segment_size,
+                    fsync,
+                    unsynced: 0,
+                    poisoned: false,
+                }),
+                view,
+                notifiers: StreamNotifiers::new(),
+                _lock: lock,
+            }),
+            batch_size: BatchSize::DEFAULT,
+        })
+    }
+
+    /// Set how many events `read_stream` and `read_all` read per page
+    /// ([`BatchSize::DEFAULT`] unless set).
+    #[must_use]
+    pub const fn with_batch_size(mut self, batch_size: BatchSize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// The configured read page size.
+    #[must_use]
+    pub const fn batch_size(&self) -> BatchSize {
+        self.batch_size
+    }
+
+    /// The directory holding this store's segments.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.inner.dir
+    }
+
+    /// Sync every acknowledged append to disk now, whatever the
+    /// [`FsyncPolicy`]. Sealed segments are always synced already.
+    ///
+    /// # Errors
+    ///
+    /// [`FileError::Io`] if the sync fails.
+    pub async fn sync(&self) -> Result<(), FileError> {
+        Ok(self.on_log(|_, log| log.sync()).await??)
+    }
+
+    /// Run `f` on the locked log, on the blocking pool.
+    async fn on_log<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Path, &mut Log) -> T + Send + 'static,
+    ) -> Result<T, FileError> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || f(&inner.dir, &mut inner.log.lock()))
+            .await
+            .map_err(FileError::Worker)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pure helpers — no IO
+// ---------------------------------------------------------------------------
+
+/// Optimistic-concurrency and strict-sequence check of a batch against the
+/// stream's `head` version.
+fn check_versions(
+    head: u64,
+    expected: Option<Version>,
+    envelopes: &[PendingEnvelope],
+    id: &StreamKey,
+) -> Result<(), AppendError<FileError>> {
+    if expected.map_or(0, Version::as_u64) != head {
+        return Err(AppendError::Conflict {
+            stream_id: ErrorId::from_display(id),
+            expected,
+            actual: Version::new(head),
+        });
+    }
+    let mut expect = head;
+    for env in envelopes {
+        expect = expect.wrapping_add(1);
+        if env.version().as_u64() != expect {
+            return Err(AppendError::Conflict {
+                stream_id: ErrorId::from_display(id),
+                expected: Version::new(expect),
+                actual: Some(env.version()),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Encode a batch as one contiguous run of records, the last one marked as
+/// ending the append. Returns the bytes and each record's offset in them.
+fn encode_append(
+    id: &StreamKey,
+    envelopes: &[PendingEnvelope],
+) -> Result<(Vec<u8>, Vec<u64>), FileError> {
+    let mut bytes = Vec::new();
+    let mut offsets = Vec::with_capacity(envelopes.len());
+    for (i, env) in envelopes.iter().enumerate() {
+        let version = env.version().as_u64();
+        let frame = wire::encode_frame(
+            env.schema_version_value(),
+            &env.event_type_value(),
+            &env.payload_value(),
+            env.metadata_value().as_ref(),
+        )
+        .map_err(|e| FileError::Frame {
+            stream_id: ErrorId::from_display(id),
+            version,
+            reason: ErrorId::from_display(&e),
+        })?;
+        offsets.push(u64::try_from(bytes.len()).unwrap_or(u64::MAX));
+        let ends_append = i + 1 == envelopes.len();
+        record::encode(
+            &mut bytes,
+            ends_append,
+            id.as_bytes(),
+            version,
+            &frame.value,
+        )
+        .map_err(|reason| FileError::InvalidInput {
+            stream_id: ErrorId::from_display(id),
+            version,
+            reason: ErrorId::from_display(&reason),
+        })?;
+    }
+    Ok((bytes, offsets))
+}
+
+/// Decode a stored record into an envelope, copying its frame onto a fresh
+/// 16-byte-aligned buffer — the alignment [`wire::encode_frame`] guarantees
+/// for the payload and zero-copy readers rely on.
+fn to_envelope(pos: FilePos, body: &RecordBody<'_>) -> Result<PersistedEnvelope, FileError> {
+    let version = Version::new(body.version).ok_or_else(|| corrupt(pos, "version is 0"))?;
+    let decoded = wire::decode_frame(body.frame).map_err(|e| corrupt(pos, &e.to_string()))?;
+    let mut aligned: AVec<u8, ConstAlign<PAYLOAD_ALIGN>> =
+        AVec::with_capacity(PAYLOAD_ALIGN, body.frame.len());
+    aligned.extend_from_slice(body.frame);
+
+    PersistedEnvelope::try_new(
+        version,
+        Bytes::from_owner(aligned),
+        decoded.schema_version,
+        decoded.offsets.event_type,
+        decoded.offsets.payload,
+        decoded.offsets.metadata,
+    )
+    .map_err(|source| FileError::EnvelopeCorrupt {
+        stream_id: ErrorId::from_display(&String::from_utf8_lossy(body.stream_id)),
+        version: body.version,
+        source,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Reads
+// ---------------------------------------------------------------------------
+
+/// Which records a [`Cursor`] yields.
+enum Scope {
+    /// Records of one stream at or after version `from`, up to the stream's
+    /// last record when the read opened.
+    Stream {
+        id: StreamKey,
+        from: u64,
+        last: FilePos,
+    },
+    /// Every record strictly after `after`.
+    All { after: Option<FilePos> },
+}
+
+impl Scope {
+    fn matches(&self, pos: FilePos, body: &RecordBody<'_>) -> bool {
+        match self {
+            Self::Stream { id, from, .. } => {
+                body.stream_id == id.as_bytes() && body.version >= *from
+            }
+            Self::All { after } => after.is_none_or(|p| pos > p),
+        }
+    }
+
+    /// Whether no record at or past `pos` can match.
+    fn exhausted_at(&self, pos: FilePos) -> bool {
+        matches!(self, Self::Stream { last, .. } if pos > *last)
+    }
+}
+
+/// A blocking, resumable scan over a pinned list of segments.
+///
+/// The segment lengths are the committed lengths when the read opened, so
+/// appends made since — including into segments created since — are left
+/// for the next read.
+struct Cursor {
+    dir: PathBuf,
+    scope: Scope,
+    /// Segments still to read; the first is being read.
+    segments: VecDeque<Segment>,
+    /// Where in the first segment to start, before its reader is open.
+    start: u64,
+    reader: Option<SegmentReader>,
+}
+
+impl Cursor {
+    /// Read up to `max` matching events; fewer means the scan is finished.
+    fn page(&mut self, max: usize) -> Result<Vec<(FilePos, PersistedEnvelope)>, FileError> {
+        let mut page = Vec::new();
+        while page.len() < max {
+            if !self.open_reader()? {
+                break;
+            }
+            let Some(reader) = self.reader.as_mut() else {
+                break;
+            };
+            match reader.next()? {
+                Next::End => {
+                    self.reader = None;
+                    self.segments.pop_front();
+                }
+                Next::Torn { offset, reason } => {
+                    let segment = self.segments.front().map_or(0, |s| s.id);
+                    return Err(corrupt(FilePos::new(segment, offset), reason));
+                }
+                Next::Record { pos, body, .. } => {
+                    if self.scope.exhausted_at(pos) {
+                        self.segments.clear();
+                        self.reader = None;
+                        break;
+                    }
+                    if self.scope.matches(pos, &body) {
+                        page.push((pos, to_envelope(pos, &body)?));
+                    }
+                }
+            }
+        }
+        Ok(page)
+    }
+
+    /// Open the current segment's reader if it is not open yet; `false`
+    /// once every segment is read.
+    fn open_reader(&mut self) -> Result<bool, FileError> {
+        if self.reader.is_none() {
+            let Some(seg) = self.segments.front() else {
+                return Ok(false);
+            };
+            let start = std::mem::take(&mut self.start).min(seg.len);
+            self.reader = Some(SegmentReader::open(&self.dir, seg.id, start, seg.len)?);
+        }
+        Ok(true)
+    }
+}
+
+/// `$all` stream type: owned, `Send`, `'static`, paged by [`Pages`].
+type AllStream =
+    futures::stream::BoxStream<'static, Result<(FilePos, PersistedEnvelope), FileError>>;
+
+/// Per-stream stream type: [`AllStream`] without the positions.
+type Stream = futures::stream::BoxStream<'static, Result<PersistedEnvelope, FileError>>;
+
+/// Async paging over a [`Cursor`]: each refill moves the cursor onto the
+/// blocking pool for one page and back.
+struct Pages {
+    /// `None` only while a page is being read, or after a worker failure.
+    cursor: Option<Cursor>,
+    batch_size: usize,
+    buffer: VecDeque<(FilePos, PersistedEnvelope)>,
+    /// Set by a short page or an error — the stream then ends rather than
+    /// skipping records.
+    done: bool,
+}
+
+impl Pages {
+    async fn refill(&mut self) -> Result<(), FileError> {
+        let Some(mut taken) = self.cursor.take() else {
+            self.done = true;
+            return Ok(());
+        };
+        let max = self.batch_size;
+        let (cursor, read) = tokio::task::spawn_blocking(move || {
+            let read = taken.page(max);
+            (taken, read)
+        })
+        .await
+        .map_err(FileError::Worker)?;
+        self.cursor = Some(cursor);
+        let page = read?;
+        self.done = page.len() < self.batch_size;
+        self.buffer = page.into();
+        Ok(())
+    }
+
+    fn into_stream(self) -> AllStream {
+        futures::stream::unfold(self, |mut s| async move {
+            loop {
+                if let Some(item) = s.buffer.pop_front() {
+                    return Some((Ok(item), s));
+                }
+                if s.done {
+                    return None;
+                }
+                if let Err(e) = s.refill().await {
+                    s.done = true;
+                    return Some((Err(e), s));
+                }
+                if s.buffer.is_empty() {
+                    return None;
+                }
+            }
+        })
+        .fuse()
+        .boxed()
+    }
+}
+
+impl FileStore {
+    /// Page over `segments` from `start` in the first, yielding `scope`.
+    fn pages(&self, scope: Scope, segments: Vec<Segment>, start: u64) -> Pages {
+        Pages {
+            cursor: Some(Cursor {
+                dir: self.inner.dir.clone(),
+                scope,
+                segments: segments.into(),
+                start,
+                reader: None,
+            }),
+            batch_size: self.batch_size.get(),
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// `RawEventStore` impl
+// ---------------------------------------------------------------------------
+
+impl RawEventStore for FileStore {
+    type Error = FileError;
+    type Stream = Stream;
+    type AllPosition = FilePos;
+    type AllStream = AllStream;
+
+    async fn append(
+        &self,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+    ) -> Result<(), AppendError<Self::Error>> {
+        // The blocking task needs owned inputs; envelope bytes are
+        // ref-counted, so the copies are cheap.
+        let stream = id.clone();
+        let batch = envelopes.to_vec();
+        self.on_log(move |dir, log| log.append(dir, &stream, expected_version, &batch))
+            .await
+            .map_err(AppendError::Store)??;
+
+        // Wake AFTER the append is written and synced per the policy
+        // (WakeSource contract: wake post-commit).
+        if !envelopes.is_empty() {
+            self.inner.notifiers.wake(id.as_bytes());
+        }
+        Ok(())
+    }
+
+    async fn read_stream(
+        &self,
+        id: &StreamKey,
+        from: Version,
+    ) -> Result<Self::Stream, Self::Error> {
+        let pinned = self.inner.view.lock().pin_stream(id, from);
+        let Some((seek, segments)) = pinned else {
+            return Ok(futures::stream::empty().boxed());
+        };
+        let scope = Scope::Stream {
+            id: id.clone(),
+            from: from.as_u64(),
+            last: seek.last,
+        };
+        Ok(self
+            .pages(scope, segments, seek.start.offset())
+            .into_stream()
+            .map_ok(|(_, env)| env)
+            .boxed())
+    }
+
+    async fn read_all(&self, from: Option<FilePos>) -> Result<Self::AllStream, Self::Error> {
+        // Pin the committed lengths once: the read yields exactly the events
+        // appended when it opened and stays finite under a steady write load.
+        let first = from.map_or(0, FilePos::segment);
+        let segments = self.inner.view.lock().segments_from(first);
+        // Start at the resume record itself (a record boundary) and skip it.
+        let start = from
+            .filter(|p| segments.first().is_some_and(|seg| seg.id == p.segment()))
+            .map_or(0, FilePos::offset);
+        Ok(self
+            .pages(Scope::All { after: from }, segments, start)
+            .into_stream())
+    }
+}
+
+/// Wakes are in-process: `append` wakes this store's registry after every
+/// write. The directory lock keeps any other process from appending to the
+/// log, so no wake can be missed.
+impl WakeSource for FileStore {
+    type Registration = WakeReg;
+    type Error = NotifyError;
+
+    fn register(&self, stream: Option<&[u8]>) -> Result<Self::Registration, Self::Error> {
+        self.inner.notifiers.register(stream)
+    }
+
+    fn wake(&self, stream: &[u8]) {
+        // `StreamNotifiers::wake` bumps BOTH the per-stream and the `$all` wake
+        // paths, so one call rouses per-stream and `$all` registrations alike.
+        self.inner.notifiers.wake(stream);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use nexus::Version;
+    use nexus_store::PendingEnvelope;
+    use nexus_store::StreamKey;
+    use nexus_store::envelope::pending_envelope;
+    use nexus_store::error::AppendError;
+
+    use std::fs::File;
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use super::{Log, View, check_versions, encode_append};
+    use crate::builder::FsyncPolicy;
+    use crate::error::FileError;
+    use crate::index::Index;
+    use crate::record::{HEADER_LEN, RecordHeader};
+    use crate::segment::{self, Segment};
+
+    fn make_envelope(version: u64) -> PendingEnvelope {
+        pending_envelope(Version::new(version).unwrap())
+            .event_type("E")
+            .payload(vec![1])
+            .unwrap()
+            .build()
+    }
+
+    #[test]
+    fn check_versions_accepts_the_next_run() {
+        let envs = [make_envelope(4), make_envelope(5)];
+        check_versions(3, Version::new(3), &envs, &StreamKey::from_slice(b"s")).unwrap();
+    }
+
+    #[test]
+    fn check_versions_rejects_stale_and_gapped_batches() {
+        let id = StreamKey::from_slice(b"s");
+        let stale = check_versions(3, Version::new(2), &[make_envelope(3)], &id);
+        assert!(matches!(stale, Err(AppendError::Conflict { .. })));
+        let gapped = check_versions(3, Version::new(3), &[make_envelope(5)], &id);
+        assert!(matches!(gapped, Err(AppendError::Conflict { .. })));
+    }
+
+    #[test]
+    fn encode_append_offsets_point_at_each_record() {
+        let envs = [make_envelope(1), make_envelope(2), make_envelope(3)];
+        let (bytes, offsets) = encode_append(&StreamKey::from_slice(b"s"), &envs).unwrap();
+        let mut at = 0;
+        for offset in offsets {
+            assert_eq!(offset, u64::try_from(at).unwrap());
+            let header = RecordHeader::parse(bytes[at..at + HEADER_LEN].try_into().unwrap());
+            at += usize::try_from(header.record_len()).unwrap();
+        }
+        assert_eq!(at, bytes.len());
+    }
+
+    /// A log whose active segment is open read-only: every write to it fails,
+    /// and so does cutting it back — the double failure a full or broken
+    /// disk can produce.
+    fn log_that_cannot_truncate(dir: &std::path::Path) -> Log {
+        File::create(segment::path(dir, 1)).unwrap();
+        Log {
+            active: File::open(segment::path(dir, 1)).unwrap(),
+            view: Arc::new(Mutex::new(View {
+                segments: vec![Segment { id: 1, len: 0 }],
+                index: Index::default(),
+            })),
+            segment_size: u64::MAX,
+            fsync: FsyncPolicy::Never,
+            unsynced: 0,
+            poisoned: false,
+        }
+    }
+
+    #[test]
+    fn a_failed_truncation_poisons_every_later_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = log_that_cannot_truncate(dir.path());
+        let id = StreamKey::from_slice(b"s");
+
+        let first = log.append(dir.path(), &id, None, &[make_envelope(1)]);
+        assert!(
+            matches!(first, Err(AppendError::Store(FileError::Io(_)))),
+            "got {first:?}"
+        );
+        let later = log.append(dir.path(), &id, None, &[make_envelope(1)]);
+        assert!(
+            matches!(later, Err(AppendError::Store(FileError::Poisoned))),
+            "got {later:?}"
+        );
+        assert_eq!(log.view.lock().index.head(b"s"), 0, "nothing indexed");
+    }
+
+    #[test]
+    fn a_failed_directory_sync_on_roll_poisons_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        File::create(segment::path(dir.path(), 1)).unwrap();
+        let mut log = Log {
+            active: File::options()
+                .append(true)
+                .open(segment::path(dir.path(), 1))
+                .unwrap(),
+            view: Arc::new(Mutex::new(View {
+                segments: vec![Segment { id: 1, len: 0 }],
+                index: Index::default(),
+            })),
+            segment_size: u64::MAX,
+            fsync: FsyncPolicy::Never,
+            unsynced: 0,
+            poisoned: false,
+        };
+
+        let rolled = log.roll_with(dir.path(), |_| Err(std::io::Error::other("injected")));
+        assert!(matches!(rolled, Err(FileError::Io(_))), "got {rolled:?}");
+        assert_eq!(
+            log.view.lock().active_segment(),
+            Segment { id: 2, len: 0 },
+            "the view ends at the segment the writer holds"
+        );
+        let later = log.append(
+            dir.path(),
+            &StreamKey::from_slice(b"s"),
+            None,
+            &[make_envelope(1)],
+        );
+        assert!(
+            matches!(later, Err(AppendError::Store(FileError::Poisoned))),
+            "got {later:?}"
+        );
+    }
+}
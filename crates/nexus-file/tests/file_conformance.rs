@@ -0,0 +1,80 @@
+//! `nexus-file::FileStore` conformance against the canonical
+//! [`EventStream`](nexus_store::stream::EventStream) and `$all` read-path
+//! contracts.
+//!
+//! Delegates every check to [`nexus_store_testing`]. Each store lives in a
+//! fresh segment directory; its reads open their own file handles, so the
+//! only lifetime to manage is the temporary directory's.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+
+use std::num::NonZeroU32;
+
+use nexus::Version;
+use nexus_file::FileStore;
+use nexus_store::PendingEnvelope;
+use nexus_store::StreamKey;
+use nexus_store::envelope::pending_envelope;
+use nexus_store::store::RawEventStore;
+use nexus_store::value::SchemaVersion;
+use nexus_store_testing::{
+    ConformanceRow, assert_all_stream_conformance, assert_event_stream_conformance,
+};
+
+/// A store over a fresh directory that outlives the test. The test
+/// process exits shortly after, so the handful of leaked dirs are bounded —
+/// the same trade the fjall conformance tests make.
+fn fresh_store() -> FileStore {
+    let tempdir = tempfile::tempdir().expect("tempdir");
+    let store = FileStore::builder(tempdir.path())
+        .open()
+        .expect("open file store");
+    Box::leak(Box::new(tempdir));
+    store
+}
+
+fn to_envelope(row: ConformanceRow) -> PendingEnvelope {
+    // `PendingEnvelope::event_type` is `&'static str`; the per-row leak is
+    // intentional and bounded, as above.
+    let event_type: &'static str = Box::leak(row.event_type.into_boxed_str());
+    let with_payload = pending_envelope(Version::new(row.version).unwrap())
+        .event_type(event_type)
+        .payload(row.payload)
+        .expect("valid payload");
+    if row.schema_version == 1 {
+        with_payload.build()
+    } else {
+        with_payload
+            .schema_version(SchemaVersion::new(
+                NonZeroU32::new(row.schema_version).unwrap(),
+            ))
+            .build()
+    }
+}
+
+#[tokio::test]
+async fn file_event_stream_conforms() {
+    assert_event_stream_conformance(|rows: Vec<ConformanceRow>| async move {
+        let store = fresh_store();
+        let stream_id = StreamKey::from_slice(b"conformance");
+        if !rows.is_empty() {
+            let envelopes: Vec<PendingEnvelope> = rows.into_iter().map(to_envelope).collect();
+            store
+                .append(&stream_id, None, &envelopes)
+                .await
+                .expect("append rows");
+        }
+        store
+            .read_stream(&stream_id, Version::INITIAL)
+            .await
+            .expect("open read_stream")
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn file_all_stream_conforms() {
+    assert_all_stream_conformance(|| async { fresh_store() }).await;
+}
@@ -0,0 +1,375 @@
+//! `FileStore` behaviour beyond the shared conformance suites: optimistic
+//! concurrency, segment rolling, crash recovery of a torn tail, corruption
+//! reporting, the index rebuilt on reopen, wakes, and the directory lock.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use futures::{StreamExt, TryStreamExt};
+use nexus::Version;
+use nexus_file::{FileError, FilePos, FileStore};
+use nexus_store::batch::BatchSize;
+use nexus_store::envelope::pending_envelope;
+use nexus_store::error::AppendError;
+use nexus_store::store::RawEventStore;
+use nexus_store::wake::{WakeRegistration, WakeSource};
+use nexus_store::{PendingEnvelope, StreamKey};
+
+const MUST_WAKE: Duration = Duration::from_secs(5);
+
+fn open(dir: &Path) -> FileStore {
+    FileStore::builder(dir).open().expect("open store")
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+async fn stream_versions(store: &FileStore, stream: &str, from: u64) -> Vec<u64> {
+    store
+        .read_stream(&sk(stream), Version::new(from).unwrap())
+        .await
+        .expect("read_stream")
+        .map(|r| r.expect("no error").version().as_u64())
+        .collect()
+        .await
+}
+
+async fn all_positions(store: &FileStore, from: Option<FilePos>) -> Vec<FilePos> {
+    store
+        .read_all(from)
+        .await
+        .expect("read_all")
+        .map(|r| r.expect("no error").0)
+        .collect()
+        .await
+}
+
+/// Every segment file in `dir`, in log order.
+fn segments(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "seg"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn file_len(path: &Path) -> u64 {
+    std::fs::metadata(path).unwrap().len()
+}
+
+// ---------------------------------------------------------------------------
+// 1. Appends and reads
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn stale_and_gapped_appends_conflict_and_write_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(dir.path());
+    store
+        .append(&sk("a"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+    let len = file_len(&segments(dir.path())[0]);
+
+    let stale = store
+        .append(&sk("a"), Version::new(2), &envelopes(3, 4))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(stale, AppendError::Conflict { actual, .. } if actual == Version::new(3)),
+        "got {stale:?}"
+    );
+    let gapped = store
+        .append(&sk("a"), Version::new(3), &envelopes(5, 6))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(gapped, AppendError::Conflict { .. }),
+        "got {gapped:?}"
+    );
+    assert_eq!(stream_versions(&store, "a", 1).await, [1, 2, 3]);
+    assert_eq!(file_len(&segments(dir.path())[0]), len);
+}
+
+#[tokio::test]
+async fn stream_reads_seek_past_other_streams_and_earlier_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(dir.path()).with_batch_size(BatchSize::new(3).unwrap());
+    // Interleave two streams so a stream read has records to skip, and make
+    // "a" long enough to carry several index marks.
+    for v in 1..=40 {
+        store
+            .append(&sk("a"), Version::new(v - 1), &envelopes(v, v))
+            .await
+            .unwrap();
+        store
+            .append(&sk("b"), Version::new(v - 1), &envelopes(v, v))
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        stream_versions(&store, "a", 1).await,
+        (1..=40).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        stream_versions(&store, "b", 18).await,
+        (18..=40).collect::<Vec<_>>()
+    );
+    assert!(stream_versions(&store, "a", 41).await.is_empty());
+    assert!(stream_versions(&store, "c", 1).await.is_empty());
+}
+
+#[tokio::test]
+async fn full_segments_roll_and_reads_cross_them_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileStore::builder(dir.path())
+        .segment_size(256)
+        .open()
+        .unwrap();
+    for v in 1..=10 {
+        store
+            .append(&sk("a"), Version::new(v - 1), &envelopes(v, v))
+            .await
+            .unwrap();
+    }
+    let files = segments(dir.path());
+    assert!(files.len() > 1, "expected a roll, got {files:?}");
+    for sealed in &files[..files.len() - 1] {
+        assert!(file_len(sealed) <= 256);
+    }
+
+    let positions = all_positions(&store, None).await;
+    assert_eq!(positions.len(), 10);
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(
+        stream_versions(&store, "a", 1).await,
+        (1..=10).collect::<Vec<_>>()
+    );
+    // Resuming from a segment's last record continues in the next one.
+    let boundary = positions
+        .windows(2)
+        .position(|w| w[0].segment() != w[1].segment())
+        .unwrap();
+    assert_eq!(
+        all_positions(&store, Some(positions[boundary])).await,
+        positions[boundary + 1..]
+    );
+}
+
+#[tokio::test]
+async fn an_append_after_read_all_opened_is_left_for_the_next_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(dir.path()).with_batch_size(BatchSize::new(2).unwrap());
+    store
+        .append(&sk("a"), None, &envelopes(1, 5))
+        .await
+        .unwrap();
+
+    let mut all = store.read_all(None).await.unwrap();
+    let first = all.next().await.unwrap().unwrap().0;
+    store
+        .append(&sk("b"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+    let rest: Vec<FilePos> = all.map_ok(|(p, _)| p).try_collect().await.unwrap();
+    assert_eq!(rest.len(), 4);
+
+    let last = *rest.last().unwrap();
+    assert!(first < last);
+    assert_eq!(all_positions(&store, Some(last)).await.len(), 3);
+}
+
+#[tokio::test]
+async fn a_committed_append_wakes_stream_and_all_registrations() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(dir.path());
+    let stream = store.register(Some(b"a")).unwrap();
+    let all = store.register(None).unwrap();
+    let (stream_woken, all_woken) = (stream.arm(), all.arm());
+
+    store
+        .append(&sk("a"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    tokio::time::timeout(MUST_WAKE, stream_woken)
+        .await
+        .expect("stream registration woken");
+    tokio::time::timeout(MUST_WAKE, all_woken)
+        .await
+        .expect("$all registration woken");
+}
+
+// ---------------------------------------------------------------------------
+// 2. Reopening and recovery
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn reopening_rebuilds_the_index_and_keeps_positions() {
+    let dir = tempfile::tempdir().unwrap();
+    let before = {
+        let store = FileStore::builder(dir.path())
+            .segment_size(512)
+            .open()
+            .unwrap();
+        store
+            .append(&sk("a"), None, &envelopes(1, 20))
+            .await
+            .unwrap();
+        store
+            .append(&sk("b"), None, &envelopes(1, 2))
+            .await
+            .unwrap();
+        all_positions(&store, None).await
+    };
+
+    let store = open(dir.path());
+    assert_eq!(all_positions(&store, None).await, before);
+    assert_eq!(
+        stream_versions(&store, "a", 17).await,
+        (17..=20).collect::<Vec<_>>()
+    );
+    // The rebuilt heads gate the next append.
+    let err = store
+        .append(&sk("b"), None, &envelopes(1, 1))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppendError::Conflict { .. }), "got {err:?}");
+    store
+        .append(&sk("b"), Version::new(2), &envelopes(3, 3))
+        .await
+        .unwrap();
+    assert_eq!(stream_versions(&store, "b", 1).await, [1, 2, 3]);
+}
+
+#[tokio::test]
+async fn a_torn_tail_is_truncated_on_open() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let store = open(dir.path());
+        store
+            .append(&sk("a"), None, &envelopes(1, 2))
+            .await
+            .unwrap();
+    }
+    let active = segments(dir.path()).pop().unwrap();
+    let committed = file_len(&active);
+    // A crash mid-write: half a record header and some of its body.
+    OpenOptions::new()
+        .append(true)
+        .open(&active)
+        .unwrap()
+        .write_all(&[0x40, 0, 0, 0, 0xde, 0xad, 0xbe, 0xef, 1, 2, 3])
+        .unwrap();
+
+    let store = open(dir.path());
+    assert_eq!(file_len(&active), committed);
+    assert_eq!(stream_versions(&store, "a", 1).await, [1, 2]);
+    store
+        .append(&sk("a"), Version::new(2), &envelopes(3, 3))
+        .await
+        .unwrap();
+    assert_eq!(stream_versions(&store, "a", 1).await, [1, 2, 3]);
+}
+
+#[tokio::test]
+async fn an_append_missing_its_last_record_is_dropped_whole() {
+    let dir = tempfile::tempdir().unwrap();
+    let active = {
+        let store = open(dir.path());
+        store
+            .append(&sk("a"), None, &envelopes(1, 1))
+            .await
+            .unwrap();
+        let active = segments(dir.path()).pop().unwrap();
+        let before = file_len(&active);
+        store
+            .append(&sk("a"), Version::new(1), &envelopes(2, 4))
+            .await
+            .unwrap();
+        // Cut the three-event append just short of its final record's end:
+        // its first two records are intact, but the append never finished.
+        let after = file_len(&active);
+        OpenOptions::new()
+            .write(true)
+            .open(&active)
+            .unwrap()
+            .set_len(after - 1)
+            .unwrap();
+        assert!(after - 1 > before);
+        (active, before)
+    };
+
+    let store = open(dir.path());
+    assert_eq!(file_len(&active.0), active.1);
+    assert_eq!(stream_versions(&store, "a", 1).await, [1]);
+}
+
+#[tokio::test]
+async fn damage_in_a_sealed_segment_refuses_to_open() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let store = FileStore::builder(dir.path())
+            .segment_size(128)
+            .open()
+            .unwrap();
+        for v in 1..=4 {
+            store
+                .append(&sk("a"), Version::new(v - 1), &envelopes(v, v))
+                .await
+                .unwrap();
+        }
+    }
+    let files = segments(dir.path());
+    assert!(files.len() > 1);
+    let sealed = &files[0];
+    // Flip a bit inside the first record's body, past its 8-byte header.
+    let mut bytes = std::fs::read(sealed).unwrap();
+    bytes[20] ^= 0x01;
+    std::fs::write(sealed, bytes).unwrap();
+
+    let err = FileStore::builder(dir.path()).open().err().unwrap();
+    assert!(
+        matches!(
+            err,
+            FileError::Corrupt {
+                segment: 1,
+                offset: 0,
+                ..
+            }
+        ),
+        "got {err:?}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 3. The directory
+// ---------------------------------------------------------------------------
+
+#[test]
+fn a_second_store_on_one_directory_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let _first = open(dir.path());
+    let err = FileStore::builder(dir.path()).open().err().unwrap();
+    assert!(matches!(err, FileError::Locked { .. }), "got {err:?}");
+}
@@ -1,7 +1,11 @@
 use crate::error::FjallError;
+#[cfg(feature = "snapshot")]
+use crate::partition::SnapshotKeyspaces;
 use crate::partition::{AllIndex, KeyspaceConfig, Partitions, point_read_defaults, scan_defaults};
 use crate::store::FjallStore;
 use fjall::KeyspaceCreateOptions;
+#[cfg(feature = "snapshot")]
+use nexus_store::SnapshotRetention;
 use nexus_store::notify::StreamNotifiers;
 use std::path::{Path, PathBuf};
 
@@ -23,6 +27,8 @@ pub struct FjallStoreBuilder<S = (), E = ()> {
     streams_config: S,
     events_config: E,
     all_index: AllIndex,
+    #[cfg(feature = "snapshot")]
+    snapshot_retention: SnapshotRetention,
 }
 
 impl FjallStoreBuilder {
@@ -33,6 +39,8 @@ impl FjallStoreBuilder {
             streams_config: (),
             events_config: (),
             all_index: AllIndex::default(),
+            #[cfg(feature = "snapshot")]
+            snapshot_retention: SnapshotRetention::LATEST,
         }
     }
 }
@@ -51,6 +59,20 @@ impl<S, E> FjallStoreBuilder<S, E> {
         self
     }
 
+    /// Choose how many snapshots are kept per id.
+    ///
+    /// Defaults to [`SnapshotRetention::LATEST`]. Keeping more writes each
+    /// snapshot to a `snapshot_history` partition as well, so
+    /// [`hydrate_at_or_before`](nexus_store::SnapshotStore::hydrate_at_or_before)
+    /// can start from an older one; entries past the retention are pruned in
+    /// the same transaction as the commit.
+    #[cfg(feature = "snapshot")]
+    #[must_use]
+    pub const fn snapshot_retention(mut self, retention: SnapshotRetention) -> Self {
+        self.snapshot_retention = retention;
+        self
+    }
+
     /// Customise the `streams` partition options.
     ///
     /// The closure receives a pre-configured `KeyspaceCreateOptions` and
@@ -66,6 +88,8 @@ impl<S, E> FjallStoreBuilder<S, E> {
             streams_config: f,
             events_config: self.events_config,
             all_index: self.all_index,
+            #[cfg(feature = "snapshot")]
+            snapshot_retention: self.snapshot_retention,
         }
     }
 
@@ -89,6 +113,8 @@ impl<S, E> FjallStoreBuilder<S, E> {
             streams_config: self.streams_config,
             events_config: f,
             all_index: self.all_index,
+            #[cfg(feature = "snapshot")]
+            snapshot_retention: self.snapshot_retention,
         }
     }
 }
@@ -120,6 +146,8 @@ impl<S: KeyspaceConfig, E: KeyspaceConfig> FjallStoreBuilder<S, E> {
 
         #[cfg(feature = "snapshot")]
         let snapshots = db.keyspace("snapshots", point_read_defaults)?;
+        #[cfg(feature = "snapshot")]
+        let snapshot_history = db.keyspace("snapshot_history", point_read_defaults)?;
 
         let global = db.keyspace("global", point_read_defaults)?;
 
@@ -131,7 +159,11 @@ impl<S: KeyspaceConfig, E: KeyspaceConfig> FjallStoreBuilder<S, E> {
                 events_global,
                 global,
                 #[cfg(feature = "snapshot")]
-                snapshots,
+                SnapshotKeyspaces {
+                    latest: snapshots,
+                    history: snapshot_history,
+                    retention: self.snapshot_retention,
+                },
             )
             .with_all_index(self.all_index),
             notifiers: StreamNotifiers::new(),
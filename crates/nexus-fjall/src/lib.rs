@@ -6,19 +6,25 @@
 //! `read_all`), [`nexus_store::WakeSource`](nexus_store::wake::WakeSource)
 //! (the live wake the generic [`nexus_store::Subscription`] loop parks on),
 //! and — under the `snapshot` feature —
-//! [`nexus_store::SnapshotStore<Vec<u8>, Version>`].
+//! [`nexus_store::SnapshotStore<Vec<u8>, Version>`],
+//! [`nexus_store::SnapshotAppend`] and [`nexus_store::SnapshotBytes`].
 //!
 //! # Partitions
 //!
 //! - `streams` — `id_bytes → version counter`. Point-read optimized.
 //! - `events` — event rows. Scan-optimized, LZ4 compressed.
 //! - `global` — one key holding the store-wide [`GlobalSeq`] counter.
-//! - `snapshots` (under `snapshot` feature) — `id_bytes → snapshot blob`.
+//! - `snapshots` (under `snapshot` feature) — `id_bytes → snapshot blob`,
+//!   its state on a 16-byte offset so archived state reads in place.
+//! - `snapshot_history` (under `snapshot` feature) — retained snapshots,
+//!   keyed like `events`; empty unless the builder's `snapshot_retention`
+//!   keeps more than the latest.
 //!
 //! Every write goes through one atomic `fjall::write_tx`. `append`
 //! claims its [`GlobalSeq`] range inside the same transaction that
 //! writes the event rows and the new version counter — half-writes are
-//! unrepresentable.
+//! unrepresentable. `append_with_snapshot` stages the snapshot blob in that
+//! same transaction, so an atomic-mode snapshot never lags its events.
 //!
 //! # Why fjall's `bytes_1` feature is load-bearing
 //!
@@ -14,7 +14,11 @@ use nexus_store::StreamKey;
 
 use crate::error::FjallError;
 use crate::plan::StagedRow;
+#[cfg(feature = "snapshot")]
+use crate::wire_key::{decode_event_key, encode_event_key};
 use crate::wire_key::{decode_stream_version, encode_stream_version};
+#[cfg(feature = "snapshot")]
+use nexus_store::SnapshotRetention;
 
 mod sealed {
     pub trait Sealed {}
@@ -102,6 +106,19 @@ pub enum AllIndex {
     Disabled,
 }
 
+/// The snapshot keyspaces plus how many snapshots they keep per id.
+#[cfg(feature = "snapshot")]
+pub struct SnapshotKeyspaces {
+    /// `id_bytes → snapshot blob` — the latest snapshot per id.
+    pub latest: SingleWriterTxKeyspace,
+    /// Retained snapshots per id, keyed like `events`:
+    /// `[u16 BE id_len][id][u64 BE version]`. Empty under
+    /// [`SnapshotRetention::LATEST`].
+    pub history: SingleWriterTxKeyspace,
+    /// How many snapshots are kept per id.
+    pub retention: SnapshotRetention,
+}
+
 /// The opened fjall keyspaces plus the codecs that read and write them — the
 /// crate's **one** owner of the physical layout.
 ///
@@ -122,7 +139,7 @@ pub struct Partitions {
     events_global: SingleWriterTxKeyspace,
     global: SingleWriterTxKeyspace,
     #[cfg(feature = "snapshot")]
-    snapshots: SingleWriterTxKeyspace,
+    snapshots: SnapshotKeyspaces,
     /// Whether the `$all` index (`events_global`) is maintained — gates the
     /// second write in [`stage_event`](Self::stage_event) and `read_all`.
     mode: AllIndex,
@@ -137,7 +154,7 @@ impl Partitions {
         events: SingleWriterTxKeyspace,
         events_global: SingleWriterTxKeyspace,
         global: SingleWriterTxKeyspace,
-        #[cfg(feature = "snapshot")] snapshots: SingleWriterTxKeyspace,
+        #[cfg(feature = "snapshot")] snapshots: SnapshotKeyspaces,
     ) -> Self {
         Self {
             streams,
@@ -250,20 +267,71 @@ impl Partitions {
         self.streams.inner().iter()
     }
 
-    // ----- snapshots (best-effort, outside the event tx) ----------------
+    // ----- snapshots (own tx, never the event tx) ----------------------
 
     /// Point-read a snapshot blob by id.
     #[cfg(feature = "snapshot")]
     pub fn read_snapshot(&self, id: &[u8]) -> Result<Option<Slice>, FjallError> {
-        self.snapshots.get(id).map_err(FjallError::Io)
+        self.snapshots.latest.get(id).map_err(FjallError::Io)
     }
 
-    /// Write a snapshot blob for id (best-effort, non-transactional).
+    /// Stage a snapshot blob for id at `version` within `tx`: it replaces the
+    /// latest, and — when more than the latest is retained — is also written
+    /// to the history, whose oldest entries beyond the retention are pruned.
     #[cfg(feature = "snapshot")]
-    pub fn write_snapshot(&self, id: &[u8], bytes: &[u8]) -> Result<(), FjallError> {
-        self.snapshots
-            .insert(id, Slice::from(bytes))
-            .map_err(FjallError::Io)
+    pub fn stage_snapshot(
+        &self,
+        tx: &mut SingleWriterWriteTx<'_>,
+        id: &[u8],
+        version: u64,
+        bytes: &[u8],
+    ) -> Result<(), FjallError> {
+        let slice = Slice::from(bytes);
+        tx.insert(&self.snapshots.latest, id, slice.clone());
+
+        let keep = self.snapshots.retention.keep().get();
+        let (lower, upper) = snapshot_history_bounds(id, version)?;
+        if keep > 1 {
+            tx.insert(&self.snapshots.history, upper, slice);
+        }
+        // Newest first; everything past the first `keep` goes. Under `LATEST`
+        // that is every entry, clearing history left by a wider retention.
+        let stale = tx
+            .range(&self.snapshots.history, lower..=history_key(id, u64::MAX)?)
+            .rev()
+            .skip(if keep > 1 { keep } else { 0 })
+            .map(|guard| guard.key().map_err(FjallError::Io))
+            .collect::<Result<Vec<_>, _>>()?;
+        for key in stale {
+            tx.remove(&self.snapshots.history, key);
+        }
+        Ok(())
+    }
+
+    /// Snapshot blobs kept in the history for id at or before `version`,
+    /// newest first, with the version each was saved at.
+    #[cfg(feature = "snapshot")]
+    pub fn snapshot_history(
+        &self,
+        id: &[u8],
+        version: u64,
+    ) -> Result<impl Iterator<Item = Result<(u64, Slice), FjallError>>, FjallError> {
+        let (lower, upper) = snapshot_history_bounds(id, version)?;
+        Ok(self
+            .snapshots
+            .history
+            .inner()
+            .range(lower..=upper)
+            .rev()
+            .map(|guard| {
+                let (key, value) = guard.into_inner().map_err(FjallError::Io)?;
+                let (_, saved_at) =
+                    decode_event_key(&key).map_err(|_| FjallError::CorruptValue {
+                        stream_id: ErrorId::from_display(&String::from_utf8_lossy(&key)),
+                        version: None,
+                    })?;
+                Ok((saved_at, value))
+            }))
     }
 
     // ----- white-box test access ----------------------------------------
@@ -282,3 +350,20 @@ impl Partitions {
         &self.global
     }
 }
+
+/// The `snapshot_history` key for id at `version` — the `events` key layout.
+#[cfg(feature = "snapshot")]
+fn history_key(id: &[u8], version: u64) -> Result<Vec<u8>, FjallError> {
+    encode_event_key(id, version).map_err(|e| FjallError::InvalidInput {
+        stream_id: ErrorId::from_display(&String::from_utf8_lossy(id)),
+        version,
+        reason: crate::error::reason_label(&e),
+    })
+}
+
+/// Inclusive `snapshot_history` key bounds for id, from its first possible
+/// entry up to `version`.
+#[cfg(feature = "snapshot")]
+fn snapshot_history_bounds(id: &[u8], version: u64) -> Result<(Vec<u8>, Vec<u8>), FjallError> {
+    Ok((history_key(id, 0)?, history_key(id, version)?))
+}
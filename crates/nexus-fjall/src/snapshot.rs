@@ -1,39 +1,112 @@
 //! Snapshot value codec (feature `snapshot`).
 //!
 //! The snapshot blob layout is fjall-private and distinct from the event
-//! wire format: `[u32 LE schema_version][u64 BE version][payload]`. Used only
-//! by the `SnapshotStore<Vec<u8>, Version>` impl in [`crate::store`].
+//! wire format: `[u32 LE 0][u32 LE schema_version][u64 BE version][payload]`.
+//! The 16-byte header puts the payload on a
+//! [`PAYLOAD_ALIGN`](nexus_store::wire::PAYLOAD_ALIGN) boundary of the value —
+//! the same invariant `encode_frame` gives event payloads — so archived state
+//! can be read in place. The leading zero tells it apart from the older
+//! 12-byte `[u32 LE schema_version][u64 BE version][payload]` layout, whose
+//! first word is a non-zero schema version; both decode. Used only by the
+//! snapshot store impls in [`crate::store`].
 
 use crate::wire_key::DecodeError;
 
-/// Size of the snapshot value header: `[u32 LE schema_version][u64 BE version]`.
-const SNAPSHOT_VALUE_HEADER_SIZE: usize = 12;
+/// Size of the snapshot value header:
+/// `[u32 LE 0][u32 LE schema_version][u64 BE version]`.
+const SNAPSHOT_VALUE_HEADER_SIZE: usize = 16;
 
-/// Encode a snapshot value as `[u32 LE schema_version][u64 BE version][payload]`.
+/// Size of the legacy header: `[u32 LE schema_version][u64 BE version]`.
+const LEGACY_SNAPSHOT_VALUE_HEADER_SIZE: usize = 12;
+
+/// Leading word of the current layout. Never a legacy first word: that is
+/// the schema version, which is non-zero.
+const ALIGNED_LAYOUT_MARKER: u32 = 0;
+
+/// Encode a snapshot value as
+/// `[u32 LE 0][u32 LE schema_version][u64 BE version][payload]`.
 pub fn encode_snapshot_value(buf: &mut Vec<u8>, schema_version: u32, version: u64, payload: &[u8]) {
     buf.clear();
     buf.reserve(SNAPSHOT_VALUE_HEADER_SIZE + payload.len());
+    buf.extend_from_slice(&ALIGNED_LAYOUT_MARKER.to_le_bytes());
     buf.extend_from_slice(&schema_version.to_le_bytes());
     buf.extend_from_slice(&version.to_be_bytes());
     buf.extend_from_slice(payload);
 }
 
-/// Decode a snapshot value from `[u32 LE schema_version][u64 BE version][payload]`.
+/// Decode a snapshot value in either layout into
+/// `(schema_version, version, payload_offset)`.
+///
+/// # Errors
+///
+/// Returns [`DecodeError::ValueTooShort`] if value is shorter than its header.
+pub fn decode_snapshot_header(value: &[u8]) -> Result<(u32, u64, usize), DecodeError> {
+    let too_short = |min| DecodeError::ValueTooShort {
+        min,
+        actual: value.len(),
+    };
+    let Some(first) = value.get(..4) else {
+        return Err(too_short(LEGACY_SNAPSHOT_VALUE_HEADER_SIZE));
+    };
+    let (schema_at, header) =
+        if u32::from_le_bytes([first[0], first[1], first[2], first[3]]) == ALIGNED_LAYOUT_MARKER {
+            (4, SNAPSHOT_VALUE_HEADER_SIZE)
+        } else {
+            (0, LEGACY_SNAPSHOT_VALUE_HEADER_SIZE)
+        };
+    if value.len() < header {
+        return Err(too_short(header));
+    }
+    let s = &value[schema_at..header];
+    let schema_version = u32::from_le_bytes([s[0], s[1], s[2], s[3]]);
+    let version = u64::from_be_bytes([s[4], s[5], s[6], s[7], s[8], s[9], s[10], s[11]]);
+    Ok((schema_version, version, header))
+}
+
+/// Decode a snapshot value in either layout into
+/// `(schema_version, version, payload)`.
 ///
 /// # Errors
 ///
-/// Returns [`DecodeError::ValueTooShort`] if value is shorter than the header.
+/// Returns [`DecodeError::ValueTooShort`] if value is shorter than its header.
 pub fn decode_snapshot_value(value: &[u8]) -> Result<(u32, u64, &[u8]), DecodeError> {
-    if value.len() < SNAPSHOT_VALUE_HEADER_SIZE {
-        return Err(DecodeError::ValueTooShort {
-            min: SNAPSHOT_VALUE_HEADER_SIZE,
-            actual: value.len(),
-        });
+    let (schema_version, version, offset) = decode_snapshot_header(value)?;
+    Ok((schema_version, version, &value[offset..]))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_starts_on_a_payload_align_offset() {
+        let mut buf = Vec::new();
+        encode_snapshot_value(&mut buf, 3, 42, b"state");
+        let (schema_version, version, offset) = decode_snapshot_header(&buf).unwrap();
+        assert_eq!((schema_version, version), (3, 42));
+        assert_eq!(offset % nexus_store::wire::PAYLOAD_ALIGN, 0);
+        assert_eq!(&buf[offset..], b"state");
+    }
+
+    #[test]
+    fn legacy_layout_still_decodes() {
+        let mut legacy = Vec::new();
+        legacy.extend_from_slice(&3_u32.to_le_bytes());
+        legacy.extend_from_slice(&42_u64.to_be_bytes());
+        legacy.extend_from_slice(b"state");
+        assert_eq!(
+            decode_snapshot_value(&legacy).unwrap(),
+            (3, 42, &b"state"[..])
+        );
+    }
+
+    #[test]
+    fn truncated_values_are_rejected() {
+        let mut buf = Vec::new();
+        encode_snapshot_value(&mut buf, 1, 1, b"");
+        assert!(decode_snapshot_value(&buf[..15]).is_err());
+        assert!(decode_snapshot_value(&buf[..3]).is_err());
+        assert!(decode_snapshot_value(&1_u32.to_le_bytes()).is_err());
     }
-    let schema_version = u32::from_le_bytes([value[0], value[1], value[2], value[3]]);
-    let version = u64::from_be_bytes([
-        value[4], value[5], value[6], value[7], value[8], value[9], value[10], value[11],
-    ]);
-    let payload = &value[12..];
-    Ok((schema_version, version, payload))
 }
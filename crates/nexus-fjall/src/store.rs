@@ -5,6 +5,7 @@ use crate::partition::{AllIndex, Partitions};
 use crate::plan;
 use crate::scan::{GlobalScan, ScanCursor, StreamScan};
 use crate::subscription_id::OwnedStreamId;
+use fjall::SingleWriterWriteTx;
 use nexus::{ErrorId, Version};
 use nexus_store::PendingEnvelope;
 use nexus_store::StreamKey;
@@ -22,8 +23,9 @@ use std::sync::Arc;
 /// stores event rows keyed by `[u16 BE id_len][id_bytes][u64 BE version]`.
 ///
 /// The `global` partition holds the store-wide global sequence counter.
-/// When the `snapshot` feature is enabled, an additional `snapshots`
-/// partition is available for storing aggregate snapshots.
+/// When the `snapshot` feature is enabled, additional `snapshots` and
+/// `snapshot_history` partitions store the latest and the retained
+/// aggregate snapshots.
 ///
 /// Use [`FjallStore::builder`] to configure and open a store.
 pub struct FjallStore {
@@ -75,45 +77,27 @@ impl FjallStore {
     }
 }
 
-/// Map a neutral [`plan::PlanError`] into the single-stream [`AppendError`]
-/// domain. A version-sequence overflow becomes a `Store(VersionOverflow)`, NOT
-/// a `Conflict` — overflow is not a retry-eligible concurrency conflict (rule
-/// 3), and this matches the atomic-append path's handling.
-fn append_plan_err(id: &StreamKey, e: &plan::PlanError) -> AppendError<FjallError> {
-    match *e {
-        plan::PlanError::Conflict { expected, actual } => AppendError::Conflict {
-            stream_id: ErrorId::from_display(id),
-            expected,
-            actual,
-        },
-        plan::PlanError::VersionOverflow => AppendError::Store(FjallError::VersionOverflow),
-        plan::PlanError::GlobalSeqOverflow => AppendError::Store(FjallError::GlobalSeqOverflow),
-        plan::PlanError::InvalidInput { version, reason } => {
-            AppendError::Store(FjallError::InvalidInput {
-                stream_id: ErrorId::from_display(id),
-                version,
-                reason,
-            })
-        }
-    }
-}
-
-impl RawEventStore for FjallStore {
-    type Error = FjallError;
-    type Stream = ScanCursor<StreamScan>;
-    type AllPosition = GlobalSeq;
-    type AllStream = ScanCursor<GlobalScan>;
-
+impl FjallStore {
+    /// The single-stream append body: `stage` runs inside the same
+    /// transaction after the events and counters are staged, so whatever it
+    /// writes commits with them.
     #[allow(
         clippy::significant_drop_tightening,
         reason = "tx must be held across concurrency check + inserts + commit"
     )]
-    async fn append(
+    fn append_staging<F>(
         &self,
         id: &StreamKey,
         expected_version: Option<Version>,
         envelopes: &[PendingEnvelope],
-    ) -> Result<(), AppendError<Self::Error>> {
+        stage: F,
+    ) -> Result<(), AppendError<FjallError>>
+    where
+        F: FnOnce(
+            &mut SingleWriterWriteTx<'_>,
+            &plan::PlannedRun,
+        ) -> Result<(), AppendError<FjallError>>,
+    {
         let id_bytes = id.as_ref();
 
         // Version check BEFORE empty-batch early return. An empty append
@@ -156,6 +140,7 @@ impl RawEventStore for FjallStore {
         self.partitions.set_global(&mut tx, planned.ending_global);
         self.partitions
             .set_version(&mut tx, id_bytes, planned.new_version);
+        stage(&mut tx, &planned)?;
 
         // Atomic cross-partition commit.
         tx.commit()
@@ -169,6 +154,45 @@ impl RawEventStore for FjallStore {
 
         Ok(())
     }
+}
+
+/// Map a neutral [`plan::PlanError`] into the single-stream [`AppendError`]
+/// domain. A version-sequence overflow becomes a `Store(VersionOverflow)`, NOT
+/// a `Conflict` — overflow is not a retry-eligible concurrency conflict (rule
+/// 3), and this matches the atomic-append path's handling.
+fn append_plan_err(id: &StreamKey, e: &plan::PlanError) -> AppendError<FjallError> {
+    match *e {
+        plan::PlanError::Conflict { expected, actual } => AppendError::Conflict {
+            stream_id: ErrorId::from_display(id),
+            expected,
+            actual,
+        },
+        plan::PlanError::VersionOverflow => AppendError::Store(FjallError::VersionOverflow),
+        plan::PlanError::GlobalSeqOverflow => AppendError::Store(FjallError::GlobalSeqOverflow),
+        plan::PlanError::InvalidInput { version, reason } => {
+            AppendError::Store(FjallError::InvalidInput {
+                stream_id: ErrorId::from_display(id),
+                version,
+                reason,
+            })
+        }
+    }
+}
+
+impl RawEventStore for FjallStore {
+    type Error = FjallError;
+    type Stream = ScanCursor<StreamScan>;
+    type AllPosition = GlobalSeq;
+    type AllStream = ScanCursor<GlobalScan>;
+
+    async fn append(
+        &self,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.append_staging(id, expected_version, envelopes, |_, _| Ok(()))
+    }
 
     async fn read_stream(
         &self,
@@ -219,12 +243,79 @@ impl RawEventStore for FjallStore {
 
 #[cfg(feature = "snapshot")]
 mod snapshot_impl {
-    use super::{ErrorId, FjallError, FjallStore, Version};
-    use crate::snapshot::{decode_snapshot_value, encode_snapshot_value};
+    use super::{ErrorId, FjallError, FjallStore, StreamKey, Version};
+    use crate::snapshot::{decode_snapshot_header, decode_snapshot_value, encode_snapshot_value};
+    use bytes::Bytes;
     use nexus::Id;
-    use nexus_store::state::SnapshotStore;
+    use nexus_store::PendingEnvelope;
+    use nexus_store::error::AppendError;
+    use nexus_store::state::{PendingSnapshot, SnapshotAppend, SnapshotBytes, SnapshotStore};
     use std::num::NonZeroU32;
 
+    /// The snapshot is staged after the events and both counters, in the
+    /// append's own `write_tx`: one commit, one journal sync, and no crash
+    /// window between the events and the snapshot that covers them.
+    impl SnapshotAppend for FjallStore {
+        async fn append_with_snapshot(
+            &self,
+            id: &StreamKey,
+            expected_version: Option<Version>,
+            envelopes: &[PendingEnvelope],
+            snapshot: PendingSnapshot<'_>,
+        ) -> Result<(), AppendError<FjallError>> {
+            self.append_staging(id, expected_version, envelopes, |tx, planned| {
+                let version = snapshot.version().as_u64();
+                if version != planned.new_version {
+                    return Err(AppendError::Store(FjallError::InvalidInput {
+                        stream_id: ErrorId::from_display(id),
+                        version,
+                        reason: ErrorId::from_display(&"snapshot version is not the last appended"),
+                    }));
+                }
+                let mut buf = Vec::new();
+                encode_snapshot_value(
+                    &mut buf,
+                    snapshot.schema_version().get(),
+                    version,
+                    snapshot.state(),
+                );
+                self.partitions
+                    .stage_snapshot(tx, id.as_ref(), version, &buf)
+                    .map_err(AppendError::Store)
+            })
+        }
+    }
+
+    /// Zero-copy: the state bytes are a slice of the stored value's own
+    /// buffer. A value in the current layout keeps them on a 16-byte offset
+    /// (see [`crate::snapshot`]); whether the buffer itself starts aligned is
+    /// up to the allocator, so archived-state readers still check.
+    impl SnapshotBytes<Version> for FjallStore {
+        async fn hydrate_bytes(
+            &self,
+            id: &impl Id,
+            schema_version: NonZeroU32,
+        ) -> Result<Option<(Version, Bytes)>, FjallError> {
+            let Some(slice) = self.partitions.read_snapshot(id.as_ref())? else {
+                return Ok(None);
+            };
+            let corrupt = || FjallError::CorruptValue {
+                stream_id: ErrorId::from_display(id),
+                version: None,
+            };
+
+            let (schema_version_raw, version_raw, offset) =
+                decode_snapshot_header(&slice).map_err(|_| corrupt())?;
+            if schema_version_raw != schema_version.get() {
+                return Ok(None);
+            }
+            let version = Version::new(version_raw).ok_or_else(corrupt)?;
+
+            let value: Bytes = slice.into();
+            Ok(Some((version, value.slice(offset..))))
+        }
+    }
+
     impl SnapshotStore<Vec<u8>, Version> for FjallStore {
         type Error = FjallError;
 
@@ -266,7 +357,64 @@ mod snapshot_impl {
         ) -> Result<(), FjallError> {
             let mut buf = Vec::new();
             encode_snapshot_value(&mut buf, schema_version.get(), position.as_u64(), state);
-            self.partitions.write_snapshot(id.as_ref(), &buf)
+            // Latest, history and pruning land together or not at all.
+            let mut tx = self.db.write_tx();
+            self.partitions
+                .stage_snapshot(&mut tx, id.as_ref(), position.as_u64(), &buf)?;
+            tx.commit().map_err(FjallError::Io)
+        }
+
+        async fn hydrate_with_schema(
+            &self,
+            id: &impl Id,
+        ) -> Result<Option<(NonZeroU32, Version, Vec<u8>)>, FjallError> {
+            let Some(bytes) = self.partitions.read_snapshot(id.as_ref())? else {
+                return Ok(None);
+            };
+            let corrupt = || FjallError::CorruptValue {
+                stream_id: ErrorId::from_display(id),
+                version: None,
+            };
+
+            let (schema_version_raw, version_raw, payload) =
+                decode_snapshot_value(&bytes).map_err(|_| corrupt())?;
+            let schema_version = NonZeroU32::new(schema_version_raw).ok_or_else(corrupt)?;
+            let version = Version::new(version_raw).ok_or_else(corrupt)?;
+
+            Ok(Some((schema_version, version, payload.to_vec())))
+        }
+
+        /// Checks the latest snapshot, then walks the `snapshot_history`
+        /// partition newest-first. A corrupt latest snapshot is skipped rather
+        /// than reported, so a retained older one can still serve the load.
+        async fn hydrate_at_or_before(
+            &self,
+            id: &impl Id,
+            schema_version: NonZeroU32,
+            position: Version,
+        ) -> Result<Option<(Version, Vec<u8>)>, FjallError> {
+            match self.hydrate(id, schema_version).await {
+                Ok(Some(hit)) if hit.0 <= position => return Ok(Some(hit)),
+                Ok(_) | Err(FjallError::CorruptValue { .. }) => {}
+                Err(e) => return Err(e),
+            }
+            let corrupt = |version| FjallError::CorruptValue {
+                stream_id: ErrorId::from_display(id),
+                version: Some(version),
+            };
+            for entry in self
+                .partitions
+                .snapshot_history(id.as_ref(), position.as_u64())?
+            {
+                let (version_raw, bytes) = entry?;
+                let (schema_version_raw, _, payload) =
+                    decode_snapshot_value(&bytes).map_err(|_| corrupt(version_raw))?;
+                if schema_version_raw == schema_version.get() {
+                    let version = Version::new(version_raw).ok_or_else(|| corrupt(version_raw))?;
+                    return Ok(Some((version, payload.to_vec())));
+                }
+            }
+            Ok(None)
         }
     }
 }
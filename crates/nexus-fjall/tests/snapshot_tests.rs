@@ -13,9 +13,10 @@
 use std::num::NonZeroU32;
 
 use nexus::Version;
-use nexus_fjall::FjallStore;
-use nexus_store::StreamKey;
-use nexus_store::state::SnapshotStore;
+use nexus_fjall::{FjallError, FjallStore};
+use nexus_store::error::AppendError;
+use nexus_store::state::{PendingSnapshot, SnapshotAppend, SnapshotStore};
+use nexus_store::{SnapshotRetention, StreamKey};
 
 const SV1: NonZeroU32 = NonZeroU32::MIN;
 
@@ -117,6 +118,132 @@ async fn snapshot_persists_across_reopen() {
     }
 }
 
+#[tokio::test]
+async fn hydrate_with_schema_reads_past_a_schema_mismatch() {
+    let (store, _dir) = temp_store();
+    let id = sk("agg-1");
+    setup_stream(&store, &id, 5).await;
+
+    store
+        .commit(&id, SV1, Version::new(5).unwrap(), &vec![7, 8])
+        .await
+        .unwrap();
+
+    let sv2 = NonZeroU32::new(2).unwrap();
+    assert!(store.hydrate(&id, sv2).await.unwrap().is_none());
+    let (schema_version, version, state) = store.hydrate_with_schema(&id).await.unwrap().unwrap();
+    assert_eq!(schema_version, SV1);
+    assert_eq!(version, Version::new(5).unwrap());
+    assert_eq!(state, vec![7, 8]);
+
+    assert!(
+        store
+            .hydrate_with_schema(&sk("nope"))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+const fn v(n: u64) -> Version {
+    Version::new(n).unwrap()
+}
+
+fn retained_store(path: &std::path::Path, keep: usize) -> FjallStore {
+    FjallStore::builder(path)
+        .snapshot_retention(SnapshotRetention::keep_last(
+            std::num::NonZeroUsize::new(keep).unwrap(),
+        ))
+        .open()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn hydrate_at_or_before_picks_the_nearest_retained_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = retained_store(&dir.path().join("db"), 3);
+    let id = sk("agg-1");
+
+    for n in [2_u8, 4, 6, 8] {
+        store.commit(&id, SV1, v(n.into()), &vec![n]).await.unwrap();
+    }
+
+    // Latest still serves plain hydrate.
+    assert_eq!(
+        store.hydrate(&id, SV1).await.unwrap(),
+        Some((v(8), vec![8]))
+    );
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(9)).await.unwrap(),
+        Some((v(8), vec![8]))
+    );
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(7)).await.unwrap(),
+        Some((v(6), vec![6]))
+    );
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(4)).await.unwrap(),
+        Some((v(4), vec![4]))
+    );
+    // v2 was pruned: only the last three are kept.
+    assert!(
+        store
+            .hydrate_at_or_before(&id, SV1, v(3))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn hydrate_at_or_before_skips_other_schema_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = retained_store(&dir.path().join("db"), 3);
+    let id = sk("agg-1");
+    let sv2 = NonZeroU32::new(2).unwrap();
+
+    store.commit(&id, SV1, v(3), &vec![1]).await.unwrap();
+    store.commit(&id, sv2, v(5), &vec![2]).await.unwrap();
+
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(10)).await.unwrap(),
+        Some((v(3), vec![1]))
+    );
+    assert_eq!(
+        store.hydrate_at_or_before(&id, sv2, v(10)).await.unwrap(),
+        Some((v(5), vec![2]))
+    );
+}
+
+#[tokio::test]
+async fn latest_retention_only_serves_the_latest_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("db");
+    let id = sk("agg-1");
+    {
+        let store = retained_store(&path, 3);
+        store.commit(&id, SV1, v(2), &vec![2]).await.unwrap();
+        store.commit(&id, SV1, v(4), &vec![4]).await.unwrap();
+    }
+
+    // Reopened with the default retention: the next commit clears the
+    // history left behind by the wider one.
+    let store = FjallStore::builder(&path).open().unwrap();
+    store.commit(&id, SV1, v(6), &vec![6]).await.unwrap();
+
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(6)).await.unwrap(),
+        Some((v(6), vec![6]))
+    );
+    assert!(
+        store
+            .hydrate_at_or_before(&id, SV1, v(5))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
 // ── 3. Defensive Boundary Tests ────────────────────────────────────
 
 #[tokio::test]
@@ -178,3 +305,341 @@ async fn different_streams_have_separate_snapshots() {
     assert_eq!(version2, Version::new(10).unwrap());
     assert_eq!(state2, vec![2]);
 }
+
+// ── 5. Atomic Event + Snapshot Commit ──────────────────────────────
+
+fn envelopes(from: u64, to: u64) -> Vec<nexus_store::PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            nexus_store::envelope::pending_envelope(v(i))
+                .event_type("TestEvent")
+                .payload(vec![1])
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+async fn stream_len(store: &FjallStore, id: &StreamKey) -> usize {
+    use futures::TryStreamExt;
+    use nexus_store::store::RawEventStore;
+
+    let stream = store.read_stream(id, Version::INITIAL).await.unwrap();
+    let events: Vec<_> = stream.try_collect().await.unwrap();
+    events.len()
+}
+
+#[tokio::test]
+async fn append_with_snapshot_commits_both() {
+    let (store, _dir) = temp_store();
+    let id = sk("agg-1");
+
+    store
+        .append_with_snapshot(
+            &id,
+            None,
+            &envelopes(1, 3),
+            PendingSnapshot::new(SV1, v(3), &[7, 7]),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(stream_len(&store, &id).await, 3);
+    assert_eq!(
+        store.hydrate(&id, SV1).await.unwrap(),
+        Some((v(3), vec![7, 7]))
+    );
+}
+
+#[tokio::test]
+async fn append_with_snapshot_conflict_writes_neither() {
+    let (store, _dir) = temp_store();
+    let id = sk("agg-1");
+    setup_stream(&store, &id, 2).await;
+
+    let result = store
+        .append_with_snapshot(
+            &id,
+            Some(v(1)),
+            &envelopes(2, 3),
+            PendingSnapshot::new(SV1, v(3), &[7]),
+        )
+        .await;
+
+    assert!(matches!(result, Err(AppendError::Conflict { .. })));
+    assert_eq!(stream_len(&store, &id).await, 2);
+    assert!(store.hydrate(&id, SV1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn append_with_snapshot_rejects_a_snapshot_off_the_last_event() {
+    let (store, _dir) = temp_store();
+    let id = sk("agg-1");
+
+    let result = store
+        .append_with_snapshot(
+            &id,
+            None,
+            &envelopes(1, 3),
+            PendingSnapshot::new(SV1, v(2), &[7]),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(AppendError::Store(FjallError::InvalidInput {
+            version: 2,
+            ..
+        }))
+    ));
+    assert_eq!(stream_len(&store, &id).await, 0);
+    assert!(store.hydrate(&id, SV1).await.unwrap().is_none());
+}
+
+// ── 6. Shared-Buffer and Archived Hydration ────────────────────────
+
+#[tokio::test]
+async fn hydrate_bytes_matches_hydrate() {
+    use nexus_store::state::SnapshotBytes;
+
+    let (store, _dir) = temp_store();
+    let id = sk("agg-bytes");
+    store.commit(&id, SV1, v(4), &vec![9, 8, 7]).await.unwrap();
+
+    let (at, bytes) = store.hydrate_bytes(&id, SV1).await.unwrap().unwrap();
+    assert_eq!(at, v(4));
+    assert_eq!(&bytes[..], [9, 8, 7]);
+    assert!(
+        store
+            .hydrate_bytes(&id, NonZeroU32::new(2).unwrap())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        store
+            .hydrate_bytes(&sk("missing"), SV1)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn archived_state_round_trips_through_fjall() {
+    use nexus_store::ArchivedSnapshotStore;
+
+    #[derive(Debug, PartialEq, Eq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+    struct Tally {
+        count: u64,
+        label: String,
+    }
+
+    let (store, _dir) = temp_store();
+    let archived = ArchivedSnapshotStore::new(store);
+    let id = sk("agg-archived");
+    let tally = Tally {
+        count: 12,
+        label: "dozen".into(),
+    };
+    archived.commit(&id, SV1, v(12), &tally).await.unwrap();
+
+    let snapshot = archived
+        .hydrate_archived::<Tally>(&id, SV1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(snapshot.version(), v(12));
+    assert_eq!(snapshot.archived().count, 12);
+    assert_eq!(snapshot.archived().label.as_str(), "dozen");
+
+    let (at, owned): (Version, Tally) = archived.hydrate(&id, SV1).await.unwrap().unwrap();
+    assert_eq!((at, owned), (v(12), tally));
+}
+
+mod atomic_repository {
+    use super::{SV1, sk, temp_store, v};
+    use std::fmt;
+    use std::num::NonZeroU64;
+
+    use nexus::{Aggregate, AggregateState, DomainEvent, Events, Id, Message};
+    use nexus_store::state::{CodecSnapshotStore, EveryNEvents, PayloadBytes, SnapshotStore};
+    use nexus_store::{Decode, Encode, PersistedEnvelope, Repository, Snapshotting, Store};
+
+    #[derive(Debug, Clone)]
+    struct Added(u8);
+
+    impl Message for Added {}
+
+    impl DomainEvent for Added {
+        fn name(&self) -> &'static str {
+            "Added"
+        }
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct Total(u64);
+
+    impl AggregateState for Total {
+        type Event = Added;
+
+        fn initial() -> Self {
+            Self::default()
+        }
+
+        fn apply(self, event: &Added) -> Self {
+            Self(self.0 + u64::from(event.0))
+        }
+    }
+
+    #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+    struct TotalId(String);
+
+    impl fmt::Display for TotalId {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl AsRef<[u8]> for TotalId {
+        fn as_ref(&self) -> &[u8] {
+            self.0.as_bytes()
+        }
+    }
+
+    impl Id for TotalId {
+        const BYTE_LEN: usize = 0;
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("never")]
+    struct Never;
+
+    #[derive(Debug)]
+    struct Counter;
+
+    impl Aggregate for Counter {
+        type State = Total;
+        type Error = Never;
+        type Id = TotalId;
+    }
+
+    /// Events as one byte, state as a little-endian `u64`.
+    struct Bytes;
+
+    fn invalid() -> std::io::Error {
+        std::io::Error::from(std::io::ErrorKind::InvalidData)
+    }
+
+    impl Encode<Added> for Bytes {
+        type Error = std::io::Error;
+
+        fn encode(&self, event: &Added) -> Result<bytes::Bytes, Self::Error> {
+            Ok(bytes::Bytes::from(vec![event.0]))
+        }
+    }
+
+    impl Decode<Added> for Bytes {
+        type Output<'a> = Added;
+        type Error = std::io::Error;
+
+        fn decode<'a>(&'a self, env: &'a PersistedEnvelope) -> Result<Added, Self::Error> {
+            env.payload()
+                .first()
+                .copied()
+                .map(Added)
+                .ok_or_else(invalid)
+        }
+    }
+
+    impl Encode<Total> for Bytes {
+        type Error = std::io::Error;
+
+        fn encode(&self, state: &Total) -> Result<bytes::Bytes, Self::Error> {
+            Ok(bytes::Bytes::copy_from_slice(&state.0.to_le_bytes()))
+        }
+    }
+
+    impl Decode<Total> for Bytes {
+        type Output<'a> = Total;
+        type Error = std::io::Error;
+
+        fn decode<'a>(&'a self, env: &'a PersistedEnvelope) -> Result<Total, Self::Error> {
+            let raw = <[u8; 8]>::try_from(env.payload()).map_err(|_| invalid())?;
+            Ok(Total(u64::from_le_bytes(raw)))
+        }
+    }
+
+    #[tokio::test]
+    async fn triggered_snapshot_lands_with_its_events() {
+        let (raw, _dir) = temp_store();
+        let store = Store::new(raw);
+        let inner = store.repository::<Counter>().codec(Bytes).build();
+        let repo = Snapshotting::new(
+            inner,
+            CodecSnapshotStore::new(store.clone(), Bytes),
+            EveryNEvents(NonZeroU64::new(2).unwrap()),
+            SV1,
+            false,
+        )
+        .atomic();
+        let id = TotalId("total-1".into());
+
+        let mut root = repo.load(id.clone()).await.unwrap();
+        for n in 1..=3 {
+            repo.save(&mut root, &Events::<_, 0>::new(Added(n)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(root.state(), &Total(6));
+
+        // EveryNEvents(2) fired on the second save only.
+        let (at, bytes) = store.hydrate(&sk("total-1"), SV1).await.unwrap().unwrap();
+        assert_eq!(at, v(2));
+        assert_eq!(bytes, 3_u64.to_le_bytes());
+
+        let loaded = repo.load(id).await.unwrap();
+        assert_eq!(loaded.state(), &Total(6));
+        assert_eq!(loaded.version(), Some(v(3)));
+        assert_eq!(repo.stats().hits, 1);
+    }
+
+    #[tokio::test]
+    async fn payload_bytes_counts_every_save_since_the_last_snapshot() {
+        let (raw, _dir) = temp_store();
+        let store = Store::new(raw);
+        let inner = store.repository::<Counter>().codec(Bytes).build();
+        // Each `Added` encodes to one byte.
+        let repo = Snapshotting::new(
+            inner,
+            CodecSnapshotStore::new(store.clone(), Bytes),
+            PayloadBytes(NonZeroU64::new(3).unwrap()),
+            SV1,
+            false,
+        )
+        .atomic();
+        let id = TotalId("total-1".into());
+
+        let mut root = repo.load(id).await.unwrap();
+        for n in 1..=2 {
+            repo.save(&mut root, &Events::<_, 0>::new(Added(n)))
+                .await
+                .unwrap();
+        }
+        assert!(store.hydrate(&sk("total-1"), SV1).await.unwrap().is_none());
+
+        repo.save(&mut root, &Events::<_, 0>::new(Added(3)))
+            .await
+            .unwrap();
+        let (at, bytes) = store.hydrate(&sk("total-1"), SV1).await.unwrap().unwrap();
+        assert_eq!(at, v(3));
+        assert_eq!(bytes, 6_u64.to_le_bytes());
+
+        // The count restarts at the snapshot.
+        repo.save(&mut root, &Events::<_, 0>::new(Added(4)))
+            .await
+            .unwrap();
+        let (at, _) = store.hydrate(&sk("total-1"), SV1).await.unwrap().unwrap();
+        assert_eq!(at, v(3));
+    }
+}
@@ -26,7 +26,13 @@ pub fn aggregate(attr: TokenStream, item: TokenStream) -> TokenStream {
     .into()
 }
 
-#[proc_macro_derive(DomainEvent)]
+/// Derive `Message` + `DomainEvent` on an event enum.
+///
+/// `name()` returns the variant name. Each variant may carry a
+/// `#[schema_version = N]` attribute (`N >= 1`, default 1) declaring the
+/// schema version new events of that variant are written at; the derive
+/// emits `DomainEvent::SCHEMA_VERSIONS` and `schema_version()` from them.
+#[proc_macro_derive(DomainEvent, attributes(schema_version))]
 pub fn domain_event(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     match parse_domain_event(&ast) {
@@ -47,35 +53,42 @@ fn parse_domain_event(ast: &DeriveInput) -> Result<proc_macro2::TokenStream> {
                 ));
             }
 
-            let variant_arms: Vec<_> = data_enum
-                .variants
-                .iter()
-                .map(|variant| {
-                    let variant_ident = &variant.ident;
-                    let variant_name = variant_ident.to_string();
-                    match &variant.fields {
-                        syn::Fields::Unit => {
-                            quote! { #name::#variant_ident => #variant_name }
-                        }
-                        syn::Fields::Unnamed(_) => {
-                            quote! { #name::#variant_ident(..) => #variant_name }
-                        }
-                        syn::Fields::Named(_) => {
-                            quote! { #name::#variant_ident { .. } => #variant_name }
-                        }
-                    }
-                })
-                .collect();
+            let mut variant_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut version_arms = Vec::with_capacity(data_enum.variants.len());
+            let mut schema_entries = Vec::with_capacity(data_enum.variants.len());
+            for variant in &data_enum.variants {
+                let variant_ident = &variant.ident;
+                let variant_name = variant_ident.to_string();
+                let pattern = match &variant.fields {
+                    syn::Fields::Unit => quote! { #name::#variant_ident },
+                    syn::Fields::Unnamed(_) => quote! { #name::#variant_ident(..) },
+                    syn::Fields::Named(_) => quote! { #name::#variant_ident { .. } },
+                };
+                let version = version_const(parse_schema_version(variant)?);
+                variant_arms.push(quote! { #pattern => #variant_name });
+                version_arms.push(quote! { #pattern => #version });
+                schema_entries.push(quote! { (#variant_name, #version) });
+            }
 
             let expanded = quote! {
                 impl ::nexus::Message for #name {}
 
                 impl ::nexus::DomainEvent for #name {
+                    const SCHEMA_VERSIONS: &'static [(&'static str, ::nexus::Version)] = &[
+                        #(#schema_entries),*
+                    ];
+
                     fn name(&self) -> &'static str {
                         match self {
                             #(#variant_arms),*
                         }
                     }
+
+                    fn schema_version(&self) -> ::nexus::Version {
+                        match self {
+                            #(#version_arms),*
+                        }
+                    }
                 }
             };
 
@@ -89,6 +102,52 @@ fn parse_domain_event(ast: &DeriveInput) -> Result<proc_macro2::TokenStream> {
     }
 }
 
+/// Read a variant's `#[schema_version = N]` attribute (default 1).
+fn parse_schema_version(variant: &syn::Variant) -> Result<u64> {
+    let mut version = None;
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("schema_version") {
+            continue;
+        }
+        if version.is_some() {
+            return Err(Error::new_spanned(
+                attr,
+                "duplicate #[schema_version] attribute",
+            ));
+        }
+        let syn::Meta::NameValue(meta) = &attr.meta else {
+            return Err(Error::new_spanned(attr, "expected `#[schema_version = N]`"));
+        };
+        let syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) = &meta.value
+        else {
+            return Err(Error::new_spanned(
+                &meta.value,
+                "schema version must be an integer literal",
+            ));
+        };
+        let parsed: u64 = lit.base10_parse()?;
+        if parsed < 1 {
+            return Err(Error::new_spanned(lit, "schema version must be >= 1"));
+        }
+        version = Some(parsed);
+    }
+    Ok(version.unwrap_or(1))
+}
+
+/// A `const`-evaluable `::nexus::Version` expression for a version already
+/// validated `>= 1` at expansion time (the `None` arm is unreachable).
+fn version_const(version: u64) -> proc_macro2::TokenStream {
+    quote! {
+        match ::nexus::Version::new(#version) {
+            ::core::option::Option::Some(v) => v,
+            ::core::option::Option::None => ::nexus::Version::INITIAL,
+        }
+    }
+}
+
 /// Generates a unit struct with inherent `upcast` and `current_version`
 /// functions from annotated transform functions.
 ///
@@ -103,14 +162,40 @@ fn parse_domain_event(ast: &DeriveInput) -> Result<proc_macro2::TokenStream> {
 /// - `to = N` — target schema version (must be `from + 1`)
 /// - `rename = "NewName"` — optional event type rename
 ///
+/// A step may also declare `json_transforms` edits (needs `nexus-store`'s
+/// `json` feature), applied in order to the parsed payload object:
+///
+/// - `rename(old -> new)` — rename a field
+/// - `default(field = expr)` — add a field when absent (`expr` is anything
+///   `Value::from` accepts, including `serde_json::json!(...)`)
+/// - `remove(a, b)` — drop fields
+/// - `move_into(a, b -> nested)` — nest fields under an object
+/// - `map_value(field = path::to::fn)` — rewrite a value with a
+///   `fn(Value) -> Result<Value, _>`
+///
+/// Field names are identifiers or string literals. With edits declared,
+/// the function takes the edited object instead of bytes —
+/// `fn(&mut JsonObject) -> Result<(), Error>` — and runs after them for
+/// anything the edits cannot express (`Ok(())` when they say it all).
+/// `Error` must implement `From<JsonTransformError>`.
+///
 /// # Compile-time validation
 ///
 /// - `from >= 1`
 /// - `to == from + 1` for each transform (contiguity per step)
 /// - No duplicate `(event, from)` pairs
-/// - **Chain coverage**: for each event type, every schema version in
-///   `[1, current_version]` is reachable via a contiguous chain — gaps
-///   produce a compile error naming the missing step
+/// - **Renames**: no rename cycles (`A -> B -> A`, or `A -> A`), no steps
+///   on an event type after it is renamed away, and no overlaps — at most
+///   one rename into each event type, and none of that type's own steps
+///   below the version it is renamed into at
+/// - **Chain coverage**: for each event type, every schema version from
+///   its entry version (1, or the version it is renamed into at) to its
+///   current version is reachable via a contiguous chain — gaps produce a
+///   compile error naming the missing step
+/// - **Chain end**: every chain ends at the schema version the aggregate's
+///   event declares for it (`#[schema_version = N]` on the
+///   `#[derive(DomainEvent)]` variant) — checked by a `const` assertion
+///   against `EventOf<aggregate>`
 ///
 /// # Emitted output
 ///
@@ -123,8 +208,12 @@ fn parse_domain_event(ast: &DeriveInput) -> Result<proc_macro2::TokenStream> {
 ///   so call sites are `OrderTransforms::upcast(morsel)` — a `'static`
 ///   function pointer pluggable into [`EventStore::load_with`].
 /// - `pub fn current_version(event_type: &str) -> Option<Version>` — the
-///   write-path schema-version stamp lookup. Also associated; call as
-///   `OrderTransforms::current_version("EventName")`.
+///   chain-end lookup. Also associated; call as
+///   `OrderTransforms::current_version("EventName")`, or pass it to
+///   `RepositoryBuilder::transforms`.
+/// - `pub const REGISTRY: TransformRegistry<Error>` — every chain as data,
+///   one `TransformPath` per origin event type, so tooling and tests can
+///   enumerate and exercise each migration step.
 ///
 /// # Example
 ///
@@ -137,6 +226,19 @@ fn parse_domain_event(ast: &DeriveInput) -> Result<proc_macro2::TokenStream> {
 ///     }
 /// }
 ///
+/// #[nexus::transforms(aggregate = Customer, error = MyError)]
+/// impl CustomerTransforms {
+///     #[transform(
+///         event = "CustomerRegistered", from = 1, to = 2,
+///         rename(name -> full_name),
+///         default(tier = "basic"),
+///         move_into(street, city -> address),
+///     )]
+///     fn registered_v1_to_v2(_: &mut JsonObject) -> Result<(), MyError> {
+///         Ok(())
+///     }
+/// }
+///
 /// // Direct call:
 /// let upgraded = OrderTransforms::upcast(morsel)?;
 ///
@@ -154,12 +256,131 @@ pub fn transforms(attr: TokenStream, item: TokenStream) -> TokenStream {
     .into()
 }
 
+/// Generates a unit struct carrying a `UPCASTERS` chain of snapshot
+/// transforms from annotated functions.
+///
+/// The snapshot counterpart of [`macro@transforms`]: same function shape,
+/// keyed by the snapshot schema version instead of an event type.
+///
+/// # Attributes
+///
+/// - `error = Type` — the error type returned by transform functions
+///   (`std::error::Error + Send + Sync + 'static`, boxed by the chain)
+///
+/// Each method must be annotated with `#[transform(from = N, to = N + 1)]`
+/// and have the signature `fn(&[u8]) -> Result<Vec<u8>, Error>`.
+///
+/// # Compile-time validation
+///
+/// - `from >= 1`, and `to` fits a `u32` schema version
+/// - `to == from + 1` for each transform
+/// - No duplicate `from`
+/// - No gaps between the lowest `from` and the highest `to`
+///
+/// # Emitted output
+///
+/// A `pub struct <Name>;` plus an inherent impl block carrying the user's
+/// functions (with `#[transform]` attrs stripped) and
+/// `pub const UPCASTERS: SnapshotUpcasters`, ordered by `from`. Hand it to
+/// `CodecSnapshotStore::with_upcasters`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[nexus::snapshot_transforms(error = MyError)]
+/// impl AccountSnapshotTransforms {
+///     #[transform(from = 1, to = 2)]
+///     fn add_currency(state: &[u8]) -> Result<Vec<u8>, MyError> {
+///         // migrate v1 state bytes → v2
+///     }
+/// }
+///
+/// let snapshots = CodecSnapshotStore::new(store, codec)
+///     .with_upcasters(AccountSnapshotTransforms::UPCASTERS);
+/// ```
+#[proc_macro_attribute]
+pub fn snapshot_transforms(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = attr;
+    let ast = parse_macro_input!(item as syn::ItemImpl);
+    match parse_snapshot_transforms(&ast, args.into()) {
+        Ok(code) => code,
+        Err(e) => e.to_compile_error(),
+    }
+    .into()
+}
+
 struct TransformDef {
     fn_name: syn::Ident,
     event_type: String,
     from_version: u64,
     to_version: u64,
     rename: Option<String>,
+    json_edits: Vec<JsonEdit>,
+}
+
+/// One declarative `json_transforms` edit inside `#[transform(...)]`,
+/// applied in declaration order.
+enum JsonEdit {
+    /// `rename(a -> b)`
+    Rename { from: String, to: String },
+    /// `default(field = expr)`
+    Default { field: String, value: syn::Expr },
+    /// `remove(a, b)`
+    Remove { fields: Vec<String> },
+    /// `move_into(a, b -> nested)`
+    MoveInto { fields: Vec<String>, into: String },
+    /// `map_value(field = path::to::fn)`
+    MapValue { field: String, map: syn::Expr },
+}
+
+/// A JSON field name: a bare identifier (keywords allowed, `r#` stripped)
+/// or a string literal for names that are not identifiers.
+fn parse_field_name(input: syn::parse::ParseStream<'_>) -> Result<String> {
+    use syn::ext::IdentExt;
+    if input.peek(syn::LitStr) {
+        Ok(input.parse::<syn::LitStr>()?.value())
+    } else {
+        Ok(syn::Ident::parse_any(input)?.unraw().to_string())
+    }
+}
+
+/// `a, b, c ->` — the field list of `move_into`, up to and including the
+/// arrow.
+fn parse_fields_until_arrow(input: syn::parse::ParseStream<'_>) -> Result<Vec<String>> {
+    let mut fields = vec![parse_field_name(input)?];
+    while !input.peek(syn::Token![->]) {
+        input.parse::<syn::Token![,]>()?;
+        fields.push(parse_field_name(input)?);
+    }
+    input.parse::<syn::Token![->]>()?;
+    Ok(fields)
+}
+
+impl JsonEdit {
+    /// The `json_transforms` call applying this edit to `object`.
+    fn to_tokens(&self) -> proc_macro2::TokenStream {
+        match self {
+            Self::Rename { from, to } => quote! {
+                ::nexus_store::json_transforms::rename_field(object, #from, #to)?;
+            },
+            Self::Default { field, value } => quote! {
+                ::nexus_store::json_transforms::add_default(
+                    object,
+                    #field,
+                    ::nexus_store::json_transforms::Value::from(#value),
+                );
+            },
+            Self::Remove { fields } => quote! {
+                #(::nexus_store::json_transforms::remove_field(object, #fields);)*
+            },
+            Self::MoveInto { fields, into } => quote! {
+                ::nexus_store::json_transforms::move_into(object, &[#(#fields),*], #into)?;
+            },
+            Self::MapValue { field, map } => quote! {
+                ::nexus_store::json_transforms::map_value(object, #field, #map)?;
+            },
+        }
+    }
 }
 
 fn parse_transform_attr(method: &syn::ImplItemFn) -> Result<Option<TransformDef>> {
@@ -175,6 +396,7 @@ fn parse_transform_attr(method: &syn::ImplItemFn) -> Result<Option<TransformDef>
             let mut from_version: Option<u64> = None;
             let mut to_version: Option<u64> = None;
             let mut rename: Option<String> = None;
+            let mut json_edits = Vec::new();
 
             attr.parse_nested_meta(|meta| {
                 if meta.path.is_ident("event") {
@@ -189,12 +411,50 @@ fn parse_transform_attr(method: &syn::ImplItemFn) -> Result<Option<TransformDef>
                     let value = meta.value()?;
                     let lit: syn::LitInt = value.parse()?;
                     to_version = Some(lit.base10_parse()?);
-                } else if meta.path.is_ident("rename") {
+                } else if meta.path.is_ident("rename") && meta.input.peek(syn::Token![=]) {
                     let value = meta.value()?;
                     let lit: syn::LitStr = value.parse()?;
                     rename = Some(lit.value());
+                } else if meta.path.is_ident("rename") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let from = parse_field_name(&content)?;
+                    content.parse::<syn::Token![->]>()?;
+                    let to = parse_field_name(&content)?;
+                    json_edits.push(JsonEdit::Rename { from, to });
+                } else if meta.path.is_ident("default") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let field = parse_field_name(&content)?;
+                    content.parse::<syn::Token![=]>()?;
+                    let value = content.parse()?;
+                    json_edits.push(JsonEdit::Default { field, value });
+                } else if meta.path.is_ident("remove") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let fields = content
+                        .parse_terminated(parse_field_name, syn::Token![,])?
+                        .into_iter()
+                        .collect();
+                    json_edits.push(JsonEdit::Remove { fields });
+                } else if meta.path.is_ident("move_into") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let fields = parse_fields_until_arrow(&content)?;
+                    let into = parse_field_name(&content)?;
+                    json_edits.push(JsonEdit::MoveInto { fields, into });
+                } else if meta.path.is_ident("map_value") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let field = parse_field_name(&content)?;
+                    content.parse::<syn::Token![=]>()?;
+                    let map = content.parse()?;
+                    json_edits.push(JsonEdit::MapValue { field, map });
                 } else {
-                    return Err(meta.error("expected `event`, `from`, `to`, or `rename`"));
+                    return Err(meta.error(
+                        "expected `event`, `from`, `to`, `rename`, `default`, `remove`, \
+                         `move_into`, or `map_value`",
+                    ));
                 }
                 Ok(())
             })?;
@@ -214,6 +474,7 @@ fn parse_transform_attr(method: &syn::ImplItemFn) -> Result<Option<TransformDef>
                 from_version,
                 to_version,
                 rename,
+                json_edits,
             });
         }
     }
@@ -221,62 +482,61 @@ fn parse_transform_attr(method: &syn::ImplItemFn) -> Result<Option<TransformDef>
     Ok(transform_attr)
 }
 
-fn parse_transforms(
-    ast: &syn::ItemImpl,
-    args: proc_macro2::TokenStream,
-) -> Result<proc_macro2::TokenStream> {
-    // 1. Parse aggregate = Type, error = Type from outer attributes
-    let mut aggregate_type: Option<Type> = None;
-    let mut error_type: Option<Type> = None;
-    let parser = syn::meta::parser(|meta| {
-        if meta.path.is_ident("aggregate") {
-            aggregate_type = Some(meta.value()?.parse()?);
-        } else if meta.path.is_ident("error") {
-            error_type = Some(meta.value()?.parse()?);
+impl TransformDef {
+    /// The event type this step writes — the rename target, if any.
+    fn output_event_type(&self) -> &str {
+        self.rename.as_deref().unwrap_or(&self.event_type)
+    }
+
+    /// The bytes-level function the chain calls for this step: the user's
+    /// function itself, or the generated wrapper running the declared JSON
+    /// edits before it.
+    fn step_fn(&self) -> syn::Ident {
+        if self.json_edits.is_empty() {
+            self.fn_name.clone()
         } else {
-            return Err(meta.error("expected `aggregate` or `error`"));
+            quote::format_ident!("__nexus_json_{}", self.fn_name)
         }
-        Ok(())
-    });
-    syn::parse::Parser::parse2(parser, args)?;
-    let _aggregate_type = aggregate_type
-        .ok_or_else(|| Error::new(proc_macro2::Span::call_site(), "`aggregate` is required"))?;
-    let error_type = error_type
-        .ok_or_else(|| Error::new(proc_macro2::Span::call_site(), "`error` is required"))?;
+    }
 
-    // 2. Get the struct name from the impl block
-    let struct_ident = match &*ast.self_ty {
-        syn::Type::Path(p) => {
-            &p.path
-                .segments
-                .last()
-                .ok_or_else(|| Error::new_spanned(&ast.self_ty, "expected a type name"))?
-                .ident
-        }
-        _ => return Err(Error::new_spanned(&ast.self_ty, "expected a type name")),
-    };
-
-    // 3. Parse each method's #[transform] attributes
-    let mut transforms = Vec::new();
-    for item in &ast.items {
-        let method = match item {
-            syn::ImplItem::Fn(m) => m,
-            _ => continue,
-        };
-        if let Some(def) = parse_transform_attr(method)? {
-            transforms.push(def);
+    /// The generated wrapper for a step with declarative JSON edits: parse
+    /// once, apply the edits in order, hand the object to the user's
+    /// function, serialize once.
+    fn json_wrapper(&self, error_type: &Type) -> Option<proc_macro2::TokenStream> {
+        if self.json_edits.is_empty() {
+            return None;
         }
+        let fn_name = &self.fn_name;
+        let step_fn = self.step_fn();
+        let edits = self.json_edits.iter().map(JsonEdit::to_tokens);
+        Some(quote! {
+            #[doc(hidden)]
+            fn #step_fn(
+                payload: &[u8],
+            ) -> ::core::result::Result<::std::vec::Vec<u8>, #error_type> {
+                ::nexus_store::json_transforms::edit_payload(payload, |object| {
+                    #(#edits)*
+                    Self::#fn_name(object)
+                })
+            }
+        })
     }
+}
 
-    // 4. Validate from >= 1
-    for t in &transforms {
+/// Reject every malformed chain shape at expansion time.
+///
+/// A chain is the sequence of steps an event type's stored payloads go
+/// through, following renames: `(event, from) → (output, from + 1)`.
+fn validate_chains(transforms: &[TransformDef]) -> Result<()> {
+    // from >= 1
+    for t in transforms {
         if t.from_version < 1 {
             return Err(Error::new_spanned(&t.fn_name, "from version must be >= 1"));
         }
     }
 
-    // 5. Validate to == from + 1
-    for t in &transforms {
+    // to == from + 1
+    for t in transforms {
         if t.to_version != t.from_version + 1 {
             return Err(Error::new_spanned(
                 &t.fn_name,
@@ -289,9 +549,9 @@ fn parse_transforms(
         }
     }
 
-    // 6. Validate no duplicate (event, from)
+    // No duplicate (event, from) — two steps leaving the same version.
     let mut seen = HashSet::new();
-    for t in &transforms {
+    for t in transforms {
         let key = (t.event_type.clone(), t.from_version);
         if !seen.insert(key) {
             return Err(Error::new_spanned(
@@ -304,46 +564,234 @@ fn parse_transforms(
         }
     }
 
-    // 6b. Validate chain coverage per event type.
-    //
-    // For each event type, every schema version in [1, current_version]
-    // must be reachable through a contiguous chain. Find the smallest gap
-    // (i.e. the smallest `from` in [1, max_from] for which no transform
-    // exists) and reject with a diagnostic naming the missing step.
-    let mut from_versions_by_event: HashMap<String, HashSet<u64>> = HashMap::new();
-    let mut max_from_by_event: HashMap<String, u64> = HashMap::new();
-    for t in &transforms {
+    validate_renames(transforms)?;
+    validate_coverage(transforms)
+}
+
+/// Renames: no steps after a rename, no rename cycles, at most one rename
+/// into each event type, and no own steps below the version it is renamed
+/// into at.
+fn validate_renames(transforms: &[TransformDef]) -> Result<()> {
+    let renames: Vec<&TransformDef> = transforms.iter().filter(|t| t.rename.is_some()).collect();
+
+    // A renamed event type is never stored past the rename, so any later
+    // step on the old name is unreachable.
+    for r in &renames {
+        if let Some(t) = transforms
+            .iter()
+            .find(|t| t.event_type == r.event_type && t.from_version > r.from_version)
+        {
+            return Err(Error::new_spanned(
+                &t.fn_name,
+                format!(
+                    "unreachable transform for event '{}' at source version {}: '{}' is renamed to '{}' at version {} -> {}",
+                    t.event_type,
+                    t.from_version,
+                    r.event_type,
+                    r.output_event_type(),
+                    r.from_version,
+                    r.to_version,
+                ),
+            ));
+        }
+    }
+
+    // Rename cycles (including renaming to itself). After the check above
+    // each event type has at most one outgoing rename, so following them
+    // from any start either terminates or revisits a name.
+    let rename_of: HashMap<&str, &str> = renames
+        .iter()
+        .map(|r| (r.event_type.as_str(), r.output_event_type()))
+        .collect();
+    for r in &renames {
+        let mut cycle = vec![r.event_type.as_str()];
+        let mut current = r.event_type.as_str();
+        while let Some(&next) = rename_of.get(current) {
+            cycle.push(next);
+            if next == r.event_type {
+                return Err(Error::new_spanned(
+                    &r.fn_name,
+                    format!(
+                        "rename cycle for event '{}': {}",
+                        r.event_type,
+                        cycle.join(" -> "),
+                    ),
+                ));
+            }
+            if cycle.len() > rename_of.len() + 1 {
+                break;
+            }
+            current = next;
+        }
+    }
+
+    // Overlaps: two renames into one event type, or an event type that
+    // migrates itself below the version another type is renamed into it at.
+    let mut renamed_into: HashMap<&str, &TransformDef> = HashMap::new();
+    for r in &renames {
+        if let Some(first) = renamed_into.insert(r.output_event_type(), r) {
+            return Err(Error::new_spanned(
+                &r.fn_name,
+                format!(
+                    "overlapping transforms for event '{}': both '{}' and '{}' are renamed into it",
+                    r.output_event_type(),
+                    first.event_type,
+                    r.event_type,
+                ),
+            ));
+        }
+    }
+    for t in transforms {
+        let Some(r) = renamed_into.get(t.event_type.as_str()) else {
+            continue;
+        };
+        if t.from_version < r.to_version {
+            return Err(Error::new_spanned(
+                &t.fn_name,
+                format!(
+                    "overlapping transforms for event '{}': it is migrated from version {}, but '{}' is renamed into it at version {}",
+                    t.event_type, t.from_version, r.event_type, r.to_version,
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Chain coverage per event type.
+///
+/// Every schema version from the type's entry version (1, or the version
+/// another type is renamed into it at) up to its last step must have a
+/// step. Reports the smallest missing one.
+fn validate_coverage(transforms: &[TransformDef]) -> Result<()> {
+    let entry_by_event: HashMap<&str, u64> = transforms
+        .iter()
+        .filter(|t| t.rename.is_some())
+        .map(|t| (t.output_event_type(), t.to_version))
+        .collect();
+    let mut from_versions_by_event: HashMap<&str, HashSet<u64>> = HashMap::new();
+    let mut max_from_by_event: HashMap<&str, u64> = HashMap::new();
+    for t in transforms {
         from_versions_by_event
-            .entry(t.event_type.clone())
+            .entry(&t.event_type)
             .or_default()
             .insert(t.from_version);
-        let entry = max_from_by_event.entry(t.event_type.clone()).or_insert(0);
+        let entry = max_from_by_event.entry(&t.event_type).or_insert(0);
         if t.from_version > *entry {
             *entry = t.from_version;
         }
     }
-    for t in &transforms {
-        let Some(from_set) = from_versions_by_event.get(&t.event_type) else {
+    for t in transforms {
+        let event_type = t.event_type.as_str();
+        let Some(from_set) = from_versions_by_event.get(event_type) else {
             continue;
         };
-        let Some(&max_from) = max_from_by_event.get(&t.event_type) else {
+        let Some(&max_from) = max_from_by_event.get(event_type) else {
             continue;
         };
-        for v in 1..max_from {
+        let entry = entry_by_event.get(event_type).copied().unwrap_or(1);
+        for v in entry..max_from {
             if !from_set.contains(&v) {
                 return Err(Error::new_spanned(
                     &t.fn_name,
                     format!(
-                        "transform chain gap for event '{}': missing step from version {} to version {} (chain must cover every version in [1, {}])",
+                        "transform chain gap for event '{}': missing step from version {} to version {} (chain must cover every version in [{}, {}])",
                         t.event_type,
                         v,
                         v + 1,
+                        entry,
                         max_from + 1,
                     ),
                 ));
             }
         }
     }
+    Ok(())
+}
+
+/// Every chain as `(origin, steps)`: one per event type that is never a
+/// rename target, following its steps (across renames) from version 1.
+/// Assumes [`validate_chains`] passed.
+fn chain_paths(transforms: &[TransformDef]) -> Vec<(&str, Vec<&TransformDef>)> {
+    let targets: HashSet<&str> = transforms
+        .iter()
+        .filter(|t| t.rename.is_some())
+        .map(TransformDef::output_event_type)
+        .collect();
+    let mut origins: Vec<&str> = Vec::new();
+    for t in transforms {
+        if !targets.contains(t.event_type.as_str()) && !origins.contains(&t.event_type.as_str()) {
+            origins.push(&t.event_type);
+        }
+    }
+    origins
+        .into_iter()
+        .map(|origin| {
+            let mut steps = Vec::new();
+            let (mut event_type, mut version) = (origin, 1);
+            while let Some(step) = transforms
+                .iter()
+                .find(|t| t.event_type == event_type && t.from_version == version)
+            {
+                steps.push(step);
+                event_type = step.output_event_type();
+                version = step.to_version;
+            }
+            (origin, steps)
+        })
+        .collect()
+}
+
+fn parse_transforms(
+    ast: &syn::ItemImpl,
+    args: proc_macro2::TokenStream,
+) -> Result<proc_macro2::TokenStream> {
+    // 1. Parse aggregate = Type, error = Type from outer attributes
+    let mut aggregate_type: Option<Type> = None;
+    let mut error_type: Option<Type> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("aggregate") {
+            aggregate_type = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("error") {
+            error_type = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("expected `aggregate` or `error`"));
+        }
+        Ok(())
+    });
+    syn::parse::Parser::parse2(parser, args)?;
+    let aggregate_type = aggregate_type
+        .ok_or_else(|| Error::new(proc_macro2::Span::call_site(), "`aggregate` is required"))?;
+    let error_type = error_type
+        .ok_or_else(|| Error::new(proc_macro2::Span::call_site(), "`error` is required"))?;
+
+    // 2. Get the struct name from the impl block
+    let struct_ident = match &*ast.self_ty {
+        syn::Type::Path(p) => {
+            &p.path
+                .segments
+                .last()
+                .ok_or_else(|| Error::new_spanned(&ast.self_ty, "expected a type name"))?
+                .ident
+        }
+        _ => return Err(Error::new_spanned(&ast.self_ty, "expected a type name")),
+    };
+
+    // 3. Parse each method's #[transform] attributes
+    let mut transforms = Vec::new();
+    for item in &ast.items {
+        let method = match item {
+            syn::ImplItem::Fn(m) => m,
+            _ => continue,
+        };
+        if let Some(def) = parse_transform_attr(method)? {
+            transforms.push(def);
+        }
+    }
+
+    // 4–6. Validate every chain: steps, duplicates, renames, gaps.
+    validate_chains(&transforms)?;
 
     // 7. Build the original impl block with #[transform] attrs stripped
     let stripped_methods: Vec<_> = ast
@@ -360,10 +808,14 @@ fn parse_transforms(
         .collect();
 
     // 8. Generate match arms for upcast()
+    let json_wrappers: Vec<_> = transforms
+        .iter()
+        .filter_map(|t| t.json_wrapper(&error_type))
+        .collect();
     let match_arms: Vec<_> = transforms
         .iter()
         .map(|t| {
-            let fn_name = &t.fn_name;
+            let fn_name = t.step_fn();
             let event_type = &t.event_type;
             let from_version = t.from_version;
             let to_version = t.to_version;
@@ -409,17 +861,83 @@ fn parse_transforms(
         })
         .collect();
 
-    // 10. Emit: pub struct + impl block carrying user methods + the two
-    //     generated associated functions. No trait impl — call sites use
-    //     path syntax (`X::upcast(...)`, `X::current_version(...)`) which
-    //     yields `'static` function pointers pluggable into the facade's
-    //     `load_with` / `save_with` methods.
+    // 10. Registry paths + one const assertion per chain end against the
+    //     aggregate event's declared schema versions.
+    let paths = chain_paths(&transforms);
+    // Each step slice and the path slice are nested `const` items: a
+    // reference to a const-fn-built array is only `'static` in the
+    // outermost position of a const initializer, not as a call argument.
+    let registry_paths: Vec<_> = paths
+        .iter()
+        .map(|(origin, steps)| {
+            let steps = steps.iter().map(|t| {
+                let fn_name = t.step_fn();
+                let step_name = t.fn_name.to_string();
+                let event_type = &t.event_type;
+                let output_event_type = t.output_event_type();
+                let from = version_const(t.from_version);
+                let to = version_const(t.to_version);
+                quote! {
+                    ::nexus_store::upcasting::TransformStep::new(
+                        #step_name,
+                        (#event_type, #from),
+                        (#output_event_type, #to),
+                        |payload| ::core::result::Result::map_err(
+                            #struct_ident::#fn_name(payload),
+                            ::core::convert::Into::into,
+                        ),
+                    )
+                }
+            });
+            quote! {
+                ::nexus_store::upcasting::TransformPath::new(#origin, {
+                    const STEPS: &[::nexus_store::upcasting::TransformStep<#error_type>] =
+                        &[#(#steps),*];
+                    STEPS
+                })
+            }
+        })
+        .collect();
+    let chain_end_assertions: Vec<_> = paths
+        .iter()
+        .filter_map(|(origin, steps)| {
+            let last = steps.last()?;
+            let event_type = last.output_event_type();
+            let version = last.to_version;
+            let message = format!(
+                "upcaster chain for '{origin}' ends at '{event_type}' schema version {version}, \
+                 but the aggregate's event declares a different version \
+                 (mark the variant `#[schema_version = {version}]`)",
+            );
+            Some(quote! {
+                const _: () = ::core::assert!(
+                    ::nexus_store::upcasting::declared_schema_version::<
+                        ::nexus::EventOf<#aggregate_type>,
+                    >(#event_type)
+                    .as_u64()
+                        == #version,
+                    #message,
+                );
+            })
+        })
+        .collect();
+
+    // 11. Emit: pub struct + impl block carrying user methods + the two
+    //     generated associated functions and the registry. No trait impl —
+    //     call sites use path syntax (`X::upcast(...)`,
+    //     `X::current_version(...)`) which yields `'static` function
+    //     pointers pluggable into the facade's `load_with` and the builder's
+    //     `transforms`.
     let expanded = quote! {
         pub struct #struct_ident;
 
+        #(#chain_end_assertions)*
+
         impl #struct_ident {
             #(#stripped_methods)*
 
+            #(#json_wrappers)*
+
             /// Run all matching transforms until the morsel reaches the
             /// current schema version. Generated by `#[nexus::transforms]`.
             pub fn upcast<'a>(
@@ -447,12 +965,226 @@ fn parse_transforms(
                     _ => ::core::option::Option::None,
                 }
             }
+
+            /// Every upcaster chain, one path per origin event type.
+            /// Generated by `#[nexus::transforms]`.
+            pub const REGISTRY: ::nexus_store::upcasting::TransformRegistry<#error_type> = {
+                const PATHS: &[::nexus_store::upcasting::TransformPath<#error_type>] =
+                    &[#(#registry_paths),*];
+                ::nexus_store::upcasting::TransformRegistry::new(PATHS)
+            };
         }
     };
 
     Ok(expanded)
 }
 
+struct SnapshotTransformDef {
+    fn_name: syn::Ident,
+    from_version: u64,
+    to_version: u64,
+}
+
+fn parse_snapshot_transform_attr(method: &syn::ImplItemFn) -> Result<Option<SnapshotTransformDef>> {
+    let mut transform_attr = None;
+
+    for attr in &method.attrs {
+        if !attr.path().is_ident("transform") {
+            continue;
+        }
+        if transform_attr.is_some() {
+            return Err(Error::new_spanned(attr, "duplicate #[transform] attribute"));
+        }
+
+        let mut from_version: Option<u64> = None;
+        let mut to_version: Option<u64> = None;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("from") {
+                let lit: syn::LitInt = meta.value()?.parse()?;
+                from_version = Some(lit.base10_parse()?);
+            } else if meta.path.is_ident("to") {
+                let lit: syn::LitInt = meta.value()?.parse()?;
+                to_version = Some(lit.base10_parse()?);
+            } else {
+                return Err(meta.error("expected `from` or `to`"));
+            }
+            Ok(())
+        })?;
+
+        let from_version = from_version
+            .ok_or_else(|| Error::new_spanned(attr, "`from` is required in #[transform(...)]"))?;
+        let to_version = to_version
+            .ok_or_else(|| Error::new_spanned(attr, "`to` is required in #[transform(...)]"))?;
+
+        transform_attr = Some(SnapshotTransformDef {
+            fn_name: method.sig.ident.clone(),
+            from_version,
+            to_version,
+        });
+    }
+
+    Ok(transform_attr)
+}
+
+/// Reject every malformed snapshot chain at expansion time: steps must be
+/// single increments within `u32`, unique per `from`, and gap-free.
+fn validate_snapshot_chain(transforms: &[SnapshotTransformDef]) -> Result<()> {
+    let mut seen = HashSet::new();
+    for t in transforms {
+        if t.from_version < 1 {
+            return Err(Error::new_spanned(&t.fn_name, "from version must be >= 1"));
+        }
+        if t.to_version != t.from_version + 1 {
+            return Err(Error::new_spanned(
+                &t.fn_name,
+                format!(
+                    "non-contiguous version: to ({}) must equal from + 1 ({})",
+                    t.to_version,
+                    t.from_version + 1,
+                ),
+            ));
+        }
+        if u32::try_from(t.to_version).is_err() {
+            return Err(Error::new_spanned(
+                &t.fn_name,
+                "snapshot schema version must fit in a u32",
+            ));
+        }
+        if !seen.insert(t.from_version) {
+            return Err(Error::new_spanned(
+                &t.fn_name,
+                format!(
+                    "duplicate snapshot transform at source version {}",
+                    t.from_version
+                ),
+            ));
+        }
+    }
+
+    let (Some(min_from), Some(max_from)) = (
+        transforms.iter().map(|t| t.from_version).min(),
+        transforms.iter().map(|t| t.from_version).max(),
+    ) else {
+        return Ok(());
+    };
+    if let Some(missing) = (min_from..max_from).find(|v| !seen.contains(v)) {
+        let after = transforms
+            .iter()
+            .find(|t| t.to_version == missing)
+            .unwrap_or(&transforms[0]);
+        return Err(Error::new_spanned(
+            &after.fn_name,
+            format!(
+                "snapshot transform chain gap: missing step from version {} to version {} (chain must cover every version in [{}, {}])",
+                missing,
+                missing + 1,
+                min_from,
+                max_from + 1,
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_snapshot_transforms(
+    ast: &syn::ItemImpl,
+    args: proc_macro2::TokenStream,
+) -> Result<proc_macro2::TokenStream> {
+    let mut error_type: Option<Type> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("error") {
+            error_type = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("expected `error`"));
+        }
+        Ok(())
+    });
+    syn::parse::Parser::parse2(parser, args)?;
+    let error_type = error_type
+        .ok_or_else(|| Error::new(proc_macro2::Span::call_site(), "`error` is required"))?;
+
+    let struct_ident = match &*ast.self_ty {
+        syn::Type::Path(p) => {
+            &p.path
+                .segments
+                .last()
+                .ok_or_else(|| Error::new_spanned(&ast.self_ty, "expected a type name"))?
+                .ident
+        }
+        _ => return Err(Error::new_spanned(&ast.self_ty, "expected a type name")),
+    };
+
+    let mut transforms = Vec::new();
+    for item in &ast.items {
+        if let syn::ImplItem::Fn(method) = item
+            && let Some(def) = parse_snapshot_transform_attr(method)?
+        {
+            transforms.push(def);
+        }
+    }
+    validate_snapshot_chain(&transforms)?;
+    transforms.sort_by_key(|t| t.from_version);
+
+    let stripped_methods: Vec<_> = ast
+        .items
+        .iter()
+        .map(|item| match item {
+            syn::ImplItem::Fn(m) => {
+                let mut method = m.clone();
+                method.attrs.retain(|a| !a.path().is_ident("transform"));
+                syn::ImplItem::Fn(method)
+            }
+            other => other.clone(),
+        })
+        .collect();
+
+    // Versions were checked to fit a nonzero `u32` above, so the `None`
+    // arms are unreachable.
+    let nonzero = |version: u64| {
+        let version = u32::try_from(version).unwrap_or(u32::MAX);
+        quote! {
+            match ::core::num::NonZeroU32::new(#version) {
+                ::core::option::Option::Some(v) => v,
+                ::core::option::Option::None => ::core::num::NonZeroU32::MIN,
+            }
+        }
+    };
+    let steps = transforms.iter().map(|t| {
+        let fn_name = &t.fn_name;
+        let step_name = fn_name.to_string();
+        let from = nonzero(t.from_version);
+        let to = nonzero(t.to_version);
+        quote! {
+            ::nexus_store::upcasting::SnapshotTransform::new(
+                #step_name,
+                #from,
+                #to,
+                |state| ::core::result::Result::map_err(
+                    #struct_ident::#fn_name(state),
+                    |e: #error_type| -> ::nexus_store::upcasting::BoxError {
+                        ::std::boxed::Box::new(e)
+                    },
+                ),
+            )
+        }
+    });
+
+    Ok(quote! {
+        pub struct #struct_ident;
+
+        impl #struct_ident {
+            #(#stripped_methods)*
+
+            /// The snapshot transform chain, ordered by source schema
+            /// version. Generated by `#[nexus::snapshot_transforms]`.
+            pub const UPCASTERS: ::nexus_store::upcasting::SnapshotUpcasters = {
+                const STEPS: &[::nexus_store::upcasting::SnapshotTransform] = &[#(#steps),*];
+                ::nexus_store::upcasting::SnapshotUpcasters::new(STEPS)
+            };
+        }
+    })
+}
+
 fn parse_aggregate(
     ast: &DeriveInput,
     args: proc_macro2::TokenStream,
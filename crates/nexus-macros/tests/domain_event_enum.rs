@@ -51,3 +51,40 @@ fn derive_domain_event_implements_domain_event() {
     fn assert_domain_event<T: DomainEvent>() {}
     assert_domain_event::<TestEvent>();
 }
+
+#[derive(Debug, Clone, nexus::DomainEvent)]
+enum VersionedEvent {
+    #[schema_version = 3]
+    Created(Created),
+    Updated(Updated),
+    #[schema_version = 2]
+    Deleted,
+}
+
+#[test]
+fn derive_domain_event_emits_declared_schema_versions() {
+    let v = |n| nexus::Version::new(n).unwrap();
+    assert_eq!(
+        VersionedEvent::SCHEMA_VERSIONS,
+        &[("Created", v(3)), ("Updated", v(1)), ("Deleted", v(2))]
+    );
+
+    let created = VersionedEvent::Created(Created {
+        name: "test".into(),
+    });
+    assert_eq!(created.schema_version(), v(3));
+    let updated = VersionedEvent::Updated(Updated { name: "new".into() });
+    assert_eq!(updated.schema_version(), nexus::Version::INITIAL);
+    assert_eq!(VersionedEvent::Deleted.schema_version(), v(2));
+}
+
+#[test]
+fn derive_domain_event_defaults_every_variant_to_initial_schema_version() {
+    assert!(
+        TestEvent::SCHEMA_VERSIONS
+            .iter()
+            .all(|(_, version)| *version == nexus::Version::INITIAL)
+    );
+    assert_eq!(TestEvent::SCHEMA_VERSIONS.len(), 4);
+    assert_eq!(TestEvent::Deleted.schema_version(), nexus::Version::INITIAL);
+}
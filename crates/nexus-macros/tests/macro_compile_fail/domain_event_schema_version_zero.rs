@@ -0,0 +1,9 @@
+/// Schema versions are 1-based — `#[schema_version = 0]` must be rejected.
+
+#[derive(Debug, Clone, nexus::DomainEvent)]
+enum OrderEvent {
+    #[schema_version = 0]
+    Created,
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+use nexus_macros::snapshot_transforms;
+
+#[derive(Debug)]
+struct MyError;
+impl std::fmt::Display for MyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error")
+    }
+}
+impl std::error::Error for MyError {}
+
+// Declares snapshot steps 1→2 and 3→4 with no 2→3. A schema-2 snapshot
+// would have no path forward, so the macro must reject the chain.
+#[snapshot_transforms(error = MyError)]
+impl AccountSnapshotTransforms {
+    #[transform(from = 1, to = 2)]
+    fn v1_to_v2(state: &[u8]) -> Result<Vec<u8>, MyError> {
+        Ok(state.to_vec())
+    }
+
+    #[transform(from = 3, to = 4)]
+    fn v3_to_v4(state: &[u8]) -> Result<Vec<u8>, MyError> {
+        Ok(state.to_vec())
+    }
+}
+
+fn main() {}
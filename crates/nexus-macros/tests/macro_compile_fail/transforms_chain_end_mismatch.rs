@@ -0,0 +1,58 @@
+use nexus_macros::transforms;
+
+#[derive(Debug)]
+struct MyError;
+impl std::fmt::Display for MyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error")
+    }
+}
+impl std::error::Error for MyError {}
+
+#[derive(Debug, Clone, nexus::DomainEvent)]
+enum OrderEvent {
+    // Declared at v1 (the default), but the chain below migrates to v2 —
+    // `save` would stamp v1 while `load_with` upcasts stored events to v2.
+    OrderCreated,
+}
+
+#[derive(Debug)]
+struct OrderState;
+impl nexus::AggregateState for OrderState {
+    type Event = OrderEvent;
+    fn initial() -> Self {
+        Self
+    }
+    fn apply(self, _: &OrderEvent) -> Self {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct OrderId;
+impl std::fmt::Display for OrderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("order")
+    }
+}
+impl AsRef<[u8]> for OrderId {
+    fn as_ref(&self) -> &[u8] {
+        b"order"
+    }
+}
+impl nexus::Id for OrderId {
+    const BYTE_LEN: usize = 5;
+}
+
+#[nexus::aggregate(state = OrderState, error = MyError, id = OrderId)]
+struct Order;
+
+#[transforms(aggregate = Order, error = MyError)]
+impl OrderTransforms {
+    #[transform(event = "OrderCreated", from = 1, to = 2)]
+    fn v1_to_v2(payload: &[u8]) -> Result<Vec<u8>, MyError> {
+        Ok(payload.to_vec())
+    }
+}
+
+fn main() {}
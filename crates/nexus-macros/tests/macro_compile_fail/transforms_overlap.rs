@@ -0,0 +1,29 @@
+use nexus_macros::transforms;
+
+struct Order;
+
+#[derive(Debug)]
+struct MyError;
+impl std::fmt::Display for MyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error")
+    }
+}
+impl std::error::Error for MyError {}
+
+// OrderVoided migrates itself 1 -> 2, and OrderCancelled is renamed into it
+// at 1 -> 2: two steps both produce OrderVoided v2.
+#[transforms(aggregate = Order, error = MyError)]
+impl OrderTransforms {
+    #[transform(event = "OrderCancelled", from = 1, to = 2, rename = "OrderVoided")]
+    fn cancelled_to_voided(payload: &[u8]) -> Result<Vec<u8>, MyError> {
+        Ok(payload.to_vec())
+    }
+
+    #[transform(event = "OrderVoided", from = 1, to = 2)]
+    fn voided_v1_to_v2(payload: &[u8]) -> Result<Vec<u8>, MyError> {
+        Ok(payload.to_vec())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use nexus_macros::transforms;
+
+struct Order;
+
+#[derive(Debug)]
+struct MyError;
+impl std::fmt::Display for MyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error")
+    }
+}
+impl std::error::Error for MyError {}
+
+// OrderCreated is renamed to OrderPlaced, which is renamed back to
+// OrderCreated — the rename graph loops, so neither name is the chain end.
+#[transforms(aggregate = Order, error = MyError)]
+impl OrderTransforms {
+    #[transform(event = "OrderCreated", from = 1, to = 2, rename = "OrderPlaced")]
+    fn created_to_placed(payload: &[u8]) -> Result<Vec<u8>, MyError> {
+        Ok(payload.to_vec())
+    }
+
+    #[transform(event = "OrderPlaced", from = 2, to = 3, rename = "OrderCreated")]
+    fn placed_to_created(payload: &[u8]) -> Result<Vec<u8>, MyError> {
+        Ok(payload.to_vec())
+    }
+}
+
+fn main() {}
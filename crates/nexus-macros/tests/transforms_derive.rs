@@ -2,6 +2,7 @@
 #![allow(clippy::expect_used, reason = "tests")]
 
 use nexus::Version;
+use nexus_store::json_transforms::{JsonObject, JsonTransformError, Value};
 use nexus_store::upcasting::EventMorsel;
 
 #[derive(Debug)]
@@ -13,7 +14,46 @@ impl std::fmt::Display for TestError {
 }
 impl std::error::Error for TestError {}
 
-#[allow(dead_code, reason = "referenced by macro attribute only")]
+#[allow(dead_code, reason = "only the declared schema versions are read")]
+#[derive(Debug, Clone, nexus::DomainEvent)]
+enum TestEvent {
+    #[schema_version = 3]
+    OrderCreated,
+    #[schema_version = 2]
+    OrderVoided,
+}
+
+#[derive(Debug)]
+struct TestState;
+impl nexus::AggregateState for TestState {
+    type Event = TestEvent;
+    fn initial() -> Self {
+        Self
+    }
+    fn apply(self, _: &TestEvent) -> Self {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct TestId;
+impl std::fmt::Display for TestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("test")
+    }
+}
+impl AsRef<[u8]> for TestId {
+    fn as_ref(&self) -> &[u8] {
+        b"test"
+    }
+}
+impl nexus::Id for TestId {
+    const BYTE_LEN: usize = 4;
+}
+
+// The macro asserts at compile time that every chain ends at the version
+// `TestEvent` declares for it (OrderCreated → 3, OrderVoided → 2).
+#[nexus::aggregate(state = TestState, error = TestError, id = TestId)]
 struct TestAggregate;
 
 #[nexus_macros::transforms(aggregate = TestAggregate, error = TestError)]
@@ -92,3 +132,281 @@ fn transforms_upcast_is_static_fn_pointer() {
         TestTransforms::upcast;
     let _: fn(&str) -> Option<Version> = TestTransforms::current_version;
 }
+
+// =============================================================================
+// Registry
+// =============================================================================
+
+#[allow(dead_code, reason = "only the declared schema versions are read")]
+#[derive(Debug, Clone, nexus::DomainEvent)]
+enum RenameEvent {
+    #[schema_version = 3]
+    OrderVoided,
+}
+
+#[derive(Debug)]
+struct RenameState;
+impl nexus::AggregateState for RenameState {
+    type Event = RenameEvent;
+    fn initial() -> Self {
+        Self
+    }
+    fn apply(self, _: &RenameEvent) -> Self {
+        self
+    }
+}
+
+#[nexus::aggregate(state = RenameState, error = TestError, id = TestId)]
+struct RenameAggregate;
+
+#[nexus_macros::transforms(aggregate = RenameAggregate, error = TestError)]
+impl RenameChainTransforms {
+    #[transform(event = "OrderCancelled", from = 1, to = 2, rename = "OrderVoided")]
+    fn cancelled_to_voided(payload: &[u8]) -> Result<Vec<u8>, TestError> {
+        let mut data = payload.to_vec();
+        data.extend_from_slice(b",voided");
+        Ok(data)
+    }
+
+    #[transform(event = "OrderVoided", from = 2, to = 3)]
+    fn voided_v2_to_v3(payload: &[u8]) -> Result<Vec<u8>, TestError> {
+        let mut data = payload.to_vec();
+        data.extend_from_slice(b",v3");
+        Ok(data)
+    }
+}
+
+#[test]
+fn registry_lists_one_path_per_origin_event_type() {
+    let registry = &TestTransforms::REGISTRY;
+    let origins: Vec<_> = registry.paths().iter().map(|p| p.origin()).collect();
+    assert_eq!(origins, ["OrderCreated", "OrderCancelled"]);
+
+    let created = registry.path("OrderCreated").unwrap();
+    let steps: Vec<_> = created
+        .steps()
+        .iter()
+        .map(|s| (s.name(), s.from().as_u64(), s.to().as_u64()))
+        .collect();
+    assert_eq!(
+        steps,
+        [("created_v1_to_v2", 1, 2), ("created_v2_to_v3", 2, 3)]
+    );
+    assert_eq!(created.current_event_type(), "OrderCreated");
+    assert_eq!(created.current_version(), Version::new(3).unwrap());
+
+    let cancelled = registry.path("OrderCancelled").unwrap();
+    assert_eq!(cancelled.current_event_type(), "OrderVoided");
+    assert_eq!(cancelled.current_version(), Version::new(2).unwrap());
+    assert!(registry.path("OrderVoided").is_none());
+    assert_eq!(registry.steps().count(), 3);
+}
+
+#[test]
+fn registry_steps_run_the_transform_functions() {
+    let created = TestTransforms::REGISTRY.path("OrderCreated").unwrap();
+    assert_eq!(created.steps()[1].apply(b"data").unwrap(), b"data,v3");
+    assert_eq!(created.migrate(b"data").unwrap(), b"data,v2,v3");
+}
+
+#[test]
+fn registry_path_follows_steps_across_a_rename() {
+    // OrderVoided is only reachable through the rename, so it is not an
+    // origin: the whole history is one path from OrderCancelled v1.
+    let registry = &RenameChainTransforms::REGISTRY;
+    assert_eq!(registry.paths().len(), 1);
+    let path = registry.path("OrderCancelled").unwrap();
+    assert_eq!(path.current_event_type(), "OrderVoided");
+    assert_eq!(path.current_version(), Version::new(3).unwrap());
+    assert_eq!(path.migrate(b"data").unwrap(), b"data,voided,v3");
+
+    let morsel = EventMorsel::borrowed("OrderCancelled", Version::INITIAL, b"data");
+    let upcast = RenameChainTransforms::upcast(morsel).unwrap();
+    assert_eq!(upcast.event_type(), "OrderVoided");
+    assert_eq!(upcast.schema_version(), Version::new(3).unwrap());
+}
+
+// =============================================================================
+// Declarative JSON edits
+// =============================================================================
+
+#[derive(Debug)]
+enum JsonTestError {
+    Transform(JsonTransformError),
+    UnknownStatus,
+}
+impl std::fmt::Display for JsonTestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transform(e) => write!(f, "{e}"),
+            Self::UnknownStatus => f.write_str("unknown status"),
+        }
+    }
+}
+impl std::error::Error for JsonTestError {}
+impl From<JsonTransformError> for JsonTestError {
+    fn from(e: JsonTransformError) -> Self {
+        Self::Transform(e)
+    }
+}
+
+#[allow(dead_code, reason = "only the declared schema versions are read")]
+#[derive(Debug, Clone, nexus::DomainEvent)]
+enum CustomerEvent {
+    #[schema_version = 3]
+    CustomerRegistered,
+}
+
+#[derive(Debug)]
+struct CustomerState;
+impl nexus::AggregateState for CustomerState {
+    type Event = CustomerEvent;
+    fn initial() -> Self {
+        Self
+    }
+    fn apply(self, _: &CustomerEvent) -> Self {
+        self
+    }
+}
+
+#[nexus::aggregate(state = CustomerState, error = JsonTestError, id = TestId)]
+struct CustomerAggregate;
+
+fn status_v2(status: Value) -> Result<Value, JsonTestError> {
+    match status.as_str() {
+        Some("new") => Ok(Value::from("Pending")),
+        Some("ok") => Ok(Value::from("Active")),
+        _ => Err(JsonTestError::UnknownStatus),
+    }
+}
+
+#[nexus_macros::transforms(aggregate = CustomerAggregate, error = JsonTestError)]
+impl CustomerTransforms {
+    #[transform(
+        event = "CustomerRegistered",
+        from = 1,
+        to = 2,
+        rename(name -> full_name),
+        default(tier = "basic"),
+        remove(legacy_flag),
+        move_into(street, "zip-code" -> address),
+    )]
+    fn registered_v1_to_v2(_: &mut JsonObject) -> Result<(), JsonTestError> {
+        Ok(())
+    }
+
+    #[transform(
+        event = "CustomerRegistered",
+        from = 2,
+        to = 3,
+        map_value(status = status_v2),
+        rename(r#type -> kind),
+    )]
+    fn registered_v2_to_v3(object: &mut JsonObject) -> Result<(), JsonTestError> {
+        object.insert("migrated".to_owned(), Value::from(true));
+        Ok(())
+    }
+}
+
+#[test]
+fn declarative_json_edits_run_in_order_then_the_function() {
+    let v1 = br#"{"name":"Ada","legacy_flag":1,"street":"Main","zip-code":"0150","status":"new","type":"person"}"#;
+    let morsel = EventMorsel::borrowed("CustomerRegistered", Version::INITIAL, v1);
+    let upcast = CustomerTransforms::upcast(morsel).unwrap();
+    assert_eq!(upcast.schema_version(), Version::new(3).unwrap());
+
+    let v3: Value = serde_json::from_slice(upcast.payload()).unwrap();
+    assert_eq!(
+        v3,
+        serde_json::json!({
+            "full_name": "Ada",
+            "tier": "basic",
+            "address": { "street": "Main", "zip-code": "0150" },
+            "status": "Pending",
+            "kind": "person",
+            "migrated": true,
+        })
+    );
+}
+
+#[test]
+fn declarative_json_edit_errors_surface_as_the_transforms_error() {
+    let path = CustomerTransforms::REGISTRY
+        .path("CustomerRegistered")
+        .unwrap();
+    assert_eq!(path.steps()[0].name(), "registered_v1_to_v2");
+
+    let err = path.steps()[1].apply(br#"{"status":"bogus"}"#).unwrap_err();
+    assert!(matches!(err, JsonTestError::UnknownStatus));
+
+    let err = path.steps()[0]
+        .apply(br#"{"name":"Ada","full_name":"Ada L"}"#)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        JsonTestError::Transform(JsonTransformError::FieldExists { .. })
+    ));
+
+    let err = path.migrate(b"[1, 2]").unwrap_err();
+    assert!(matches!(
+        err,
+        JsonTestError::Transform(JsonTransformError::NotAnObject)
+    ));
+}
+
+// =============================================================================
+// Snapshot transforms
+// =============================================================================
+
+// Declared out of order: `UPCASTERS` is sorted by source version.
+#[nexus_macros::snapshot_transforms(error = TestError)]
+impl TestSnapshotTransforms {
+    #[transform(from = 3, to = 4)]
+    fn v3_to_v4(state: &[u8]) -> Result<Vec<u8>, TestError> {
+        if state.is_empty() {
+            return Err(TestError);
+        }
+        let mut data = state.to_vec();
+        data.extend_from_slice(b",s4");
+        Ok(data)
+    }
+
+    #[transform(from = 2, to = 3)]
+    fn v2_to_v3(state: &[u8]) -> Result<Vec<u8>, TestError> {
+        let mut data = state.to_vec();
+        data.extend_from_slice(b",s3");
+        Ok(data)
+    }
+}
+
+#[test]
+fn snapshot_transforms_emit_ordered_chain() {
+    let upcasters = TestSnapshotTransforms::UPCASTERS;
+    let steps: Vec<_> = upcasters
+        .steps()
+        .iter()
+        .map(|step| (step.name(), step.from().get(), step.to().get()))
+        .collect();
+    assert_eq!(steps, [("v2_to_v3", 2, 3), ("v3_to_v4", 3, 4)]);
+    assert_eq!(upcasters.current_version().map(|v| v.get()), Some(4));
+}
+
+#[test]
+fn snapshot_transforms_migrate_through_the_chain() {
+    let sv = |v| std::num::NonZeroU32::new(v).unwrap();
+    let upcasters = TestSnapshotTransforms::UPCASTERS;
+    assert_eq!(
+        upcasters.migrate(sv(2), sv(4), b"state").unwrap().unwrap(),
+        b"state,s3,s4"
+    );
+    assert_eq!(
+        upcasters.migrate(sv(3), sv(4), b"state").unwrap().unwrap(),
+        b"state,s4"
+    );
+    // Below the chain's first step: no path.
+    assert!(upcasters.migrate(sv(1), sv(4), b"state").unwrap().is_none());
+
+    let err = upcasters.migrate(sv(3), sv(4), b"").unwrap_err();
+    assert_eq!((err.from.get(), err.to.get()), (3, 4));
+    assert!(err.source.downcast_ref::<TestError>().is_some());
+}
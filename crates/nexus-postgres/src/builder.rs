@@ -1,35 +1,271 @@
+use nexus_store::SnapshotRetention;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 
 use crate::error::PostgresError;
-use crate::schema::ensure_schema;
+use crate::migrate::{self, MigrationMode, SCHEMA_VERSION};
+use crate::partition::{self, PartitionManager, Partitioning};
+use crate::schema::{DEFAULT_NOTIFY_CHANNEL, Names, Statements};
+use crate::stall::StallDetection;
 use crate::store::PostgresStore;
+use crate::wake::WakeStrategy;
 
-impl PostgresStore {
-    /// Connect to `url`, create a connection pool, and ensure the schema exists.
+/// Builder for [`PostgresStore`]: where its tables live and which
+/// `LISTEN/NOTIFY` channel it uses.
+///
+/// Stores built with distinct namespaces share one database without seeing
+/// each other's events, snapshots or checkpoints — one per bounded context or
+/// tenant. The defaults reproduce [`PostgresStore::connect`]: `events`,
+/// `snapshots` and `checkpoints` on the `search_path`, notifying on
+/// `nexus_events`.
+///
+/// ```ignore
+/// let store = PostgresStore::builder()
+///     .schema("billing")
+///     .table_prefix("tenant_a_")
+///     .notify_channel("billing_tenant_a_events")
+///     .open(pool)
+///     .await?;
+/// ```
+///
+/// Names are validated when the store is built, before any connection is
+/// made: lowercase ASCII letters, digits and `_`, not starting with a digit.
+///
+/// Opening runs the namespace's pending [migrations](crate::migrate) unless
+/// [`migration_mode`](Self::migration_mode) says to only verify them.
+///
+/// Behind `PgBouncer` in transaction pooling mode, or on a managed database
+/// that refuses `LISTEN`, pick a polling [`wake_strategy`](Self::wake_strategy).
+#[derive(Debug, Clone)]
+pub struct PostgresStoreBuilder {
+    schema: Option<String>,
+    table_prefix: String,
+    notify_channel: String,
+    migration_mode: MigrationMode,
+    wake_strategy: WakeStrategy,
+    stall_detection: Option<StallDetection>,
+    partitioning: Option<Partitioning>,
+    snapshot_retention: SnapshotRetention,
+    copy_appends: bool,
+}
+
+impl PostgresStoreBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            schema: None,
+            table_prefix: String::new(),
+            notify_channel: DEFAULT_NOTIFY_CHANNEL.to_owned(),
+            migration_mode: MigrationMode::Apply,
+            wake_strategy: WakeStrategy::Listen,
+            stall_detection: None,
+            partitioning: None,
+            snapshot_retention: SnapshotRetention::LATEST,
+            copy_appends: false,
+        }
+    }
+
+    /// Put the tables in `schema`, creating it if absent. Defaults to none:
+    /// unqualified names, resolved through the connection's `search_path`.
+    #[must_use]
+    pub fn schema(mut self, schema: impl Into<String>) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    /// Prepend `prefix` to every table and index name (`tenant_a_` gives
+    /// `tenant_a_events`). Defaults to none.
+    #[must_use]
+    pub fn table_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.table_prefix = prefix.into();
+        self
+    }
+
+    /// Notify and listen on `channel` instead of `nexus_events`.
+    ///
+    /// A `NOTIFY` is only a hint to re-scan, so stores sharing a channel stay
+    /// correct — but each wakes its subscribers for the others' appends. Give
+    /// busy namespaces their own channel.
+    #[must_use]
+    pub fn notify_channel(mut self, channel: impl Into<String>) -> Self {
+        self.notify_channel = channel.into();
+        self
+    }
+
+    /// Choose whether opening applies pending migrations
+    /// ([`MigrationMode::Apply`], the default) or only checks that none are
+    /// pending ([`MigrationMode::VerifyOnly`]), for roles without DDL rights.
+    #[must_use]
+    pub const fn migration_mode(mut self, mode: MigrationMode) -> Self {
+        self.migration_mode = mode;
+        self
+    }
+
+    /// Choose how the store notices events committed by other connections:
+    /// `LISTEN/NOTIFY` ([`WakeStrategy::Listen`], the default), polling, or
+    /// both.
+    #[must_use]
+    pub const fn wake_strategy(mut self, strategy: WakeStrategy) -> Self {
+        self.wake_strategy = strategy;
+        self
+    }
+
+    /// Watch the `$all` watermark and report when a long-running transaction
+    /// pins it (see [`StallDetection`]). Off by default.
+    #[must_use]
+    pub fn stall_detection(mut self, detection: StallDetection) -> Self {
+        self.stall_detection = Some(detection);
+        self
+    }
+
+    /// Lay the events table out in range partitions (see [`Partitioning`]).
+    /// Off by default.
+    ///
+    /// The layout is fixed when the namespace's tables are created: opening
+    /// an existing namespace with a different one fails with
+    /// [`PostgresError::Partition`].
+    #[must_use]
+    pub const fn partitioning(mut self, partitioning: Partitioning) -> Self {
+        self.partitioning = Some(partitioning);
+        self
+    }
+
+    /// How many aggregate snapshots to keep per id.
+    ///
+    /// Defaults to [`SnapshotRetention::LATEST`]. Keeping more lets
+    /// [`hydrate_at_or_before`](nexus_store::SnapshotStore::hydrate_at_or_before)
+    /// start from an older snapshot; rows past the retention are deleted in
+    /// the commit's own transaction.
+    #[must_use]
+    pub const fn snapshot_retention(mut self, retention: SnapshotRetention) -> Self {
+        self.snapshot_retention = retention;
+        self
+    }
+
+    /// Send large `append` and `append_in_tx` batches through binary `COPY`,
+    /// as imports always are. Off by default.
+    ///
+    /// Pays off for bulk writers appending hundreds of events at once; each
+    /// such append stages its rows in a temporary table in its transaction,
+    /// then merges them into the events table in one statement.
+    #[must_use]
+    pub const fn copy_appends(mut self, enabled: bool) -> Self {
+        self.copy_appends = enabled;
+        self
+    }
+
+    /// Connect to `url`, create a connection pool, and migrate the schema.
     ///
     /// # Errors
     ///
-    /// Returns [`PostgresError::Sqlx`] on connection failure or schema DDL
-    /// failure.
-    pub async fn connect(url: &str) -> Result<Self, PostgresError> {
+    /// Returns [`PostgresError::InvalidIdentifier`] for a malformed name
+    /// (before connecting), [`PostgresError::SchemaOutdated`] when verifying
+    /// finds a pending migration, [`PostgresError::Partition`] when the
+    /// table's partitioning is not the configured one, and
+    /// [`PostgresError::Sqlx`] on connection or migration failure.
+    pub async fn connect(self, url: &str) -> Result<PostgresStore, PostgresError> {
+        let names = self.names()?;
         let pool = PgPoolOptions::new()
             .connect(url)
             .await
             .map_err(PostgresError::Sqlx)?;
-        Self::from_pool(pool).await
+        self.finish(pool, &names).await
+    }
+
+    /// Build from an existing pool and migrate the schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::InvalidIdentifier`] for a malformed name,
+    /// [`PostgresError::SchemaOutdated`] when verifying finds a pending
+    /// migration, [`PostgresError::Partition`] when the table's partitioning
+    /// is not the configured one, and [`PostgresError::Sqlx`] if a migration
+    /// fails.
+    pub async fn open(self, pool: PgPool) -> Result<PostgresStore, PostgresError> {
+        let names = self.names()?;
+        self.finish(pool, &names).await
+    }
+
+    /// Apply the namespace's migrations up to and including `target`, without
+    /// opening a store, and return the highest one applied. For staged
+    /// rollouts and upgrade tests; the configured mode is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::InvalidIdentifier`] for a malformed name, and
+    /// [`PostgresError::Sqlx`] if a migration fails.
+    pub async fn migrate_to(&self, pool: &PgPool, target: u32) -> Result<u32, PostgresError> {
+        migrate::run(pool, &self.names()?, MigrationMode::Apply, target).await
+    }
+
+    fn names(&self) -> Result<Names, PostgresError> {
+        let names = Names::new(
+            self.schema.as_deref(),
+            &self.table_prefix,
+            &self.notify_channel,
+        )?;
+        match self.partitioning {
+            Some(partitioning) => names.partitioned(partitioning.scheme()),
+            None => Ok(names),
+        }
+    }
+
+    async fn finish(self, pool: PgPool, names: &Names) -> Result<PostgresStore, PostgresError> {
+        migrate::run(&pool, names, self.migration_mode, SCHEMA_VERSION).await?;
+        partition::check_layout(&pool, names).await?;
+        // Only a store allowed to run DDL creates partitions.
+        let partitions = self.partitioning.map(|partitioning| {
+            PartitionManager::new(
+                names.clone(),
+                partitioning,
+                self.migration_mode == MigrationMode::Apply,
+            )
+        });
+        if let Some(manager) = partitions.as_ref().filter(|m| m.maintains()) {
+            manager.ensure(&pool).await?;
+        }
+        PostgresStore::assemble(
+            pool,
+            Statements::new(names),
+            self.wake_strategy,
+            self.stall_detection,
+            partitions,
+        )
+        .await
+        .map(|store| {
+            store
+                .with_snapshot_retention(self.snapshot_retention)
+                .with_copy_appends(self.copy_appends)
+        })
+    }
+}
+
+impl PostgresStore {
+    /// Start a [`PostgresStoreBuilder`] to configure the schema, table prefix
+    /// or notify channel.
+    #[must_use]
+    pub fn builder() -> PostgresStoreBuilder {
+        PostgresStoreBuilder::new()
+    }
+
+    /// Connect to `url`, create a connection pool, and migrate the schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::Sqlx`] on connection failure or migration
+    /// failure.
+    pub async fn connect(url: &str) -> Result<Self, PostgresError> {
+        Self::builder().connect(url).await
     }
 
-    /// Build from an existing pool and ensure the schema exists.
+    /// Build from an existing pool and migrate the schema.
     ///
     /// Prefer this when the caller already holds a `PgPool` (e.g. a shared
     /// pool across multiple stores in an application).
     ///
     /// # Errors
     ///
-    /// Returns [`PostgresError::Sqlx`] if the schema DDL fails.
+    /// Returns [`PostgresError::Sqlx`] if a migration fails.
     pub async fn from_pool(pool: PgPool) -> Result<Self, PostgresError> {
-        ensure_schema(&pool).await?;
-        Ok(Self::assemble(pool))
+        Self::builder().open(pool).await
     }
 }
@@ -0,0 +1,220 @@
+//! Bulk inserts through `COPY ... FROM STDIN (FORMAT binary)`.
+//!
+//! A row-by-row `INSERT` costs one round trip per event, which dominates a
+//! large import. Import batches
+//! ([`AtomicAppend`](nexus_store::import::AtomicAppend)) of at least
+//! [`COPY_THRESHOLD`] rows are instead streamed into a session-local staging
+//! table with one binary `COPY`, then merged into `events` with a single
+//! `INSERT ... SELECT`. Ordinary appends take this path only when the store
+//! is built with
+//! [`copy_appends`](crate::PostgresStoreBuilder::copy_appends): the staging
+//! table is DDL in the caller's transaction, which a command's append
+//! should not pay for unasked.
+//!
+//!
+//! - the identity column and the `txid` default still apply, and rows merge
+//!   in staging order, so `global_seq` follows the batch order exactly as the
+//!   per-row path assigns it;
+//! - the merge skips rows a concurrent writer claimed first
+//!   (`ON CONFLICT DO NOTHING`) and reports the first one, so a race is still
+//!   a conflict on a known write rather than a bare unique violation. A
+//!   partitioned table has no unique constraint to conflict on; there the
+//!   merge runs with the version-claim trigger set to skip instead (see
+//!   [`crate::partition`]).
+//!
+//! Head and contiguity checks run before anything is staged (see
+//! `prepare_inserts`), so the merge only ever sees rows that passed them.
+//!
+//! The staging table is `ON COMMIT DROP` and truncated after each merge, so
+//! a pooled connection carries nothing over between transactions.
+
+use nexus::ErrorId;
+
+use crate::error::PostgresError;
+
+/// The smallest batch that goes through `COPY`. Below it, per-row `INSERT`s
+/// finish before the staging round trips would.
+pub const COPY_THRESHOLD: usize = 64;
+
+/// Encoded bytes buffered before they are sent to the server.
+pub const FLUSH_AT: usize = 1 << 20;
+
+/// The staging table, created on first use in a transaction.
+pub const CREATE_STAGING: &str = "CREATE TEMP TABLE IF NOT EXISTS nexus_staged_events (
+    ord            BIGINT   NOT NULL,
+    stream_id      BYTEA    NOT NULL,
+    version        BIGINT   NOT NULL,
+    event_type     TEXT     NOT NULL,
+    schema_version BIGINT   NOT NULL,
+    payload        BYTEA    NOT NULL,
+    metadata       BYTEA
+) ON COMMIT DROP";
+
+/// Fill the staging table; the column order is [`BinaryCopy::push`]'s.
+pub const COPY_STAGING: &str = "COPY nexus_staged_events \
+     (ord, stream_id, version, event_type, schema_version, payload, metadata) \
+     FROM STDIN (FORMAT binary)";
+
+/// Empty the staging table for the next batch in the same transaction.
+pub const CLEAR_STAGING: &str = "TRUNCATE nexus_staged_events";
+
+/// The binary `COPY` file header: signature, flags, header extension length.
+const HEADER: &[u8] = b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0";
+
+/// The end-of-data marker: a tuple with field count -1.
+const TRAILER: &[u8] = &(-1_i16).to_be_bytes();
+
+/// Columns per staged row.
+const FIELDS: i16 = 7;
+
+/// One event to stage, in the staging table's column order.
+#[derive(Debug)]
+pub struct StagedRow<'a> {
+    /// Position in the batch; the merge inserts in this order.
+    pub ord: i64,
+    pub stream_id: &'a [u8],
+    pub version: i64,
+    pub event_type: &'a str,
+    pub schema_version: i64,
+    pub payload: &'a [u8],
+    pub metadata: Option<&'a [u8]>,
+}
+
+/// An incrementally built binary `COPY` stream.
+///
+/// [`push`](Self::push) appends rows; [`take`](Self::take) hands off what is
+/// buffered so far, so a large batch is sent in [`FLUSH_AT`]-sized pieces
+/// instead of being encoded whole.
+#[derive(Debug)]
+pub struct BinaryCopy {
+    buf: Vec<u8>,
+}
+
+impl BinaryCopy {
+    /// A stream holding just the file header.
+    pub fn new() -> Self {
+        Self {
+            buf: HEADER.to_vec(),
+        }
+    }
+
+    /// Append one row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::InvalidInput`] for a field longer than a
+    /// `COPY` field length can express (2 GiB) — postgres would refuse it
+    /// anyway.
+    pub fn push(&mut self, row: &StagedRow<'_>) -> Result<(), PostgresError> {
+        self.buf.extend_from_slice(&FIELDS.to_be_bytes());
+        self.int8(row.ord);
+        self.bytes(Some(row.stream_id), row)?;
+        self.int8(row.version);
+        self.bytes(Some(row.event_type.as_bytes()), row)?;
+        self.int8(row.schema_version);
+        self.bytes(Some(row.payload), row)?;
+        self.bytes(row.metadata, row)
+    }
+
+    /// Bytes buffered so far.
+    pub const fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Take the buffered bytes, leaving the stream empty.
+    pub fn take(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buf)
+    }
+
+    /// Append the end-of-data marker and return the remaining bytes.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.buf.extend_from_slice(TRAILER);
+        self.buf
+    }
+
+    fn int8(&mut self, value: i64) {
+        self.buf.extend_from_slice(&8_i32.to_be_bytes());
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// A length-prefixed field, or the NULL marker for `None`.
+    fn bytes(&mut self, value: Option<&[u8]>, row: &StagedRow<'_>) -> Result<(), PostgresError> {
+        let Some(data) = value else {
+            self.buf.extend_from_slice(&(-1_i32).to_be_bytes());
+            return Ok(());
+        };
+        let len = i32::try_from(data.len()).map_err(|_| PostgresError::InvalidInput {
+            stream_id: ErrorId::from_display(&String::from_utf8_lossy(row.stream_id)),
+            version: u64::try_from(row.version).unwrap_or_default(),
+            reason: ErrorId::from_display(&"field exceeds the 2 GiB COPY limit"),
+        })?;
+        self.buf.extend_from_slice(&len.to_be_bytes());
+        self.buf.extend_from_slice(data);
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for the binary encoding
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    const fn row(metadata: Option<&[u8]>) -> StagedRow<'_> {
+        StagedRow {
+            ord: 1,
+            stream_id: b"s",
+            version: 2,
+            event_type: "E",
+            schema_version: 3,
+            payload: b"pp",
+            metadata,
+        }
+    }
+
+    #[test]
+    fn empty_stream_is_header_and_trailer() {
+        let bytes = BinaryCopy::new().finish();
+        assert_eq!(bytes.len(), 19 + 2);
+        assert!(bytes.starts_with(b"PGCOPY\n\xff\r\n\0"));
+        assert!(bytes.ends_with(&[0xff, 0xff]));
+    }
+
+    #[test]
+    fn rows_encode_each_field_length_prefixed() {
+        let mut copy = BinaryCopy::new();
+        copy.push(&row(None)).unwrap();
+        let bytes = copy.finish();
+        let tuple = &bytes[HEADER.len()..bytes.len() - TRAILER.len()];
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&7_i16.to_be_bytes());
+        for (len, data) in [
+            (8, 1_i64.to_be_bytes().as_slice()),
+            (1, b"s".as_slice()),
+            (8, 2_i64.to_be_bytes().as_slice()),
+            (1, b"E".as_slice()),
+            (8, 3_i64.to_be_bytes().as_slice()),
+            (2, b"pp".as_slice()),
+        ] {
+            expected.extend_from_slice(&i32::to_be_bytes(len));
+            expected.extend_from_slice(data);
+        }
+        expected.extend_from_slice(&(-1_i32).to_be_bytes());
+        assert_eq!(tuple, expected);
+    }
+
+    #[test]
+    fn take_hands_off_the_buffer_and_keeps_encoding() {
+        let mut copy = BinaryCopy::new();
+        copy.push(&row(Some(b"m"))).unwrap();
+        let first = copy.take();
+        assert_eq!(copy.len(), 0);
+        copy.push(&row(None)).unwrap();
+        let rest = copy.finish();
+        // The second tuple is one byte shorter: NULL metadata has no data.
+        assert_eq!(first.len() - HEADER.len(), rest.len() - TRAILER.len() + 1);
+    }
+}
@@ -29,6 +29,18 @@ pub enum PostgresError {
         reason: ErrorId<128>,
     },
 
+    /// Invalid input on the write path (e.g. a snapshot that does not cover
+    /// the last appended event).
+    ///
+    /// Distinct from `CorruptRow`, which indicates already-persisted data is
+    /// unreadable. This error fires before anything is committed.
+    #[error("invalid input for stream '{stream_id}' at version {version}: {reason}")]
+    InvalidInput {
+        stream_id: ErrorId,
+        version: u64,
+        reason: ErrorId<128>,
+    },
+
     /// Building the canonical wire frame for a row failed.
     #[error("wire frame build failed in stream '{stream_id}' at version {version}: {reason}")]
     Frame {
@@ -37,6 +49,25 @@ pub enum PostgresError {
         reason: ErrorId<128>,
     },
 
+    /// A configured schema, table prefix or notify channel is not a name the
+    /// store can safely use. Raised by the builder before connecting.
+    #[error("invalid identifier '{name}': {reason}")]
+    InvalidIdentifier { name: ErrorId, reason: ErrorId<128> },
+
+    /// The database is missing migrations this build requires, and the store
+    /// was opened [`VerifyOnly`](crate::MigrationMode::VerifyOnly).
+    #[error("schema is at migration {applied}, this build requires {required}")]
+    SchemaOutdated { applied: u32, required: u32 },
+
+    /// The events table's partition layout does not match the configured
+    /// [`Partitioning`](crate::Partitioning), or a partition operation was
+    /// refused.
+    #[error("partitioning of '{table}': {reason}")]
+    Partition {
+        table: ErrorId,
+        reason: ErrorId<128>,
+    },
+
     /// Wake registration over `LISTEN/NOTIFY` failed.
     #[error("listen/notify wake setup failed: {0}")]
     Wake(#[source] sqlx::Error),
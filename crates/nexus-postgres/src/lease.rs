@@ -0,0 +1,175 @@
+//! Leader leases on session-level advisory locks.
+//!
+//! [`PostgresLeases`] implements [`LeaderLease`] with
+//! `pg_try_advisory_lock`, keyed by a hash of the lease name and the store's
+//! events table — stores in different namespaces never contend for the same
+//! lease. A session lock lives exactly as long as the session that took it,
+//! so each held lease owns a dedicated connection, outside the pool:
+//! returning it to the pool would leave the lock held by whichever task
+//! borrowed the connection next.
+//!
+//! A keeper task pings that connection every heartbeat. When the ping
+//! fails or times out, the session (and with it the lock) may already be
+//! gone, so the guard reports the lease lost and the keeper drops the
+//! connection — a replica that cannot prove it is leader stops acting as
+//! one. Dropping the guard unlocks and closes the connection.
+//!
+//! Waiting for a lease retries `pg_try_advisory_lock` rather than blocking
+//! in `pg_advisory_lock`: a blocked backend whose client has gone away
+//! would still be granted the lock, and hold it until it next writes to
+//! the dead socket.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use nexus_store::lease::{LeaderLease, LeaseGuard};
+use sqlx::{Connection, PgConnection, PgPool};
+use tokio::sync::{oneshot, watch};
+
+use crate::error::PostgresError;
+use crate::schema::Statements;
+
+/// How often a held lease's connection is pinged unless configured.
+pub const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(1);
+
+/// How often a waiting [`acquire`](LeaderLease::acquire) retries unless
+/// configured.
+pub const DEFAULT_RETRY: Duration = Duration::from_secs(1);
+
+/// Advisory-lock leader leases, from
+/// [`PostgresStore::leases`](crate::PostgresStore::leases).
+///
+/// ```ignore
+/// let leases = store.leases().heartbeat(Duration::from_millis(500));
+/// let guard = leases.acquire("order-summary").await?;
+/// let events = while_held(guard, Subscription::new(&handle).subscribe_all(from)?);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PostgresLeases {
+    pool: PgPool,
+    sql: Arc<Statements>,
+    heartbeat: Duration,
+    retry: Duration,
+}
+
+impl PostgresLeases {
+    pub(crate) const fn new(pool: PgPool, sql: Arc<Statements>) -> Self {
+        Self {
+            pool,
+            sql,
+            heartbeat: DEFAULT_HEARTBEAT,
+            retry: DEFAULT_RETRY,
+        }
+    }
+
+    /// Ping a held lease's connection this often ([`DEFAULT_HEARTBEAT`]
+    /// unless set). A lost connection is noticed within about two
+    /// heartbeats: one to the next ping, one for the ping to time out.
+    #[must_use]
+    pub const fn heartbeat(mut self, every: Duration) -> Self {
+        self.heartbeat = every;
+        self
+    }
+
+    /// Retry a taken lease this often while waiting in
+    /// [`acquire`](LeaderLease::acquire) ([`DEFAULT_RETRY`] unless set).
+    #[must_use]
+    pub const fn retry_every(mut self, every: Duration) -> Self {
+        self.retry = every;
+        self
+    }
+
+    /// A dedicated session for one lease attempt.
+    async fn connect(&self) -> Result<PgConnection, PostgresError> {
+        PgConnection::connect_with(&self.pool.connect_options())
+            .await
+            .map_err(PostgresError::Sqlx)
+    }
+
+    /// Try the lock once on `conn`.
+    async fn try_lock(&self, conn: &mut PgConnection, name: &str) -> Result<bool, PostgresError> {
+        sqlx::query_scalar(&self.sql.try_lease)
+            .bind(name)
+            .fetch_one(conn)
+            .await
+            .map_err(PostgresError::Sqlx)
+    }
+
+    /// Spawn the keeper that holds `session` — and its lock — for the guard.
+    fn keep(&self, mut session: PgConnection, name: String) -> PgLeaseGuard {
+        let (signal, held) = watch::channel(true);
+        let (release, mut released) = oneshot::channel::<()>();
+        let heartbeat = self.heartbeat;
+        let unlock = self.sql.release_lease.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    // The guard was dropped.
+                    _ = &mut released => {
+                        // Best effort: closing the session unlocks anyway.
+                        let _ = sqlx::query(&unlock).bind(&name).execute(&mut session).await;
+                        let _ = session.close().await;
+                        return;
+                    }
+                    () = tokio::time::sleep(heartbeat) => {
+                        let ping = tokio::time::timeout(heartbeat, session.ping()).await;
+                        if !matches!(ping, Ok(Ok(()))) {
+                            signal.send_replace(false);
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+        PgLeaseGuard {
+            held,
+            _release: release,
+        }
+    }
+}
+
+impl LeaderLease for PostgresLeases {
+    type Guard = PgLeaseGuard;
+    type Error = PostgresError;
+
+    async fn try_acquire(&self, name: &str) -> Result<Option<Self::Guard>, Self::Error> {
+        let mut conn = self.connect().await?;
+        if self.try_lock(&mut conn, name).await? {
+            return Ok(Some(self.keep(conn, name.to_owned())));
+        }
+        // Not ours: hang up rather than keep an idle session open.
+        let _ = conn.close().await;
+        Ok(None)
+    }
+
+    async fn acquire(&self, name: &str) -> Result<Self::Guard, Self::Error> {
+        let mut conn = self.connect().await?;
+        while !self.try_lock(&mut conn, name).await? {
+            tokio::time::sleep(self.retry).await;
+        }
+        Ok(self.keep(conn, name.to_owned()))
+    }
+}
+
+/// A held [`PostgresLeases`] lease. Dropping it unlocks the lease and closes
+/// its connection.
+#[derive(Debug)]
+pub struct PgLeaseGuard {
+    held: watch::Receiver<bool>,
+    /// Dropped with the guard, which tells the keeper to let go.
+    _release: oneshot::Sender<()>,
+}
+
+impl LeaseGuard for PgLeaseGuard {
+    fn is_held(&self) -> bool {
+        *self.held.borrow()
+    }
+
+    fn lost(&self) -> impl Future<Output = ()> + Send + use<> {
+        let mut held = self.held.clone();
+        async move {
+            // The keeper dropping its signal also means the lease is gone.
+            let _ = held.wait_for(|still| !still).await;
+        }
+    }
+}
@@ -15,15 +15,73 @@
 //! [`PgAllPos`] `(txid, seq)` (the #213 ordering decision, made correct by
 //! construction via the #266 adapter-defined-position seam). The second
 //! adapter, written to validate the adapter contract before the 1.0 freeze.
+//!
+//! Aggregate snapshots ([`SnapshotStore<Vec<u8>, Version>`](nexus_store::SnapshotStore),
+//! [`SnapshotAppend`](nexus_store::SnapshotAppend)) and projection checkpoints
+//! (`SnapshotStore<Vec<u8>, PgAllPos>`) live in the same database as the
+//! events, in tables the same schema setup creates.
+//!
+//! [`TransactionalAppend`](nexus_store::TransactionalAppend) appends inside a
+//! caller-owned `sqlx::Transaction`, so events commit atomically with the
+//! application's own tables; `EventStore::save_in_tx` is the repository-level
+//! form.
+//!
+//! [`PostgresStore::builder`] places a store's tables under a schema and/or
+//! table prefix and picks its `LISTEN/NOTIFY` channel, so independent bounded
+//! contexts or tenants share one database without colliding.
+//!
+//! The schema evolves through embedded, versioned migrations applied on open
+//! under an advisory lock, or only verified with [`MigrationMode::VerifyOnly`]
+//! where the store's role may not run DDL.
+//!
+//! Wakes come from `LISTEN/NOTIFY` by default. [`WakeStrategy::Poll`] polls
+//! for newly settled rows instead, for `PgBouncer` in transaction pooling mode
+//! and databases that refuse `LISTEN`; [`WakeStrategy::Hybrid`] listens and
+//! falls back to polling while the listener is down.
+//! [`PostgresStore::listener_health`] reports the listener's state.
+//!
+//! `$all` reads stop at the oldest in-flight transaction, so one long
+//! transaction stalls every `$all` subscriber. [`StallDetection`] reports such
+//! a stall, with the transaction and backend holding it, and
+//! [`PostgresStore::subscribe_all_with_stalls`] tells subscribers about it
+//! once they have read everything it does not hold back.
+//!
+//! [`PostgresStore::leases`] hands out [`LeaderLease`](nexus_store::LeaderLease)s
+//! on session advisory locks, so a projection or saga poller runs on one
+//! replica at a time and stops promptly when its connection drops.
+//!
+//! [`Partitioning`] lays a new namespace's events table out in range
+//! partitions by `global_seq` or by month, created ahead of need, so old
+//! partitions can be detached for archival. Reads and the `$all` watermark
+//! work unchanged across partitions.
+//!
+//! With the `export` / `import` features, [`StreamLister`](nexus_store::export::StreamLister)
+//! pages stream ids by keyset and [`AtomicAppend`](nexus_store::import::AtomicAppend)
+//! applies a whole import batch in one transaction, so the
+//! [`EventExporter`](nexus_store::export::EventExporter) /
+//! [`EventImporter`](nexus_store::import::EventImporter) backup path works
+//! against postgres too.
 
 mod builder;
+mod copy;
 mod error;
 mod hex;
+mod lease;
+mod migrate;
+mod partition;
 mod position;
 mod schema;
+mod snapshot;
+mod stall;
 mod store;
 mod wake;
 
+pub use builder::PostgresStoreBuilder;
 pub use error::PostgresError;
+pub use lease::{DEFAULT_HEARTBEAT, DEFAULT_RETRY, PgLeaseGuard, PostgresLeases};
+pub use migrate::{MigrationMode, SCHEMA_VERSION};
+pub use partition::{EventPartition, PartitionRange, PartitionScheme, Partitioning};
 pub use position::PgAllPos;
+pub use stall::{AllItem, StallDetection, StallObserver, WatermarkStall};
 pub use store::PostgresStore;
+pub use wake::{ListenerHealth, PollInterval, WakeStrategy};
@@ -0,0 +1,345 @@
+//! Versioned schema migrations for [`PostgresStore`](crate::PostgresStore).
+//!
+//! The schema is an ordered list of embedded [`Migration`]s, numbered from 1.
+//! Each namespace records the ones it has applied in its own
+//! `nexus_schema_migrations` table (prefixed and schema-qualified like the
+//! store's other tables), so a store opened by a newer build applies exactly
+//! the migrations its database is missing — no manual DDL per environment.
+//!
+//! Applying runs every pending migration and its bookkeeping row in one
+//! transaction under a transaction-scoped advisory lock: concurrent starts
+//! queue on the lock, and each later one finds nothing left to do. DDL is
+//! transactional in postgres, so a failed migration leaves the schema as it
+//! was.
+//!
+//! [`MigrationMode::VerifyOnly`] runs no DDL at all. It only reads the
+//! bookkeeping table and fails with [`PostgresError::SchemaOutdated`] if a
+//! migration is missing — for production roles without DDL rights, where
+//! migrations are applied out of band by a privileged deploy step.
+//!
+//! Migrations 1 and 2 reproduce the `CREATE … IF NOT EXISTS` setup that
+//! predates the runner, so a database created before it is adopted in place.
+//! Later migrations are plain, forward-only DDL and must never be edited once
+//! released — append a new one instead.
+
+use sqlx::{Executor, PgPool};
+
+use crate::error::PostgresError;
+use crate::partition;
+use crate::schema::Names;
+
+/// The advisory lock key every migration run takes, whatever its namespace.
+/// One key for all namespaces also serializes the `CREATE SCHEMA` of stores
+/// sharing a schema, which would otherwise race. Partition upkeep takes it
+/// too, so it never runs against a half-migrated namespace.
+pub const MIGRATION_LOCK: i64 = 0x6e65_7875_735f_6d67; // "nexus_mg"
+
+/// The bookkeeping table, before the namespace's prefix.
+const MIGRATIONS_TABLE: &str = "nexus_schema_migrations";
+
+/// How a store treats migrations its database is missing when it opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MigrationMode {
+    /// Apply pending migrations under the advisory lock. Needs DDL rights.
+    #[default]
+    Apply,
+    /// Apply nothing; fail with [`PostgresError::SchemaOutdated`] unless
+    /// every migration is already applied. Needs only `SELECT` on the
+    /// bookkeeping table.
+    VerifyOnly,
+}
+
+/// One embedded schema change.
+struct Migration {
+    version: u32,
+    name: &'static str,
+    /// Renders the DDL against the store's namespace.
+    sql: fn(&Names) -> String,
+}
+
+/// Every migration, in the order they apply. Append only.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "events",
+        sql: events_table,
+    },
+    Migration {
+        version: 2,
+        name: "snapshots and checkpoints",
+        sql: state_tables,
+    },
+    Migration {
+        version: 3,
+        name: "snapshot history",
+        sql: snapshot_history,
+    },
+];
+
+/// The schema version this build requires: the last migration's number.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// The events table and its indexes.
+///
+/// - `BIGINT GENERATED ALWAYS AS IDENTITY` (not `BIGSERIAL`) — SQL-standard,
+///   prevents manual inserts into the id column.
+/// - `txid xid8 DEFAULT pg_current_xact_id()` — writing transaction id, the
+///   Way-2 watermark key. `xid8` is 64-bit, non-wrapping.
+/// - `UNIQUE (stream_id, version)` — per-stream conflict arbiter; a
+///   concurrent INSERT that races the same version raises a unique violation →
+///   `AppendError::Conflict`. Atomic, no `SELECT … FOR UPDATE` needed.
+///
+/// A partitioned namespace gets [`partition::events_ddl`] instead; the layout
+/// is fixed when this migration first runs.
+fn events_table(names: &Names) -> String {
+    if let Some(scheme) = names.partitioning() {
+        return partition::events_ddl(names, scheme);
+    }
+    let events = names.table("events");
+    let stream_idx = names.index("events_stream_idx");
+    let watermark_idx = names.index("events_watermark_idx");
+    format!(
+        r"
+CREATE TABLE IF NOT EXISTS {events} (
+    global_seq     BIGINT GENERATED ALWAYS AS IDENTITY,
+    stream_id      BYTEA    NOT NULL,
+    version        BIGINT   NOT NULL,
+    txid           xid8     NOT NULL DEFAULT pg_current_xact_id(),
+    event_type     TEXT     NOT NULL,
+    schema_version BIGINT   NOT NULL,
+    payload        BYTEA    NOT NULL,
+    metadata       BYTEA,
+    PRIMARY KEY (global_seq),
+    UNIQUE (stream_id, version)
+);
+CREATE INDEX IF NOT EXISTS {stream_idx}    ON {events} (stream_id, version);
+CREATE INDEX IF NOT EXISTS {watermark_idx} ON {events} (txid, global_seq);
+"
+    )
+}
+
+/// The snapshot and checkpoint tables: one row per id, the latest aggregate
+/// snapshot at a `version`, and the latest projection checkpoint at a
+/// `(txid, seq)` [`PgAllPos`](crate::PgAllPos). State and position share a
+/// row, so a commit is one atomic upsert. `schema_version` is `BIGINT` for
+/// the same full-`NonZeroU32` reason as on `events`.
+fn state_tables(names: &Names) -> String {
+    let snapshots = names.table("snapshots");
+    let checkpoints = names.table("checkpoints");
+    format!(
+        r"
+CREATE TABLE IF NOT EXISTS {snapshots} (
+    id             BYTEA    NOT NULL,
+    schema_version BIGINT   NOT NULL,
+    version        BIGINT   NOT NULL,
+    state          BYTEA    NOT NULL,
+    PRIMARY KEY (id)
+);
+CREATE TABLE IF NOT EXISTS {checkpoints} (
+    id             BYTEA    NOT NULL,
+    schema_version BIGINT   NOT NULL,
+    txid           BIGINT   NOT NULL,
+    seq            BIGINT   NOT NULL,
+    state          BYTEA    NOT NULL,
+    PRIMARY KEY (id)
+);
+"
+    )
+}
+
+/// Key snapshots on `(id, version)`, so an id keeps a history of snapshots
+/// — as many as the store's
+/// [`snapshot_retention`](crate::PostgresStoreBuilder::snapshot_retention)
+/// allows — instead of one row. Existing rows become each id's only entry.
+fn snapshot_history(names: &Names) -> String {
+    let snapshots = names.table("snapshots");
+    let pkey = names.index("snapshots_pkey");
+    format!(
+        r"
+ALTER TABLE {snapshots}
+    DROP CONSTRAINT {pkey},
+    ADD PRIMARY KEY (id, version);
+"
+    )
+}
+
+/// The namespace's schema, if any, and its bookkeeping table.
+fn bookkeeping_ddl(names: &Names) -> String {
+    let create_schema = names.schema().map_or_else(String::new, |schema| {
+        format!("CREATE SCHEMA IF NOT EXISTS \"{schema}\";")
+    });
+    let table = names.table(MIGRATIONS_TABLE);
+    format!(
+        r"{create_schema}
+CREATE TABLE IF NOT EXISTS {table} (
+    version    BIGINT      NOT NULL,
+    name       TEXT        NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (version)
+);
+"
+    )
+}
+
+/// The highest applied migration this build knows, 0 if none.
+fn highest_applied(applied: &[i64]) -> u32 {
+    MIGRATIONS
+        .iter()
+        .filter(|m| applied.contains(&i64::from(m.version)))
+        .map(|m| m.version)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Migrations up to `target` not yet in `applied`, in order.
+fn pending(applied: &[i64], target: u32) -> impl Iterator<Item = &'static Migration> + '_ {
+    MIGRATIONS
+        .iter()
+        .filter(move |m| m.version <= target && !applied.contains(&i64::from(m.version)))
+}
+
+/// Bring the namespace to `target` (or check it is there) according to
+/// `mode`. Returns the highest applied migration afterwards.
+///
+/// A database that already records migrations this build does not know —
+/// one migrated by a newer build — is accepted: migrations only add, so an
+/// older build keeps working against the newer schema during a rollout.
+///
+/// # Errors
+///
+/// Returns [`PostgresError::SchemaOutdated`] in
+/// [`VerifyOnly`](MigrationMode::VerifyOnly) mode when a migration up to
+/// `target` is missing, and [`PostgresError::Sqlx`] if a query or migration
+/// fails.
+pub async fn run(
+    pool: &PgPool,
+    names: &Names,
+    mode: MigrationMode,
+    target: u32,
+) -> Result<u32, PostgresError> {
+    match mode {
+        MigrationMode::Apply => apply(pool, names, target).await,
+        MigrationMode::VerifyOnly => verify(pool, names, target).await,
+    }
+}
+
+async fn apply(pool: &PgPool, names: &Names, target: u32) -> Result<u32, PostgresError> {
+    let table = names.table(MIGRATIONS_TABLE);
+    let setup = bookkeeping_ddl(names);
+    let select = format!("SELECT version FROM {table}");
+    let record = format!("INSERT INTO {table} (version, name) VALUES ($1, $2)");
+    let mut tx = pool.begin().await.map_err(PostgresError::Sqlx)?;
+    // Held until commit or rollback; a concurrent start waits here, then
+    // reads the versions this run recorded.
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(MIGRATION_LOCK)
+        .execute(&mut *tx)
+        .await
+        .map_err(PostgresError::Sqlx)?;
+    tx.execute(setup.as_str())
+        .await
+        .map_err(PostgresError::Sqlx)?;
+
+    let mut applied: Vec<i64> = sqlx::query_scalar(&select)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(PostgresError::Sqlx)?;
+    let todo: Vec<(&Migration, String)> = pending(&applied, target)
+        .map(|migration| (migration, (migration.sql)(names)))
+        .collect();
+    for (migration, ddl) in &todo {
+        tx.execute(ddl.as_str())
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        sqlx::query(&record)
+            .bind(i64::from(migration.version))
+            .bind(migration.name)
+            .execute(&mut *tx)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        applied.push(i64::from(migration.version));
+    }
+    tx.commit().await.map_err(PostgresError::Sqlx)?;
+    Ok(highest_applied(&applied))
+}
+
+async fn verify(pool: &PgPool, names: &Names, target: u32) -> Result<u32, PostgresError> {
+    let table = names.table(MIGRATIONS_TABLE);
+    let select = format!("SELECT version FROM {table}");
+    // `to_regclass` is NULL for a missing table *or* schema, where a plain
+    // SELECT would fail — both just mean nothing is applied yet.
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(&table)
+        .fetch_one(pool)
+        .await
+        .map_err(PostgresError::Sqlx)?;
+    let applied: Vec<i64> = if exists {
+        sqlx::query_scalar(&select)
+            .fetch_all(pool)
+            .await
+            .map_err(PostgresError::Sqlx)?
+    } else {
+        Vec::new()
+    };
+
+    if pending(&applied, target).next().is_some() {
+        return Err(PostgresError::SchemaOutdated {
+            applied: highest_applied(&applied),
+            required: target,
+        });
+    }
+    Ok(highest_applied(&applied))
+}
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for the migration list
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_numbered_consecutively_from_one() {
+        for (expected, migration) in (1..).zip(MIGRATIONS) {
+            assert_eq!(migration.version, expected, "{}", migration.name);
+        }
+        assert_eq!(SCHEMA_VERSION, 3);
+    }
+
+    #[test]
+    fn pending_skips_applied_and_stops_at_the_target() {
+        let versions = |applied: &[i64], target| {
+            pending(applied, target)
+                .map(|m| m.version)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(versions(&[], SCHEMA_VERSION), [1, 2, 3]);
+        assert_eq!(versions(&[1], SCHEMA_VERSION), [2, 3]);
+        assert_eq!(versions(&[], 1), [1]);
+        assert!(versions(&[1, 2, 3, 99], SCHEMA_VERSION).is_empty());
+    }
+
+    #[test]
+    fn highest_applied_ignores_unknown_versions() {
+        assert_eq!(highest_applied(&[]), 0);
+        assert_eq!(highest_applied(&[2, 1]), 2);
+        assert_eq!(highest_applied(&[1, 99]), 1);
+    }
+
+    #[test]
+    fn migrations_render_against_the_namespace() {
+        let names = Names::new(Some("billing"), "tenant_a_", "ch").unwrap();
+        assert!(bookkeeping_ddl(&names).starts_with("CREATE SCHEMA IF NOT EXISTS \"billing\";"));
+        assert!(
+            bookkeeping_ddl(&names).contains("\"billing\".\"tenant_a_nexus_schema_migrations\"")
+        );
+        assert!(
+            events_table(&names)
+                .contains("\"tenant_a_events_watermark_idx\" ON \"billing\".\"tenant_a_events\"")
+        );
+        assert!(state_tables(&names).contains("\"billing\".\"tenant_a_checkpoints\""));
+        assert!(snapshot_history(&names).contains("DROP CONSTRAINT \"tenant_a_snapshots_pkey\""));
+    }
+}
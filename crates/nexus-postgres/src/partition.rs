@@ -0,0 +1,681 @@
+//! Opt-in range-partitioned events table.
+//!
+//! One heap holding every event grows without bound, and vacuum and index
+//! maintenance on it grow with it. With [`Partitioning`] the events table is
+//! created `PARTITION BY RANGE` — on `global_seq`, or on a `recorded_at`
+//! timestamp by calendar month — so each partition is vacuumed and indexed
+//! on its own, and old ones are detached for archival instead of deleted row
+//! by row.
+//!
+//! Reads are unchanged: the stream, `$all` and watermark queries run against
+//! the parent table, and postgres merges the partitions' indexes in order.
+//!
+//! # Conflicts
+//!
+//! A unique constraint on a partitioned table must include the partition
+//! key, so `UNIQUE (stream_id, version)` cannot be declared. Instead a
+//! `BEFORE INSERT` trigger claims each version in a `stream_heads` table —
+//! one row per stream, holding its head — and raises the same
+//! `unique_violation` the constraint would when the version is already
+//! taken. Concurrent writers to one stream queue on its head row exactly as
+//! they would on the unique index. The bulk `COPY` merge switches the
+//! trigger to skip a taken version instead, the partitioned equivalent of
+//! `ON CONFLICT DO NOTHING` (see [`crate::copy`]).
+//!
+//! The head table also answers "where is this stream?" in one index probe
+//! rather than one per partition, and keeps answering it after the
+//! partitions holding a stream's events are detached — an archived stream
+//! continues at its next version instead of starting over.
+//!
+//! # Partition upkeep
+//!
+//! There is no default partition: a row with nowhere to go fails its
+//! insert. Opening the store creates the current partition and
+//! [`keep_ahead`](Partitioning::keep_ahead) more, and a background task
+//! repeats that every [`check_every`](Partitioning::check_every). Under
+//! [`MigrationMode::VerifyOnly`](crate::MigrationMode::VerifyOnly) neither
+//! runs — the deploy step that applies migrations calls
+//! [`PostgresStore::ensure_partitions`] instead.
+//!
+//! The layout is chosen when the namespace is created. Opening an existing
+//! namespace with a different layout fails with [`PostgresError::Partition`]
+//! rather than running statements written for the other one.
+
+use std::num::NonZeroU64;
+use std::time::Duration;
+
+use nexus::ErrorId;
+use sqlx::{Executor, PgConnection, PgPool};
+use tokio::task::JoinHandle;
+
+use crate::error::PostgresError;
+use crate::migrate::MIGRATION_LOCK;
+use crate::schema::Names;
+use crate::store::PostgresStore;
+
+/// How many partitions past the current one are kept ready unless
+/// configured.
+pub const DEFAULT_AHEAD: u32 = 2;
+
+/// How often the background task tops up the partitions unless configured.
+pub const DEFAULT_CHECK_EVERY: Duration = Duration::from_mins(1);
+
+/// Make the version-claim trigger skip taken versions for the rest of the
+/// transaction (or until [`RAISE_CONFLICTS`]).
+pub const SKIP_CONFLICTS: &str = "SELECT set_config('nexus.skip_conflicts', 'on', true)";
+
+/// Restore the trigger's default: raise `unique_violation`.
+pub const RAISE_CONFLICTS: &str = "SELECT set_config('nexus.skip_conflicts', 'off', true)";
+
+/// The longest partition name, before the table prefix: a `global_seq`
+/// partition starting at `i64::MAX`.
+pub const LONGEST_PARTITION: &str = "events_9223372036854775807";
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// What the events table is partitioned on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PartitionScheme {
+    /// Ranges of `span` consecutive `global_seq` values.
+    GlobalSeq { span: NonZeroU64 },
+    /// Calendar months (UTC) of a `recorded_at` column set at insert.
+    Monthly,
+}
+
+impl PartitionScheme {
+    /// The partition key column.
+    const fn column(self) -> &'static str {
+        match self {
+            Self::GlobalSeq { .. } => "global_seq",
+            Self::Monthly => "recorded_at",
+        }
+    }
+}
+
+/// A partitioned events table, enabled with
+/// [`PostgresStoreBuilder::partitioning`](crate::PostgresStoreBuilder::partitioning).
+///
+/// ```ignore
+/// let store = PostgresStore::builder()
+///     .partitioning(Partitioning::monthly().keep_ahead(3))
+///     .open(pool)
+///     .await?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Partitioning {
+    scheme: PartitionScheme,
+    ahead: u32,
+    check_every: Duration,
+}
+
+impl Partitioning {
+    /// One partition per `span` consecutive `global_seq` values.
+    #[must_use]
+    pub const fn by_global_seq(span: NonZeroU64) -> Self {
+        Self::new(PartitionScheme::GlobalSeq { span })
+    }
+
+    /// One partition per calendar month (UTC), on a `recorded_at` column.
+    #[must_use]
+    pub const fn monthly() -> Self {
+        Self::new(PartitionScheme::Monthly)
+    }
+
+    const fn new(scheme: PartitionScheme) -> Self {
+        Self {
+            scheme,
+            ahead: DEFAULT_AHEAD,
+            check_every: DEFAULT_CHECK_EVERY,
+        }
+    }
+
+    /// Keep `partitions` empty partitions ready past the current one
+    /// ([`DEFAULT_AHEAD`] unless set). Size it so a burst cannot fill them
+    /// all between two checks.
+    #[must_use]
+    pub const fn keep_ahead(mut self, partitions: u32) -> Self {
+        self.ahead = partitions;
+        self
+    }
+
+    /// Top up the partitions this often ([`DEFAULT_CHECK_EVERY`] unless set).
+    #[must_use]
+    pub const fn check_every(mut self, every: Duration) -> Self {
+        self.check_every = every;
+        self
+    }
+
+    /// What the table is partitioned on.
+    #[must_use]
+    pub const fn scheme(&self) -> PartitionScheme {
+        self.scheme
+    }
+
+    /// How many partitions are kept ready past the current one.
+    #[must_use]
+    pub const fn partitions_ahead(&self) -> u32 {
+        self.ahead
+    }
+
+    /// How often the background task tops up the partitions.
+    #[must_use]
+    pub const fn check_interval(&self) -> Duration {
+        self.check_every
+    }
+}
+
+/// One attached partition of the events table, as listed by
+/// [`PostgresStore::partitions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventPartition {
+    /// The partition's table name, in the store's schema.
+    pub name: String,
+    /// The rows it holds.
+    pub range: PartitionRange,
+}
+
+/// The rows one partition holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum PartitionRange {
+    /// `global_seq` in `start..end`.
+    GlobalSeq { start: u64, end: u64 },
+    /// `recorded_at` within the month, UTC.
+    Month { year: i32, month: u32 },
+}
+
+impl PartitionRange {
+    /// The partition's name, before the table prefix.
+    fn table(self) -> String {
+        match self {
+            Self::GlobalSeq { start, .. } => format!("events_{start}"),
+            Self::Month { year, month } => format!("events_{year:04}_{month:02}"),
+        }
+    }
+
+    /// The `FOR VALUES` clause.
+    fn bounds(self) -> String {
+        match self {
+            Self::GlobalSeq { start, end } => format!("FROM ({start}) TO ({end})"),
+            Self::Month { year, month } => {
+                let (next_year, next_month) = next_month(year, month);
+                format!(
+                    "FROM ('{year:04}-{month:02}-01 00:00:00+00') \
+                     TO ('{next_year:04}-{next_month:02}-01 00:00:00+00')"
+                )
+            }
+        }
+    }
+
+    /// Parse a `FOR VALUES FROM (..) TO (..)` clause rendered with the
+    /// session time zone set to UTC.
+    fn parse(scheme: PartitionScheme, bounds: &str) -> Option<Self> {
+        let mut numbers = bounds
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|run| !run.is_empty());
+        match scheme {
+            PartitionScheme::GlobalSeq { .. } => Some(Self::GlobalSeq {
+                start: numbers.next()?.parse().ok()?,
+                end: numbers.next()?.parse().ok()?,
+            }),
+            PartitionScheme::Monthly => Some(Self::Month {
+                year: numbers.next()?.parse().ok()?,
+                month: numbers.next()?.parse().ok()?,
+            }),
+        }
+    }
+}
+
+const fn next_month(year: i32, month: u32) -> (i32, u32) {
+    if month >= 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Schema
+// ---------------------------------------------------------------------------
+
+/// The partitioned events table, its head table and the version-claim
+/// trigger. Migration 1's DDL for a partitioned namespace.
+pub fn events_ddl(names: &Names, scheme: PartitionScheme) -> String {
+    let events = names.table("events");
+    let heads = names.table("stream_heads");
+    let claim = names.table("events_claim_version");
+    let trigger = names.index("events_claim_version");
+    let stream_idx = names.index("events_stream_idx");
+    let watermark_idx = names.index("events_watermark_idx");
+    let column = scheme.column();
+    let (recorded_at, key) = match scheme {
+        PartitionScheme::GlobalSeq { .. } => ("", "global_seq"),
+        PartitionScheme::Monthly => (
+            "recorded_at    TIMESTAMPTZ NOT NULL DEFAULT now(),\n    ",
+            "global_seq, recorded_at",
+        ),
+    };
+    format!(
+        r"
+CREATE TABLE IF NOT EXISTS {events} (
+    global_seq     BIGINT GENERATED ALWAYS AS IDENTITY,
+    stream_id      BYTEA    NOT NULL,
+    version        BIGINT   NOT NULL,
+    txid           xid8     NOT NULL DEFAULT pg_current_xact_id(),
+    event_type     TEXT     NOT NULL,
+    schema_version BIGINT   NOT NULL,
+    payload        BYTEA    NOT NULL,
+    metadata       BYTEA,
+    {recorded_at}PRIMARY KEY ({key})
+) PARTITION BY RANGE ({column});
+CREATE INDEX IF NOT EXISTS {stream_idx}    ON {events} (stream_id, version);
+CREATE INDEX IF NOT EXISTS {watermark_idx} ON {events} (txid, global_seq);
+CREATE TABLE IF NOT EXISTS {heads} (
+    stream_id      BYTEA    NOT NULL,
+    version        BIGINT   NOT NULL,
+    PRIMARY KEY (stream_id)
+);
+CREATE OR REPLACE FUNCTION {claim}() RETURNS trigger LANGUAGE plpgsql AS $claim$
+BEGIN
+    INSERT INTO {heads} AS head (stream_id, version)
+    VALUES (NEW.stream_id, NEW.version)
+    ON CONFLICT (stream_id) DO UPDATE SET version = EXCLUDED.version
+    WHERE head.version < EXCLUDED.version;
+    IF FOUND THEN
+        RETURN NEW;
+    END IF;
+    IF current_setting('nexus.skip_conflicts', true) = 'on' THEN
+        RETURN NULL;
+    END IF;
+    RAISE unique_violation
+        USING MESSAGE = format('version %s of the stream is already taken', NEW.version);
+END
+$claim$;
+CREATE OR REPLACE TRIGGER {trigger} BEFORE INSERT ON {events}
+    FOR EACH ROW EXECUTE FUNCTION {claim}();
+"
+    )
+}
+
+/// Fail unless the events table's layout is the configured one.
+///
+/// # Errors
+///
+/// Returns [`PostgresError::Partition`] on a mismatch, and
+/// [`PostgresError::Sqlx`] if the catalog cannot be read.
+pub async fn check_layout(pool: &PgPool, names: &Names) -> Result<(), PostgresError> {
+    let events = names.table("events");
+    let found: Option<String> = sqlx::query_scalar(&format!(
+        "SELECT a.attname::text FROM pg_partitioned_table p \
+         JOIN pg_attribute a ON a.attrelid = p.partrelid AND a.attnum = p.partattrs[0] \
+         WHERE p.partrelid = '{events}'::regclass"
+    ))
+    .fetch_optional(pool)
+    .await
+    .map_err(PostgresError::Sqlx)?;
+    let wanted = names.partitioning().map(PartitionScheme::column);
+    if found.as_deref() == wanted {
+        return Ok(());
+    }
+    let reason = match (found, wanted) {
+        (None, _) => {
+            "the table is not partitioned; pick the layout before it is created".to_owned()
+        }
+        (Some(column), None) => {
+            format!("the table is partitioned on {column}; configure matching partitioning")
+        }
+        (Some(column), Some(configured)) => {
+            format!("the table is partitioned on {column}, not {configured}")
+        }
+    };
+    Err(partition_err(&events, &reason))
+}
+
+fn partition_err(table: &str, reason: &str) -> PostgresError {
+    PostgresError::Partition {
+        table: ErrorId::from_display(&table),
+        reason: ErrorId::from_display(&reason),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Upkeep
+// ---------------------------------------------------------------------------
+
+/// Creates, lists and detaches one store's partitions.
+#[derive(Debug, Clone)]
+pub struct PartitionManager {
+    names: Names,
+    partitioning: Partitioning,
+    /// Whether this store creates partitions itself (not when only
+    /// verifying migrations).
+    maintain: bool,
+}
+
+impl PartitionManager {
+    pub const fn new(names: Names, partitioning: Partitioning, maintain: bool) -> Self {
+        Self {
+            names,
+            partitioning,
+            maintain,
+        }
+    }
+
+    pub const fn partitioning(&self) -> Partitioning {
+        self.partitioning
+    }
+
+    pub const fn maintains(&self) -> bool {
+        self.maintain
+    }
+
+    /// Create the current partition and the configured number ahead of it.
+    ///
+    /// Runs under the migration lock, so concurrent starts do not race to
+    /// create the same partition.
+    pub async fn ensure(&self, pool: &PgPool) -> Result<(), PostgresError> {
+        let events = self.names.table("events");
+        let mut tx = pool.begin().await.map_err(PostgresError::Sqlx)?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(MIGRATION_LOCK)
+            .execute(&mut *tx)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        for range in self.missing(&mut tx).await? {
+            let partition = self.names.table(&range.table());
+            tx.execute(
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {partition} PARTITION OF {events} \
+                     FOR VALUES {}",
+                    range.bounds()
+                )
+                .as_str(),
+            )
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        }
+        tx.commit().await.map_err(PostgresError::Sqlx)
+    }
+
+    /// The partitions to create so the current one and the configured number
+    /// ahead exist.
+    async fn missing(&self, conn: &mut PgConnection) -> Result<Vec<PartitionRange>, PostgresError> {
+        let ahead = self.partitioning.ahead;
+        match self.partitioning.scheme {
+            PartitionScheme::GlobalSeq { span: width } => {
+                let span = width.get();
+                // The current partition is the one the next sequence value
+                // lands in.
+                let current = self.last_seq(conn).await?.saturating_add(1) / span * span;
+                // Continue from the last partition, so a changed span never
+                // overlaps the existing ones.
+                let mut start = self
+                    .list(conn)
+                    .await?
+                    .iter()
+                    .filter_map(|p| match p.range {
+                        PartitionRange::GlobalSeq { end, .. } => Some(end),
+                        PartitionRange::Month { .. } => None,
+                    })
+                    .max()
+                    .unwrap_or(current);
+                let horizon = current.saturating_add(span.saturating_mul(u64::from(ahead)));
+                let mut wanted = Vec::new();
+                while start <= horizon {
+                    let end = start.saturating_add(span);
+                    wanted.push(PartitionRange::GlobalSeq { start, end });
+                    start = end;
+                }
+                Ok(wanted)
+            }
+            PartitionScheme::Monthly => {
+                let (mut year, mut month) = current_month(conn).await?;
+                let mut wanted = Vec::new();
+                for _ in 0..=ahead {
+                    wanted.push(PartitionRange::Month { year, month });
+                    (year, month) = next_month(year, month);
+                }
+                Ok(wanted)
+            }
+        }
+    }
+
+    /// The attached partitions, in range order.
+    pub async fn list(
+        &self,
+        conn: &mut PgConnection,
+    ) -> Result<Vec<EventPartition>, PostgresError> {
+        let events = self.names.table("events");
+        // Render timestamp bounds in UTC, the zone they were written in.
+        let mut tx = sqlx::Acquire::begin(conn)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        tx.execute("SET LOCAL TimeZone = 'UTC'")
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        let rows: Vec<(String, String)> = sqlx::query_as(&format!(
+            "SELECT c.relname::text, pg_get_expr(c.relpartbound, c.oid) \
+             FROM pg_inherits i JOIN pg_class c ON c.oid = i.inhrelid \
+             WHERE i.inhparent = '{events}'::regclass"
+        ))
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(PostgresError::Sqlx)?;
+        tx.commit().await.map_err(PostgresError::Sqlx)?;
+        let mut partitions: Vec<EventPartition> = rows
+            .into_iter()
+            .filter_map(|(name, bounds)| {
+                PartitionRange::parse(self.partitioning.scheme, &bounds)
+                    .map(|range| EventPartition { name, range })
+            })
+            .collect();
+        partitions.sort_by_key(|p| p.range);
+        Ok(partitions)
+    }
+
+    /// Detach partition `name` once no new event can land in it.
+    pub async fn detach(&self, pool: &PgPool, name: &str) -> Result<(), PostgresError> {
+        let events = self.names.table("events");
+        let mut tx = pool.begin().await.map_err(PostgresError::Sqlx)?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(MIGRATION_LOCK)
+            .execute(&mut *tx)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        let Some(partition) = self
+            .list(&mut tx)
+            .await?
+            .into_iter()
+            .find(|p| p.name == name)
+        else {
+            return Err(partition_err(
+                name,
+                "not an attached partition of the events table",
+            ));
+        };
+        let live = match partition.range {
+            PartitionRange::GlobalSeq { end, .. } => {
+                self.last_seq(&mut tx).await?.saturating_add(1) < end
+            }
+            PartitionRange::Month { year, month } => {
+                (year, month) >= current_month(&mut tx).await?
+            }
+        };
+        if live {
+            return Err(partition_err(
+                name,
+                "the partition still receives new events",
+            ));
+        }
+        let qualified = self.names.qualified(&partition.name);
+        tx.execute(format!("ALTER TABLE {events} DETACH PARTITION {qualified}").as_str())
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        tx.commit().await.map_err(PostgresError::Sqlx)
+    }
+
+    /// The last `global_seq` handed out, 0 before the first.
+    async fn last_seq(&self, conn: &mut PgConnection) -> Result<u64, PostgresError> {
+        let events = self.names.table("events");
+        let last: i64 = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(pg_sequence_last_value(\
+                 pg_get_serial_sequence('{events}', 'global_seq')), 0)"
+        ))
+        .fetch_one(conn)
+        .await
+        .map_err(PostgresError::Sqlx)?;
+        Ok(u64::try_from(last).unwrap_or_default())
+    }
+
+    /// Top up the partitions every `check_every` until the pool closes.
+    pub fn spawn(&self, pool: PgPool) -> JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(manager.partitioning.check_every).await;
+                if manager.ensure(&pool).await.is_err() && pool.is_closed() {
+                    return;
+                }
+            }
+        })
+    }
+}
+
+/// The current month, UTC.
+async fn current_month(conn: &mut PgConnection) -> Result<(i32, u32), PostgresError> {
+    let (year, month): (i32, i32) = sqlx::query_as(
+        "SELECT EXTRACT(YEAR FROM now() AT TIME ZONE 'UTC')::int, \
+                EXTRACT(MONTH FROM now() AT TIME ZONE 'UTC')::int",
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(PostgresError::Sqlx)?;
+    Ok((year, u32::try_from(month).unwrap_or(1)))
+}
+
+// ---------------------------------------------------------------------------
+// Store surface
+// ---------------------------------------------------------------------------
+
+impl PostgresStore {
+    /// The configured [`Partitioning`], if the events table is partitioned.
+    #[must_use]
+    pub fn partitioning(&self) -> Option<Partitioning> {
+        self.partition_manager().map(PartitionManager::partitioning)
+    }
+
+    /// Create the current partition and the configured number ahead of it,
+    /// if missing. A no-op for an unpartitioned table.
+    ///
+    /// The store does this itself on open and in the background unless it
+    /// was opened [`VerifyOnly`](crate::MigrationMode::VerifyOnly).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::Sqlx`] if the catalog cannot be read or a
+    /// partition cannot be created.
+    pub async fn ensure_partitions(&self) -> Result<(), PostgresError> {
+        match self.partition_manager() {
+            Some(manager) => manager.ensure(self.pool()).await,
+            None => Ok(()),
+        }
+    }
+
+    /// The events table's attached partitions, oldest first. Empty for an
+    /// unpartitioned table.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::Sqlx`] if the catalog cannot be read.
+    pub async fn partitions(&self) -> Result<Vec<EventPartition>, PostgresError> {
+        let Some(manager) = self.partition_manager() else {
+            return Ok(Vec::new());
+        };
+        let mut conn = self.pool().acquire().await.map_err(PostgresError::Sqlx)?;
+        manager.list(&mut conn).await
+    }
+
+    /// Detach partition `name` from the events table for archival. It stays
+    /// in the database as an ordinary table — dump it, then drop it.
+    ///
+    /// Its events disappear from every read, `$all` included; subscribers
+    /// past it are unaffected. Stream heads are kept, so a stream whose early
+    /// events were archived continues at its next version. Detaching briefly
+    /// locks the events table.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::Partition`] if the table is not partitioned,
+    /// `name` is not one of its partitions, or new events may still land in
+    /// it (the current partition and those ahead of it), and
+    /// [`PostgresError::Sqlx`] if the detach fails.
+    pub async fn detach_partition(&self, name: &str) -> Result<(), PostgresError> {
+        match self.partition_manager() {
+            Some(manager) => manager.detach(self.pool(), name).await,
+            None => Err(partition_err(
+                &self.sql().events,
+                "the events table is not partitioned",
+            )),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for partition naming and bounds
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    const SEQ: PartitionScheme = PartitionScheme::GlobalSeq {
+        span: NonZeroU64::new(1000).unwrap(),
+    };
+
+    #[test]
+    fn seq_partitions_are_named_by_start_and_round_trip_their_bounds() {
+        let range = PartitionRange::GlobalSeq {
+            start: 2000,
+            end: 3000,
+        };
+        assert_eq!(range.table(), "events_2000");
+        assert_eq!(range.bounds(), "FROM (2000) TO (3000)");
+        assert_eq!(
+            PartitionRange::parse(SEQ, "FOR VALUES FROM ('2000') TO ('3000')"),
+            Some(range)
+        );
+        assert_eq!(PartitionRange::parse(SEQ, "DEFAULT"), None);
+    }
+
+    #[test]
+    fn month_partitions_roll_over_the_year_in_utc() {
+        let range = PartitionRange::Month {
+            year: 2026,
+            month: 12,
+        };
+        assert_eq!(range.table(), "events_2026_12");
+        assert_eq!(
+            range.bounds(),
+            "FROM ('2026-12-01 00:00:00+00') TO ('2027-01-01 00:00:00+00')"
+        );
+        assert_eq!(
+            PartitionRange::parse(
+                PartitionScheme::Monthly,
+                "FOR VALUES FROM ('2026-12-01 00:00:00+00') TO ('2027-01-01 00:00:00+00')"
+            ),
+            Some(range)
+        );
+    }
+
+    #[test]
+    fn the_longest_partition_name_is_a_seq_partition() {
+        let range = PartitionRange::GlobalSeq {
+            start: i64::MAX.unsigned_abs(),
+            end: u64::MAX,
+        };
+        assert_eq!(range.table(), LONGEST_PARTITION);
+    }
+}
@@ -1,42 +1,449 @@
-use sqlx::PgPool;
+use nexus::ErrorId;
 
 use crate::error::PostgresError;
+use crate::partition::{LONGEST_PARTITION, PartitionScheme};
 
-/// The events table and indexes. See the plan's schema section for rationale.
+/// The `LISTEN/NOTIFY` channel a store uses unless configured otherwise.
+pub const DEFAULT_NOTIFY_CHANNEL: &str = "nexus_events";
+
+/// Postgres keeps at most `NAMEDATALEN - 1` bytes of an identifier and
+/// silently truncates the rest — two long prefixes could fold onto one table.
+const MAX_IDENTIFIER_LEN: usize = 63;
+
+/// The longest object name the table prefix is prepended to.
+const LONGEST_PREFIXED: &str = "nexus_schema_migrations";
+
+// ---------------------------------------------------------------------------
+// Names — validated schema, table prefix and notify channel
+// ---------------------------------------------------------------------------
+
+/// Where one store's tables live and which channel it notifies on.
 ///
-/// - `BIGINT GENERATED ALWAYS AS IDENTITY` (not `BIGSERIAL`) — SQL-standard,
-///   prevents manual inserts into the id column.
-/// - `txid xid8 DEFAULT pg_current_xact_id()` — writing transaction id, the
-///   Way-2 watermark key. `xid8` is 64-bit, non-wrapping.
-/// - `UNIQUE (stream_id, version)` — per-stream conflict arbiter; a
-///   concurrent INSERT that races the same version raises a unique violation →
-///   `AppendError::Conflict`. Atomic, no `SELECT … FOR UPDATE` needed.
-const SCHEMA_SQL: &str = r"
-CREATE TABLE IF NOT EXISTS events (
-    global_seq     BIGINT GENERATED ALWAYS AS IDENTITY,
-    stream_id      BYTEA    NOT NULL,
-    version        BIGINT   NOT NULL,
-    txid           xid8     NOT NULL DEFAULT pg_current_xact_id(),
-    event_type     TEXT     NOT NULL,
-    schema_version BIGINT   NOT NULL,
-    payload        BYTEA    NOT NULL,
-    metadata       BYTEA,
-    PRIMARY KEY (global_seq),
-    UNIQUE (stream_id, version)
-);
-CREATE INDEX IF NOT EXISTS events_stream_idx    ON events (stream_id, version);
-CREATE INDEX IF NOT EXISTS events_watermark_idx ON events (txid, global_seq);
-";
-
-/// Apply the schema if absent. Idempotent — safe to call on every open.
+/// Every part is checked against a deliberately narrow alphabet — lowercase
+/// ASCII letters, digits and `_`, not starting with a digit — so the names
+/// are spelled the same quoted or unquoted, and are safe to splice into SQL.
+/// Rendered names are still double-quoted, so reserved words (`user`) work.
 ///
-/// # Errors
+/// No schema means unqualified names, resolved through the connection's
+/// `search_path` as before the namespace options existed.
 ///
-/// Returns [`PostgresError::Sqlx`] if the DDL statements fail.
-pub async fn ensure_schema(pool: &PgPool) -> Result<(), PostgresError> {
-    sqlx::raw_sql(SCHEMA_SQL)
-        .execute(pool)
-        .await
-        .map(|_| ())
-        .map_err(PostgresError::Sqlx)
+/// The partitioning scheme rides along because it decides the events
+/// table's DDL and which table holds stream heads.
+#[derive(Debug, Clone)]
+pub struct Names {
+    schema: Option<String>,
+    prefix: String,
+    channel: String,
+    partitioning: Option<PartitionScheme>,
+}
+
+impl Names {
+    /// Validate the three parts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::InvalidIdentifier`] for an empty schema or
+    /// channel, a character outside the allowed alphabet, a leading digit, or
+    /// a name postgres would truncate.
+    pub fn new(schema: Option<&str>, prefix: &str, channel: &str) -> Result<Self, PostgresError> {
+        if let Some(name) = schema {
+            check_identifier(name, MAX_IDENTIFIER_LEN)?;
+        }
+        if !prefix.is_empty() {
+            check_identifier(prefix, MAX_IDENTIFIER_LEN - LONGEST_PREFIXED.len())?;
+        }
+        check_identifier(channel, MAX_IDENTIFIER_LEN)?;
+        Ok(Self {
+            schema: schema.map(str::to_owned),
+            prefix: prefix.to_owned(),
+            channel: channel.to_owned(),
+            partitioning: None,
+        })
+    }
+
+    /// Lay the events table out partitioned by `scheme`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::InvalidIdentifier`] if the table prefix would
+    /// make a partition name postgres truncates.
+    pub fn partitioned(mut self, scheme: PartitionScheme) -> Result<Self, PostgresError> {
+        if !self.prefix.is_empty() {
+            check_identifier(&self.prefix, MAX_IDENTIFIER_LEN - LONGEST_PARTITION.len())?;
+        }
+        self.partitioning = Some(scheme);
+        Ok(self)
+    }
+
+    /// The configured schema, if any.
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    /// The partitioning scheme, if the events table is partitioned.
+    pub const fn partitioning(&self) -> Option<PartitionScheme> {
+        self.partitioning
+    }
+
+    /// The quoted, schema-qualified name of table `name`.
+    pub fn table(&self, name: &str) -> String {
+        self.qualified(&format!("{}{name}", self.prefix))
+    }
+
+    /// The quoted, schema-qualified name of a relation already carrying the
+    /// prefix, such as a partition listed from the catalog.
+    pub fn qualified(&self, relation: &str) -> String {
+        self.schema.as_ref().map_or_else(
+            || format!("\"{relation}\""),
+            |schema| format!("\"{schema}\".\"{relation}\""),
+        )
+    }
+
+    /// The quoted name of index `name`. Indexes always live in their table's
+    /// schema, so they are never qualified.
+    pub fn index(&self, name: &str) -> String {
+        format!("\"{}{name}\"", self.prefix)
+    }
+}
+
+/// Reject anything but a non-empty `[a-z_][a-z0-9_]*` of at most `max_len`
+/// bytes.
+fn check_identifier(name: &str, max_len: usize) -> Result<(), PostgresError> {
+    let reason = if name.is_empty() {
+        Some("must not be empty")
+    } else if name.len() > max_len {
+        Some("too long; postgres would truncate it")
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        Some("must not start with a digit")
+    } else if !name
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+    {
+        Some("only lowercase ASCII letters, digits and '_' are allowed")
+    } else {
+        None
+    };
+    reason.map_or(Ok(()), |why| {
+        Err(PostgresError::InvalidIdentifier {
+            name: ErrorId::from_display(&name),
+            reason: ErrorId::from_display(&why),
+        })
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Statements — every query a store runs, rendered once against its names
+// ---------------------------------------------------------------------------
+
+/// The SQL for one store, rendered from its [`Names`] when the store is
+/// built so no query is formatted on the hot path.
+///
+/// Positional binds are unchanged from the unqualified statements; only the
+/// table names differ. A partitioned store reads stream heads from its
+/// `stream_heads` table (see [`crate::partition`]).
+#[derive(Debug)]
+pub struct Statements {
+    /// The `LISTEN/NOTIFY` channel, unquoted (`pg_notify` and `PgListener`
+    /// both take the raw name).
+    pub channel: String,
+    /// The quoted, qualified events table.
+    pub events: String,
+    /// Whether the events table is partitioned: the bulk merge then switches
+    /// the version-claim trigger to skip taken versions.
+    pub partitioned: bool,
+    /// A stream's head version, 0 when absent.
+    pub current_version: String,
+    /// Insert one event row.
+    pub insert_event: String,
+    /// Move the staged rows into the events table in staging order, skipping
+    /// any a concurrent writer claimed first; yields the first skipped `ord`.
+    pub merge_staged: String,
+    /// The head version of each stream id in the `$1` array, 0 when absent.
+    #[cfg(feature = "import")]
+    pub stream_heads: String,
+    /// One `read_stream` page: `version >= $2`, at most `$3` rows.
+    pub stream_page: String,
+    /// One `read_all` page: strictly after the `($1, $2)` cursor and below
+    /// the `$3` watermark, at most `$4` rows.
+    pub all_page: String,
+    /// The newest settled row's `(txid, global_seq)`, where the poller
+    /// starts.
+    pub poll_head: String,
+    /// One poll: the stream of each settled row strictly after the
+    /// `($1, $2)` cursor, at most `$3` rows.
+    pub poll_page: String,
+    /// The current watermark, and whether committed rows wait behind it.
+    pub stall_probe: String,
+    /// Whether any settled row lies strictly after the `($1, $2)` cursor.
+    pub settled_after: String,
+    /// Take the session advisory lock for lease `$1`, if free. The key hashes
+    /// the name together with the events table, so leases are per namespace.
+    pub try_lease: String,
+    /// Release the session advisory lock for lease `$1`.
+    pub release_lease: String,
+    /// One page of distinct stream ids strictly after `$1`.
+    #[cfg(feature = "export")]
+    pub stream_ids_page: String,
+    /// Save the id's snapshot at a version, replacing one already saved at
+    /// that version.
+    pub upsert_snapshot: String,
+    /// Delete all but the id's newest `$2` snapshots.
+    pub prune_snapshots: String,
+    /// Replace the id's checkpoint unless the stored one is further along, by
+    /// the same `(txid, seq)` order as [`PgAllPos`](crate::PgAllPos).
+    pub upsert_checkpoint: String,
+    /// The id's newest snapshot row from the namespace's `snapshots` table.
+    pub read_snapshot: String,
+    /// The id's newest snapshot row in the `snapshots` table at or before
+    /// version `$2`, saved under schema version `$3`.
+    pub read_snapshot_at_or_before: String,
+    /// The id's checkpoint row from the namespace's `checkpoints` table.
+    pub read_checkpoint: String,
+    /// Only the schema version of the id's newest row in the `snapshots`
+    /// table.
+    pub snapshot_schema_version: String,
+    /// Only the schema version of the id's row in the `checkpoints` table.
+    pub checkpoint_schema_version: String,
+}
+
+impl Statements {
+    #[allow(clippy::too_many_lines, reason = "one rendered statement per field")]
+    pub fn new(names: &Names) -> Self {
+        let events = names.table("events");
+        let snapshots = names.table("snapshots");
+        let checkpoints = names.table("checkpoints");
+        let partitioned = names.partitioning.is_some();
+        // The head table keeps a stream's head after its partitions are
+        // detached; `MAX(version)` over the events would forget it.
+        let heads = if partitioned {
+            names.table("stream_heads")
+        } else {
+            events.clone()
+        };
+        // No unique constraint to arbitrate on: the version-claim trigger
+        // skips taken versions instead.
+        let on_conflict = if partitioned {
+            ""
+        } else {
+            "ON CONFLICT (stream_id, version) DO NOTHING "
+        };
+        Self {
+            channel: names.channel.clone(),
+            events: events.clone(),
+            partitioned,
+            current_version: format!(
+                "SELECT COALESCE(MAX(version), 0) FROM {heads} WHERE stream_id = $1"
+            ),
+            insert_event: format!(
+                "INSERT INTO {events} \
+                 (stream_id, version, event_type, schema_version, payload, metadata) \
+                 VALUES ($1, $2, $3, $4, $5, $6)"
+            ),
+            merge_staged: format!(
+                "WITH merged AS ( \
+                     INSERT INTO {events} \
+                     (stream_id, version, event_type, schema_version, payload, metadata) \
+                     SELECT stream_id, version, event_type, schema_version, payload, metadata \
+                     FROM nexus_staged_events ORDER BY ord \
+                     {on_conflict}RETURNING stream_id, version) \
+                 SELECT s.ord FROM nexus_staged_events s \
+                 WHERE NOT EXISTS (SELECT 1 FROM merged m \
+                                   WHERE m.stream_id = s.stream_id AND m.version = s.version) \
+                 ORDER BY s.ord LIMIT 1"
+            ),
+            #[cfg(feature = "import")]
+            stream_heads: format!(
+                "SELECT t.stream_id, COALESCE(MAX(h.version), 0) \
+                 FROM unnest($1::bytea[]) AS t(stream_id) \
+                 LEFT JOIN {heads} h ON h.stream_id = t.stream_id \
+                 GROUP BY t.stream_id"
+            ),
+            stream_page: format!(
+                "SELECT version, event_type, schema_version, payload, metadata \
+                 FROM {events} \
+                 WHERE stream_id = $1 AND version >= $2 \
+                 ORDER BY version \
+                 LIMIT $3"
+            ),
+            all_page: format!(
+                "SELECT txid::text::bigint AS txid, global_seq, \
+                        version, event_type, schema_version, payload, metadata \
+                 FROM {events} \
+                 WHERE ($1::text IS NULL OR (txid, global_seq) > ($1::text::xid8, $2)) \
+                   AND txid < $3::text::xid8 \
+                 ORDER BY txid, global_seq \
+                 LIMIT $4"
+            ),
+            poll_head: format!(
+                "SELECT txid::text AS txid, global_seq FROM {events} \
+                 WHERE txid < pg_snapshot_xmin(pg_current_snapshot()) \
+                 ORDER BY txid DESC, global_seq DESC LIMIT 1"
+            ),
+            poll_page: format!(
+                "SELECT txid::text AS txid, global_seq, stream_id FROM {events} \
+                 WHERE ($1::text IS NULL OR (txid, global_seq) > ($1::text::xid8, $2)) \
+                   AND txid < pg_snapshot_xmin(pg_current_snapshot()) \
+                 ORDER BY txid, global_seq \
+                 LIMIT $3"
+            ),
+            stall_probe: format!(
+                "SELECT pg_snapshot_xmin(pg_current_snapshot())::text::bigint, \
+                        EXISTS (SELECT 1 FROM {events} \
+                                WHERE txid >= pg_snapshot_xmin(pg_current_snapshot()))"
+            ),
+            settled_after: format!(
+                "SELECT EXISTS (SELECT 1 FROM {events} \
+                 WHERE ($1::text IS NULL OR (txid, global_seq) > ($1::text::xid8, $2)) \
+                   AND txid < pg_snapshot_xmin(pg_current_snapshot()))"
+            ),
+            try_lease: format!(
+                "SELECT pg_try_advisory_lock(hashtextextended('{events}:' || $1, 0))"
+            ),
+            release_lease: format!(
+                "SELECT pg_advisory_unlock(hashtextextended('{events}:' || $1, 0))"
+            ),
+            #[cfg(feature = "export")]
+            stream_ids_page: format!(
+                "SELECT DISTINCT stream_id FROM {events} \
+                 WHERE $1::bytea IS NULL OR stream_id > $1 \
+                 ORDER BY stream_id \
+                 LIMIT $2"
+            ),
+            upsert_snapshot: format!(
+                "INSERT INTO {snapshots} (id, schema_version, version, state) \
+                 VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT (id, version) DO UPDATE SET \
+                     schema_version = EXCLUDED.schema_version, \
+                     state = EXCLUDED.state"
+            ),
+            prune_snapshots: format!(
+                "DELETE FROM {snapshots} \
+                 WHERE id = $1 AND version NOT IN ( \
+                     SELECT version FROM {snapshots} WHERE id = $1 \
+                     ORDER BY version DESC \
+                     LIMIT $2)"
+            ),
+            upsert_checkpoint: format!(
+                "INSERT INTO {checkpoints} AS stored (id, schema_version, txid, seq, state) \
+                 VALUES ($1, $2, $3, $4, $5) \
+                 ON CONFLICT (id) DO UPDATE SET \
+                     schema_version = EXCLUDED.schema_version, \
+                     txid = EXCLUDED.txid, \
+                     seq = EXCLUDED.seq, \
+                     state = EXCLUDED.state \
+                 WHERE (stored.txid, stored.seq) <= (EXCLUDED.txid, EXCLUDED.seq)"
+            ),
+            read_snapshot: format!(
+                "SELECT schema_version, version, state FROM {snapshots} WHERE id = $1 \
+                 ORDER BY version DESC \
+                 LIMIT 1"
+            ),
+            read_snapshot_at_or_before: format!(
+                "SELECT schema_version, version, state FROM {snapshots} \
+                 WHERE id = $1 AND version <= $2 AND schema_version = $3 \
+                 ORDER BY version DESC \
+                 LIMIT 1"
+            ),
+            read_checkpoint: format!(
+                "SELECT schema_version, txid, seq, state FROM {checkpoints} WHERE id = $1"
+            ),
+            snapshot_schema_version: format!(
+                "SELECT schema_version FROM {snapshots} WHERE id = $1 \
+                 ORDER BY version DESC \
+                 LIMIT 1"
+            ),
+            checkpoint_schema_version: format!(
+                "SELECT schema_version FROM {checkpoints} WHERE id = $1"
+            ),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for name validation and rendering
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    fn rejected(schema: Option<&str>, prefix: &str, channel: &str) -> bool {
+        matches!(
+            Names::new(schema, prefix, channel),
+            Err(PostgresError::InvalidIdentifier { .. })
+        )
+    }
+
+    #[test]
+    fn default_names_render_the_unqualified_tables() {
+        let names = Names::new(None, "", DEFAULT_NOTIFY_CHANNEL).unwrap();
+        assert_eq!(names.table("events"), "\"events\"");
+        let sql = Statements::new(&names);
+        assert!(sql.insert_event.starts_with("INSERT INTO \"events\" "));
+        assert_eq!(sql.channel, "nexus_events");
+    }
+
+    #[test]
+    fn schema_and_prefix_qualify_tables_but_not_indexes() {
+        let names = Names::new(Some("billing"), "tenant_a_", "billing_events").unwrap();
+        assert_eq!(names.table("events"), "\"billing\".\"tenant_a_events\"");
+        assert_eq!(
+            names.index("events_stream_idx"),
+            "\"tenant_a_events_stream_idx\""
+        );
+        let sql = Statements::new(&names);
+        assert!(
+            sql.all_page
+                .contains("FROM \"billing\".\"tenant_a_events\" ")
+        );
+    }
+
+    #[test]
+    fn malformed_identifiers_are_rejected() {
+        assert!(rejected(Some(""), "", "ch"));
+        assert!(rejected(Some("Billing"), "", "ch"));
+        assert!(rejected(Some("bill-ing"), "", "ch"));
+        assert!(rejected(Some("bill\"; DROP TABLE events; --"), "", "ch"));
+        assert!(rejected(None, "9tenant_", "ch"));
+        assert!(rejected(None, "", ""));
+        assert!(rejected(None, "", "événements"));
+    }
+
+    #[test]
+    fn names_postgres_would_truncate_are_rejected() {
+        let longest_prefix = "p".repeat(MAX_IDENTIFIER_LEN - LONGEST_PREFIXED.len());
+        assert!(Names::new(None, &longest_prefix, "ch").is_ok());
+        assert!(rejected(None, &format!("{longest_prefix}p"), "ch"));
+        assert!(Names::new(Some(&"s".repeat(63)), "", "ch").is_ok());
+        assert!(rejected(Some(&"s".repeat(64)), "", "ch"));
+        assert!(rejected(None, "", &"c".repeat(64)));
+    }
+
+    #[test]
+    fn partitioning_shortens_the_prefix_and_reads_heads_from_their_table() {
+        let longest_prefix = "p".repeat(MAX_IDENTIFIER_LEN - LONGEST_PARTITION.len());
+        let fits = Names::new(None, &longest_prefix, "ch").unwrap();
+        assert!(fits.partitioned(PartitionScheme::Monthly).is_ok());
+        let too_long = Names::new(None, &format!("{longest_prefix}p"), "ch").unwrap();
+        assert!(matches!(
+            too_long.partitioned(PartitionScheme::Monthly),
+            Err(PostgresError::InvalidIdentifier { .. })
+        ));
+
+        let names = Names::new(Some("billing"), "", "ch")
+            .unwrap()
+            .partitioned(PartitionScheme::Monthly)
+            .unwrap();
+        let sql = Statements::new(&names);
+        assert!(sql.partitioned);
+        assert!(
+            sql.current_version
+                .contains("FROM \"billing\".\"stream_heads\" ")
+        );
+        assert!(!sql.merge_staged.contains("ON CONFLICT"));
+        assert_eq!(
+            names.qualified("events_2026_01"),
+            "\"billing\".\"events_2026_01\""
+        );
+    }
 }
@@ -0,0 +1,429 @@
+//! Snapshot and checkpoint persistence for [`PostgresStore`].
+//!
+//! Two [`SnapshotStore`] impls over two tables created by
+//! [migration 2](crate::migrate):
+//!
+//! - `SnapshotStore<Vec<u8>, Version>` — aggregate snapshots, in `snapshots`.
+//! - `SnapshotStore<Vec<u8>, PgAllPos>` — projection checkpoints, in
+//!   `checkpoints`, so a projection's state lives in the same database as the
+//!   events it folds.
+//!
+//! A snapshot row is keyed on `(id, version)`: each commit inserts one, then
+//! deletes all but the id's newest rows per the store's
+//! [`snapshot_retention`](crate::PostgresStoreBuilder::snapshot_retention),
+//! in one transaction. The newest row is the one `hydrate` serves, so a late
+//! commit of an older version (a slow background worker) never rolls a newer
+//! snapshot back — under the default retention it is pruned at once. A
+//! commit at an existing version replaces that row, which is how a migrated
+//! snapshot is rewritten under its new schema version. `hydrate_at_or_before`
+//! picks the newest retained row at or before the requested version.
+//!
+//! A checkpoint has one row per id, so its commit is a single upsert —
+//! atomic without an explicit transaction. The upsert is **monotonic**: its
+//! `ON CONFLICT … WHERE` only replaces a row whose position is at or before
+//! the incoming one, so a lagging projection replica's late commit is
+//! dropped. Only the latest checkpoint is kept, so its
+//! `hydrate_at_or_before` is the trait's latest-only default.
+//!
+//! [`SnapshotAppend`] stages the snapshot upsert in the append's own
+//! transaction, so an atomic-mode snapshot never lags its events.
+
+use std::num::NonZeroU32;
+
+use nexus::{ErrorId, Id, Version};
+use nexus_store::PendingEnvelope;
+use nexus_store::StreamKey;
+use nexus_store::error::AppendError;
+use nexus_store::state::{PendingSnapshot, SnapshotAppend, SnapshotBytes, SnapshotStore};
+use sqlx::PgConnection;
+
+use crate::error::PostgresError;
+use crate::position::PgAllPos;
+use crate::store::{PostgresStore, corrupt, store_err};
+
+/// A `snapshots` row as stored.
+#[derive(sqlx::FromRow)]
+struct SnapshotRow {
+    schema_version: i64,
+    version: i64,
+    state: Vec<u8>,
+}
+
+/// A `checkpoints` row as stored.
+#[derive(sqlx::FromRow)]
+struct CheckpointRow {
+    schema_version: i64,
+    txid: i64,
+    seq: i64,
+    state: Vec<u8>,
+}
+
+// ---------------------------------------------------------------------------
+// Pure helpers — narrowing between the domain types and the BIGINT columns
+// ---------------------------------------------------------------------------
+
+/// A stored `schema_version`, which must be a non-zero `u32`.
+fn stored_schema(raw: i64, label: ErrorId) -> Result<NonZeroU32, PostgresError> {
+    u32::try_from(raw)
+        .ok()
+        .and_then(NonZeroU32::new)
+        .ok_or_else(|| corrupt(label, "schema_version == 0 or out of range"))
+}
+
+/// A stored position component, which must be non-negative.
+fn stored_u64(raw: i64, label: ErrorId, reason: &str) -> Result<u64, PostgresError> {
+    u64::try_from(raw).map_err(|_| corrupt(label, reason))
+}
+
+/// Narrow a position component to its BIGINT column, rejecting values past
+/// `i64::MAX` before anything is written.
+fn column_i64(value: u64, label: ErrorId, reason: &str) -> Result<i64, PostgresError> {
+    i64::try_from(value).map_err(|_| PostgresError::InvalidInput {
+        stream_id: label,
+        version: value,
+        reason: ErrorId::from_display(&reason),
+    })
+}
+
+impl SnapshotRow {
+    fn decode(self, label: ErrorId) -> Result<(NonZeroU32, Version, Vec<u8>), PostgresError> {
+        let schema_version = stored_schema(self.schema_version, label)?;
+        let version = stored_u64(self.version, label, "snapshot version out of range")
+            .map(Version::new)?
+            .ok_or_else(|| corrupt(label, "snapshot version == 0"))?;
+        Ok((schema_version, version, self.state))
+    }
+}
+
+impl CheckpointRow {
+    fn decode(self, label: ErrorId) -> Result<(NonZeroU32, PgAllPos, Vec<u8>), PostgresError> {
+        let schema_version = stored_schema(self.schema_version, label)?;
+        let txid = stored_u64(self.txid, label, "checkpoint txid out of range")?;
+        let seq = stored_u64(self.seq, label, "checkpoint seq out of range")?;
+        Ok((schema_version, PgAllPos::new(txid, seq), self.state))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// IO helpers
+// ---------------------------------------------------------------------------
+
+impl PostgresStore {
+    /// Save a snapshot of `id` on `conn`, then prune the id's history down
+    /// to the retention. `conn` is an open transaction — the commit's own, or
+    /// the append's — so the insert and the prune land together.
+    async fn stage_snapshot(
+        &self,
+        conn: &mut PgConnection,
+        id: &[u8],
+        label: ErrorId,
+        snapshot: PendingSnapshot<'_>,
+    ) -> Result<(), PostgresError> {
+        let version = column_i64(
+            snapshot.version().as_u64(),
+            label,
+            "version exceeds i64::MAX",
+        )?;
+        sqlx::query(&self.sql().upsert_snapshot)
+            .bind(id)
+            .bind(i64::from(snapshot.schema_version().get()))
+            .bind(version)
+            .bind(snapshot.state())
+            .execute(&mut *conn)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        let keep = i64::try_from(self.snapshot_retention().keep().get()).unwrap_or(i64::MAX);
+        sqlx::query(&self.sql().prune_snapshots)
+            .bind(id)
+            .bind(keep)
+            .execute(conn)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        Ok(())
+    }
+
+    async fn read_snapshot(&self, id: &[u8]) -> Result<Option<SnapshotRow>, PostgresError> {
+        sqlx::query_as(&self.sql().read_snapshot)
+            .bind(id)
+            .fetch_optional(self.pool())
+            .await
+            .map_err(PostgresError::Sqlx)
+    }
+
+    async fn read_checkpoint(&self, id: &[u8]) -> Result<Option<CheckpointRow>, PostgresError> {
+        sqlx::query_as(&self.sql().read_checkpoint)
+            .bind(id)
+            .fetch_optional(self.pool())
+            .await
+            .map_err(PostgresError::Sqlx)
+    }
+
+    /// Read just the schema version, via `sql` over either table.
+    async fn read_schema_version(
+        &self,
+        sql: &str,
+        id: &[u8],
+        label: ErrorId,
+    ) -> Result<Option<NonZeroU32>, PostgresError> {
+        let raw: Option<i64> = sqlx::query_scalar(sql)
+            .bind(id)
+            .fetch_optional(self.pool())
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        raw.map(|schema_version| stored_schema(schema_version, label))
+            .transpose()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Aggregate snapshots — `SnapshotStore<Vec<u8>, Version>`
+// ---------------------------------------------------------------------------
+
+impl SnapshotStore<Vec<u8>, Version> for PostgresStore {
+    type Error = PostgresError;
+
+    async fn hydrate(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> Result<Option<(Version, Vec<u8>)>, PostgresError> {
+        Ok(
+            <Self as SnapshotStore<Vec<u8>, Version>>::hydrate_with_schema(self, id)
+                .await?
+                .filter(|(stored, _, _)| *stored == schema_version)
+                .map(|(_, version, state)| (version, state)),
+        )
+    }
+
+    async fn commit(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: Version,
+        state: &Vec<u8>,
+    ) -> Result<(), PostgresError> {
+        let label = ErrorId::from_display(id);
+        let snapshot = PendingSnapshot::new(schema_version, position, state);
+        let mut tx = self.pool().begin().await.map_err(PostgresError::Sqlx)?;
+        self.stage_snapshot(&mut tx, id.as_ref(), label, snapshot)
+            .await?;
+        tx.commit().await.map_err(PostgresError::Sqlx)
+    }
+
+    async fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> Result<Option<(NonZeroU32, Version, Vec<u8>)>, PostgresError> {
+        let label = ErrorId::from_display(id);
+        self.read_snapshot(id.as_ref())
+            .await?
+            .map(|row| row.decode(label))
+            .transpose()
+    }
+
+    async fn stored_schema_version(
+        &self,
+        id: &impl Id,
+    ) -> Result<Option<NonZeroU32>, PostgresError> {
+        self.read_schema_version(
+            &self.sql().snapshot_schema_version,
+            id.as_ref(),
+            ErrorId::from_display(id),
+        )
+        .await
+    }
+
+    async fn hydrate_at_or_before(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: Version,
+    ) -> Result<Option<(Version, Vec<u8>)>, PostgresError> {
+        let label = ErrorId::from_display(id);
+        // Past `i64::MAX` every stored version qualifies.
+        let bound = i64::try_from(position.as_u64()).unwrap_or(i64::MAX);
+        let found: Option<SnapshotRow> = sqlx::query_as(&self.sql().read_snapshot_at_or_before)
+            .bind(id.as_ref())
+            .bind(bound)
+            .bind(i64::from(schema_version.get()))
+            .fetch_optional(self.pool())
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        found
+            .map(|row| {
+                row.decode(label)
+                    .map(|(_, version, state)| (version, state))
+            })
+            .transpose()
+    }
+}
+
+/// Defaults to copying the hydrated `Vec` — postgres hands rows back as owned
+/// buffers, so there is nothing to share.
+impl SnapshotBytes<Version> for PostgresStore {}
+
+/// The snapshot insert and prune run after the event inserts, in the append's own
+/// transaction: one commit, and no window in which the events are visible
+/// without the snapshot that covers them.
+impl SnapshotAppend for PostgresStore {
+    async fn append_with_snapshot(
+        &self,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+        snapshot: PendingSnapshot<'_>,
+    ) -> Result<(), AppendError<PostgresError>> {
+        let label = ErrorId::from_display(id);
+        let mut tx = self.pool().begin().await.map_err(store_err)?;
+
+        let head = self
+            .insert_events(&mut tx, id, expected_version, envelopes)
+            .await?;
+        let version = snapshot.version().as_u64();
+        if version != head {
+            // Dropping `tx` rolls the inserts back.
+            return Err(AppendError::Store(PostgresError::InvalidInput {
+                stream_id: label,
+                version,
+                reason: ErrorId::from_display(&"snapshot version is not the last appended"),
+            }));
+        }
+        self.stage_snapshot(&mut tx, id.as_bytes(), label, snapshot)
+            .await
+            .map_err(AppendError::Store)?;
+        tx.commit().await.map_err(store_err)?;
+
+        if !envelopes.is_empty() {
+            self.notify_committed(id.as_bytes()).await;
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Projection checkpoints — `SnapshotStore<Vec<u8>, PgAllPos>`
+// ---------------------------------------------------------------------------
+
+impl SnapshotStore<Vec<u8>, PgAllPos> for PostgresStore {
+    type Error = PostgresError;
+
+    async fn hydrate(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> Result<Option<(PgAllPos, Vec<u8>)>, PostgresError> {
+        Ok(
+            <Self as SnapshotStore<Vec<u8>, PgAllPos>>::hydrate_with_schema(self, id)
+                .await?
+                .filter(|(stored, _, _)| *stored == schema_version)
+                .map(|(_, position, state)| (position, state)),
+        )
+    }
+
+    async fn commit(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: PgAllPos,
+        state: &Vec<u8>,
+    ) -> Result<(), PostgresError> {
+        let label = ErrorId::from_display(id);
+        let txid = column_i64(position.txid(), label, "txid exceeds i64::MAX")?;
+        let seq = column_i64(position.seq(), label, "seq exceeds i64::MAX")?;
+        sqlx::query(&self.sql().upsert_checkpoint)
+            .bind(id.as_ref())
+            .bind(i64::from(schema_version.get()))
+            .bind(txid)
+            .bind(seq)
+            .bind(state.as_slice())
+            .execute(self.pool())
+            .await
+            .map(|_| ())
+            .map_err(PostgresError::Sqlx)
+    }
+
+    async fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> Result<Option<(NonZeroU32, PgAllPos, Vec<u8>)>, PostgresError> {
+        let label = ErrorId::from_display(id);
+        self.read_checkpoint(id.as_ref())
+            .await?
+            .map(|row| row.decode(label))
+            .transpose()
+    }
+
+    async fn stored_schema_version(
+        &self,
+        id: &impl Id,
+    ) -> Result<Option<NonZeroU32>, PostgresError> {
+        self.read_schema_version(
+            &self.sql().checkpoint_schema_version,
+            id.as_ref(),
+            ErrorId::from_display(id),
+        )
+        .await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for the column narrowing
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    fn label() -> ErrorId {
+        ErrorId::from_display(&"snap")
+    }
+
+    #[test]
+    fn snapshot_row_decodes_full_schema_range() {
+        let row = SnapshotRow {
+            schema_version: i64::from(u32::MAX),
+            version: 7,
+            state: vec![1],
+        };
+        let (schema_version, version, state) = row.decode(label()).unwrap();
+        assert_eq!(schema_version.get(), u32::MAX);
+        assert_eq!(version.as_u64(), 7);
+        assert_eq!(state, [1]);
+    }
+
+    #[test]
+    fn corrupt_snapshot_rows_are_rejected() {
+        for (schema_version, version) in [(0, 1), (i64::from(u32::MAX) + 1, 1), (1, 0), (1, -1)] {
+            let row = SnapshotRow {
+                schema_version,
+                version,
+                state: Vec::new(),
+            };
+            assert!(matches!(
+                row.decode(label()),
+                Err(PostgresError::CorruptRow { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn checkpoint_row_decodes_into_pg_all_pos() {
+        let row = CheckpointRow {
+            schema_version: 1,
+            txid: 9,
+            seq: 3,
+            state: Vec::new(),
+        };
+        let (_, position, _) = row.decode(label()).unwrap();
+        assert_eq!(position, PgAllPos::new(9, 3));
+    }
+
+    #[test]
+    fn positions_past_i64_max_are_invalid_input() {
+        assert!(matches!(
+            column_i64(u64::MAX, label(), "too big"),
+            Err(PostgresError::InvalidInput { .. })
+        ));
+        assert_eq!(column_i64(42, label(), "too big").unwrap(), 42);
+    }
+}
@@ -0,0 +1,397 @@
+//! Watermark-stall detection for the `$all` read.
+//!
+//! `read_all` only yields rows below `pg_snapshot_xmin` — the oldest
+//! transaction still in flight anywhere in the cluster. One long-running
+//! transaction, even in an unrelated database on the same server, therefore
+//! pins that watermark and freezes every `$all` subscriber, which keep
+//! parking on an empty re-scan without any error.
+//!
+//! With [`StallDetection`] configured, a background task probes the
+//! watermark periodically. When it has not moved for the configured
+//! threshold *while committed rows are waiting behind it*, the store records
+//! a [`WatermarkStall`] naming the transaction and backend holding it,
+//! reports it to the [`StallObserver`] once, and tells subscribers opened
+//! with [`PostgresStore::subscribe_all_with_stalls`] through an
+//! [`AllItem::Stalled`] item instead of letting them hang quietly.
+//!
+//! A stall never fails a read: rows below the watermark are still delivered,
+//! and a subscriber only hears of the stall once it has read them all. The
+//! item is informational — the subscription carries on and resumes
+//! delivering as soon as the transaction ends.
+//!
+//! A long transaction with nothing waiting behind it is not a stall: no
+//! subscriber is held back by it.
+
+use std::fmt;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::{self, Either};
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use nexus::ErrorId;
+use nexus_store::envelope::PersistedEnvelope;
+use nexus_store::notify::{NotifyError, StreamNotifiers};
+use nexus_store::{Store, Subscription};
+use sqlx::PgPool;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::error::PostgresError;
+use crate::position::PgAllPos;
+use crate::schema::Statements;
+use crate::store::{PostgresStore, all_cursor};
+
+/// The shortest pause between probes, whatever the threshold.
+const MIN_PROBE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The backend whose transaction id is the watermark. `backend_xid` is the
+/// 32-bit `xid`, so it is matched against the low half of the `xid8`.
+const BLOCKING_BACKEND: &str = "SELECT pid FROM pg_stat_activity \
+     WHERE backend_xid::text::bigint = $1 % 4294967296 \
+     ORDER BY xact_start \
+     LIMIT 1";
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Watermark-stall detection settings, enabled with
+/// [`PostgresStoreBuilder::stall_detection`](crate::PostgresStoreBuilder::stall_detection).
+///
+/// ```ignore
+/// let detection = StallDetection::after(Duration::from_secs(30))
+///     .observe(|stall: &WatermarkStall| warn!(?stall, "$all watermark stalled"));
+/// ```
+#[derive(Clone)]
+pub struct StallDetection {
+    threshold: Duration,
+    observer: Option<Arc<dyn StallObserver>>,
+}
+
+impl StallDetection {
+    /// Report a stall once the watermark has been pinned for `threshold`
+    /// with rows waiting behind it. The watermark is probed four times per
+    /// threshold.
+    #[must_use]
+    pub const fn after(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            observer: None,
+        }
+    }
+
+    /// Report each stall to `observer` when it is detected.
+    #[must_use]
+    pub fn observe(mut self, observer: impl StallObserver + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// How long the watermark may stay pinned before it counts as a stall.
+    #[must_use]
+    pub const fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    fn probe_interval(&self) -> Duration {
+        (self.threshold / 4).max(MIN_PROBE_INTERVAL)
+    }
+}
+
+impl fmt::Debug for StallDetection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StallDetection")
+            .field("threshold", &self.threshold)
+            .field("observer", &self.observer.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// Callback for detected watermark stalls.
+///
+/// Implemented for any `Fn(&WatermarkStall) + Send + Sync` closure. Runs on
+/// the monitor task once per stall — log it, bump a metric, page someone.
+pub trait StallObserver: Send + Sync {
+    /// The `$all` watermark has been pinned past the threshold.
+    fn on_stall(&self, stall: &WatermarkStall);
+}
+
+impl<F> StallObserver for F
+where
+    F: Fn(&WatermarkStall) + Send + Sync,
+{
+    fn on_stall(&self, stall: &WatermarkStall) {
+        self(stall);
+    }
+}
+
+/// A pinned `$all` watermark, as reported to a [`StallObserver`] and by
+/// [`PostgresStore::watermark_stall`](crate::PostgresStore::watermark_stall).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatermarkStall {
+    /// The in-flight transaction holding the watermark.
+    pub xid: u64,
+    /// The backend running it — `pg_terminate_backend` target. `None` when
+    /// no session owns it (a prepared transaction) or it is not visible.
+    pub pid: Option<i32>,
+    /// How long the watermark had been pinned when the stall was detected.
+    pub stalled_for: Duration,
+}
+
+// ---------------------------------------------------------------------------
+// Monitor
+// ---------------------------------------------------------------------------
+
+/// The current stall, shared between the monitor task, the store and the
+/// stall-aware subscriptions watching it.
+#[derive(Debug)]
+pub struct StallCell(watch::Sender<Option<WatermarkStall>>);
+
+impl Default for StallCell {
+    fn default() -> Self {
+        Self(watch::Sender::new(None))
+    }
+}
+
+impl StallCell {
+    pub fn get(&self) -> Option<WatermarkStall> {
+        self.0.borrow().clone()
+    }
+
+    fn replace(&self, stall: Option<WatermarkStall>) -> Option<WatermarkStall> {
+        // Only a change notifies watchers: clearing an absent stall is not news.
+        let mut previous = None;
+        self.0.send_if_modified(|current| {
+            let changed = *current != stall;
+            previous = std::mem::replace(current, stall);
+            changed
+        });
+        previous
+    }
+
+    /// End any stall in progress. The rows it held back are readable now;
+    /// parked `$all` subscribers would otherwise wait for the next commit to
+    /// look again.
+    fn clear(&self, notifiers: &StreamNotifiers) {
+        if self.replace(None).is_some() {
+            notifiers.wake_all();
+        }
+    }
+
+    /// Watch for stalls, seeing one already in progress as news.
+    fn watch(&self) -> watch::Receiver<Option<WatermarkStall>> {
+        let mut rx = self.0.subscribe();
+        if rx.borrow().is_some() {
+            rx.mark_changed();
+        }
+        rx
+    }
+}
+
+/// Spawn the task that probes the watermark and records stalls in `cell`.
+pub fn spawn_stall_monitor(
+    pool: PgPool,
+    sql: Arc<Statements>,
+    notifiers: Arc<StreamNotifiers>,
+    detection: StallDetection,
+    cell: Arc<StallCell>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        // The watermark being watched and when it was first seen pinned.
+        let mut pinned: Option<(i64, Instant)> = None;
+        loop {
+            tokio::time::sleep(detection.probe_interval()).await;
+            let probe = sqlx::query_as::<_, (i64, bool)>(&sql.stall_probe)
+                .fetch_one(&pool)
+                .await;
+            let Ok((xmin, held_back)) = probe else {
+                if pool.is_closed() {
+                    return;
+                }
+                continue;
+            };
+            if !held_back {
+                pinned = None;
+                cell.clear(&notifiers);
+                continue;
+            }
+            let since = match pinned {
+                Some((watched, since)) if watched == xmin => since,
+                // A new watermark: any earlier stall is over.
+                _ => {
+                    cell.clear(&notifiers);
+                    let now = Instant::now();
+                    pinned = Some((xmin, now));
+                    now
+                }
+            };
+            let stalled_for = since.elapsed();
+            if stalled_for < detection.threshold {
+                continue;
+            }
+            if cell.get().is_some() {
+                // Already reported.
+                continue;
+            }
+            let pid = sqlx::query_scalar::<_, i32>(BLOCKING_BACKEND)
+                .bind(xmin)
+                .fetch_optional(&pool)
+                .await
+                .ok()
+                .flatten();
+            let stall = WatermarkStall {
+                xid: u64::try_from(xmin).unwrap_or_default(),
+                pid,
+                stalled_for,
+            };
+            cell.replace(Some(stall.clone()));
+            if let Some(observer) = &detection.observer {
+                observer.on_stall(&stall);
+            }
+        }
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Stall-aware `$all` subscription
+// ---------------------------------------------------------------------------
+
+/// An item of a [`PostgresStore::subscribe_all_with_stalls`] subscription.
+#[derive(Debug, Clone)]
+pub enum AllItem {
+    /// The next event in `$all` order, tagged with its position — checkpoint
+    /// the tag as with [`Subscription::subscribe_all`].
+    Event(PgAllPos, PersistedEnvelope),
+    /// Every event below the watermark has been delivered and a stall holds
+    /// the rest back. Informational: keep polling, delivery resumes once the
+    /// transaction ends. Yielded once per stall.
+    Stalled(WatermarkStall),
+}
+
+type Events = BoxStream<'static, Result<(PgAllPos, PersistedEnvelope), PostgresError>>;
+
+/// The `$all` live cursor plus a watch on the store's stall state.
+struct StallAware {
+    events: Events,
+    stalls: watch::Receiver<Option<WatermarkStall>>,
+    /// Probes whether anything below the watermark is left to read. Also
+    /// keeps the stall cell, and so the watch sender, alive.
+    store: PostgresStore,
+    /// The last delivered position, where the probe reads from.
+    last: Option<PgAllPos>,
+    /// A stall was seen and this subscriber has not been told yet.
+    unreported: bool,
+}
+
+impl StallAware {
+    /// The next item; `None` only if the underlying cursor ends, which the
+    /// live cursor never does.
+    async fn next(&mut self) -> Option<Result<AllItem, PostgresError>> {
+        loop {
+            if self.unreported {
+                let Some(stall) = self.stalls.borrow_and_update().clone() else {
+                    // Cleared before it was worth mentioning.
+                    self.unreported = false;
+                    continue;
+                };
+                match caught_up(&self.store, self.last).await {
+                    Ok(true) => {
+                        self.unreported = false;
+                        return Some(Ok(AllItem::Stalled(stall)));
+                    }
+                    // Rows below the watermark come first.
+                    Ok(false) => return self.event().await,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            let woke =
+                match future::select(pin!(self.events.next()), pin!(self.stalls.changed())).await {
+                    Either::Left((item, _)) => Some(item),
+                    Either::Right(_) => None,
+                };
+            match woke {
+                Some(item) => return self.deliver(item),
+                None => self.unreported = self.stalls.borrow().is_some(),
+            }
+        }
+    }
+
+    async fn event(&mut self) -> Option<Result<AllItem, PostgresError>> {
+        let item = self.events.next().await;
+        self.deliver(item)
+    }
+
+    fn deliver(
+        &mut self,
+        item: Option<Result<(PgAllPos, PersistedEnvelope), PostgresError>>,
+    ) -> Option<Result<AllItem, PostgresError>> {
+        Some(item?.map(|(pos, env)| {
+            self.last = Some(pos);
+            AllItem::Event(pos, env)
+        }))
+    }
+}
+
+/// Whether every row readable now after `last` has been delivered. An
+/// `EXISTS` probe: it runs for each event delivered while a stall waits to
+/// be reported, so it must not read a page.
+async fn caught_up(store: &PostgresStore, last: Option<PgAllPos>) -> Result<bool, PostgresError> {
+    let after = all_cursor(last, ErrorId::default())?;
+    let (txid, seq) = after.unzip();
+    let pending = sqlx::query_scalar::<_, bool>(&store.sql().settled_after)
+        .bind(txid)
+        .bind(seq)
+        .fetch_one(store.pool())
+        .await
+        .map_err(PostgresError::Sqlx)?;
+    Ok(!pending)
+}
+
+/// Open a `$all` subscription that also reports watermark stalls.
+pub fn subscribe_all_with_stalls(
+    store: &PostgresStore,
+    cell: &StallCell,
+    from: Option<PgAllPos>,
+) -> Result<impl Stream<Item = Result<AllItem, PostgresError>> + Send + use<>, NotifyError> {
+    let events = Subscription::new(&Store::new(store.clone()))
+        .subscribe_all(from)?
+        .boxed();
+    let state = StallAware {
+        events,
+        stalls: cell.watch(),
+        store: store.clone(),
+        last: from,
+        unreported: false,
+    };
+    Ok(futures::stream::unfold(state, |mut s| async move {
+        let item = s.next().await?;
+        Some((item, s))
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for the configuration
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes_four_times_per_threshold_but_never_spin() {
+        let detection = StallDetection::after(Duration::from_secs(2));
+        assert_eq!(detection.probe_interval(), Duration::from_millis(500));
+        assert_eq!(
+            StallDetection::after(Duration::ZERO).probe_interval(),
+            MIN_PROBE_INTERVAL
+        );
+    }
+
+    #[test]
+    fn closures_observe_and_debug_hides_them() {
+        let detection =
+            StallDetection::after(Duration::from_secs(1)).observe(|_: &WatermarkStall| {});
+        assert!(format!("{detection:?}").contains("observer: Some(\"..\")"));
+    }
+}
@@ -1,88 +1,224 @@
+use std::collections::VecDeque;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 
 use bytes::Bytes;
+use futures::StreamExt;
 use nexus::{ErrorId, Version};
 use nexus_store::PendingEnvelope;
+use nexus_store::SnapshotRetention;
 use nexus_store::StreamKey;
+use nexus_store::batch::BatchSize;
 use nexus_store::envelope::PersistedEnvelope;
 use nexus_store::error::AppendError;
 use nexus_store::notify::StreamNotifiers;
-use nexus_store::store::RawEventStore;
+use nexus_store::store::{RawEventStore, TransactionalAppend};
 use nexus_store::value::{EventType, Metadata, Payload, SchemaVersion};
 use nexus_store::wire;
-use sqlx::PgPool;
-use sqlx::postgres::PgListener;
+use sqlx::{Acquire, Executor, PgPool, Postgres};
 use tokio::task::JoinHandle;
 
+use crate::copy::{
+    BinaryCopy, CLEAR_STAGING, COPY_STAGING, COPY_THRESHOLD, CREATE_STAGING, FLUSH_AT, StagedRow,
+};
 use crate::error::PostgresError;
 use crate::hex;
+use crate::lease::PostgresLeases;
+use crate::partition::{PartitionManager, RAISE_CONFLICTS, SKIP_CONFLICTS};
 use crate::position::PgAllPos;
-
-/// The `LISTEN/NOTIFY` channel every store instance shares. The write side
-/// (`notify_committed`) publishes on it; each store's listener task subscribes.
-const NOTIFY_CHANNEL: &str = "nexus_events";
+use crate::schema::Statements;
+use crate::stall::{
+    AllItem, StallCell, StallDetection, WatermarkStall, spawn_stall_monitor,
+    subscribe_all_with_stalls,
+};
+use crate::wake::{HealthCell, ListenerHealth, WakeStrategy, spawn_wake_tasks};
 
 /// Shared, `Arc`-owned interior of a [`PostgresStore`].
 ///
 /// One `Inner` is created per `from_pool`; every [`PostgresStore`] clone shares
-/// it. The `LISTEN/NOTIFY` listener task lives exactly as long as this `Inner`:
-/// [`Drop`] aborts it, so the background connection is released when the last
-/// clone of the store is dropped — a test that creates and drops many stores
-/// does not leak one listener connection per store (the reason `PostgresStore`
-/// is a thin `Arc<Inner>` handle rather than a bare `PgPool`).
+/// it. The background tasks (listener, poller, stall monitor) live exactly as
+/// long as this `Inner`: [`Drop`] aborts them, so the background connection is released
+/// when the last clone of the store is dropped — a test that creates and drops
+/// many stores does not leak one listener connection per store (the reason
+/// `PostgresStore` is a thin `Arc<Inner>` handle rather than a bare `PgPool`).
 struct Inner {
     pool: PgPool,
-    /// In-process wake registry. The `LISTEN/NOTIFY` listener task drives it:
-    /// every `NOTIFY` becomes a `notifiers.wake(id)`, which rouses both the
+    /// Every query this store runs, rendered against its configured schema,
+    /// table prefix and notify channel. Shared with the lazy read streams.
+    sql: Arc<Statements>,
+    /// In-process wake registry. The wake tasks drive it: every `NOTIFY` or
+    /// polled row becomes a `notifiers.wake(id)`, which rouses both the
     /// per-stream and `$all` armed registrations. Reusing this audited registry
-    /// keeps `LISTEN/NOTIFY` a thin transport that merely *hints* "scan sooner".
+    /// keeps the transport a thin layer that merely *hints* "scan sooner".
     notifiers: Arc<StreamNotifiers>,
-    /// The background `PgListener` task's handle. Aborted on drop so the task
-    /// (and its dedicated connection) stops with the store.
-    listener_task: JoinHandle<()>,
+    /// How commits from other connections are noticed.
+    strategy: WakeStrategy,
+    /// The listener task's connection state, `Disabled` under `Poll`.
+    health: Arc<HealthCell>,
+    /// The current watermark stall, kept by the stall monitor.
+    stall: Arc<StallCell>,
+    /// Partition upkeep, if the events table is partitioned.
+    partitions: Option<PartitionManager>,
+    /// The background tasks' handles. Aborted on drop so the tasks (and the
+    /// listener's dedicated connection) stop with the store.
+    tasks: Vec<JoinHandle<()>>,
 }
 
 impl Drop for Inner {
     fn drop(&mut self) {
-        // Stop the listener task with the store. Its `PgListener` holds a
-        // connection for the life of the task; aborting here prevents a
+        // Stop the background tasks with the store. A `PgListener` holds a
+        // connection for the life of its task; aborting here prevents a
         // per-store connection leak across the many create/drop cycles the
         // tests perform.
-        self.listener_task.abort();
+        for task in &self.tasks {
+            task.abort();
+        }
     }
 }
 
 /// PostgreSQL-backed event store.
 ///
 /// Implements [`RawEventStore`](nexus_store::RawEventStore) and
-/// [`WakeSource`](nexus_store::wake::WakeSource) (Tasks 4–7). Constructed via
+/// [`WakeSource`](nexus_store::wake::WakeSource) (Tasks 4–7), plus
+/// [`SnapshotStore`](nexus_store::SnapshotStore) for aggregate snapshots and
+/// projection checkpoints (see [`crate::snapshot`]). Constructed via
 /// [`connect`](Self::connect) or [`from_pool`](Self::from_pool) in
 /// [`builder`](crate::builder).
 ///
 /// Clone is cheap: it is a single `Arc` bump over the shared [`Inner`]
 /// (`PgPool` is itself `Arc`-backed). All clones share one wake registry and
-/// one `LISTEN/NOTIFY` listener task.
+/// one set of wake tasks.
+///
+/// Reads page lazily: `read_stream` and `read_all` hold at most
+/// [`batch_size`](Self::batch_size) rows at a time.
 #[derive(Clone)]
 pub struct PostgresStore {
     inner: Arc<Inner>,
+    batch_size: BatchSize,
+    snapshot_retention: SnapshotRetention,
+    copy_appends: bool,
 }
 
 impl PostgresStore {
-    /// Assemble a store from a pool: build the wake registry, spawn the single
-    /// `LISTEN/NOTIFY` listener task, and wrap it all in the shared [`Inner`].
+    /// Assemble a store from a pool: build the wake registry, spawn the wake
+    /// tasks `strategy` calls for, the stall monitor and the partition
+    /// maintainer, and wrap it all in the shared [`Inner`].
+    ///
+    /// Called by the [`builder`](crate::builder) *after* the schema is ensured.
     ///
-    /// Called by [`from_pool`](Self::from_pool) *after* the schema is ensured.
-    pub(crate) fn assemble(pool: PgPool) -> Self {
+    /// # Errors
+    ///
+    /// Returns [`PostgresError::Sqlx`] if a poller's starting point cannot be
+    /// read.
+    pub(crate) async fn assemble(
+        pool: PgPool,
+        statements: Statements,
+        strategy: WakeStrategy,
+        stall_detection: Option<StallDetection>,
+        partitions: Option<PartitionManager>,
+    ) -> Result<Self, PostgresError> {
         let notifiers = StreamNotifiers::new();
-        let listener_task = spawn_listener(pool.clone(), Arc::clone(&notifiers));
-        Self {
+        let sql = Arc::new(statements);
+        let health = Arc::new(HealthCell::new(match strategy {
+            WakeStrategy::Poll(_) => ListenerHealth::Disabled,
+            _ => ListenerHealth::Connecting,
+        }));
+        let mut tasks = spawn_wake_tasks(&pool, &sql, &notifiers, strategy, &health).await?;
+        let stall = Arc::new(StallCell::default());
+        if let Some(detection) = &stall_detection {
+            tasks.push(spawn_stall_monitor(
+                pool.clone(),
+                Arc::clone(&sql),
+                Arc::clone(&notifiers),
+                detection.clone(),
+                Arc::clone(&stall),
+            ));
+        }
+        if let Some(manager) = partitions.as_ref().filter(|m| m.maintains()) {
+            tasks.push(manager.spawn(pool.clone()));
+        }
+        Ok(Self {
             inner: Arc::new(Inner {
                 pool,
+                sql,
                 notifiers,
-                listener_task,
+                strategy,
+                health,
+                stall,
+                partitions,
+                tasks,
             }),
-        }
+            batch_size: BatchSize::DEFAULT,
+            snapshot_retention: SnapshotRetention::LATEST,
+            copy_appends: false,
+        })
+    }
+
+    /// Set how many rows `read_stream` and `read_all` fetch per page
+    /// ([`BatchSize::DEFAULT`] unless set). A read holds at most one page in
+    /// memory and fetches the next when it drains.
+    #[must_use]
+    pub const fn with_batch_size(mut self, batch_size: BatchSize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// The configured read page size.
+    #[must_use]
+    pub const fn batch_size(&self) -> BatchSize {
+        self.batch_size
+    }
+
+    /// The configured [`WakeStrategy`].
+    #[must_use]
+    pub fn wake_strategy(&self) -> WakeStrategy {
+        self.inner.strategy
+    }
+
+    /// The current state of the `LISTEN/NOTIFY` connection — for health
+    /// checks that should flag a store whose cross-process wakes are stalled.
+    #[must_use]
+    pub fn listener_health(&self) -> ListenerHealth {
+        self.inner.health.get()
+    }
+
+    /// The current `$all` watermark stall, if [stall
+    /// detection](crate::StallDetection) is on and one is in progress.
+    #[must_use]
+    pub fn watermark_stall(&self) -> Option<WatermarkStall> {
+        self.inner.stall.get()
+    }
+
+    /// Open an `$all` subscription that also reports watermark stalls.
+    ///
+    /// Events arrive as [`AllItem::Event`], exactly as
+    /// [`Subscription::subscribe_all`](nexus_store::Subscription::subscribe_all)
+    /// would yield them. Once every event below a pinned watermark has been
+    /// delivered, the stall arrives as one [`AllItem::Stalled`]; the
+    /// subscription carries on and resumes with the held-back events when the
+    /// transaction ends. Without [stall detection](crate::StallDetection) it
+    /// never yields a stall. The stream never returns `None` and is `!Unpin`,
+    /// so `pin!` it before polling.
+    ///
+    /// # Errors
+    ///
+    /// [`NotifyError`](nexus_store::notify::NotifyError) if wake registration
+    /// fails. Read errors are `Err` items, as on any subscription.
+    pub fn subscribe_all_with_stalls(
+        &self,
+        from: Option<PgAllPos>,
+    ) -> Result<
+        impl futures::Stream<Item = Result<AllItem, PostgresError>> + Send + use<>,
+        nexus_store::notify::NotifyError,
+    > {
+        subscribe_all_with_stalls(self, &self.inner.stall, from)
+    }
+
+    /// Leader leases scoped to this store's namespace, on session advisory
+    /// locks in its database. See [`PostgresLeases`].
+    #[must_use]
+    pub fn leases(&self) -> PostgresLeases {
+        PostgresLeases::new(self.inner.pool.clone(), Arc::clone(&self.inner.sql))
     }
 
     /// The connection pool. `pub(crate)` for the sibling modules (`builder`,
@@ -91,57 +227,45 @@ impl PostgresStore {
         &self.inner.pool
     }
 
-    /// The shared wake registry, driven by the `LISTEN/NOTIFY` listener task.
-    /// `pub(crate)` so the `WakeSource` impl in [`crate::wake`] can delegate to
-    /// it.
+    /// Partition upkeep, if the events table is partitioned. `pub(crate)` for
+    /// [`crate::partition`]'s store methods.
+    pub(crate) fn partition_manager(&self) -> Option<&PartitionManager> {
+        self.inner.partitions.as_ref()
+    }
+
+    /// The store's rendered SQL. `pub(crate)` for the sibling modules that
+    /// query its tables.
+    pub(crate) fn sql(&self) -> &Statements {
+        &self.inner.sql
+    }
+
+    /// Keep `retention` snapshots per id. Set by the
+    /// [`builder`](crate::PostgresStoreBuilder::snapshot_retention).
+    pub(crate) const fn with_snapshot_retention(mut self, retention: SnapshotRetention) -> Self {
+        self.snapshot_retention = retention;
+        self
+    }
+
+    /// Send large appends through `COPY`. Set by the
+    /// [`builder`](crate::PostgresStoreBuilder::copy_appends).
+    pub(crate) const fn with_copy_appends(mut self, enabled: bool) -> Self {
+        self.copy_appends = enabled;
+        self
+    }
+
+    /// How many aggregate snapshots each id keeps. `pub(crate)` for the
+    /// snapshot commit's prune.
+    pub(crate) const fn snapshot_retention(&self) -> SnapshotRetention {
+        self.snapshot_retention
+    }
+
+    /// The shared wake registry, driven by the wake tasks. `pub(crate)` so the
+    /// `WakeSource` impl in [`crate::wake`] can delegate to it.
     pub(crate) fn wake_registry(&self) -> &Arc<StreamNotifiers> {
         &self.inner.notifiers
     }
 }
 
-/// Spawn the single background task that owns a [`PgListener`] and forwards each
-/// `NOTIFY` into the store's [`StreamNotifiers`].
-///
-/// # Correctness — this redundant catch-up scan must NOT be optimized away
-///
-/// `PgListener` **auto-reconnects** on connection loss and, per the sqlx 0.8
-/// docs, *"any notifications received while the connection was lost will not be
-/// returned"* — a `NOTIFY` can be silently dropped. This is tolerable ONLY
-/// because [`StreamNotifiers`] drives nexus-store's arm-before-confirm-rescan
-/// discipline: every `wake()` (and every reopen of the generic live loop)
-/// re-scans the store via `read_stream`/`read_all`, so a dropped `NOTIFY` merely
-/// **delays** a wake until the next scan — it never loses the event. The
-/// `NOTIFY` is a *hint to scan sooner*, never the source of truth. A future
-/// refactor MUST NOT remove that redundant catch-up scan: without it, a dropped
-/// `NOTIFY` would become a lost event.
-fn spawn_listener(pool: PgPool, notifiers: Arc<StreamNotifiers>) -> JoinHandle<()> {
-    tokio::spawn(async move {
-        // Obtain a dedicated listener connection from the pool and subscribe.
-        // On setup failure there is nothing to wake from; exit — the store
-        // still works, wakes just fall back entirely to the catch-up scan.
-        let Ok(mut listener) = PgListener::connect_with(&pool).await else {
-            return;
-        };
-        if listener.listen(NOTIFY_CHANNEL).await.is_err() {
-            return;
-        }
-        // `recv()` transparently reconnects on connection loss (dropping any
-        // NOTIFYs sent while disconnected — see the correctness note above). It
-        // only errors when the pool is closed, i.e. the store is being torn
-        // down; exit the loop then so the task ends cleanly.
-        while let Ok(notification) = listener.recv().await {
-            // The payload is the hex-encoded raw stream id. A malformed payload
-            // is dropped (see `hex::decode`): a mis-routed wake could rouse the
-            // wrong stream, whereas a dropped one is caught by the next scan.
-            if let Some(stream) = hex::decode(notification.payload()) {
-                // `wake` bumps BOTH the per-stream and `$all` paths, so one
-                // NOTIFY rouses per-stream and `$all` armed registrations alike.
-                notifiers.wake(&stream);
-            }
-        }
-    })
-}
-
 // ---------------------------------------------------------------------------
 // Per-event columns that reconstitute a `PersistedEnvelope`.
 // NOTE: no `global_seq` — that is an `$all`-position concern, not an envelope one.
@@ -244,8 +368,22 @@ fn row_to_envelope(
     })
 }
 
+/// A `$all` position as the `(txid, global_seq)` binds of a keyset resume:
+/// the txid as text (cast to `xid8` in SQL), `None` for the start.
+pub fn all_cursor(
+    from: Option<PgAllPos>,
+    label: ErrorId,
+) -> Result<Option<(String, i64)>, PostgresError> {
+    from.map(|p| {
+        i64::try_from(p.seq())
+            .map(|seq| (p.txid().to_string(), seq))
+            .map_err(|_| corrupt(label, "from seq exceeds i64::MAX"))
+    })
+    .transpose()
+}
+
 /// Build a [`PostgresError::CorruptRow`] from a fixed-string reason.
-fn corrupt(stream_id: ErrorId, reason: &str) -> PostgresError {
+pub fn corrupt(stream_id: ErrorId, reason: &str) -> PostgresError {
     PostgresError::CorruptRow {
         stream_id,
         reason: ErrorId::from_display(&reason),
@@ -270,6 +408,21 @@ struct PreparedInsert<'a> {
     env: &'a PendingEnvelope,
 }
 
+impl<'a> PreparedInsert<'a> {
+    /// This row as staging row `ord` of stream `id`, for the `COPY` path.
+    fn staged(&self, ord: i64, id: &'a StreamKey) -> StagedRow<'a> {
+        StagedRow {
+            ord,
+            stream_id: id.as_bytes(),
+            version: self.version,
+            event_type: self.env.event_type(),
+            schema_version: self.schema_version,
+            payload: self.env.payload(),
+            metadata: self.env.metadata(),
+        }
+    }
+}
+
 /// Pure, IO-free batch validation + narrowing — **unit-testable without a DB.**
 ///
 /// Given the stream's `current` (max) version (already read inside the txn):
@@ -336,30 +489,181 @@ fn prepare_inserts<'a>(
 // IO helpers
 // ---------------------------------------------------------------------------
 
-/// Read the stream's current (max) version inside `conn`. Absent stream → 0.
-///
-/// A *negative* stored version is corruption surfaced as an error, NOT a silent
-/// 0 (rule 2 — no `unwrap_or(sentinel)` on a failed conversion).
-async fn read_current_version(
-    conn: &mut sqlx::PgConnection,
-    id: &StreamKey,
-) -> Result<u64, AppendError<PostgresError>> {
-    let current: i64 =
-        sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM events WHERE stream_id = $1")
+impl PostgresStore {
+    /// Read the stream's current (max) version inside `conn`. Absent stream → 0.
+    ///
+    /// A *negative* stored version is corruption surfaced as an error, NOT a
+    /// silent 0 (rule 2 — no `unwrap_or(sentinel)` on a failed conversion).
+    async fn read_current_version(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        id: &StreamKey,
+    ) -> Result<u64, AppendError<PostgresError>> {
+        let current: i64 = sqlx::query_scalar(&self.sql().current_version)
             .bind(id.as_bytes())
             .fetch_one(&mut *conn)
             .await
             .map_err(store_err)?;
-    u64::try_from(current).map_err(|_| {
-        AppendError::Store(PostgresError::CorruptRow {
-            stream_id: ErrorId::from_display(id),
-            reason: ErrorId::from_display(&"stored version is negative"),
+        u64::try_from(current).map_err(|_| {
+            AppendError::Store(PostgresError::CorruptRow {
+                stream_id: ErrorId::from_display(id),
+                reason: ErrorId::from_display(&"stored version is negative"),
+            })
         })
-    })
+    }
+
+    /// The stream's committed head, read outside any open transaction — after
+    /// a `COPY` merge lost rows to a racer, the transaction's own view also
+    /// holds the rows it merged.
+    async fn committed_version(&self, id: &StreamKey) -> Result<u64, AppendError<PostgresError>> {
+        let mut conn = self.pool().acquire().await.map_err(store_err)?;
+        self.read_current_version(&mut conn, id).await
+    }
+
+    /// Check and insert a batch inside `conn`'s open transaction — the whole
+    /// of `append` but the commit, so a caller can stage more writes alongside
+    /// it.
+    ///
+    /// Returns the stream's head version afterwards (unchanged for an empty
+    /// batch; 0 for a stream that still has no events).
+    pub(crate) async fn insert_events(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+    ) -> Result<u64, AppendError<PostgresError>> {
+        let current = self.read_current_version(conn, id).await?; // IO
+        let rows = prepare_inserts(current, expected_version, envelopes, id)?; // PURE
+        let head = rows
+            .last()
+            .map_or(current, |row| row.env.version().as_u64());
+
+        self.insert_rows(conn, id, &rows).await?;
+        Ok(head)
+    }
+
+    /// `INSERT` validated rows inside `conn`'s open transaction — through
+    /// `COPY` from [`COPY_THRESHOLD`] rows up when the store
+    /// [copies appends](crate::PostgresStoreBuilder::copy_appends).
+    async fn insert_rows(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        id: &StreamKey,
+        rows: &[PreparedInsert<'_>],
+    ) -> Result<(), AppendError<PostgresError>> {
+        if self.copy_appends && rows.len() >= COPY_THRESHOLD {
+            let staged: Vec<StagedRow<'_>> = rows
+                .iter()
+                .zip(0_i64..)
+                .map(|(row, ord)| row.staged(ord, id))
+                .collect();
+            let skipped = self
+                .copy_rows(conn, &staged)
+                .await
+                .map_err(AppendError::Store)?;
+            let Some(ord) = skipped else {
+                return Ok(());
+            };
+            let actual = self.committed_version(id).await?;
+            return Err(AppendError::Conflict {
+                stream_id: ErrorId::from_display(id),
+                expected: rows.get(ord).map(|row| row.env.version()),
+                actual: Version::new(actual),
+            });
+        }
+        for row in rows {
+            let result = sqlx::query(&self.sql().insert_event)
+                .bind(id.as_bytes())
+                .bind(row.version)
+                .bind(row.env.event_type())
+                .bind(row.schema_version)
+                .bind(row.env.payload())
+                .bind(row.env.metadata())
+                .execute(&mut *conn)
+                .await;
+
+            if let Err(e) = result {
+                // A UNIQUE(stream_id, version) violation means a concurrent
+                // writer claimed this version first — a conflict, not a Store
+                // error (CLAUDE rule 3: one variant = one failure domain).
+                if is_unique_violation(&e) {
+                    return Err(AppendError::Conflict {
+                        stream_id: ErrorId::from_display(id),
+                        expected: Some(row.env.version()),
+                        actual: None, // a racer committed; exact actual unknown here
+                    });
+                }
+                return Err(AppendError::Store(PostgresError::Sqlx(e)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PostgresStore {
+    /// Stream `rows` into the staging table with one binary `COPY` and merge
+    /// them into `events` in order (see [`crate::copy`]).
+    ///
+    /// Returns the position of the first row a concurrent writer had already
+    /// claimed, if any. The rows around it may have merged, so the caller
+    /// must abandon the transaction.
+    async fn copy_rows(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        rows: &[StagedRow<'_>],
+    ) -> Result<Option<usize>, PostgresError> {
+        conn.execute(CREATE_STAGING)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        let mut copy_in = conn
+            .copy_in_raw(COPY_STAGING)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        let mut encoder = BinaryCopy::new();
+        for row in rows {
+            if let Err(e) = encoder.push(row) {
+                // The transaction is abandoned either way; the abort only
+                // returns the connection to a usable state.
+                let _ = copy_in.abort("invalid row").await;
+                return Err(e);
+            }
+            if encoder.len() >= FLUSH_AT {
+                copy_in
+                    .send(encoder.take())
+                    .await
+                    .map_err(PostgresError::Sqlx)?;
+            }
+        }
+        copy_in
+            .send(encoder.finish())
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        copy_in.finish().await.map_err(PostgresError::Sqlx)?;
+
+        if self.sql().partitioned {
+            conn.execute(SKIP_CONFLICTS)
+                .await
+                .map_err(PostgresError::Sqlx)?;
+        }
+        let skipped: Option<i64> = sqlx::query_scalar(&self.sql().merge_staged)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        if self.sql().partitioned {
+            conn.execute(RAISE_CONFLICTS)
+                .await
+                .map_err(PostgresError::Sqlx)?;
+        }
+        conn.execute(CLEAR_STAGING)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+        Ok(skipped.and_then(|ord| usize::try_from(ord).ok()))
+    }
 }
 
 /// Map a sqlx error to an `AppendError<PostgresError>` store variant.
-fn store_err<E: Into<sqlx::Error>>(e: E) -> AppendError<PostgresError> {
+pub fn store_err<E: Into<sqlx::Error>>(e: E) -> AppendError<PostgresError> {
     AppendError::Store(PostgresError::Sqlx(e.into()))
 }
 
@@ -373,7 +677,7 @@ fn is_unique_violation(e: &sqlx::Error) -> bool {
 // ---------------------------------------------------------------------------
 
 impl PostgresStore {
-    /// Fire `pg_notify('nexus_events', <hex stream id>)` after a durable
+    /// Fire `pg_notify(<channel>, <hex stream id>)` after a durable
     /// commit. Best-effort — a failed `NOTIFY` must not fail an already-durable
     /// append (the catch-up scan will still pick the event up on the next poll).
     pub(crate) async fn notify_committed(&self, stream: &[u8]) {
@@ -381,9 +685,14 @@ impl PostgresStore {
         // be ASCII text, but a stream id is arbitrary bytes). The listener task
         // reverses this via `hex::decode` before calling `wake` — one shared,
         // unit-tested codec on both ends (see `crate::hex`).
+        // Without a listener (or with one that may be down), this process's
+        // own subscribers would otherwise wait for the next poll.
+        if self.inner.strategy != WakeStrategy::Listen {
+            self.inner.notifiers.wake(stream);
+        }
         let payload = hex::encode(stream);
         let _ = sqlx::query("SELECT pg_notify($1, $2)")
-            .bind(NOTIFY_CHANNEL)
+            .bind(&self.sql().channel)
             .bind(payload)
             .execute(self.pool())
             .await;
@@ -394,22 +703,170 @@ impl PostgresStore {
 // `RawEventStore` impl
 // ---------------------------------------------------------------------------
 
-/// Per-stream stream type: owned, `Send`, `'static` iterator-backed stream of
-/// `Result<PersistedEnvelope, PostgresError>`.
-///
-/// Materialized from a `Vec` via `futures::stream::iter`. The whole result set
-/// is fetched eagerly — this is the validation skeleton, not the production
-/// shape (see the plan's `fetch_all` note for the keyset-paginated follow-up).
-type Stream = futures::stream::Iter<std::vec::IntoIter<Result<PersistedEnvelope, PostgresError>>>;
+/// Per-stream stream type: owned, `Send`, `'static` stream of
+/// `Result<PersistedEnvelope, PostgresError>`, paged by [`StreamPages`].
+type Stream = futures::stream::BoxStream<'static, Result<PersistedEnvelope, PostgresError>>;
 
-/// `$all` stream type: owned, `Send`, `'static` iterator-backed stream of
-/// `Result<(PgAllPos, PersistedEnvelope), PostgresError>`.
+/// `$all` stream type: owned, `Send`, `'static` stream of
+/// `Result<(PgAllPos, PersistedEnvelope), PostgresError>`, paged by
+/// [`AllPages`].
 ///
 /// Distinct from [`Stream`] because `$all` items are position-tagged
 /// (`(PgAllPos, PersistedEnvelope)`) while per-stream items are not
-/// (`PersistedEnvelope` only). Same eager-fetch caveat as [`Stream`].
+/// (`PersistedEnvelope` only).
 type AllStream =
-    futures::stream::Iter<std::vec::IntoIter<Result<(PgAllPos, PersistedEnvelope), PostgresError>>>;
+    futures::stream::BoxStream<'static, Result<(PgAllPos, PersistedEnvelope), PostgresError>>;
+
+/// Keyset-paginating state for one `read_stream`: each refill fetches the next
+/// `batch_size` rows at `version >= next`.
+struct StreamPages {
+    pool: PgPool,
+    sql: Arc<Statements>,
+    stream_id: StreamKey,
+    label: ErrorId,
+    /// Inclusive lower bound of the next page.
+    next: i64,
+    batch_size: usize,
+    limit: i64,
+    buffer: VecDeque<EventRow>,
+    /// Set by a short page (no more rows), a version at `i64::MAX`, or an
+    /// error — the stream then ends rather than skipping rows.
+    done: bool,
+}
+
+impl StreamPages {
+    async fn refill(&mut self) -> Result<(), PostgresError> {
+        let rows: Vec<EventRow> = sqlx::query_as(&self.sql.stream_page)
+            .bind(self.stream_id.as_bytes())
+            .bind(self.next)
+            .bind(self.limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+
+        self.done = rows.len() < self.batch_size;
+        if let Some(last) = rows.last() {
+            match last.version.checked_add(1) {
+                Some(next) => self.next = next,
+                None => self.done = true,
+            }
+        }
+        self.buffer = rows.into();
+        Ok(())
+    }
+
+    fn into_stream(self) -> Stream {
+        futures::stream::unfold(self, |mut s| async move {
+            loop {
+                if let Some(row) = s.buffer.pop_front() {
+                    let item = row_to_envelope(row, s.label);
+                    if item.is_err() {
+                        s.buffer.clear();
+                        s.done = true;
+                    }
+                    return Some((item, s));
+                }
+                if s.done {
+                    return None;
+                }
+                if let Err(e) = s.refill().await {
+                    s.done = true;
+                    return Some((Err(e), s));
+                }
+                if s.buffer.is_empty() {
+                    return None;
+                }
+            }
+        })
+        .fuse()
+        .boxed()
+    }
+}
+
+/// Keyset-paginating state for one `read_all`: each refill fetches the next
+/// `batch_size` rows strictly after the `(txid, global_seq)` cursor and below
+/// the watermark pinned when the read opened.
+struct AllPages {
+    pool: PgPool,
+    sql: Arc<Statements>,
+    /// Exclusive resume cursor: `txid` as text (bound back as `xid8`) and
+    /// `global_seq`. `None` = from the very beginning.
+    after: Option<(String, i64)>,
+    /// `pg_snapshot_xmin` at open, as text (bound back as `xid8`).
+    watermark: String,
+    batch_size: usize,
+    limit: i64,
+    buffer: VecDeque<AllEventRow>,
+    /// Set by a short page or an error — the stream then ends.
+    done: bool,
+}
+
+impl AllPages {
+    async fn refill(&mut self) -> Result<(), PostgresError> {
+        let (after_txid, after_seq) = self.after.as_ref().map_or((None, None), |(txid, seq)| {
+            (Some(txid.as_str()), Some(*seq))
+        });
+        let rows: Vec<AllEventRow> = sqlx::query_as(&self.sql.all_page)
+            .bind(after_txid)
+            .bind(after_seq)
+            .bind(&self.watermark)
+            .bind(self.limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(PostgresError::Sqlx)?;
+
+        self.done = rows.len() < self.batch_size;
+        if let Some(last) = rows.last() {
+            self.after = Some((last.txid.to_string(), last.global_seq));
+        }
+        self.buffer = rows.into();
+        Ok(())
+    }
+
+    fn into_stream(self) -> AllStream {
+        futures::stream::unfold(self, |mut s| async move {
+            loop {
+                if let Some(row) = s.buffer.pop_front() {
+                    let item = tag_all_row(row);
+                    if item.is_err() {
+                        s.buffer.clear();
+                        s.done = true;
+                    }
+                    return Some((item, s));
+                }
+                if s.done {
+                    return None;
+                }
+                if let Err(e) = s.refill().await {
+                    s.done = true;
+                    return Some((Err(e), s));
+                }
+                if s.buffer.is_empty() {
+                    return None;
+                }
+            }
+        })
+        .fuse()
+        .boxed()
+    }
+}
+
+/// Decode one `$all` row into its position tag and envelope.
+fn tag_all_row(row: AllEventRow) -> Result<(PgAllPos, PersistedEnvelope), PostgresError> {
+    let label = ErrorId::default();
+    let txid = u64::try_from(row.txid).map_err(|_| corrupt(label, "txid out of range"))?;
+    let seq = u64::try_from(row.global_seq)
+        .map_err(|_| corrupt(label, "global_seq <= 0 or out of range"))?;
+    // `row.event` is the flattened `EventRow` — reuse the single rebuild path.
+    let env = row_to_envelope(row.event, label)?;
+    Ok((PgAllPos::new(txid, seq), env))
+}
+
+/// A [`BatchSize`] as a SQL `LIMIT`. Infallible in practice — `BatchSize` is
+/// at most [`MAX_BATCH`](nexus_store::MAX_BATCH).
+fn page_limit(batch_size: BatchSize, label: ErrorId) -> Result<i64, PostgresError> {
+    i64::try_from(batch_size.get()).map_err(|_| corrupt(label, "batch size exceeds i64::MAX"))
+}
 
 impl RawEventStore for PostgresStore {
     type Error = PostgresError;
@@ -424,43 +881,11 @@ impl RawEventStore for PostgresStore {
         envelopes: &[PendingEnvelope],
     ) -> Result<(), AppendError<Self::Error>> {
         let mut tx = self.pool().begin().await.map_err(store_err)?;
-
-        let current = read_current_version(&mut tx, id).await?; // IO
-        let rows = prepare_inserts(current, expected_version, envelopes, id)?; // PURE
-        if rows.is_empty() {
+        self.insert_events(&mut tx, id, expected_version, envelopes)
+            .await?;
+        if envelopes.is_empty() {
             return Ok(()); // version checked; nothing to write
         }
-
-        for row in &rows {
-            let result = sqlx::query(
-                "INSERT INTO events \
-                 (stream_id, version, event_type, schema_version, payload, metadata) \
-                 VALUES ($1, $2, $3, $4, $5, $6)",
-            )
-            .bind(id.as_bytes())
-            .bind(row.version)
-            .bind(row.env.event_type())
-            .bind(row.schema_version)
-            .bind(row.env.payload())
-            .bind(row.env.metadata())
-            .execute(&mut *tx)
-            .await;
-
-            if let Err(e) = result {
-                // A UNIQUE(stream_id, version) violation means a concurrent
-                // writer claimed this version first — a conflict, not a Store
-                // error (CLAUDE rule 3: one variant = one failure domain).
-                if is_unique_violation(&e) {
-                    return Err(AppendError::Conflict {
-                        stream_id: ErrorId::from_display(id),
-                        expected: Some(row.env.version()),
-                        actual: None, // a racer committed; exact actual unknown here
-                    });
-                }
-                return Err(AppendError::Store(PostgresError::Sqlx(e)));
-            }
-        }
-
         tx.commit().await.map_err(store_err)?;
 
         // Wake AFTER durable commit (WakeSource contract: wake post-commit).
@@ -477,23 +902,18 @@ impl RawEventStore for PostgresStore {
         let from_i64 = i64::try_from(from.as_u64())
             .map_err(|_| corrupt(label, "from version exceeds i64::MAX"))?;
 
-        let rows: Vec<EventRow> = sqlx::query_as(
-            "SELECT version, event_type, schema_version, payload, metadata \
-             FROM events \
-             WHERE stream_id = $1 AND version >= $2 \
-             ORDER BY version",
-        )
-        .bind(id.as_bytes())
-        .bind(from_i64)
-        .fetch_all(self.pool())
-        .await
-        .map_err(PostgresError::Sqlx)?;
-
-        let envelopes: Vec<Result<PersistedEnvelope, PostgresError>> = rows
-            .into_iter()
-            .map(move |r| row_to_envelope(r, label))
-            .collect();
-        Ok(futures::stream::iter(envelopes))
+        Ok(StreamPages {
+            pool: self.pool().clone(),
+            sql: Arc::clone(&self.inner.sql),
+            stream_id: id.clone(),
+            label,
+            next: from_i64,
+            batch_size: self.batch_size.get(),
+            limit: page_limit(self.batch_size, label)?,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+        .into_stream())
     }
 
     async fn read_all(&self, from: Option<PgAllPos>) -> Result<Self::AllStream, Self::Error> {
@@ -501,74 +921,309 @@ impl RawEventStore for PostgresStore {
 
         // Absence is expressed as SQL NULL, NOT a magic sentinel (CLAUDE rule 3 —
         // "unknown values must be Option, not sentinels"). `from = None` binds two
-        // NULLs and the `$1::bigint IS NULL` guard short-circuits the resume
+        // NULLs and the `$1::text IS NULL` guard short-circuits the resume
         // predicate, so the read starts from the very beginning. `from = Some`
-        // binds the pair and the row-value comparison applies. One query, no
-        // out-of-band `-1`.
-        let (from_txid, from_seq): (Option<i64>, Option<i64>) = match from {
-            Some(p) => (
-                Some(
-                    i64::try_from(p.txid())
-                        .map_err(|_| corrupt(label, "from txid exceeds i64::MAX"))?,
-                ),
-                Some(
-                    i64::try_from(p.seq())
-                        .map_err(|_| corrupt(label, "from seq exceeds i64::MAX"))?,
-                ),
-            ),
-            None => (None, None),
-        };
-
-        // Way-2 read. TWO predicates, both required for by-construction correctness:
+        // binds the pair and the row-value comparison applies.
+        let after = all_cursor(from, label)?;
+
+        // Way-2 read. TWO predicates on every page, both required for
+        // by-construction correctness:
         //
-        //   1. Watermark `txid < pg_snapshot_xmin(pg_current_snapshot())` — never
-        //      consume a row whose writing txn (or any older still-in-flight txn)
-        //      hasn't settled, so no smaller `(txid, seq)` can appear *after* a
-        //      larger one is delivered. This is what makes the live `$all` gap-free.
+        //   1. Watermark `txid < xmin` — never consume a row whose writing txn
+        //      (or any older still-in-flight txn) hasn't settled, so no smaller
+        //      `(txid, seq)` can appear *after* a larger one is delivered. This
+        //      is what makes the live `$all` gap-free. `xmin` is
+        //      `pg_snapshot_xmin(pg_current_snapshot())` read ONCE here and
+        //      pinned for every page: the read yields exactly the rows settled
+        //      when it opened, as one unpaged query would, and stays finite
+        //      under a steady write load. Rows settled later are picked up by
+        //      the next `read_all` from the last yielded position.
         //
-        //   2. Exclusive resume `$1::bigint IS NULL OR (txid::text::bigint, global_seq)
-        //      > ($1, $2)` — `Ord`-based strict-after when resuming, unbounded-below
-        //      when starting fresh; matches `PgAllPos`'s lexicographic `derive(Ord)`.
+        //   2. Exclusive keyset resume `(txid, global_seq) > cursor` — `Ord`-based
+        //      strict-after, matching `PgAllPos`'s lexicographic `derive(Ord)`.
+        //      The first page uses `from` (unbounded below when `None`); each
+        //      later page resumes after the last row of the previous one.
         //
         // `ORDER BY (txid, global_seq)` is native xid8/bigint ascending — the same
         // order `PgAllPos` sorts by (xid8 is an unsigned 64-bit, non-wrapping count).
         //
-        // SQL note: `xid8` has no direct cast to `bigint`; `txid::text::bigint`
-        // round-trips it (valid while the xid8 value ≤ `i64::MAX`, i.e. effectively
-        // forever). `pg_snapshot_xmin(...)` returns `xid8`, so the watermark
-        // comparison `txid < pg_snapshot_xmin(...)` stays native (no cast). The
-        // `::text::bigint` cast defeats the `events_watermark_idx` for the resume
-        // predicate — acceptable for the validation skeleton (materialize-all). For
-        // a later keyset-paginated cursor, bind the resume position back *to* `xid8`
-        // (`WHERE (txid, global_seq) > ($1::text::xid8, $2)`) rather than casting
-        // the column down to `bigint`.
-        let rows: Vec<AllEventRow> = sqlx::query_as(
-            "SELECT txid::text::bigint AS txid, global_seq, \
-                    version, event_type, schema_version, payload, metadata \
-             FROM events \
-             WHERE ($1::bigint IS NULL OR (txid::text::bigint, global_seq) > ($1, $2)) \
-               AND txid < pg_snapshot_xmin(pg_current_snapshot()) \
-             ORDER BY txid, global_seq",
-        )
-        .bind(from_txid)
-        .bind(from_seq)
-        .fetch_all(self.pool())
-        .await
-        .map_err(PostgresError::Sqlx)?;
-
-        let tagged: Vec<Result<(PgAllPos, PersistedEnvelope), PostgresError>> = rows
-            .into_iter()
-            .map(move |r| {
-                let txid =
-                    u64::try_from(r.txid).map_err(|_| corrupt(label, "txid out of range"))?;
-                let seq = u64::try_from(r.global_seq)
-                    .map_err(|_| corrupt(label, "global_seq <= 0 or out of range"))?;
-                // `r.event` is the flattened `EventRow` — reuse the single rebuild path.
-                let env = row_to_envelope(r.event, label)?;
-                Ok((PgAllPos::new(txid, seq), env))
-            })
-            .collect();
-        Ok(futures::stream::iter(tagged))
+        // SQL note: `xid8` has no direct cast to or from `bigint`, so the cursor
+        // and watermark travel as text and are cast *to* `xid8` in the query
+        // (`$1::text::xid8`), keeping the column uncast so the keyset predicate
+        // is served by `events_watermark_idx`. Rows still read `txid` back via
+        // `txid::text::bigint` (valid while the xid8 value ≤ `i64::MAX`, i.e.
+        // effectively forever).
+        let watermark: String =
+            sqlx::query_scalar("SELECT pg_snapshot_xmin(pg_current_snapshot())::text")
+                .fetch_one(self.pool())
+                .await
+                .map_err(PostgresError::Sqlx)?;
+
+        Ok(AllPages {
+            pool: self.pool().clone(),
+            sql: Arc::clone(&self.inner.sql),
+            after,
+            watermark,
+            batch_size: self.batch_size.get(),
+            limit: page_limit(self.batch_size, label)?,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+        .into_stream())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// `TransactionalAppend` — append inside a caller-owned transaction
+// ---------------------------------------------------------------------------
+
+/// Appends join the caller's `sqlx::Transaction` under a savepoint: a failed
+/// append — conflict or store error — rolls back to the savepoint, leaving
+/// the caller's own writes and the transaction itself usable.
+///
+/// The `NOTIFY` is queued inside the transaction too. Postgres delivers it
+/// on commit and drops it on rollback, so subscribers are woken for exactly
+/// the events that became durable.
+impl<'c> TransactionalAppend<sqlx::Transaction<'c, Postgres>> for PostgresStore {
+    async fn append_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'c, Postgres>,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+    ) -> Result<(), AppendError<Self::Error>> {
+        let mut savepoint = tx.begin().await.map_err(store_err)?;
+        self.insert_events(&mut savepoint, id, expected_version, envelopes)
+            .await?;
+        if !envelopes.is_empty() {
+            sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(&self.sql().channel)
+                .bind(hex::encode(id.as_bytes()))
+                .execute(&mut *savepoint)
+                .await
+                .map_err(store_err)?;
+        }
+        savepoint.commit().await.map_err(store_err)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// `AtomicAppend` — whole-chunk import in one transaction
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "import")]
+mod atomic_append_impl {
+    use std::collections::HashMap;
+
+    use nexus::{ErrorId, Version};
+    use nexus_store::error::AppendError;
+    use nexus_store::import::{AtomicAppend, AtomicAppendError, PlannedAppend};
+
+    use super::{COPY_THRESHOLD, PostgresError, PostgresStore, corrupt, prepare_inserts};
+
+    /// Re-home a per-stream [`AppendError`] onto the batch write at `index`.
+    /// `actual` is the target's head when known (`None` for a racing writer's
+    /// unique violation, whose head was never read).
+    fn atomic_err(
+        index: usize,
+        actual: Option<Version>,
+        e: AppendError<PostgresError>,
+    ) -> AtomicAppendError<PostgresError> {
+        match e {
+            AppendError::Store(source) => AtomicAppendError::Store(source),
+            _ => AtomicAppendError::Conflict { index, actual },
+        }
+    }
+
+    impl PostgresStore {
+        /// Every target's head version, read in one round trip inside `conn`.
+        async fn stream_heads(
+            &self,
+            conn: &mut sqlx::PgConnection,
+            writes: &[PlannedAppend],
+        ) -> Result<HashMap<Vec<u8>, u64>, PostgresError> {
+            let targets: Vec<&[u8]> = writes.iter().map(|w| w.target.as_bytes()).collect();
+            let heads: Vec<(Vec<u8>, i64)> = sqlx::query_as(&self.sql().stream_heads)
+                .bind(&targets)
+                .fetch_all(&mut *conn)
+                .await
+                .map_err(PostgresError::Sqlx)?;
+            heads
+                .into_iter()
+                .map(|(id, head)| {
+                    let version = u64::try_from(head).map_err(|_| PostgresError::CorruptRow {
+                        stream_id: ErrorId::from_display(&String::from_utf8_lossy(&id)),
+                        reason: ErrorId::from_display(&"stored version is negative"),
+                    })?;
+                    Ok((id, version))
+                })
+                .collect()
+        }
+    }
+
+    impl AtomicAppend for PostgresStore {
+        async fn atomic_append_many(
+            &self,
+            writes: &[PlannedAppend],
+        ) -> Result<(), AtomicAppendError<PostgresError>> {
+            // Nothing to commit — don't open an empty transaction.
+            if writes.is_empty() {
+                return Ok(());
+            }
+
+            // One transaction spans every run. Any early Err drops `tx`
+            // uncommitted, rolling back the runs already inserted.
+            let mut tx = self
+                .pool()
+                .begin()
+                .await
+                .map_err(|e| AtomicAppendError::Store(PostgresError::Sqlx(e)))?;
+
+            // Check every write set-wise before writing any: one read of all
+            // heads, then each write against its target's *running* head, so
+            // a second write to one stream conflicts instead of concatenating.
+            let mut heads = self
+                .stream_heads(&mut tx, writes)
+                .await
+                .map_err(AtomicAppendError::Store)?;
+            let mut planned = Vec::with_capacity(writes.len());
+            for (index, w) in writes.iter().enumerate() {
+                let head = heads.entry(w.target.as_bytes().to_vec()).or_default();
+                let rows = prepare_inserts(*head, w.expected_version, &w.events, &w.target)
+                    .map_err(|e| atomic_err(index, Version::new(*head), e))?;
+                if let Some(last) = rows.last() {
+                    *head = last.env.version().as_u64();
+                }
+                planned.push(rows);
+            }
+
+            if planned.iter().map(Vec::len).sum::<usize>() >= COPY_THRESHOLD {
+                // One COPY for the whole chunk. `owners[ord]` is the write a
+                // staged row belongs to, to name the one a racer beat.
+                let owners: Vec<usize> = planned
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(index, rows)| std::iter::repeat_n(index, rows.len()))
+                    .collect();
+                let staged = writes
+                    .iter()
+                    .zip(&planned)
+                    .flat_map(|(w, rows)| rows.iter().map(move |row| (w, row)))
+                    .zip(0_i64..)
+                    .map(|((w, row), ord)| row.staged(ord, &w.target))
+                    .collect::<Vec<_>>();
+                let skipped = self
+                    .copy_rows(&mut tx, &staged)
+                    .await
+                    .map_err(AtomicAppendError::Store)?;
+                if let Some(ord) = skipped {
+                    let (index, w) = owners
+                        .get(ord)
+                        .and_then(|&index| writes.get(index).map(|w| (index, w)))
+                        .ok_or_else(|| {
+                            AtomicAppendError::Store(corrupt(
+                                ErrorId::default(),
+                                "merge skipped a row outside the batch",
+                            ))
+                        })?;
+                    let actual = self
+                        .committed_version(&w.target)
+                        .await
+                        .map_err(|e| atomic_err(index, None, e))?;
+                    return Err(AtomicAppendError::Conflict {
+                        index,
+                        actual: Version::new(actual),
+                    });
+                }
+            } else {
+                for (index, (w, rows)) in writes.iter().zip(&planned).enumerate() {
+                    self.insert_rows(&mut tx, &w.target, rows)
+                        .await
+                        .map_err(|e| atomic_err(index, None, e))?;
+                }
+            }
+            tx.commit()
+                .await
+                .map_err(|e| AtomicAppendError::Store(PostgresError::Sqlx(e)))?;
+
+            // Wake AFTER the durable commit: one NOTIFY per touched stream.
+            for w in writes {
+                if !w.events.is_empty() {
+                    self.notify_committed(w.target.as_bytes()).await;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// `StreamLister` — keyset-paginated stream ids for export
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "export")]
+mod stream_lister_impl {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+    use nexus_store::StreamKey;
+    use nexus_store::export::StreamLister;
+    use sqlx::PgPool;
+
+    use super::{PostgresError, PostgresStore, Statements};
+
+    /// Ids fetched per round trip.
+    const LIST_PAGE_SIZE: i64 = 512;
+
+    /// Lazily paged stream ids, in ascending byte order.
+    pub type StreamIdPages = BoxStream<'static, Result<StreamKey, PostgresError>>;
+
+    /// The page of distinct ids strictly after `after` (from the start when
+    /// `None`). Served by the `(stream_id, version)` index.
+    async fn next_page(
+        pool: &PgPool,
+        sql: &Statements,
+        after: Option<&[u8]>,
+    ) -> Result<Vec<Vec<u8>>, PostgresError> {
+        sqlx::query_scalar(&sql.stream_ids_page)
+            .bind(after)
+            .bind(LIST_PAGE_SIZE)
+            .fetch_all(pool)
+            .await
+            .map_err(PostgresError::Sqlx)
+    }
+
+    /// Each page is its own query keyed on the last id seen, so memory is
+    /// bounded by one page and no connection is held between polls. The
+    /// listing is not one point-in-time view: a stream created mid-listing
+    /// appears iff its id sorts after the cursor. Every id appears at most
+    /// once — the keyset only moves forward.
+    impl StreamLister for PostgresStore {
+        type StreamList = StreamIdPages;
+
+        async fn list_streams(&self) -> Result<Self::StreamList, PostgresError> {
+            let shared = self.pool().clone();
+            let statements = Arc::clone(&self.inner.sql);
+            let pages = stream::try_unfold(None::<Vec<u8>>, move |after| {
+                let pool = shared.clone();
+                let sql = Arc::clone(&statements);
+                async move {
+                    let page = next_page(&pool, &sql, after.as_deref()).await?;
+                    // An empty page ends the listing.
+                    Ok(page.last().cloned().map(|last| (page, Some(last))))
+                }
+            });
+            Ok(pages
+                .map_ok(|page| {
+                    stream::iter(
+                        page.into_iter()
+                            .map(|id| Ok(StreamKey::from_bytes(Bytes::from(id)))),
+                    )
+                })
+                .try_flatten()
+                .boxed())
+        }
     }
 }
 
@@ -1,28 +1,394 @@
-//! `WakeSource` for [`PostgresStore`] via `LISTEN/NOTIFY` → [`StreamNotifiers`].
+//! `WakeSource` for [`PostgresStore`] via `LISTEN/NOTIFY`, polling, or both →
+//! [`StreamNotifiers`].
 //!
 //! The generic subscription loop in `nexus_store::subscription` parks on a
 //! [`WakeRegistration`] until new events may exist. Postgres drives that loop by
 //! reusing the audited in-process [`StreamNotifiers`] registry as the wake
-//! machinery: a single background `PgListener` task (spawned in
-//! [`crate::store`]) turns each `LISTEN/NOTIFY` message into a
-//! `notifiers.wake(id)`, and the trait methods here delegate straight to that
-//! registry. So `LISTEN/NOTIFY` is a thin transport whose only job is to *hint*
-//! "scan sooner"; correctness rests on the registry's arm-before-confirm-rescan
-//! discipline, not on `NOTIFY` delivery (see the listener task's correctness
-//! note). `Registration` and `Error` are therefore the registry's own
-//! [`WakeReg`] / [`NotifyError`] — no postgres-specific wake types are needed.
-
-use nexus_store::notify::{NotifyError, WakeReg};
+//! machinery: background tasks spawned here turn evidence of new events into
+//! `notifiers.wake(id)`, and the trait methods delegate straight to that
+//! registry. The evidence comes from the store's [`WakeStrategy`]:
+//!
+//! - [`Listen`](WakeStrategy::Listen) — a `PgListener` task turns each
+//!   `LISTEN/NOTIFY` message into a wake.
+//! - [`Poll`](WakeStrategy::Poll) — a poller task reads the rows settled since
+//!   its last poll and wakes their streams, for `PgBouncer` in transaction
+//!   pooling mode and managed databases that refuse `LISTEN`.
+//! - [`Hybrid`](WakeStrategy::Hybrid) — both: the listener carries wakes while
+//!   it is up, and the poller takes over at its adaptive rate while it is down.
+//!
+//! Either way the transport only *hints* "scan sooner"; correctness rests on
+//! the registry's arm-before-confirm-rescan discipline, not on delivery (see
+//! the listener task's correctness note). `Registration` and `Error` are
+//! therefore the registry's own [`WakeReg`] / [`NotifyError`] — no
+//! postgres-specific wake types are needed.
+//!
+//! [`WakeRegistration`]: nexus_store::wake::WakeRegistration
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use nexus_store::notify::{NotifyError, StreamNotifiers, WakeReg};
 use nexus_store::wake::WakeSource;
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use tokio::task::JoinHandle;
 
+use crate::error::PostgresError;
+use crate::hex;
+use crate::schema::Statements;
 use crate::store::PostgresStore;
 
-/// Delegates wake-routing to the store's [`StreamNotifiers`], which the shared
-/// `PgListener` task drives from `LISTEN/NOTIFY`. Mirrors the trait mechanics of
-/// `nexus-fjall`'s `impl WakeSource` (delegate to an `Arc<StreamNotifiers>`),
-/// the difference being the wake *source*: fjall's `append` calls `wake`
-/// in-process, whereas postgres routes it through `NOTIFY` so a writer in
-/// *another* process (or connection) still rouses this process's subscribers.
+/// Pause between listener reconnect attempts.
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Rows read per poll; a full page polls again at once.
+const POLL_PAGE: i64 = 512;
+
+// ---------------------------------------------------------------------------
+// Configuration and health
+// ---------------------------------------------------------------------------
+
+/// How a [`PostgresStore`] learns that events were committed, set with
+/// [`PostgresStoreBuilder::wake_strategy`](crate::PostgresStoreBuilder::wake_strategy).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WakeStrategy {
+    /// `LISTEN/NOTIFY` on a dedicated connection: commit-latency wakes, and
+    /// no load while idle. Needs session-level connections, so not behind
+    /// `PgBouncer` in transaction pooling mode.
+    #[default]
+    Listen,
+    /// Poll for newly settled rows, backing off while idle. Works through
+    /// any pooler; wake latency is up to the current poll interval.
+    Poll(PollInterval),
+    /// Listen, and poll as well — at the interval's maximum while the
+    /// listener is up, as a safety net for `NOTIFY`s lost on reconnect, and
+    /// adaptively while it is [`Down`](ListenerHealth::Down).
+    Hybrid(PollInterval),
+}
+
+/// The bounds of an adaptive poll interval: it starts at `min`, doubles
+/// after each poll that finds nothing up to `max`, and drops back to `min`
+/// as soon as one finds rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollInterval {
+    min: Duration,
+    max: Duration,
+}
+
+impl PollInterval {
+    /// 50 ms when busy, backing off to 2 s when idle.
+    pub const DEFAULT: Self = Self {
+        min: Duration::from_millis(50),
+        max: Duration::from_secs(2),
+    };
+
+    /// Bounds from `min` to `max`. Returns `None` if `min` is zero or
+    /// exceeds `max`.
+    #[must_use]
+    pub const fn new(min: Duration, max: Duration) -> Option<Self> {
+        if min.is_zero() || min.as_nanos() > max.as_nanos() {
+            return None;
+        }
+        Some(Self { min, max })
+    }
+
+    /// The interval while events keep arriving.
+    #[must_use]
+    pub const fn min(&self) -> Duration {
+        self.min
+    }
+
+    /// The interval once the store has been idle for a while.
+    #[must_use]
+    pub const fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// The interval after a poll that `found` rows or not.
+    fn next(self, current: Duration, found: bool) -> Duration {
+        if found {
+            self.min
+        } else {
+            current.saturating_mul(2).min(self.max)
+        }
+    }
+}
+
+impl Default for PollInterval {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The state of a store's `LISTEN/NOTIFY` connection, from
+/// [`PostgresStore::listener_health`].
+///
+/// Anything but `Listening` under
+/// [`WakeStrategy::Listen`] means wakes from other processes are delayed
+/// until the connection is back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListenerHealth {
+    /// The strategy runs no listener ([`WakeStrategy::Poll`]).
+    Disabled,
+    /// The first connection is being set up.
+    Connecting,
+    /// Subscribed: `NOTIFY`s arrive as their transactions commit.
+    Listening,
+    /// The connection was lost or refused; it is retried every second.
+    Down,
+}
+
+/// A [`ListenerHealth`] shared between the listener task and the store.
+#[derive(Debug)]
+pub struct HealthCell(AtomicU8);
+
+impl HealthCell {
+    pub const fn new(health: ListenerHealth) -> Self {
+        Self(AtomicU8::new(Self::encode(health)))
+    }
+
+    pub fn get(&self) -> ListenerHealth {
+        match self.0.load(Ordering::Relaxed) {
+            0 => ListenerHealth::Disabled,
+            1 => ListenerHealth::Connecting,
+            2 => ListenerHealth::Listening,
+            _ => ListenerHealth::Down,
+        }
+    }
+
+    fn set(&self, health: ListenerHealth) {
+        self.0.store(Self::encode(health), Ordering::Relaxed);
+    }
+
+    const fn encode(health: ListenerHealth) -> u8 {
+        match health {
+            ListenerHealth::Disabled => 0,
+            ListenerHealth::Connecting => 1,
+            ListenerHealth::Listening => 2,
+            ListenerHealth::Down => 3,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Background tasks
+// ---------------------------------------------------------------------------
+
+/// Spawn the tasks `strategy` calls for: a listener, a poller, or both. The
+/// store aborts them when its last clone drops.
+///
+/// The poller's starting point is read here, before the store is handed out,
+/// so a row committed after a subscriber's first scan is never behind it.
+///
+/// # Errors
+///
+/// Returns [`PostgresError::Sqlx`] if the poller's starting point cannot be
+/// read.
+pub async fn spawn_wake_tasks(
+    pool: &PgPool,
+    sql: &Arc<Statements>,
+    notifiers: &Arc<StreamNotifiers>,
+    strategy: WakeStrategy,
+    health: &Arc<HealthCell>,
+) -> Result<Vec<JoinHandle<()>>, PostgresError> {
+    let listener = || {
+        spawn_listener(
+            pool.clone(),
+            Arc::clone(sql),
+            Arc::clone(notifiers),
+            Arc::clone(health),
+        )
+    };
+    let (bounds, listener_health) = match strategy {
+        WakeStrategy::Listen => return Ok(vec![listener()]),
+        WakeStrategy::Poll(bounds) => (bounds, None),
+        WakeStrategy::Hybrid(bounds) => (bounds, Some(Arc::clone(health))),
+    };
+    let cursor = sqlx::query_as::<_, (String, i64)>(&sql.poll_head)
+        .fetch_optional(pool)
+        .await
+        .map_err(PostgresError::Sqlx)?;
+    let poller = spawn_poller(
+        pool.clone(),
+        Arc::clone(sql),
+        Arc::clone(notifiers),
+        Poller {
+            bounds,
+            listener: listener_health,
+            cursor,
+        },
+    );
+    Ok(if matches!(strategy, WakeStrategy::Hybrid(_)) {
+        vec![listener(), poller]
+    } else {
+        vec![poller]
+    })
+}
+
+/// Spawn the background task that owns a [`PgListener`] and forwards each
+/// `NOTIFY` into the store's [`StreamNotifiers`], reporting its connection
+/// state through `health`.
+///
+/// # Correctness — this redundant catch-up scan must NOT be optimized away
+///
+/// A `NOTIFY` sent while the listener's connection is down is silently
+/// dropped: postgres only queues it for sessions listening at commit time.
+/// This is tolerable ONLY because [`StreamNotifiers`] drives nexus-store's
+/// arm-before-confirm-rescan discipline: every `wake()` (and every reopen of
+/// the generic live loop) re-scans the store via `read_stream`/`read_all`, so
+/// a dropped `NOTIFY` merely **delays** a wake until the next scan — it never
+/// loses the event. The `NOTIFY` is a *hint to scan sooner*, never the source
+/// of truth. A future refactor MUST NOT remove that redundant catch-up scan:
+/// without it, a dropped `NOTIFY` would become a lost event.
+fn spawn_listener(
+    pool: PgPool,
+    sql: Arc<Statements>,
+    notifiers: Arc<StreamNotifiers>,
+    health: Arc<HealthCell>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            if let Ok(mut listener) = subscribe(&pool, &sql.channel).await {
+                health.set(ListenerHealth::Listening);
+                // With eager reconnect off, a lost connection ends this loop
+                // (`Ok(None)`) instead of being silently replaced, so every
+                // gap is reported and every reconnect goes through `subscribe`.
+                while let Ok(Some(notification)) = listener.try_recv().await {
+                    // The payload is the hex-encoded raw stream id. A malformed
+                    // payload is dropped (see `hex::decode`): a mis-routed wake
+                    // could rouse the wrong stream, whereas a dropped one is
+                    // caught by the next scan.
+                    if let Some(stream) = hex::decode(notification.payload()) {
+                        // `wake` bumps BOTH the per-stream and `$all` paths, so
+                        // one NOTIFY rouses per-stream and `$all` armed
+                        // registrations alike.
+                        notifiers.wake(&stream);
+                    }
+                }
+            }
+            // A closed pool means the store is being torn down.
+            if pool.is_closed() {
+                return;
+            }
+            health.set(ListenerHealth::Down);
+            tokio::time::sleep(RECONNECT_BACKOFF).await;
+        }
+    })
+}
+
+/// A dedicated listener connection subscribed to `channel`.
+async fn subscribe(pool: &PgPool, channel: &str) -> Result<PgListener, sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.eager_reconnect(false);
+    listener.listen(channel).await?;
+    Ok(listener)
+}
+
+/// One polled row: its `$all` position and stream.
+#[derive(sqlx::FromRow)]
+struct PolledRow {
+    txid: String,
+    global_seq: i64,
+    stream_id: Vec<u8>,
+}
+
+/// Exclusive `(txid, global_seq)` resume cursor, `txid` as text like the
+/// `$all` read's.
+type Cursor = Option<(String, i64)>;
+
+/// The poller's settings and starting point.
+struct Poller {
+    bounds: PollInterval,
+    /// The listener's health under `Hybrid`, `None` under `Poll`.
+    listener: Option<Arc<HealthCell>>,
+    /// Where polling resumes: the newest row settled when the store opened,
+    /// as subscribers scan what was already there themselves.
+    cursor: Cursor,
+}
+
+/// Spawn the poller: it keysets through the rows settled since the last poll
+/// — below `pg_snapshot_xmin`, exactly like `read_all`, so a transaction that
+/// commits late with a smaller position is still seen — and wakes their
+/// streams. Under `Hybrid`, polling stays at `bounds.max` while the listener
+/// is `Listening`.
+fn spawn_poller(
+    pool: PgPool,
+    sql: Arc<Statements>,
+    notifiers: Arc<StreamNotifiers>,
+    poller: Poller,
+) -> JoinHandle<()> {
+    let Poller {
+        bounds,
+        listener,
+        mut cursor,
+    } = poller;
+    tokio::spawn(async move {
+        let mut interval = bounds.min;
+        loop {
+            match poll_once(&pool, &sql, &notifiers, &mut cursor).await {
+                // A full page: more rows are waiting, fetch them now.
+                Ok(rows) if rows == POLL_PAGE => {
+                    interval = bounds.min;
+                    continue;
+                }
+                Ok(rows) => interval = bounds.next(interval, rows > 0),
+                Err(_) if pool.is_closed() => return,
+                Err(_) => interval = bounds.max,
+            }
+            let listening = listener
+                .as_ref()
+                .is_some_and(|health| health.get() == ListenerHealth::Listening);
+            tokio::time::sleep(if listening { bounds.max } else { interval }).await;
+        }
+    })
+}
+
+/// Read one page after `cursor`, wake its streams, and advance the cursor.
+/// Returns the number of rows read.
+async fn poll_once(
+    pool: &PgPool,
+    sql: &Statements,
+    notifiers: &StreamNotifiers,
+    cursor: &mut Cursor,
+) -> Result<i64, sqlx::Error> {
+    let (after_txid, after_seq) = cursor.as_ref().map_or((None, None), |(txid, seq)| {
+        (Some(txid.as_str()), Some(*seq))
+    });
+    let rows: Vec<PolledRow> = sqlx::query_as(&sql.poll_page)
+        .bind(after_txid)
+        .bind(after_seq)
+        .bind(POLL_PAGE)
+        .fetch_all(pool)
+        .await?;
+
+    // A batch append is a run of one stream: wake it once.
+    let mut last_woken: Option<&[u8]> = None;
+    for row in &rows {
+        if last_woken != Some(row.stream_id.as_slice()) {
+            notifiers.wake(&row.stream_id);
+            last_woken = Some(&row.stream_id);
+        }
+    }
+    let count = rows.len();
+    if let Some(last) = rows.into_iter().last() {
+        *cursor = Some((last.txid, last.global_seq));
+    }
+    Ok(i64::try_from(count).unwrap_or(POLL_PAGE))
+}
+
+// ---------------------------------------------------------------------------
+// `WakeSource` impl
+// ---------------------------------------------------------------------------
+
+/// Delegates wake-routing to the store's [`StreamNotifiers`], which the
+/// store's wake tasks drive. Mirrors the trait mechanics of `nexus-fjall`'s
+/// `impl WakeSource` (delegate to an `Arc<StreamNotifiers>`), the difference
+/// being the wake *source*: fjall's `append` calls `wake` in-process, whereas
+/// postgres routes it through `NOTIFY` or the poller so a writer in *another*
+/// process (or connection) still rouses this process's subscribers.
 impl WakeSource for PostgresStore {
     type Registration = WakeReg;
     type Error = NotifyError;
@@ -37,3 +403,51 @@ impl WakeSource for PostgresStore {
         self.wake_registry().wake(stream);
     }
 }
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for the poll interval and health encoding
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    const fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn poll_interval_rejects_zero_and_inverted_bounds() {
+        assert!(PollInterval::new(ms(0), ms(10)).is_none());
+        assert!(PollInterval::new(ms(20), ms(10)).is_none());
+        let fixed = PollInterval::new(ms(10), ms(10)).unwrap();
+        assert_eq!((fixed.min(), fixed.max()), (ms(10), ms(10)));
+    }
+
+    #[test]
+    fn idle_polls_back_off_to_the_max_and_rows_reset_to_the_min() {
+        let bounds = PollInterval::new(ms(10), ms(50)).unwrap();
+        let mut interval = bounds.min();
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            interval = bounds.next(interval, false);
+            seen.push(interval);
+        }
+        assert_eq!(seen, [ms(20), ms(40), ms(50), ms(50)]);
+        assert_eq!(bounds.next(interval, true), ms(10));
+    }
+
+    #[test]
+    fn health_round_trips_through_the_cell() {
+        let cell = HealthCell::new(ListenerHealth::Connecting);
+        for health in [
+            ListenerHealth::Disabled,
+            ListenerHealth::Connecting,
+            ListenerHealth::Listening,
+            ListenerHealth::Down,
+        ] {
+            cell.set(health);
+            assert_eq!(cell.get(), health);
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Bulk writes through binary `COPY`: imports, and appends on a store that
+//! opts in, at or above the `COPY` threshold land in batch order with a
+//! contiguous `$all` sequence, keep `WholeChunk` all-or-nothing and
+//! `PerStream` per-section reports, and fail a conflicting write — by index,
+//! with the racer's actual head — with nothing landed.
+//!
+//! Each test works in its own schema, dropped up front. Source events for
+//! imports are written to and exported from a second schema.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`pool`] first. If `DATABASE_URL` is unset, `pool`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![cfg(all(feature = "export", feature = "import"))]
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use futures::StreamExt;
+use nexus::Version;
+use nexus_postgres::PostgresStore;
+use nexus_store::envelope::{PersistedEnvelope, pending_envelope};
+use nexus_store::error::AppendError;
+use nexus_store::export::EventExporter;
+use nexus_store::import::{
+    AtomicAppend, AtomicAppendError, Atomicity, EventImporter, ImportBlock, ImportError,
+    PlannedAppend, StreamOutcome, StreamSection,
+};
+use nexus_store::store::{RawEventStore, TransactionalAppend};
+use nexus_store::{PendingEnvelope, StreamKey};
+use sqlx::PgPool;
+
+/// Comfortably above the `COPY` threshold.
+const BULK: u64 = 200;
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect and drop `schemas` so the test starts from nothing.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn pool(schemas: &[&str]) -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    for schema in schemas {
+        sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+            .execute(&pool)
+            .await
+            .expect("drop schema");
+    }
+    Some(pool)
+}
+
+async fn store(pool: &PgPool, schema: &str) -> PostgresStore {
+    PostgresStore::builder()
+        .schema(schema)
+        .notify_channel(format!("{schema}_events"))
+        .open(pool.clone())
+        .await
+        .expect("open store")
+}
+
+/// A store whose appends go through `COPY` too.
+async fn copying_store(pool: &PgPool, schema: &str) -> PostgresStore {
+    PostgresStore::builder()
+        .schema(schema)
+        .notify_channel(format!("{schema}_events"))
+        .copy_appends(true)
+        .open(pool.clone())
+        .await
+        .expect("open store")
+}
+
+/// Whether `conn`'s transaction has created the `COPY` staging table.
+async fn staged(conn: &mut sqlx::PgConnection) -> bool {
+    sqlx::query_scalar::<_, bool>("SELECT to_regclass('pg_temp.nexus_staged_events') IS NOT NULL")
+        .fetch_one(conn)
+        .await
+        .unwrap()
+}
+
+/// Append `envelopes(1, to)` to `stream` in a transaction left open, so a
+/// racing write blocks on its rows until [`commit`](sqlx::Transaction::commit).
+async fn uncommitted(
+    store: &PostgresStore,
+    pool: &PgPool,
+    stream: &str,
+    to: u64,
+) -> sqlx::Transaction<'static, sqlx::Postgres> {
+    let mut tx = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut tx, &sk(stream), None, &envelopes(1, to))
+        .await
+        .unwrap();
+    tx
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+/// Events `from..=to`, every other one carrying metadata.
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            let builder = pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload");
+            if i % 2 == 0 {
+                builder
+                    .with_metadata(format!("meta-{i}").into_bytes())
+                    .expect("valid metadata")
+            } else {
+                builder.build()
+            }
+        })
+        .collect()
+}
+
+fn planned(target: &str, expected: u64, from: u64, to: u64) -> PlannedAppend {
+    PlannedAppend {
+        target: sk(target),
+        expected_version: Version::new(expected),
+        events: envelopes(from, to),
+    }
+}
+
+async fn collect_stream(store: &PostgresStore, id: &StreamKey) -> Vec<PersistedEnvelope> {
+    store
+        .export_stream(id, Version::INITIAL)
+        .await
+        .expect("export opens")
+        .map(|r| r.expect("no read error"))
+        .collect()
+        .await
+}
+
+/// `$all` sequence numbers, in read order.
+async fn all_seqs(store: &PostgresStore) -> Vec<u64> {
+    store
+        .read_all(None)
+        .await
+        .expect("read_all")
+        .map(|r| r.expect("no error").0.seq())
+        .collect()
+        .await
+}
+
+/// Assert `stream` holds exactly `envelopes(1, to)`, in order.
+async fn assert_stream(store: &PostgresStore, stream: &str, to: u64) {
+    let events = collect_stream(store, &sk(stream)).await;
+    let expected = envelopes(1, to);
+    assert_eq!(events.len(), expected.len(), "{stream} length");
+    for (got, want) in events.iter().zip(&expected) {
+        assert_eq!(got.version(), want.version());
+        assert_eq!(got.event_type(), want.event_type());
+        assert_eq!(got.payload(), want.payload());
+        assert_eq!(got.metadata(), want.metadata());
+    }
+}
+
+/// An import section holding `stream`'s events as exported from `source`.
+async fn section(source: &PostgresStore, stream: &str) -> StreamSection {
+    StreamSection {
+        origin: stream.as_bytes().to_vec().into(),
+        blocks: collect_stream(source, &sk(stream))
+            .await
+            .into_iter()
+            .map(ImportBlock::Event)
+            .collect(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 1. Appends
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn a_bulk_append_lands_in_order_with_a_contiguous_sequence() {
+    let Some(pool) = pool(&["copy_append"]).await else {
+        return;
+    };
+    let store = copying_store(&pool, "copy_append").await;
+    store
+        .append(&sk("small"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+    store
+        .append(&sk("bulk"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    store
+        .append(
+            &sk("bulk"),
+            Version::new(BULK),
+            &envelopes(BULK + 1, BULK * 2),
+        )
+        .await
+        .unwrap();
+
+    assert_stream(&store, "bulk", BULK * 2).await;
+    let expected: Vec<u64> = (1..=BULK * 2 + 3).collect();
+    assert_eq!(all_seqs(&store).await, expected);
+}
+
+#[tokio::test]
+async fn a_stale_bulk_append_conflicts_and_lands_nothing() {
+    let Some(pool) = pool(&["copy_append_conflict"]).await else {
+        return;
+    };
+    let store = copying_store(&pool, "copy_append_conflict").await;
+    store
+        .append(&sk("bulk"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+
+    let err = store
+        .append(&sk("bulk"), None, &envelopes(1, BULK))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppendError::Conflict { .. }), "got {err:?}");
+    assert_eq!(collect_stream(&store, &sk("bulk")).await.len(), 1);
+}
+
+#[tokio::test]
+async fn bulk_appends_share_one_transaction() {
+    let Some(pool) = pool(&["copy_tx"]).await else {
+        return;
+    };
+    let store = copying_store(&pool, "copy_tx").await;
+
+    // The second batch reuses the staging table the first one created.
+    let mut committed = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut committed, &sk("a"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    assert!(staged(&mut committed).await);
+    store
+        .append_in_tx(&mut committed, &sk("b"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    committed.commit().await.unwrap();
+    assert_stream(&store, "a", BULK).await;
+    assert_stream(&store, "b", BULK).await;
+
+    // Rolled back, a bulk batch leaves nothing behind.
+    let mut rolled_back = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut rolled_back, &sk("c"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    rolled_back.rollback().await.unwrap();
+    assert!(collect_stream(&store, &sk("c")).await.is_empty());
+}
+
+#[tokio::test]
+async fn appends_skip_copy_unless_the_store_opts_in() {
+    let Some(pool) = pool(&["copy_default"]).await else {
+        return;
+    };
+    let store = store(&pool, "copy_default").await;
+
+    let mut tx = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut tx, &sk("bulk"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    assert!(!staged(&mut tx).await);
+    tx.commit().await.unwrap();
+    assert_stream(&store, "bulk", BULK).await;
+}
+
+#[tokio::test]
+async fn a_bulk_append_beaten_by_a_racer_reports_its_head() {
+    let Some(pool) = pool(&["copy_append_race"]).await else {
+        return;
+    };
+    let store = copying_store(&pool, "copy_append_race").await;
+
+    // The bulk append reads head 0, then its merge waits on the racer's rows.
+    let racer = uncommitted(&store, &pool, "bulk", 5).await;
+    let writer = store.clone();
+    let bulk =
+        tokio::spawn(async move { writer.append(&sk("bulk"), None, &envelopes(1, BULK)).await });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    racer.commit().await.unwrap();
+
+    let err = bulk.await.unwrap().unwrap_err();
+    assert!(
+        matches!(err, AppendError::Conflict { actual, .. } if actual == Version::new(5)),
+        "got {err:?}"
+    );
+    assert_stream(&store, "bulk", 5).await;
+}
+
+// ---------------------------------------------------------------------------
+// 2. Atomic batches and imports
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn a_bulk_atomic_batch_commits_every_run_in_batch_order() {
+    let Some(pool) = pool(&["copy_batch"]).await else {
+        return;
+    };
+    let store = store(&pool, "copy_batch").await;
+    store
+        .append(&sk("b"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+
+    store
+        .atomic_append_many(&[
+            planned("a", 0, 1, BULK),
+            planned("b", 1, 2, 10),
+            planned("a", BULK, BULK + 1, BULK + 5),
+        ])
+        .await
+        .unwrap();
+
+    assert_stream(&store, "a", BULK + 5).await;
+    assert_stream(&store, "b", 10).await;
+    let expected: Vec<u64> = (1..=BULK + 15).collect();
+    assert_eq!(all_seqs(&store).await, expected);
+}
+
+#[tokio::test]
+async fn a_late_conflict_in_a_bulk_batch_names_its_write_and_lands_nothing() {
+    let Some(pool) = pool(&["copy_batch_conflict"]).await else {
+        return;
+    };
+    let store = store(&pool, "copy_batch_conflict").await;
+    store
+        .append(&sk("c"), None, &envelopes(1, 2))
+        .await
+        .unwrap();
+
+    // Write 2 expects "c" at v1, but it is at v2.
+    let err = store
+        .atomic_append_many(&[
+            planned("a", 0, 1, BULK),
+            planned("b", 0, 1, BULK),
+            planned("c", 1, 2, 3),
+        ])
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, AtomicAppendError::Conflict { index: 2, actual } if actual == Version::new(2)),
+        "got {err:?}"
+    );
+    assert!(collect_stream(&store, &sk("a")).await.is_empty());
+    assert!(collect_stream(&store, &sk("b")).await.is_empty());
+    assert_eq!(all_seqs(&store).await.len(), 2);
+}
+
+#[tokio::test]
+async fn a_bulk_batch_beaten_by_a_racer_names_its_write_and_head() {
+    let Some(pool) = pool(&["copy_batch_race"]).await else {
+        return;
+    };
+    let store = store(&pool, "copy_batch_race").await;
+
+    let racer = uncommitted(&store, &pool, "b", 3).await;
+    let writer = store.clone();
+    let batch = tokio::spawn(async move {
+        writer
+            .atomic_append_many(&[planned("a", 0, 1, BULK), planned("b", 0, 1, BULK)])
+            .await
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    racer.commit().await.unwrap();
+
+    let err = batch.await.unwrap().unwrap_err();
+    assert!(
+        matches!(err, AtomicAppendError::Conflict { index: 1, actual } if actual == Version::new(3)),
+        "got {err:?}"
+    );
+    assert!(collect_stream(&store, &sk("a")).await.is_empty());
+    assert_stream(&store, "b", 3).await;
+}
+
+#[tokio::test]
+async fn a_whole_chunk_bulk_import_is_all_or_nothing() {
+    let Some(pool) = pool(&["copy_import_src", "copy_import_dst"]).await else {
+        return;
+    };
+    let source = store(&pool, "copy_import_src").await;
+    let target = store(&pool, "copy_import_dst").await;
+    source
+        .append(&sk("a"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    source
+        .append(&sk("b"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    let sections = [section(&source, "a").await, section(&source, "b").await];
+
+    // "b" already exists in the target: the whole chunk is refused.
+    target
+        .append(&sk("b"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    let err = target
+        .import(&sections, StreamKey::from_slice, Atomicity::WholeChunk)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ImportError::Aborted { ref stream, .. } if *stream == sk("b")),
+        "got {err:?}"
+    );
+    assert!(collect_stream(&target, &sk("a")).await.is_empty());
+
+    sqlx::raw_sql("TRUNCATE copy_import_dst.events RESTART IDENTITY")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let report = target
+        .import(&sections, StreamKey::from_slice, Atomicity::WholeChunk)
+        .await
+        .unwrap();
+    assert!(report.all_complete());
+    assert_stream(&target, "a", BULK).await;
+    assert_stream(&target, "b", BULK).await;
+}
+
+#[tokio::test]
+async fn a_per_stream_bulk_import_reports_each_section() {
+    let Some(pool) = pool(&["copy_per_src", "copy_per_dst"]).await else {
+        return;
+    };
+    let source = store(&pool, "copy_per_src").await;
+    let target = store(&pool, "copy_per_dst").await;
+    source
+        .append(&sk("a"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    source
+        .append(&sk("b"), None, &envelopes(1, BULK))
+        .await
+        .unwrap();
+    let mut gapped = section(&source, "b").await;
+    gapped.blocks.remove(100);
+    let sections = [section(&source, "a").await, gapped];
+
+    let report = target
+        .import(&sections, StreamKey::from_slice, Atomicity::PerStream)
+        .await
+        .unwrap();
+    let outcomes: Vec<_> = report.streams().iter().map(|r| r.outcome).collect();
+    assert_eq!(
+        outcomes,
+        [
+            StreamOutcome::Complete {
+                version: Version::new(BULK).unwrap()
+            },
+            StreamOutcome::Mismatch {
+                reached: Version::new(100),
+                got: Version::new(102).unwrap(),
+            },
+        ]
+    );
+    assert_stream(&target, "a", BULK).await;
+    assert_stream(&target, "b", 100).await;
+}
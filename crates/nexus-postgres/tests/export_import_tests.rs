@@ -0,0 +1,483 @@
+//! End-to-end export → CBOR-box → file → import coverage on `PostgresStore`.
+//!
+//! Ported from `nexus-fjall`'s `export_import_tests.rs`: real `list_streams` +
+//! `export_stream`, a real CBOR chunk written to and read back from a temp
+//! file, and a real `import` — plus the corruption distinctions a restore must
+//! surface. There is one database, so "a fresh destination store" is the same
+//! store after [`reset`]; source streams are captured before the reset and
+//! compared after the restore.
+//!
+//! Also covers what is postgres-specific: `list_streams` paging past one page,
+//! and `atomic_append_many` rolling back a whole batch on a late conflict.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`setup`] first. If `DATABASE_URL` is unset, `setup`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![cfg(all(feature = "export", feature = "import"))]
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+#![allow(clippy::too_many_arguments, reason = "tests")]
+
+use std::collections::BTreeSet;
+
+use futures::StreamExt;
+use nexus::Version;
+use nexus_postgres::PostgresStore;
+use nexus_store::StreamKey;
+use nexus_store::cbor::{ChunkError, ChunkWriter, decode_chunk};
+use nexus_store::envelope::{PersistedEnvelope, pending_envelope};
+use nexus_store::export::{EventExporter, StreamLister};
+use nexus_store::import::{
+    AbortReason, AtomicAppend, AtomicAppendError, Atomicity, EventImporter, ImportError,
+    PlannedAppend, StreamOutcome,
+};
+use nexus_store::store::RawEventStore;
+use sqlx::PgPool;
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect, ensure the schema, and truncate the events for isolation.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn setup() -> Option<(PostgresStore, PgPool)> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    let store = PostgresStore::from_pool(pool.clone())
+        .await
+        .expect("from_pool");
+    reset(&pool).await;
+    Some((store, pool))
+}
+
+/// Empty the events table, restarting the `$all` sequence.
+async fn reset(pool: &PgPool) {
+    sqlx::query("TRUNCATE events RESTART IDENTITY")
+        .execute(pool)
+        .await
+        .expect("truncate");
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+/// Route a section's origin id bytes straight back to the same target key.
+fn identity_route(origin: &[u8]) -> StreamKey {
+    StreamKey::from_slice(origin)
+}
+
+/// Append one event with the given fields, advancing the stream by one.
+async fn append_one(
+    store: &PostgresStore,
+    id: &StreamKey,
+    version: u64,
+    event_type: &'static str,
+    metadata: Option<&[u8]>,
+    payload: &[u8],
+) {
+    let builder = pending_envelope(Version::new(version).expect("nonzero"))
+        .event_type(event_type)
+        .payload(payload.to_vec())
+        .expect("valid payload");
+    let env = match metadata {
+        Some(m) => builder.with_metadata(m.to_vec()).expect("valid metadata"),
+        None => builder.build(),
+    };
+    let expected = Version::new(version - 1);
+    store.append(id, expected, &[env]).await.expect("append");
+}
+
+/// Drain a stream into owned envelopes (whole stream from v1).
+async fn collect_stream(store: &PostgresStore, id: &StreamKey) -> Vec<PersistedEnvelope> {
+    store
+        .export_stream(id, Version::INITIAL)
+        .await
+        .expect("export opens")
+        .map(|r| r.expect("no read error"))
+        .collect()
+        .await
+}
+
+/// Every stream id `list_streams` yields, in yield order.
+async fn listed(store: &PostgresStore) -> Vec<Vec<u8>> {
+    store
+        .list_streams()
+        .await
+        .expect("list opens")
+        .map(|r| r.expect("no list error").as_bytes().to_vec())
+        .collect()
+        .await
+}
+
+/// Build a CBOR backup chunk by walking the store's own `list_streams` +
+/// `export_stream` — the exact production export path.
+async fn build_chunk(store: &PostgresStore) -> Vec<u8> {
+    let mut w = ChunkWriter::new(Vec::new(), None).expect("writer");
+    for id_bytes in listed(store).await {
+        let id = identity_route(&id_bytes);
+        let events = store
+            .read_stream(&id, Version::INITIAL)
+            .await
+            .expect("read opens");
+        w.section(&id_bytes)
+            .expect("section")
+            .try_extend(events)
+            .await
+            .expect("extend");
+    }
+    w.into_sink()
+}
+
+/// Assert a restored stream matches the captured source **modulo
+/// `global_seq`** (export preserves version/schema/type/payload/metadata;
+/// import re-stamps the `$all` position on re-append).
+fn assert_streams_equal(source: &[PersistedEnvelope], restored: &[PersistedEnvelope]) {
+    assert_eq!(source.len(), restored.len(), "stream length differs");
+    for (x, y) in source.iter().zip(restored) {
+        assert_eq!(x.version(), y.version(), "version differs");
+        assert_eq!(x.schema_version(), y.schema_version(), "schema differs");
+        assert_eq!(x.event_type(), y.event_type(), "event_type differs");
+        assert_eq!(x.payload(), y.payload(), "payload differs");
+        assert_eq!(x.metadata(), y.metadata(), "metadata differs");
+    }
+}
+
+fn planned(target: &str, expected: u64, versions: std::ops::RangeInclusive<u64>) -> PlannedAppend {
+    PlannedAppend {
+        target: sk(target),
+        expected_version: Version::new(expected),
+        events: versions
+            .map(|i| {
+                pending_envelope(Version::new(i).unwrap())
+                    .event_type("E")
+                    .payload(vec![1])
+                    .unwrap()
+                    .build()
+            })
+            .collect(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 1. Lifecycle + sequence: full round-trip through a file
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn round_trip_through_a_file_preserves_streams() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let chunk_file = dir.path().join("backup.nxch");
+
+    // 1. Seed two streams of varied shape.
+    append_one(
+        &store,
+        &sk("task-1"),
+        1,
+        "Created",
+        Some(b"hlc=1"),
+        b"alpha",
+    )
+    .await;
+    append_one(&store, &sk("task-1"), 2, "Updated", None, b"beta").await;
+    append_one(&store, &sk("task-1"), 3, "Closed", Some(b"hlc=3"), b"gamma").await;
+    append_one(&store, &sk("task-2"), 1, "Created", None, b"solo").await;
+    let task_1 = collect_stream(&store, &sk("task-1")).await;
+    let task_2 = collect_stream(&store, &sk("task-2")).await;
+
+    // 2 + 3. Export + CBOR-encode to a real file.
+    std::fs::write(&chunk_file, build_chunk(&store).await).expect("write backup file");
+
+    // 4. Read the file back and decode it.
+    let read_back = std::fs::read(&chunk_file).expect("read backup file");
+    let sections = decode_chunk(&read_back).expect("decode chunk");
+    assert_eq!(sections.len(), 2, "two stream sections round-tripped");
+
+    // 5. Import into the emptied store (whole-chunk atomic restore).
+    reset(&pool).await;
+    let report = store
+        .import(&sections, identity_route, Atomicity::WholeChunk)
+        .await
+        .expect("import ok");
+    assert!(report.all_complete(), "every stream restored");
+
+    // 6. Streams are equal modulo global_seq.
+    assert_streams_equal(&task_1, &collect_stream(&store, &sk("task-1")).await);
+    assert_streams_equal(&task_2, &collect_stream(&store, &sk("task-2")).await);
+
+    // The whole-chunk restore is one transaction, so its four events share
+    // one txid and take a fresh, contiguous `$all` sequence.
+    let positions: Vec<_> = store
+        .read_all(None)
+        .await
+        .expect("read_all")
+        .map(|r| r.expect("no error").0)
+        .collect()
+        .await;
+    assert_eq!(positions.len(), 4);
+    assert!(positions.iter().all(|p| p.txid() == positions[0].txid()));
+    let mut seqs: Vec<u64> = positions.iter().map(|p| p.seq()).collect();
+    seqs.sort_unstable();
+    assert_eq!(seqs, vec![1, 2, 3, 4], "$all position re-stamped on import");
+}
+
+#[tokio::test]
+async fn round_trip_survives_store_reopen_before_export() {
+    // Lifecycle: write, drop the store, reopen it over a fresh pool, then
+    // export — the backup reflects committed state, not in-process residue.
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    append_one(&store, &sk("s"), 1, "E", None, b"one").await;
+    append_one(&store, &sk("s"), 2, "E", None, b"two").await;
+    drop(store);
+
+    let reopened = PostgresStore::from_pool(pool.clone()).await.unwrap();
+    let source = collect_stream(&reopened, &sk("s")).await;
+    let chunk = build_chunk(&reopened).await;
+
+    reset(&pool).await;
+    let sections = decode_chunk(&chunk).expect("decode");
+    reopened
+        .import(&sections, identity_route, Atomicity::PerStream)
+        .await
+        .expect("import");
+    assert_streams_equal(&source, &collect_stream(&reopened, &sk("s")).await);
+}
+
+// ---------------------------------------------------------------------------
+// 2. Defensive boundary: corruption surfaces correctly
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn block_corruption_imports_as_stream_corrupt_not_malformed() {
+    // A failed per-block checksum is a non-fatal `ImportBlock::Corrupt` →
+    // `StreamOutcome::Corrupt`, NOT a whole-chunk `ChunkError::Malformed`.
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let chunk_file = dir.path().join("backup.nxch");
+    append_one(&store, &sk("s"), 1, "E", None, b"good-one").await;
+    append_one(&store, &sk("s"), 2, "E", None, b"good-two").await;
+    std::fs::write(&chunk_file, build_chunk(&store).await).expect("write");
+
+    // Corrupt the LAST byte on disk — the tail of the last block's body
+    // (payload "good-two"). Framing stays intact, so decode still parses.
+    let mut bytes = std::fs::read(&chunk_file).expect("read");
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    std::fs::write(&chunk_file, &bytes).expect("rewrite");
+
+    let sections = decode_chunk(&std::fs::read(&chunk_file).expect("read"))
+        .expect("framing still parses — only a block body is corrupt");
+    assert_eq!(sections.len(), 1);
+
+    // Import per-stream: v1 applies, the corrupt v2 block halts the stream.
+    reset(&pool).await;
+    let report = store
+        .import(&sections, identity_route, Atomicity::PerStream)
+        .await
+        .expect("per-stream import never aborts the whole op");
+    assert_eq!(report.streams().len(), 1);
+    assert_eq!(
+        report.streams()[0].outcome,
+        StreamOutcome::Corrupt {
+            reached: Some(Version::new(1).unwrap())
+        },
+        "good prefix applied, corrupt block halts the stream",
+    );
+    let restored = collect_stream(&store, &sk("s")).await;
+    assert_eq!(restored.len(), 1);
+    assert_eq!(restored[0].version(), Version::new(1).unwrap());
+    assert_eq!(restored[0].payload(), b"good-one");
+}
+
+#[tokio::test]
+async fn block_corruption_aborts_whole_chunk_restore() {
+    // The same corruption under WHOLE-CHUNK atomicity aborts the entire
+    // restore — the transaction rolls back and nothing lands.
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    append_one(&store, &sk("s"), 1, "E", None, b"good-one").await;
+    append_one(&store, &sk("s"), 2, "E", None, b"good-two").await;
+    let mut chunk = build_chunk(&store).await;
+    let last = chunk.len() - 1;
+    chunk[last] ^= 0xFF; // corrupt the last block's body
+
+    let sections = decode_chunk(&chunk).expect("framing parses");
+    reset(&pool).await;
+    let err = store
+        .import(&sections, identity_route, Atomicity::WholeChunk)
+        .await
+        .expect_err("whole-chunk corruption must abort");
+    match err {
+        ImportError::Aborted { stream, reason } => {
+            assert_eq!(stream, sk("s"));
+            assert_eq!(reason, AbortReason::Corrupt);
+        }
+        other => panic!("expected Aborted/Corrupt, got {other:?}"),
+    }
+    assert!(
+        listed(&store).await.is_empty(),
+        "aborted restore wrote nothing"
+    );
+}
+
+#[tokio::test]
+async fn malformed_chunk_framing_is_a_decode_error_not_a_corrupt_block() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let garbage = b"this is definitely not a valid nxch chunk header";
+    match decode_chunk(garbage) {
+        Err(ChunkError::Malformed(_)) => {}
+        other => panic!("expected Malformed, got {other:?}"),
+    }
+
+    // A real header with a flipped magic byte is also Malformed.
+    append_one(&store, &sk("s"), 1, "E", None, b"x").await;
+    let mut chunk = build_chunk(&store).await;
+    chunk[3] ^= 0xFF; // the magic is the first bytes of the header map
+    match decode_chunk(&chunk) {
+        Err(ChunkError::Malformed(_)) => {}
+        other => panic!("expected Malformed on bad magic, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn non_injective_route_aborts_whole_chunk_without_concatenating() {
+    // Two distinct origin streams routed to ONE target: whole-chunk import
+    // must abort with nothing committed — never a [1,2,1,2] stream.
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    append_one(&store, &sk("o1"), 1, "E", None, b"a").await;
+    append_one(&store, &sk("o1"), 2, "E", None, b"b").await;
+    append_one(&store, &sk("o2"), 1, "E", None, b"c").await;
+    append_one(&store, &sk("o2"), 2, "E", None, b"d").await;
+    let sections = decode_chunk(&build_chunk(&store).await).expect("decode");
+
+    let to_same = |_origin: &[u8]| sk("merged");
+    let err = store
+        .import(&sections, to_same, Atomicity::WholeChunk)
+        .await
+        .expect_err("non-injective route must abort");
+    assert!(matches!(err, ImportError::Aborted { .. }));
+    assert!(collect_stream(&store, &sk("merged")).await.is_empty());
+
+    let origins: BTreeSet<Vec<u8>> = listed(&store).await.into_iter().collect();
+    assert_eq!(origins.len(), 2, "only the two source streams exist");
+}
+
+// ---------------------------------------------------------------------------
+// 3. Postgres-specific: paged listing, transactional batch
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn list_streams_pages_past_one_page_in_order_without_duplicates() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    // 1,200 single-event streams (over two pages), seeded in one statement.
+    sqlx::query(
+        "INSERT INTO events (stream_id, version, event_type, schema_version, payload) \
+         SELECT convert_to(format('s-%s', lpad(i::text, 5, '0')), 'UTF8'), 1, 'E', 1, '\\x00' \
+         FROM generate_series(1, 1200) AS i",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    // A multi-event stream is listed once.
+    append_one(&store, &sk("s-00001"), 2, "E", None, b"x").await;
+
+    let ids = listed(&store).await;
+    assert_eq!(ids.len(), 1200);
+    assert!(ids.windows(2).all(|w| w[0] < w[1]), "strictly ascending");
+    assert_eq!(ids[0], b"s-00001");
+    assert_eq!(ids[1199], b"s-01200");
+}
+
+#[tokio::test]
+async fn list_streams_on_an_empty_store_is_empty() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    assert!(listed(&store).await.is_empty());
+}
+
+#[tokio::test]
+async fn atomic_append_many_commits_every_run() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    append_one(&store, &sk("b"), 1, "E", None, b"x").await;
+
+    store
+        .atomic_append_many(&[planned("a", 0, 1..=3), planned("b", 1, 2..=2)])
+        .await
+        .unwrap();
+
+    assert_eq!(collect_stream(&store, &sk("a")).await.len(), 3);
+    assert_eq!(collect_stream(&store, &sk("b")).await.len(), 2);
+}
+
+#[tokio::test]
+async fn atomic_append_many_late_conflict_rolls_back_earlier_runs() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    append_one(&store, &sk("b"), 1, "E", None, b"x").await;
+
+    // Write 1 expects a fresh "b", but it is at v1.
+    let err = store
+        .atomic_append_many(&[planned("a", 0, 1..=2), planned("b", 0, 1..=1)])
+        .await
+        .unwrap_err();
+    match err {
+        AtomicAppendError::Conflict { index, actual } => {
+            assert_eq!(index, 1);
+            assert_eq!(actual, Version::new(1));
+        }
+        other => panic!("expected Conflict, got {other:?}"),
+    }
+    assert!(
+        collect_stream(&store, &sk("a")).await.is_empty(),
+        "write 0 rolled back with the batch"
+    );
+}
+
+#[tokio::test]
+async fn atomic_append_many_second_write_to_one_target_sees_the_first() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    // Both runs claim "a" from empty: the second sees the first's v1..=2.
+    let err = store
+        .atomic_append_many(&[planned("a", 0, 1..=2), planned("a", 0, 1..=2)])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AtomicAppendError::Conflict { index: 1, actual } if actual == Version::new(2)
+    ));
+    assert!(collect_stream(&store, &sk("a")).await.is_empty());
+
+    // Chained correctly, the same-target runs are one contiguous stream.
+    store
+        .atomic_append_many(&[planned("a", 0, 1..=2), planned("a", 2, 3..=3)])
+        .await
+        .unwrap();
+    assert_eq!(collect_stream(&store, &sk("a")).await.len(), 3);
+}
@@ -0,0 +1,212 @@
+//! Leader leases on advisory locks: one holder per name and namespace, a
+//! waiting replica takes over when the holder lets go, and a holder whose
+//! connection is killed sees the loss — ending its lease-bound subscription —
+//! while the lease passes to the next replica.
+//!
+//! Each test works in its own schema, dropped up front.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`pool`] first. If `DATABASE_URL` is unset, `pool`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use nexus::Version;
+use nexus_postgres::{PostgresLeases, PostgresStore};
+use nexus_store::envelope::pending_envelope;
+use nexus_store::lease::{LeaderLease, LeaseGuard, while_held};
+use nexus_store::store::RawEventStore;
+use nexus_store::{PendingEnvelope, StreamKey, Subscription};
+use sqlx::PgPool;
+
+/// Short enough that a lost lease is noticed well within [`LOSS_WAIT`].
+const HEARTBEAT: Duration = Duration::from_millis(50);
+
+/// How long to wait for a loss or a takeover that should happen.
+const LOSS_WAIT: Duration = Duration::from_secs(5);
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect and drop `schema` so the test starts from nothing.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn pool(schema: &str) -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&pool)
+        .await
+        .expect("drop schema");
+    Some(pool)
+}
+
+async fn store(pool: &PgPool, schema: &str) -> PostgresStore {
+    PostgresStore::builder()
+        .schema(schema)
+        .notify_channel(format!("{schema}_events"))
+        .open(pool.clone())
+        .await
+        .expect("open store")
+}
+
+/// Leases for one replica, with a fast heartbeat and retry.
+fn leases(store: &PostgresStore) -> PostgresLeases {
+    store.leases().heartbeat(HEARTBEAT).retry_every(HEARTBEAT)
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+/// Terminate the backend holding lease `name` of `schema`'s store, as a
+/// network partition or a failover would. Returns how many were killed.
+async fn kill_lease_holder(pool: &PgPool, schema: &str, name: &str) -> i64 {
+    // A bigint advisory key is split across `classid` (high half) and
+    // `objid` (low half).
+    sqlx::query_scalar(
+        "SELECT count(pg_terminate_backend(pid)) FROM pg_locks \
+         WHERE locktype = 'advisory' AND granted AND objsubid = 1 \
+           AND ((classid::bigint << 32) | objid::bigint) = hashtextextended($1, 0)",
+    )
+    .bind(format!("\"{schema}\".\"events\":{name}"))
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// 1. Exclusivity
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn one_holder_per_name_within_a_namespace() {
+    let Some(pool) = pool("lease_exclusive").await else {
+        return;
+    };
+    let first = leases(&store(&pool, "lease_exclusive").await);
+    let second = leases(&store(&pool, "lease_exclusive").await);
+
+    let guard = first.try_acquire("projector").await.unwrap().unwrap();
+    assert!(guard.is_held());
+    assert!(second.try_acquire("projector").await.unwrap().is_none());
+    assert!(second.try_acquire("saga").await.unwrap().is_some());
+
+    drop(guard);
+    let taken = tokio::time::timeout(LOSS_WAIT, second.acquire("projector"))
+        .await
+        .expect("a released lease is free again")
+        .unwrap();
+    assert!(taken.is_held());
+}
+
+#[tokio::test]
+async fn namespaces_do_not_share_leases() {
+    let Some(pool) = pool("lease_ns_a").await else {
+        return;
+    };
+    sqlx::raw_sql("DROP SCHEMA IF EXISTS lease_ns_b CASCADE")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let a = leases(&store(&pool, "lease_ns_a").await);
+    let b = leases(&store(&pool, "lease_ns_b").await);
+
+    let _held = a.try_acquire("projector").await.unwrap().unwrap();
+    assert!(b.try_acquire("projector").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn a_waiting_replica_takes_over_when_the_holder_lets_go() {
+    let Some(pool) = pool("lease_takeover").await else {
+        return;
+    };
+    let leader = leases(&store(&pool, "lease_takeover").await);
+    let standby = leases(&store(&pool, "lease_takeover").await);
+
+    let guard = leader.acquire("projector").await.unwrap();
+    let waiting = tokio::spawn(async move { standby.acquire("projector").await.unwrap() });
+    tokio::time::sleep(HEARTBEAT * 4).await;
+    assert!(!waiting.is_finished(), "the standby waits while it is held");
+
+    drop(guard);
+    let taken = tokio::time::timeout(LOSS_WAIT, waiting)
+        .await
+        .expect("the standby takes over")
+        .unwrap();
+    assert!(taken.is_held());
+}
+
+// ---------------------------------------------------------------------------
+// 2. Loss
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn a_killed_connection_loses_the_lease_and_stops_delivery() {
+    let Some(pool) = pool("lease_lost").await else {
+        return;
+    };
+    let leader_store = store(&pool, "lease_lost").await;
+    let leader = leases(&leader_store);
+    let standby = leases(&store(&pool, "lease_lost").await);
+    let handle = leader_store.into_store();
+
+    let guard = leader.acquire("projector").await.unwrap();
+    let lost = guard.lost();
+    let events = while_held(
+        guard,
+        Subscription::new(&handle).subscribe_all(None).unwrap(),
+    );
+    futures::pin_mut!(events);
+    handle
+        .append(&sk("order-1"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    let first = tokio::time::timeout(LOSS_WAIT, events.next())
+        .await
+        .expect("delivered while held")
+        .unwrap();
+    assert_eq!(first.unwrap().1.version(), Version::INITIAL);
+
+    assert_eq!(
+        kill_lease_holder(&pool, "lease_lost", "projector").await,
+        1,
+        "found the holder"
+    );
+    tokio::time::timeout(LOSS_WAIT, lost)
+        .await
+        .expect("the holder notices the loss");
+    let ended = tokio::time::timeout(LOSS_WAIT, events.next())
+        .await
+        .expect("the lease-bound subscription ends");
+    assert!(ended.is_none(), "got {ended:?}");
+
+    let taken = tokio::time::timeout(LOSS_WAIT, standby.acquire("projector"))
+        .await
+        .expect("the lease passes on")
+        .unwrap();
+    assert!(taken.is_held());
+}
@@ -0,0 +1,330 @@
+//! Versioned schema migrations: a namespace opened by this build ends at
+//! [`SCHEMA_VERSION`] whatever earlier version it started from — including a
+//! database created before the migration runner existed — concurrent opens
+//! apply each migration once, and verify-only mode refuses a schema that is
+//! behind without touching it.
+//!
+//! Each test works in its own schema, dropped up front.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`pool`] first. If `DATABASE_URL` is unset, `pool`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use std::num::NonZeroU32;
+
+use futures::StreamExt;
+use futures::future::join_all;
+use nexus::Version;
+use nexus_postgres::{
+    MigrationMode, PostgresError, PostgresStore, PostgresStoreBuilder, SCHEMA_VERSION,
+};
+use nexus_store::envelope::pending_envelope;
+use nexus_store::store::RawEventStore;
+use nexus_store::{PendingEnvelope, SnapshotStore, StreamKey};
+use sqlx::PgPool;
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect and drop `schema` so the test starts from nothing.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn pool(schema: &str) -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&pool)
+        .await
+        .expect("drop schema");
+    Some(pool)
+}
+
+fn builder(schema: &str) -> PostgresStoreBuilder {
+    PostgresStore::builder()
+        .schema(schema)
+        .notify_channel(format!("{schema}_events"))
+}
+
+/// The recorded migrations, in order.
+async fn recorded(pool: &PgPool, schema: &str) -> Vec<(i64, String)> {
+    sqlx::query_as(&format!(
+        "SELECT version, name FROM {schema}.nexus_schema_migrations ORDER BY version"
+    ))
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+async fn recorded_versions(pool: &PgPool, schema: &str) -> Vec<i64> {
+    recorded(pool, schema)
+        .await
+        .into_iter()
+        .map(|(version, _)| version)
+        .collect()
+}
+
+fn every_version() -> Vec<i64> {
+    (1..=i64::from(SCHEMA_VERSION)).collect()
+}
+
+async fn schema_exists(pool: &PgPool, schema: &str) -> bool {
+    sqlx::query_scalar("SELECT to_regnamespace($1) IS NOT NULL")
+        .bind(schema)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+async fn stream_versions(store: &PostgresStore, id: &StreamKey) -> Vec<u64> {
+    store
+        .read_stream(id, Version::INITIAL)
+        .await
+        .unwrap()
+        .map(|r| r.unwrap().version().as_u64())
+        .collect()
+        .await
+}
+
+/// Write one event row straight into `schema.events`, as an older build
+/// would have.
+async fn insert_legacy_event(pool: &PgPool, schema: &str) {
+    sqlx::query(&format!(
+        "INSERT INTO {schema}.events (stream_id, version, event_type, schema_version, payload) \
+         VALUES ($1, 1, 'TestEvent', 1, $2)"
+    ))
+    .bind(b"legacy".as_slice())
+    .bind(b"payload-1".as_slice())
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// The store works end to end on the migrated schema: the legacy event reads
+/// back, appends continue the stream, and a snapshot commits.
+async fn assert_usable(store: &PostgresStore) {
+    let id = sk("legacy");
+    assert_eq!(stream_versions(store, &id).await, [1]);
+    store
+        .append(&id, Version::new(1), &envelopes(2, 2))
+        .await
+        .unwrap();
+    assert_eq!(stream_versions(store, &id).await, [1, 2]);
+    SnapshotStore::<Vec<u8>, Version>::commit(
+        store,
+        &id,
+        NonZeroU32::MIN,
+        Version::new(2).unwrap(),
+        &vec![2],
+    )
+    .await
+    .unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// 1. Applying
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn a_fresh_namespace_is_migrated_to_the_latest_version() {
+    let Some(pool) = pool("mig_fresh").await else {
+        return;
+    };
+    let _store = builder("mig_fresh").open(pool.clone()).await.unwrap();
+
+    let names: Vec<String> = recorded(&pool, "mig_fresh")
+        .await
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    assert_eq!(
+        names,
+        ["events", "snapshots and checkpoints", "snapshot history"]
+    );
+
+    // Reopening applies nothing further.
+    let _again = builder("mig_fresh").open(pool.clone()).await.unwrap();
+    assert_eq!(recorded_versions(&pool, "mig_fresh").await, every_version());
+}
+
+#[tokio::test]
+async fn every_prior_version_upgrades_to_the_latest() {
+    let Some(pool) = pool("mig_upgrade").await else {
+        return;
+    };
+    for from in 0..SCHEMA_VERSION {
+        sqlx::raw_sql("DROP SCHEMA IF EXISTS mig_upgrade CASCADE")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let reached = builder("mig_upgrade")
+            .migrate_to(&pool, from)
+            .await
+            .unwrap();
+        assert_eq!(reached, from);
+        if from >= 1 {
+            insert_legacy_event(&pool, "mig_upgrade").await;
+        }
+
+        let store = builder("mig_upgrade").open(pool.clone()).await.unwrap();
+        assert_eq!(
+            recorded_versions(&pool, "mig_upgrade").await,
+            every_version(),
+            "upgrading from {from}"
+        );
+        if from == 0 {
+            store
+                .append(&sk("legacy"), None, &envelopes(1, 1))
+                .await
+                .unwrap();
+        }
+        assert_usable(&store).await;
+    }
+}
+
+#[tokio::test]
+async fn a_database_from_before_the_runner_is_adopted_in_place() {
+    let Some(pool) = pool("mig_legacy").await else {
+        return;
+    };
+    // The DDL the store ran unconditionally before migrations existed.
+    sqlx::raw_sql(
+        "CREATE SCHEMA mig_legacy;
+         CREATE TABLE mig_legacy.events (
+             global_seq     BIGINT GENERATED ALWAYS AS IDENTITY,
+             stream_id      BYTEA    NOT NULL,
+             version        BIGINT   NOT NULL,
+             txid           xid8     NOT NULL DEFAULT pg_current_xact_id(),
+             event_type     TEXT     NOT NULL,
+             schema_version BIGINT   NOT NULL,
+             payload        BYTEA    NOT NULL,
+             metadata       BYTEA,
+             PRIMARY KEY (global_seq),
+             UNIQUE (stream_id, version)
+         );
+         CREATE INDEX events_stream_idx    ON mig_legacy.events (stream_id, version);
+         CREATE INDEX events_watermark_idx ON mig_legacy.events (txid, global_seq);",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    insert_legacy_event(&pool, "mig_legacy").await;
+
+    let store = builder("mig_legacy").open(pool.clone()).await.unwrap();
+    assert_eq!(
+        recorded_versions(&pool, "mig_legacy").await,
+        every_version()
+    );
+    assert_usable(&store).await;
+}
+
+#[tokio::test]
+async fn concurrent_opens_apply_each_migration_once() {
+    let Some(pool) = pool("mig_race").await else {
+        return;
+    };
+    let opens = (0..8).map(|_| builder("mig_race").open(pool.clone()));
+    for opened in join_all(opens).await {
+        opened.expect("every concurrent open succeeds");
+    }
+    assert_eq!(recorded_versions(&pool, "mig_race").await, every_version());
+}
+
+#[tokio::test]
+async fn migrations_recorded_by_a_newer_build_are_accepted() {
+    let Some(pool) = pool("mig_newer").await else {
+        return;
+    };
+    let _store = builder("mig_newer").open(pool.clone()).await.unwrap();
+    sqlx::query(
+        "INSERT INTO mig_newer.nexus_schema_migrations (version, name) VALUES (999, 'future')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    builder("mig_newer").open(pool.clone()).await.unwrap();
+    builder("mig_newer")
+        .migration_mode(MigrationMode::VerifyOnly)
+        .open(pool.clone())
+        .await
+        .unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// 2. Verify-only
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn verify_only_refuses_an_unmigrated_namespace_without_touching_it() {
+    let Some(pool) = pool("mig_verify").await else {
+        return;
+    };
+    let err = builder("mig_verify")
+        .migration_mode(MigrationMode::VerifyOnly)
+        .open(pool.clone())
+        .await
+        .err()
+        .expect("nothing is migrated");
+    assert!(
+        matches!(
+            err,
+            PostgresError::SchemaOutdated { applied: 0, required } if required == SCHEMA_VERSION
+        ),
+        "got {err:?}"
+    );
+    assert!(!schema_exists(&pool, "mig_verify").await, "no DDL ran");
+}
+
+#[tokio::test]
+async fn verify_only_refuses_a_partial_schema_and_accepts_a_complete_one() {
+    let Some(pool) = pool("mig_partial").await else {
+        return;
+    };
+    builder("mig_partial").migrate_to(&pool, 1).await.unwrap();
+    let verify = builder("mig_partial").migration_mode(MigrationMode::VerifyOnly);
+
+    let err = verify
+        .clone()
+        .open(pool.clone())
+        .await
+        .err()
+        .expect("migration 2 is pending");
+    assert!(
+        matches!(err, PostgresError::SchemaOutdated { applied: 1, .. }),
+        "got {err:?}"
+    );
+    assert_eq!(recorded_versions(&pool, "mig_partial").await, [1]);
+
+    builder("mig_partial").open(pool.clone()).await.unwrap();
+    let store = verify.open(pool.clone()).await.unwrap();
+    store
+        .append(&sk("s"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,280 @@
+//! `PostgresStore::builder` namespaces: stores configured with distinct
+//! schemas, table prefixes and notify channels share one database without
+//! seeing each other's events, snapshots, checkpoints or wakes.
+//!
+//! Each test works in its own schema, dropped and recreated up front, so the
+//! default-namespace tables the other suites truncate are never touched.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`pool`] first. If `DATABASE_URL` is unset, `pool`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use futures::StreamExt;
+use nexus::Version;
+use nexus_postgres::{PgAllPos, PostgresError, PostgresStore};
+use nexus_store::envelope::pending_envelope;
+use nexus_store::error::AppendError;
+use nexus_store::store::RawEventStore;
+use nexus_store::wake::{WakeRegistration, WakeSource};
+use nexus_store::{PendingEnvelope, SnapshotStore, StreamKey};
+use sqlx::PgPool;
+
+/// How long to wait for a wake that should (or should not) arrive.
+const WAKE_WAIT: Duration = Duration::from_millis(500);
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect and drop `schema` so the test starts from nothing.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn pool(schema: &str) -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    drop_schema(&pool, schema).await;
+    Some(pool)
+}
+
+async fn drop_schema(pool: &PgPool, schema: &str) {
+    sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(pool)
+        .await
+        .expect("drop schema");
+}
+
+async fn store(pool: &PgPool, schema: &str, prefix: &str) -> PostgresStore {
+    PostgresStore::builder()
+        .schema(schema)
+        .table_prefix(prefix)
+        .notify_channel(format!("{schema}_{prefix}events"))
+        .open(pool.clone())
+        .await
+        .expect("open namespaced store")
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+async fn stream_len(store: &PostgresStore, id: &StreamKey) -> usize {
+    store
+        .read_stream(id, Version::INITIAL)
+        .await
+        .unwrap()
+        .count()
+        .await
+}
+
+async fn all_len(store: &PostgresStore) -> usize {
+    store.read_all(None).await.unwrap().count().await
+}
+
+async fn tables_in(pool: &PgPool, schema: &str) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT table_name::text FROM information_schema.tables \
+         WHERE table_schema = $1 ORDER BY table_name",
+    )
+    .bind(schema)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+const fn schema_v1() -> NonZeroU32 {
+    NonZeroU32::MIN
+}
+
+// ---------------------------------------------------------------------------
+// 1. Schema setup
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn builder_creates_the_schema_and_prefixed_tables() {
+    let Some(pool) = pool("ns_setup").await else {
+        return;
+    };
+    let _store = store(&pool, "ns_setup", "tenant_a_").await;
+
+    assert_eq!(
+        tables_in(&pool, "ns_setup").await,
+        [
+            "tenant_a_checkpoints",
+            "tenant_a_events",
+            "tenant_a_nexus_schema_migrations",
+            "tenant_a_snapshots"
+        ]
+    );
+
+    // Opening again is idempotent, and a second prefix adds its own tables.
+    let _again = store(&pool, "ns_setup", "tenant_a_").await;
+    let _other = store(&pool, "ns_setup", "tenant_b_").await;
+    assert_eq!(tables_in(&pool, "ns_setup").await.len(), 8);
+}
+
+#[tokio::test]
+async fn malformed_names_are_rejected_before_connecting() {
+    // Nothing listens on port 1: reaching the network would be a Sqlx error.
+    for builder in [
+        PostgresStore::builder().schema("Billing"),
+        PostgresStore::builder().table_prefix("tenant-a_"),
+        PostgresStore::builder().notify_channel(""),
+    ] {
+        let err = builder
+            .connect("postgres://nobody@127.0.0.1:1/none")
+            .await
+            .err()
+            .expect("invalid name");
+        assert!(
+            matches!(err, PostgresError::InvalidIdentifier { .. }),
+            "got {err:?}"
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 2. Isolation
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn prefixed_stores_keep_separate_streams() {
+    let Some(pool) = pool("ns_events").await else {
+        return;
+    };
+    let a = store(&pool, "ns_events", "tenant_a_").await;
+    let b = store(&pool, "ns_events", "tenant_b_").await;
+    let id = sk("order-1");
+
+    a.append(&id, None, &envelopes(1, 3)).await.unwrap();
+    // The same stream id starts fresh in the other namespace.
+    b.append(&id, None, &envelopes(1, 1)).await.unwrap();
+
+    assert_eq!(stream_len(&a, &id).await, 3);
+    assert_eq!(stream_len(&b, &id).await, 1);
+    assert_eq!(all_len(&a).await, 3);
+    assert_eq!(all_len(&b).await, 1);
+
+    // Each namespace's optimistic check sees only its own head.
+    let err = b
+        .append(&id, Version::new(3), &envelopes(4, 4))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppendError::Conflict { .. }), "got {err:?}");
+}
+
+#[tokio::test]
+async fn schemas_isolate_stores_with_the_same_prefix() {
+    let Some(pool) = pool("ns_left").await else {
+        return;
+    };
+    drop_schema(&pool, "ns_right").await;
+    let left = store(&pool, "ns_left", "").await;
+    let right = store(&pool, "ns_right", "").await;
+
+    left.append(&sk("s"), None, &envelopes(1, 2)).await.unwrap();
+    assert_eq!(all_len(&left).await, 2);
+    assert_eq!(all_len(&right).await, 0);
+}
+
+#[tokio::test]
+async fn snapshots_and_checkpoints_stay_in_their_namespace() {
+    let Some(pool) = pool("ns_state").await else {
+        return;
+    };
+    let a = store(&pool, "ns_state", "tenant_a_").await;
+    let b = store(&pool, "ns_state", "tenant_b_").await;
+    let id = sk("agg-1");
+
+    SnapshotStore::<Vec<u8>, Version>::commit(
+        &a,
+        &id,
+        schema_v1(),
+        Version::new(5).unwrap(),
+        &vec![5],
+    )
+    .await
+    .unwrap();
+    SnapshotStore::<Vec<u8>, PgAllPos>::commit(&b, &id, schema_v1(), PgAllPos::new(9, 1), &vec![9])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        SnapshotStore::<Vec<u8>, Version>::hydrate(&a, &id, schema_v1())
+            .await
+            .unwrap(),
+        Some((Version::new(5).unwrap(), vec![5]))
+    );
+    assert_eq!(
+        SnapshotStore::<Vec<u8>, Version>::hydrate(&b, &id, schema_v1())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        SnapshotStore::<Vec<u8>, PgAllPos>::hydrate(&a, &id, schema_v1())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        SnapshotStore::<Vec<u8>, PgAllPos>::hydrate(&b, &id, schema_v1())
+            .await
+            .unwrap(),
+        Some((PgAllPos::new(9, 1), vec![9]))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 3. Notify channels
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn appends_wake_only_stores_on_the_same_channel() {
+    let Some(pool) = pool("ns_wake").await else {
+        return;
+    };
+    let watcher = store(&pool, "ns_wake", "tenant_a_").await;
+    let writer = store(&pool, "ns_wake", "tenant_a_").await;
+    let neighbour = store(&pool, "ns_wake", "tenant_b_").await;
+    // Let the listener tasks subscribe before anything is notified.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let id = sk("order-1");
+    let registration = watcher.register(Some(id.as_bytes())).unwrap();
+
+    let quiet = registration.arm();
+    neighbour.append(&id, None, &envelopes(1, 1)).await.unwrap();
+    assert!(
+        tokio::time::timeout(WAKE_WAIT, quiet).await.is_err(),
+        "another channel's append must not wake this store"
+    );
+
+    let woken = registration.arm();
+    writer.append(&id, None, &envelopes(1, 1)).await.unwrap();
+    tokio::time::timeout(WAKE_WAIT, woken)
+        .await
+        .expect("an append on the shared channel wakes every store on it");
+}
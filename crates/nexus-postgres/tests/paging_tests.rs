@@ -0,0 +1,259 @@
+//! Keyset-paginated reads: `read_stream` and `read_all` fetch at most
+//! `batch_size` rows per round trip and resume from the last row yielded.
+//!
+//! Page boundaries must be invisible in the output — same rows, same order,
+//! no gaps or repeats — and `read_all` must keep the `pg_snapshot_xmin`
+//! watermark it opened with across every page.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`setup`] first. If `DATABASE_URL` is unset, `setup`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use futures::StreamExt;
+use nexus::Version;
+use nexus_postgres::{PgAllPos, PostgresStore};
+use nexus_store::batch::BatchSize;
+use nexus_store::envelope::pending_envelope;
+use nexus_store::store::RawEventStore;
+use nexus_store::{PendingEnvelope, StreamKey};
+use sqlx::PgPool;
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect, ensure the schema, truncate, and page `batch` rows at a time.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn setup(batch: usize) -> Option<(PostgresStore, PgPool)> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    let store = PostgresStore::from_pool(pool.clone())
+        .await
+        .expect("from_pool")
+        .with_batch_size(BatchSize::new(batch).unwrap());
+    sqlx::query("TRUNCATE events RESTART IDENTITY")
+        .execute(&pool)
+        .await
+        .expect("truncate events");
+    Some((store, pool))
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+async fn stream_versions(store: &PostgresStore, id: &StreamKey, from: u64) -> Vec<u64> {
+    store
+        .read_stream(id, Version::new(from).unwrap())
+        .await
+        .unwrap()
+        .map(|r| r.unwrap().version().as_u64())
+        .collect()
+        .await
+}
+
+async fn all_positions(store: &PostgresStore, from: Option<PgAllPos>) -> Vec<PgAllPos> {
+    store
+        .read_all(from)
+        .await
+        .unwrap()
+        .map(|r| r.unwrap().0)
+        .collect()
+        .await
+}
+
+// ---------------------------------------------------------------------------
+// 1. Configuration
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn batch_size_defaults_and_overrides() {
+    let Some((store, _pool)) = setup(3).await else {
+        return;
+    };
+    assert_eq!(store.batch_size().get(), 3);
+    let reset = store.with_batch_size(BatchSize::DEFAULT);
+    assert_eq!(reset.batch_size(), BatchSize::DEFAULT);
+}
+
+// ---------------------------------------------------------------------------
+// 2. Per-stream pages
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn read_stream_spans_pages_without_gaps_or_repeats() {
+    let Some((store, _pool)) = setup(2).await else {
+        return;
+    };
+    let id = sk("paged");
+    store.append(&id, None, &envelopes(1, 7)).await.unwrap();
+    store
+        .append(&sk("other"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        stream_versions(&store, &id, 1).await,
+        (1..=7).collect::<Vec<_>>()
+    );
+    // A mid-stream start still pages from there.
+    assert_eq!(stream_versions(&store, &id, 4).await, vec![4, 5, 6, 7]);
+}
+
+#[tokio::test]
+async fn read_stream_exact_multiple_of_batch_ends_on_an_empty_page() {
+    let Some((store, _pool)) = setup(3).await else {
+        return;
+    };
+    let id = sk("exact");
+    store.append(&id, None, &envelopes(1, 6)).await.unwrap();
+
+    assert_eq!(
+        stream_versions(&store, &id, 1).await,
+        (1..=6).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn read_stream_sees_rows_appended_after_the_first_page() {
+    // Per-stream reads are not pinned: a later page reflects later commits.
+    let Some((store, _pool)) = setup(2).await else {
+        return;
+    };
+    let id = sk("growing");
+    store.append(&id, None, &envelopes(1, 3)).await.unwrap();
+
+    let mut stream = store.read_stream(&id, Version::INITIAL).await.unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first.version().as_u64(), 1);
+    store
+        .append(&id, Version::new(3), &envelopes(4, 4))
+        .await
+        .unwrap();
+
+    let rest: Vec<u64> = stream
+        .map(|r| r.unwrap().version().as_u64())
+        .collect()
+        .await;
+    assert_eq!(rest, vec![2, 3, 4]);
+}
+
+// ---------------------------------------------------------------------------
+// 3. `$all` pages
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn read_all_spans_pages_in_position_order() {
+    let Some((store, _pool)) = setup(2).await else {
+        return;
+    };
+    store
+        .append(&sk("a"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+    store
+        .append(&sk("b"), None, &envelopes(1, 2))
+        .await
+        .unwrap();
+    store
+        .append(&sk("a"), Version::new(3), &envelopes(4, 4))
+        .await
+        .unwrap();
+
+    let positions = all_positions(&store, None).await;
+    assert_eq!(positions.len(), 6);
+    assert!(
+        positions.windows(2).all(|w| w[0] < w[1]),
+        "strictly ascending"
+    );
+
+    // Resuming from any yielded position returns exactly the suffix.
+    for (i, pos) in positions.iter().enumerate() {
+        assert_eq!(all_positions(&store, Some(*pos)).await, positions[i + 1..]);
+    }
+}
+
+#[tokio::test]
+async fn read_all_keeps_its_opening_watermark_across_pages() {
+    // The watermark is pinned when the read opens: rows committed while it is
+    // being drained are left for the next `read_all`, on every page.
+    let Some((store, _pool)) = setup(1).await else {
+        return;
+    };
+    store
+        .append(&sk("a"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+
+    let mut stream = store.read_all(None).await.unwrap();
+    let (first, _) = stream.next().await.unwrap().unwrap();
+    store
+        .append(&sk("b"), None, &envelopes(1, 2))
+        .await
+        .unwrap();
+
+    let rest: Vec<PgAllPos> = stream.map(|r| r.unwrap().0).collect().await;
+    assert_eq!(rest.len(), 2, "only the rows settled at open");
+
+    let later = all_positions(&store, rest.last().copied()).await;
+    assert_eq!(later.len(), 2, "the next read picks up the new rows");
+    assert!(first < rest[0] && rest[1] < later[0]);
+}
+
+#[tokio::test]
+async fn read_all_withholds_rows_behind_an_open_transaction_on_every_page() {
+    // An in-flight writer holds the watermark below its txid: a committed
+    // later writer's rows stay hidden even when the earlier pages drain.
+    let Some((store, pool)) = setup(1).await else {
+        return;
+    };
+    store
+        .append(&sk("a"), None, &envelopes(1, 2))
+        .await
+        .unwrap();
+
+    let mut open = pool.begin().await.unwrap();
+    sqlx::query(
+        "INSERT INTO events (stream_id, version, event_type, schema_version, payload) \
+         VALUES ($1, 1, 'E', 1, '\\x00')",
+    )
+    .bind(b"pending".as_slice())
+    .execute(&mut *open)
+    .await
+    .unwrap();
+    store
+        .append(&sk("b"), None, &envelopes(1, 2))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        all_positions(&store, None).await.len(),
+        2,
+        "only the rows before the open txn"
+    );
+
+    open.commit().await.unwrap();
+    assert_eq!(all_positions(&store, None).await.len(), 5);
+}
@@ -0,0 +1,367 @@
+//! Partitioned events tables: opening creates partitions ahead of need,
+//! reads and `$all` run unchanged across them, version conflicts are still
+//! detected without a unique constraint, old partitions detach while live
+//! ones are refused, and a store never opens a namespace laid out
+//! differently.
+//!
+//! Each test works in its own schema, dropped up front.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`pool`] first. If `DATABASE_URL` is unset, `pool`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use std::num::NonZeroU64;
+use std::time::Duration;
+
+use futures::StreamExt;
+use nexus::Version;
+use nexus_postgres::{PartitionRange, Partitioning, PostgresError, PostgresStore};
+use nexus_store::envelope::pending_envelope;
+use nexus_store::error::AppendError;
+use nexus_store::store::RawEventStore;
+use nexus_store::{PendingEnvelope, StreamKey};
+use sqlx::PgPool;
+
+/// Ten `global_seq` values per partition, so a few appends cross several.
+const SPAN: NonZeroU64 = NonZeroU64::new(10).unwrap();
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect and drop `schema` so the test starts from nothing.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn pool(schema: &str) -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&pool)
+        .await
+        .expect("drop schema");
+    Some(pool)
+}
+
+async fn open(
+    pool: &PgPool,
+    schema: &str,
+    partitioning: Option<Partitioning>,
+) -> Result<PostgresStore, PostgresError> {
+    let builder = PostgresStore::builder()
+        .schema(schema)
+        .notify_channel(format!("{schema}_events"));
+    match partitioning {
+        Some(layout) => builder.partitioning(layout),
+        None => builder,
+    }
+    .open(pool.clone())
+    .await
+}
+
+async fn store(pool: &PgPool, schema: &str, partitioning: Partitioning) -> PostgresStore {
+    open(pool, schema, Some(partitioning))
+        .await
+        .expect("open store")
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+/// `$all` sequence numbers, in read order.
+async fn all_seqs(store: &PostgresStore) -> Vec<u64> {
+    store
+        .read_all(None)
+        .await
+        .expect("read_all")
+        .map(|r| r.expect("no error").0.seq())
+        .collect()
+        .await
+}
+
+async fn stream_versions(store: &PostgresStore, stream: &str) -> Vec<u64> {
+    store
+        .read_stream(&sk(stream), Version::INITIAL)
+        .await
+        .expect("read_stream")
+        .map(|r| r.expect("no error").version().as_u64())
+        .collect()
+        .await
+}
+
+fn seq_starts(partitions: &[nexus_postgres::EventPartition]) -> Vec<u64> {
+    partitions
+        .iter()
+        .map(|p| match p.range {
+            PartitionRange::GlobalSeq { start, .. } => start,
+            other => panic!("not a global_seq partition: {other:?}"),
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// 1. Reads and writes across partitions
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn appends_span_partitions_and_read_back_in_order() {
+    let Some(pool) = pool("part_seq").await else {
+        return;
+    };
+    let store = store(&pool, "part_seq", Partitioning::by_global_seq(SPAN)).await;
+    assert_eq!(seq_starts(&store.partitions().await.unwrap()), [0, 10, 20]);
+
+    store
+        .append(&sk("a"), None, &envelopes(1, 8))
+        .await
+        .unwrap();
+    store
+        .append(&sk("b"), None, &envelopes(1, 7))
+        .await
+        .unwrap();
+    store
+        .append(&sk("a"), Version::new(8), &envelopes(9, 12))
+        .await
+        .unwrap();
+
+    assert_eq!(all_seqs(&store).await, (1..=19).collect::<Vec<_>>());
+    assert_eq!(
+        stream_versions(&store, "a").await,
+        (1..=12).collect::<Vec<_>>()
+    );
+
+    // Topping up keeps two partitions ahead of the one now being filled.
+    store.ensure_partitions().await.unwrap();
+    assert_eq!(
+        seq_starts(&store.partitions().await.unwrap()),
+        [0, 10, 20, 30, 40]
+    );
+}
+
+#[tokio::test]
+async fn version_conflicts_are_detected_without_a_unique_constraint() {
+    let Some(pool) = pool("part_conflict").await else {
+        return;
+    };
+    let store = store(&pool, "part_conflict", Partitioning::by_global_seq(SPAN)).await;
+    store
+        .append(&sk("a"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+
+    let stale = store
+        .append(&sk("a"), Version::new(1), &envelopes(2, 2))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(stale, AppendError::Conflict { .. }),
+        "got {stale:?}"
+    );
+
+    // The bulk path merges through the same trigger.
+    let bulk = store
+        .append(&sk("a"), None, &envelopes(1, 100))
+        .await
+        .unwrap_err();
+    assert!(matches!(bulk, AppendError::Conflict { .. }), "got {bulk:?}");
+
+    // A raw duplicate that bypasses the store is refused by the trigger.
+    let raw = sqlx::query(
+        "INSERT INTO part_conflict.events \
+         (stream_id, version, event_type, schema_version, payload) \
+         VALUES ($1, 2, 'TestEvent', 1, '')",
+    )
+    .bind(b"a".as_slice())
+    .execute(&pool)
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(&raw, sqlx::Error::Database(db) if db.is_unique_violation()),
+        "got {raw:?}"
+    );
+    assert_eq!(stream_versions(&store, "a").await, [1, 2, 3]);
+}
+
+// ---------------------------------------------------------------------------
+// 2. Detaching
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn a_detached_partition_leaves_reads_and_keeps_stream_heads() {
+    let Some(pool) = pool("part_detach").await else {
+        return;
+    };
+    let store = store(&pool, "part_detach", Partitioning::by_global_seq(SPAN)).await;
+    store
+        .append(&sk("old"), None, &envelopes(1, 9))
+        .await
+        .unwrap();
+    store
+        .append(&sk("new"), None, &envelopes(1, 5))
+        .await
+        .unwrap();
+
+    // Sequence 14 is the last handed out: [10, 20) still receives events.
+    let live = store.detach_partition("events_10").await.unwrap_err();
+    assert!(
+        matches!(live, PostgresError::Partition { .. }),
+        "got {live:?}"
+    );
+    let unknown = store.detach_partition("events_99").await.unwrap_err();
+    assert!(
+        matches!(unknown, PostgresError::Partition { .. }),
+        "got {unknown:?}"
+    );
+
+    store.detach_partition("events_0").await.unwrap();
+    assert_eq!(seq_starts(&store.partitions().await.unwrap()), [10, 20]);
+    assert_eq!(all_seqs(&store).await, (10..=14).collect::<Vec<_>>());
+    assert!(stream_versions(&store, "old").await.is_empty());
+
+    // The archived stream continues at its next version, not from scratch.
+    let restart = store
+        .append(&sk("old"), None, &envelopes(1, 1))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(restart, AppendError::Conflict { .. }),
+        "got {restart:?}"
+    );
+    store
+        .append(&sk("old"), Version::new(9), &envelopes(10, 10))
+        .await
+        .unwrap();
+    assert_eq!(stream_versions(&store, "old").await, [10]);
+
+    // The detached partition is an ordinary table, ready to be archived.
+    let archived: i64 = sqlx::query_scalar("SELECT count(*) FROM part_detach.events_0")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(archived, 9);
+}
+
+#[tokio::test]
+async fn monthly_partitions_cover_this_month_and_refuse_to_detach_it() {
+    let Some(pool) = pool("part_monthly").await else {
+        return;
+    };
+    let store = store(&pool, "part_monthly", Partitioning::monthly()).await;
+    let partitions = store.partitions().await.unwrap();
+    assert_eq!(partitions.len(), 3, "this month and two ahead");
+    assert!(
+        partitions
+            .iter()
+            .all(|p| matches!(p.range, PartitionRange::Month { .. }))
+    );
+
+    store
+        .append(&sk("a"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+    assert_eq!(all_seqs(&store).await, [1, 2, 3]);
+
+    let current = partitions[0].name.clone();
+    let err = store.detach_partition(&current).await.unwrap_err();
+    assert!(
+        matches!(err, PostgresError::Partition { .. }),
+        "got {err:?}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 3. Layout and upkeep
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn a_store_refuses_a_namespace_laid_out_differently() {
+    let Some(pool) = pool("part_layout").await else {
+        return;
+    };
+    sqlx::raw_sql("DROP SCHEMA IF EXISTS part_layout_plain CASCADE")
+        .execute(&pool)
+        .await
+        .unwrap();
+    store(&pool, "part_layout", Partitioning::by_global_seq(SPAN)).await;
+    open(&pool, "part_layout_plain", None).await.unwrap();
+
+    let unpartitioned = open(&pool, "part_layout", None)
+        .await
+        .err()
+        .expect("partitioned table, none configured");
+    assert!(
+        matches!(unpartitioned, PostgresError::Partition { .. }),
+        "got {unpartitioned:?}"
+    );
+    let monthly = open(&pool, "part_layout", Some(Partitioning::monthly()))
+        .await
+        .err()
+        .expect("partitioned on another column");
+    assert!(
+        matches!(monthly, PostgresError::Partition { .. }),
+        "got {monthly:?}"
+    );
+    let partitioned = open(
+        &pool,
+        "part_layout_plain",
+        Some(Partitioning::by_global_seq(SPAN)),
+    )
+    .await
+    .err()
+    .expect("plain table, partitioning configured");
+    assert!(
+        matches!(partitioned, PostgresError::Partition { .. }),
+        "got {partitioned:?}"
+    );
+}
+
+#[tokio::test]
+async fn the_background_task_keeps_partitions_ahead_of_appends() {
+    let Some(pool) = pool("part_upkeep").await else {
+        return;
+    };
+    let partitioning = Partitioning::by_global_seq(SPAN)
+        .keep_ahead(1)
+        .check_every(Duration::from_millis(50));
+    let store = store(&pool, "part_upkeep", partitioning).await;
+    assert_eq!(seq_starts(&store.partitions().await.unwrap()), [0, 10]);
+
+    store
+        .append(&sk("a"), None, &envelopes(1, 9))
+        .await
+        .unwrap();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while seq_starts(&store.partitions().await.unwrap()) != [0, 10, 20] {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "no partition created ahead"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    store
+        .append(&sk("a"), Version::new(9), &envelopes(10, 19))
+        .await
+        .unwrap();
+    assert_eq!(all_seqs(&store).await, (1..=19).collect::<Vec<_>>());
+}
@@ -0,0 +1,607 @@
+//! `PostgresStore` as a snapshot store: aggregate snapshots keyed by
+//! [`Version`] and projection checkpoints keyed by [`PgAllPos`].
+//!
+//! Mirrors `nexus-fjall`'s `snapshot_tests.rs`, plus the monotonic-commit
+//! guarantee.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`setup`] first. If `DATABASE_URL` is unset, `setup`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use std::num::NonZeroU32;
+
+use futures::StreamExt;
+use nexus::Version;
+use nexus_postgres::{PgAllPos, PostgresError, PostgresStore};
+use nexus_store::envelope::pending_envelope;
+use nexus_store::error::AppendError;
+use nexus_store::state::{PendingSnapshot, SnapshotAppend, SnapshotStore};
+use nexus_store::store::RawEventStore;
+use nexus_store::{PendingEnvelope, SnapshotRetention, StreamKey};
+use sqlx::PgPool;
+
+const SV1: NonZeroU32 = NonZeroU32::MIN;
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect, ensure the schema, and truncate every table for isolation.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn setup() -> Option<(PostgresStore, PgPool)> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    let store = PostgresStore::from_pool(pool.clone())
+        .await
+        .expect("from_pool");
+    sqlx::query("TRUNCATE events, snapshots, checkpoints RESTART IDENTITY")
+        .execute(&pool)
+        .await
+        .expect("truncate");
+    Some((store, pool))
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+const fn v(n: u64) -> Version {
+    Version::new(n).unwrap()
+}
+
+const fn sv2() -> NonZeroU32 {
+    NonZeroU32::new(2).unwrap()
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(v(i))
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+async fn stream_len(store: &PostgresStore, id: &StreamKey) -> usize {
+    store
+        .read_stream(id, Version::INITIAL)
+        .await
+        .unwrap()
+        .count()
+        .await
+}
+
+/// The snapshot-store view of `store`, to pick the `Version` impl.
+fn snapshots(
+    store: &PostgresStore,
+) -> &impl SnapshotStore<Vec<u8>, Version, Error = PostgresError> {
+    store
+}
+
+/// The checkpoint-store view of `store`, to pick the `PgAllPos` impl.
+fn checkpoints(
+    store: &PostgresStore,
+) -> &impl SnapshotStore<Vec<u8>, PgAllPos, Error = PostgresError> {
+    store
+}
+
+// ---------------------------------------------------------------------------
+// 1. Sequence/protocol — aggregate snapshots
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn commit_then_hydrate_roundtrips() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    store.append(&id, None, &envelopes(1, 5)).await.unwrap();
+
+    let snaps = snapshots(&store);
+    snaps.commit(&id, SV1, v(5), &vec![1, 2, 3]).await.unwrap();
+
+    assert_eq!(
+        snaps.hydrate(&id, SV1).await.unwrap(),
+        Some((v(5), vec![1, 2, 3]))
+    );
+}
+
+#[tokio::test]
+async fn commit_overwrites_previous_snapshot() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    let snaps = snapshots(&store);
+
+    snaps.commit(&id, SV1, v(5), &vec![1]).await.unwrap();
+    snaps.commit(&id, sv2(), v(10), &vec![2, 3]).await.unwrap();
+
+    assert_eq!(
+        snaps.hydrate(&id, sv2()).await.unwrap(),
+        Some((v(10), vec![2, 3]))
+    );
+    // Old schema version is filtered at the store level.
+    assert!(snaps.hydrate(&id, SV1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn hydrate_with_schema_reads_past_a_schema_mismatch() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    let snaps = snapshots(&store);
+    snaps.commit(&id, SV1, v(5), &vec![7, 8]).await.unwrap();
+
+    assert!(snaps.hydrate(&id, sv2()).await.unwrap().is_none());
+    assert_eq!(
+        snaps.hydrate_with_schema(&id).await.unwrap(),
+        Some((SV1, v(5), vec![7, 8]))
+    );
+    assert_eq!(snaps.stored_schema_version(&id).await.unwrap(), Some(SV1));
+    assert!(
+        snaps
+            .hydrate_with_schema(&sk("nope"))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+async fn retained_store(pool: &PgPool, keep: usize) -> PostgresStore {
+    PostgresStore::builder()
+        .snapshot_retention(SnapshotRetention::keep_last(
+            std::num::NonZeroUsize::new(keep).unwrap(),
+        ))
+        .open(pool.clone())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn hydrate_at_or_before_picks_the_nearest_retained_snapshot() {
+    let Some((_, pool)) = setup().await else {
+        return;
+    };
+    let retained = retained_store(&pool, 3).await;
+    let store = snapshots(&retained);
+    let id = sk("agg-1");
+
+    for n in [2_u8, 4, 6, 8] {
+        store.commit(&id, SV1, v(n.into()), &vec![n]).await.unwrap();
+    }
+
+    // Latest still serves plain hydrate.
+    assert_eq!(
+        store.hydrate(&id, SV1).await.unwrap(),
+        Some((v(8), vec![8]))
+    );
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(9)).await.unwrap(),
+        Some((v(8), vec![8]))
+    );
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(7)).await.unwrap(),
+        Some((v(6), vec![6]))
+    );
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(4)).await.unwrap(),
+        Some((v(4), vec![4]))
+    );
+    // v2 was pruned: only the last three are kept.
+    assert!(
+        store
+            .hydrate_at_or_before(&id, SV1, v(3))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn hydrate_at_or_before_skips_other_schema_versions() {
+    let Some((_, pool)) = setup().await else {
+        return;
+    };
+    let retained = retained_store(&pool, 3).await;
+    let store = snapshots(&retained);
+    let id = sk("agg-1");
+    let sv2 = NonZeroU32::new(2).unwrap();
+
+    store.commit(&id, SV1, v(3), &vec![1]).await.unwrap();
+    store.commit(&id, sv2, v(5), &vec![2]).await.unwrap();
+
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(10)).await.unwrap(),
+        Some((v(3), vec![1]))
+    );
+    assert_eq!(
+        store.hydrate_at_or_before(&id, sv2, v(10)).await.unwrap(),
+        Some((v(5), vec![2]))
+    );
+}
+
+#[tokio::test]
+async fn latest_retention_only_serves_the_latest_snapshot() {
+    let Some((_, pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    {
+        let retained = retained_store(&pool, 3).await;
+        let store = snapshots(&retained);
+        store.commit(&id, SV1, v(2), &vec![2]).await.unwrap();
+        store.commit(&id, SV1, v(4), &vec![4]).await.unwrap();
+    }
+
+    // Reopened with the default retention: the next commit clears the
+    // history left behind by the wider one.
+    let reopened = PostgresStore::from_pool(pool).await.unwrap();
+    let store = snapshots(&reopened);
+    store.commit(&id, SV1, v(6), &vec![6]).await.unwrap();
+
+    assert_eq!(
+        store.hydrate_at_or_before(&id, SV1, v(6)).await.unwrap(),
+        Some((v(6), vec![6]))
+    );
+    assert!(
+        store
+            .hydrate_at_or_before(&id, SV1, v(5))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 2. Monotonic commits
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn older_snapshot_never_overwrites_a_newer_one() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    let snaps = snapshots(&store);
+
+    snaps.commit(&id, SV1, v(10), &vec![10]).await.unwrap();
+    snaps.commit(&id, sv2(), v(3), &vec![3]).await.unwrap();
+
+    assert_eq!(
+        snaps.hydrate_with_schema(&id).await.unwrap(),
+        Some((SV1, v(10), vec![10]))
+    );
+}
+
+#[tokio::test]
+async fn equal_version_commit_replaces_the_snapshot() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    let snaps = snapshots(&store);
+
+    // A migrated snapshot is rewritten at the same version, new schema.
+    snaps.commit(&id, SV1, v(6), &vec![1]).await.unwrap();
+    snaps.commit(&id, sv2(), v(6), &vec![2]).await.unwrap();
+
+    assert_eq!(
+        snaps.hydrate(&id, sv2()).await.unwrap(),
+        Some((v(6), vec![2]))
+    );
+}
+
+#[tokio::test]
+async fn older_checkpoint_never_overwrites_a_newer_one() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("projection");
+    let cps = checkpoints(&store);
+
+    cps.commit(&id, SV1, PgAllPos::new(5, 9), &vec![1])
+        .await
+        .unwrap();
+    // Lower txid loses even with a higher seq — the `PgAllPos` order.
+    cps.commit(&id, SV1, PgAllPos::new(4, 100), &vec![2])
+        .await
+        .unwrap();
+    assert_eq!(
+        cps.hydrate(&id, SV1).await.unwrap(),
+        Some((PgAllPos::new(5, 9), vec![1]))
+    );
+
+    cps.commit(&id, SV1, PgAllPos::new(5, 10), &vec![3])
+        .await
+        .unwrap();
+    assert_eq!(
+        cps.hydrate(&id, SV1).await.unwrap(),
+        Some((PgAllPos::new(5, 10), vec![3]))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 3. Lifecycle
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn snapshot_and_checkpoint_persist_across_reconnect() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    snapshots(&store)
+        .commit(&id, SV1, v(5), &vec![42])
+        .await
+        .unwrap();
+    checkpoints(&store)
+        .commit(&id, SV1, PgAllPos::new(7, 2), &vec![43])
+        .await
+        .unwrap();
+    drop(store);
+
+    let reopened = PostgresStore::from_pool(pool).await.unwrap();
+    assert_eq!(
+        snapshots(&reopened).hydrate(&id, SV1).await.unwrap(),
+        Some((v(5), vec![42]))
+    );
+    assert_eq!(
+        checkpoints(&reopened).hydrate(&id, SV1).await.unwrap(),
+        Some((PgAllPos::new(7, 2), vec![43]))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 4. Defensive boundary
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn hydrate_unknown_id_returns_none() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    assert!(
+        snapshots(&store)
+            .hydrate(&sk("nope"), SV1)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        checkpoints(&store)
+            .hydrate(&sk("nope"), SV1)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        snapshots(&store)
+            .stored_schema_version(&sk("nope"))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn hydrate_id_without_snapshot_returns_none() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    store.append(&id, None, &envelopes(1, 3)).await.unwrap();
+
+    assert!(snapshots(&store).hydrate(&id, SV1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn commit_without_event_stream_is_persisted() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let snaps = snapshots(&store);
+    snaps
+        .commit(&sk("nope"), SV1, v(1), &vec![1])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        snaps.hydrate(&sk("nope"), SV1).await.unwrap(),
+        Some((v(1), vec![1]))
+    );
+}
+
+#[tokio::test]
+async fn positions_past_i64_max_are_rejected_before_writing() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+
+    let snapshot_err = snapshots(&store)
+        .commit(&id, SV1, v(u64::MAX), &vec![1])
+        .await
+        .unwrap_err();
+    assert!(matches!(snapshot_err, PostgresError::InvalidInput { .. }));
+    let checkpoint_err = checkpoints(&store)
+        .commit(&id, SV1, PgAllPos::new(u64::MAX, 1), &vec![1])
+        .await
+        .unwrap_err();
+    assert!(matches!(checkpoint_err, PostgresError::InvalidInput { .. }));
+
+    assert!(snapshots(&store).hydrate(&id, SV1).await.unwrap().is_none());
+    assert!(
+        checkpoints(&store)
+            .hydrate(&id, SV1)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn full_schema_version_range_round_trips() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    let max = NonZeroU32::MAX;
+    snapshots(&store)
+        .commit(&id, max, v(1), &vec![1])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        snapshots(&store).hydrate(&id, max).await.unwrap(),
+        Some((v(1), vec![1]))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 5. Isolation
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn different_ids_have_separate_snapshots() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let snaps = snapshots(&store);
+    snaps
+        .commit(&sk("agg-1"), SV1, v(5), &vec![1])
+        .await
+        .unwrap();
+    snaps
+        .commit(&sk("agg-2"), SV1, v(10), &vec![2])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        snaps.hydrate(&sk("agg-1"), SV1).await.unwrap(),
+        Some((v(5), vec![1]))
+    );
+    assert_eq!(
+        snaps.hydrate(&sk("agg-2"), SV1).await.unwrap(),
+        Some((v(10), vec![2]))
+    );
+}
+
+#[tokio::test]
+async fn snapshots_and_checkpoints_do_not_share_ids() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("shared");
+    snapshots(&store)
+        .commit(&id, SV1, v(3), &vec![1])
+        .await
+        .unwrap();
+
+    assert!(
+        checkpoints(&store)
+            .hydrate(&id, SV1)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    checkpoints(&store)
+        .commit(&id, SV1, PgAllPos::new(1, 1), &vec![2])
+        .await
+        .unwrap();
+    assert_eq!(
+        snapshots(&store).hydrate(&id, SV1).await.unwrap(),
+        Some((v(3), vec![1]))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 6. Atomic event + snapshot commit
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn append_with_snapshot_commits_both() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+
+    store
+        .append_with_snapshot(
+            &id,
+            None,
+            &envelopes(1, 3),
+            PendingSnapshot::new(SV1, v(3), &[9]),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(stream_len(&store, &id).await, 3);
+    assert_eq!(
+        snapshots(&store).hydrate(&id, SV1).await.unwrap(),
+        Some((v(3), vec![9]))
+    );
+}
+
+#[tokio::test]
+async fn append_with_snapshot_conflict_writes_neither() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+    store.append(&id, None, &envelopes(1, 2)).await.unwrap();
+
+    let result = store
+        .append_with_snapshot(
+            &id,
+            None,
+            &envelopes(1, 3),
+            PendingSnapshot::new(SV1, v(3), &[9]),
+        )
+        .await;
+
+    assert!(matches!(result, Err(AppendError::Conflict { .. })));
+    assert_eq!(stream_len(&store, &id).await, 2);
+    assert!(snapshots(&store).hydrate(&id, SV1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn append_with_snapshot_rejects_a_snapshot_off_the_last_event() {
+    let Some((store, _pool)) = setup().await else {
+        return;
+    };
+    let id = sk("agg-1");
+
+    let result = store
+        .append_with_snapshot(
+            &id,
+            None,
+            &envelopes(1, 3),
+            PendingSnapshot::new(SV1, v(2), &[7]),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(AppendError::Store(PostgresError::InvalidInput {
+            version: 2,
+            ..
+        }))
+    ));
+    assert_eq!(stream_len(&store, &id).await, 0);
+    assert!(snapshots(&store).hydrate(&id, SV1).await.unwrap().is_none());
+}
@@ -0,0 +1,253 @@
+//! Watermark-stall detection: a transaction left open while newer events
+//! commit is reported — with its xid and backend — once the threshold
+//! passes, reaches stall-aware `$all` subscribers after the events below the
+//! watermark without ending them, and clears when the transaction ends. A
+//! long transaction with nothing waiting behind it is not a stall.
+//!
+//! Each test works in its own schema, dropped up front. A blocking
+//! transaction pins the watermark for the whole cluster, so the tests hold
+//! [`SERIAL`] to run one at a time whatever the harness's thread count.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`pool`] first. If `DATABASE_URL` is unset, `pool`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use std::sync::{Arc, atomic::AtomicUsize, atomic::Ordering};
+use std::time::Duration;
+
+use futures::StreamExt;
+use nexus::Version;
+use nexus_postgres::{AllItem, PgAllPos, PostgresStore, StallDetection, WatermarkStall};
+use nexus_store::envelope::pending_envelope;
+use nexus_store::store::RawEventStore;
+use nexus_store::{PendingEnvelope, StreamKey};
+use sqlx::pool::PoolConnection;
+use sqlx::{PgPool, Postgres};
+
+/// The stall threshold the tests configure.
+const THRESHOLD: Duration = Duration::from_millis(200);
+
+/// How long to wait for the monitor to notice a change.
+const NOTICE_WAIT: Duration = Duration::from_secs(5);
+
+static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect and drop `schema` so the test starts from nothing.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn pool(schema: &str) -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&pool)
+        .await
+        .expect("drop schema");
+    Some(pool)
+}
+
+async fn store(pool: &PgPool, schema: &str, detection: Option<StallDetection>) -> PostgresStore {
+    let base = PostgresStore::builder()
+        .schema(schema)
+        .notify_channel(format!("{schema}_events"));
+    let configured = match detection {
+        Some(wanted) => base.stall_detection(wanted),
+        None => base,
+    };
+    configured.open(pool.clone()).await.expect("open store")
+}
+
+/// A stall detector counting its reports.
+fn counting(reports: &Arc<AtomicUsize>) -> StallDetection {
+    let counter = Arc::clone(reports);
+    StallDetection::after(THRESHOLD).observe(move |_: &WatermarkStall| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    })
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+/// A session with an open transaction that holds an xid, as a forgotten
+/// `BEGIN` in a psql window would. Returns the connection, its xid and pid.
+async fn open_blocker(pool: &PgPool) -> (PoolConnection<Postgres>, u64, i32) {
+    let mut conn = pool.acquire().await.unwrap();
+    sqlx::raw_sql("BEGIN").execute(&mut *conn).await.unwrap();
+    let (xid, pid): (i64, i32) =
+        sqlx::query_as("SELECT pg_current_xact_id()::text::bigint, pg_backend_pid()")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+    (conn, u64::try_from(xid).unwrap(), pid)
+}
+
+/// Wait until the store's stall state matches `stalled`.
+async fn await_stall(store: &PostgresStore, stalled: bool) -> Option<WatermarkStall> {
+    tokio::time::timeout(NOTICE_WAIT, async {
+        loop {
+            let stall = store.watermark_stall();
+            if stall.is_some() == stalled {
+                return stall;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("stall state never became {stalled}"))
+}
+
+async fn all_len(store: &PostgresStore) -> usize {
+    store.read_all(None).await.unwrap().count().await
+}
+
+/// The next item of a stall-aware subscription.
+async fn next_item<S>(stream: &mut S) -> AllItem
+where
+    S: futures::Stream<Item = Result<AllItem, nexus_postgres::PostgresError>> + Unpin,
+{
+    tokio::time::timeout(NOTICE_WAIT, stream.next())
+        .await
+        .expect("an item arrives")
+        .expect("a subscription never ends")
+        .expect("no read error")
+}
+
+fn event_pos(item: &AllItem) -> PgAllPos {
+    match item {
+        AllItem::Event(pos, _) => *pos,
+        AllItem::Stalled(stall) => panic!("expected an event, got {stall:?}"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 1. Detection
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn an_open_transaction_pinning_new_events_is_reported_and_clears() {
+    let _serial = SERIAL.lock().await;
+    let Some(pool) = pool("stall_pinned").await else {
+        return;
+    };
+    let reports = Arc::new(AtomicUsize::new(0));
+    let watched = store(&pool, "stall_pinned", Some(counting(&reports))).await;
+
+    let (mut blocker, xid, pid) = open_blocker(&pool).await;
+    watched
+        .append(&sk("order-1"), None, &envelopes(1, 2))
+        .await
+        .unwrap();
+
+    let stall = await_stall(&watched, true).await.unwrap();
+    assert_eq!(stall.xid, xid);
+    assert_eq!(stall.pid, Some(pid));
+    assert!(stall.stalled_for >= THRESHOLD, "{stall:?}");
+
+    // Reported once, however long it lasts.
+    tokio::time::sleep(THRESHOLD * 2).await;
+    assert_eq!(reports.load(Ordering::SeqCst), 1);
+
+    // A stall never fails a read: it stops short at the watermark.
+    assert_eq!(all_len(&watched).await, 0);
+
+    sqlx::raw_sql("ROLLBACK")
+        .execute(&mut *blocker)
+        .await
+        .unwrap();
+    await_stall(&watched, false).await;
+    assert_eq!(all_len(&watched).await, 2);
+    assert_eq!(reports.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn subscribers_keep_receiving_events_through_a_stall() {
+    let _serial = SERIAL.lock().await;
+    let Some(pool) = pool("stall_subscription").await else {
+        return;
+    };
+    let reports = Arc::new(AtomicUsize::new(0));
+    let watched = store(&pool, "stall_subscription", Some(counting(&reports))).await;
+    watched
+        .append(&sk("order-1"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+
+    // Caught up and parked before the stall begins.
+    let early = watched.subscribe_all_with_stalls(None).expect("subscribe");
+    futures::pin_mut!(early);
+    let before = event_pos(&next_item(&mut early).await);
+
+    let (mut blocker, xid, _) = open_blocker(&pool).await;
+    watched
+        .append(&sk("order-2"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    await_stall(&watched, true).await;
+
+    // Opened mid-stall: the event below the watermark comes first.
+    let late = watched.subscribe_all_with_stalls(None).expect("subscribe");
+    futures::pin_mut!(late);
+    assert_eq!(event_pos(&next_item(&mut late).await), before);
+
+    for stream in [&mut early, &mut late] {
+        let item = next_item(stream).await;
+        assert!(
+            matches!(&item, AllItem::Stalled(stall) if stall.xid == xid),
+            "got {item:?}"
+        );
+    }
+
+    // Neither subscription ended: both resume with the held-back event.
+    sqlx::raw_sql("ROLLBACK")
+        .execute(&mut *blocker)
+        .await
+        .unwrap();
+    let held = event_pos(&next_item(&mut early).await);
+    assert!(held > before);
+    assert_eq!(event_pos(&next_item(&mut late).await), held);
+}
+
+#[tokio::test]
+async fn a_long_transaction_with_nothing_behind_it_is_not_a_stall() {
+    let _serial = SERIAL.lock().await;
+    let Some(pool) = pool("stall_idle").await else {
+        return;
+    };
+    let reports = Arc::new(AtomicUsize::new(0));
+    let watched = store(&pool, "stall_idle", Some(counting(&reports))).await;
+    watched
+        .append(&sk("order-1"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+
+    let (_blocker, _, _) = open_blocker(&pool).await;
+    tokio::time::sleep(THRESHOLD * 3).await;
+    assert_eq!(watched.watermark_stall(), None);
+    assert_eq!(reports.load(Ordering::SeqCst), 0);
+    assert_eq!(all_len(&watched).await, 1);
+}
@@ -37,7 +37,7 @@ const TIMEOUT: Duration = Duration::from_secs(5);
 // Test infrastructure
 // ---------------------------------------------------------------------------
 
-/// Connect to the store, run `ensure_schema`, and truncate for isolation.
+/// Connect to the store, run its migrations, and truncate for isolation.
 /// Returns `None` (skip) if `DATABASE_URL` is unset.
 ///
 /// Returns the `Store<PostgresStore>` handle (for `Subscription::new`) and
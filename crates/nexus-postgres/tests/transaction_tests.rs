@@ -0,0 +1,484 @@
+//! `TransactionalAppend` — appends that join a caller-owned `sqlx::Transaction`
+//! and commit or roll back with the caller's own writes, plus the
+//! repository-level `save_in_tx` built on it.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`setup`] first. If `DATABASE_URL` is unset, `setup`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use std::fmt;
+use std::time::Duration;
+
+use futures::StreamExt;
+use nexus::{Aggregate, AggregateState, DomainEvent, Events, Id, Message, Version};
+use nexus_postgres::PostgresStore;
+use nexus_store::envelope::{PersistedEnvelope, pending_envelope};
+use nexus_store::error::AppendError;
+use nexus_store::store::{RawEventStore, TransactionalAppend};
+use nexus_store::{Decode, Encode, PendingEnvelope, Repository, Store, StreamKey};
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+
+/// How long to wait for a `NOTIFY` that should (or should not) arrive.
+const NOTIFY_WAIT: Duration = Duration::from_millis(500);
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect, ensure the schema plus a caller-owned `outbox` table, and
+/// truncate both for isolation.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn setup() -> Option<(PostgresStore, PgPool)> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    let store = PostgresStore::from_pool(pool.clone())
+        .await
+        .expect("from_pool");
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS tx_test_outbox (note TEXT NOT NULL); \
+         TRUNCATE events, tx_test_outbox RESTART IDENTITY",
+    )
+    .execute(&pool)
+    .await
+    .expect("prepare tables");
+    Some((store, pool))
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+async fn stream_len(store: &PostgresStore, id: &StreamKey) -> usize {
+    store
+        .read_stream(id, Version::INITIAL)
+        .await
+        .unwrap()
+        .count()
+        .await
+}
+
+async fn outbox(pool: &PgPool) -> Vec<String> {
+    sqlx::query_scalar("SELECT note FROM tx_test_outbox ORDER BY note")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+async fn write_outbox(tx: &mut sqlx::PgConnection, note: &str) {
+    sqlx::query("INSERT INTO tx_test_outbox (note) VALUES ($1)")
+        .bind(note)
+        .execute(tx)
+        .await
+        .unwrap();
+}
+
+/// A listener on the store's `NOTIFY` channel.
+async fn listen(pool: &PgPool) -> PgListener {
+    let mut listener = PgListener::connect_with(pool).await.unwrap();
+    listener.listen("nexus_events").await.unwrap();
+    listener
+}
+
+// ---------------------------------------------------------------------------
+// 1. Commit and rollback
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn events_and_caller_writes_commit_together() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let id = sk("order-1");
+
+    let mut tx = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut tx, &id, None, &envelopes(1, 2))
+        .await
+        .unwrap();
+    write_outbox(&mut tx, "order-1 placed").await;
+
+    // Staged, not committed: neither write is visible outside `tx`.
+    assert_eq!(stream_len(&store, &id).await, 0);
+    assert!(outbox(&pool).await.is_empty());
+
+    tx.commit().await.unwrap();
+    assert_eq!(stream_len(&store, &id).await, 2);
+    assert_eq!(outbox(&pool).await, ["order-1 placed"]);
+}
+
+#[tokio::test]
+async fn rollback_discards_events_with_caller_writes() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let id = sk("order-1");
+
+    let mut tx = pool.begin().await.unwrap();
+    write_outbox(&mut tx, "order-1 placed").await;
+    store
+        .append_in_tx(&mut tx, &id, None, &envelopes(1, 2))
+        .await
+        .unwrap();
+    tx.rollback().await.unwrap();
+
+    assert_eq!(stream_len(&store, &id).await, 0);
+    assert!(outbox(&pool).await.is_empty());
+    // The stream is still fresh: a plain append from `None` succeeds.
+    store.append(&id, None, &envelopes(1, 1)).await.unwrap();
+}
+
+#[tokio::test]
+async fn several_streams_append_in_one_transaction() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let mut tx = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut tx, &sk("a"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    store
+        .append_in_tx(&mut tx, &sk("b"), None, &envelopes(1, 2))
+        .await
+        .unwrap();
+    // A second append to "a" sees the first one's staged head.
+    store
+        .append_in_tx(&mut tx, &sk("a"), Version::new(1), &envelopes(2, 2))
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(stream_len(&store, &sk("a")).await, 2);
+    assert_eq!(stream_len(&store, &sk("b")).await, 2);
+}
+
+// ---------------------------------------------------------------------------
+// 2. Conflicts
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn conflict_is_reported_as_append_reports_it() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let id = sk("order-1");
+    store.append(&id, None, &envelopes(1, 2)).await.unwrap();
+
+    let plain = store.append(&id, None, &envelopes(1, 1)).await.unwrap_err();
+    let mut tx = pool.begin().await.unwrap();
+    let in_tx = store
+        .append_in_tx(&mut tx, &id, None, &envelopes(1, 1))
+        .await
+        .unwrap_err();
+
+    match (plain, in_tx) {
+        (
+            AppendError::Conflict {
+                stream_id: a_id,
+                expected: a_expected,
+                actual: a_actual,
+            },
+            AppendError::Conflict {
+                stream_id: b_id,
+                expected: b_expected,
+                actual: b_actual,
+            },
+        ) => {
+            assert_eq!(a_id.to_string(), b_id.to_string());
+            assert_eq!((a_expected, a_actual), (b_expected, b_actual));
+            assert_eq!(b_actual, Version::new(2));
+        }
+        other => panic!("expected two conflicts, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn failed_append_leaves_the_transaction_usable() {
+    // The append runs under a savepoint. A racing writer makes its INSERT hit
+    // a unique violation — an error postgres would otherwise let abort the
+    // whole transaction — yet the caller's earlier and later writes commit.
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let id = sk("order-1");
+    store.append(&id, None, &envelopes(1, 1)).await.unwrap();
+
+    // The racer stages v2 but doesn't commit yet.
+    let mut racer = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut racer, &id, Version::new(1), &envelopes(2, 2))
+        .await
+        .unwrap();
+
+    let mut tx = pool.begin().await.unwrap();
+    write_outbox(&mut tx, "before").await;
+    // Our append reads head 1, then blocks on the racer's v2 until it
+    // commits, and fails with the unique violation.
+    let commit_racer = async {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        racer.commit().await.unwrap();
+    };
+    let ours = envelopes(2, 2);
+    let (result, ()) = tokio::join!(
+        store.append_in_tx(&mut tx, &id, Version::new(1), &ours),
+        commit_racer
+    );
+    assert!(matches!(
+        result,
+        Err(AppendError::Conflict { actual: None, .. })
+    ));
+
+    write_outbox(&mut tx, "after").await;
+    store
+        .append_in_tx(&mut tx, &id, Version::new(2), &envelopes(3, 3))
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(outbox(&pool).await, ["after", "before"]);
+    assert_eq!(stream_len(&store, &id).await, 3);
+}
+
+// ---------------------------------------------------------------------------
+// 3. Wake-ups
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn notify_fires_on_commit_not_before() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let mut listener = listen(&pool).await;
+
+    let mut tx = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut tx, &sk("order-1"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    assert!(
+        tokio::time::timeout(NOTIFY_WAIT, listener.recv())
+            .await
+            .is_err(),
+        "no wake while the transaction is open"
+    );
+
+    tx.commit().await.unwrap();
+    let notification = tokio::time::timeout(NOTIFY_WAIT, listener.recv())
+        .await
+        .expect("wake after commit")
+        .unwrap();
+    // The payload is the hex-encoded stream id.
+    assert_eq!(notification.payload(), "6f726465722d31");
+}
+
+#[tokio::test]
+async fn rolled_back_append_never_notifies() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let mut listener = listen(&pool).await;
+
+    let mut tx = pool.begin().await.unwrap();
+    store
+        .append_in_tx(&mut tx, &sk("order-1"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    tx.rollback().await.unwrap();
+
+    assert!(
+        tokio::time::timeout(NOTIFY_WAIT, listener.recv())
+            .await
+            .is_err()
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 4. Repository `save_in_tx`
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Added(u8);
+
+impl Message for Added {}
+
+impl DomainEvent for Added {
+    fn name(&self) -> &'static str {
+        "Added"
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Total(u64);
+
+impl AggregateState for Total {
+    type Event = Added;
+
+    fn initial() -> Self {
+        Self::default()
+    }
+
+    fn apply(self, event: &Added) -> Self {
+        Self(self.0 + u64::from(event.0))
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct TotalId(String);
+
+impl fmt::Display for TotalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<[u8]> for TotalId {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl Id for TotalId {
+    const BYTE_LEN: usize = 0;
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("never")]
+struct Never;
+
+struct Counter;
+
+impl Aggregate for Counter {
+    type State = Total;
+    type Error = Never;
+    type Id = TotalId;
+}
+
+/// Events as one byte.
+struct OneByte;
+
+impl Encode<Added> for OneByte {
+    type Error = std::io::Error;
+
+    fn encode(&self, event: &Added) -> Result<bytes::Bytes, Self::Error> {
+        Ok(bytes::Bytes::from(vec![event.0]))
+    }
+}
+
+impl Decode<Added> for OneByte {
+    type Output<'a> = Added;
+    type Error = std::io::Error;
+
+    fn decode<'a>(&'a self, env: &'a PersistedEnvelope) -> Result<Added, Self::Error> {
+        env.payload()
+            .first()
+            .copied()
+            .map(Added)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::InvalidData))
+    }
+}
+
+#[tokio::test]
+async fn save_in_tx_commits_with_caller_writes() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let repo = Store::new(store)
+        .repository::<Counter>()
+        .codec(OneByte)
+        .build();
+    let id = TotalId("total-1".into());
+
+    let mut root = repo.load(id.clone()).await.unwrap();
+    let mut tx = pool.begin().await.unwrap();
+    let mut added = Events::<_, 1>::new(Added(2));
+    added.add(Added(3));
+    repo.save_in_tx(&mut tx, &mut root, &added).await.unwrap();
+    write_outbox(&mut tx, "total-1 added").await;
+    assert_eq!(root.version(), Version::new(2));
+    assert_eq!(root.state(), &Total(5));
+    tx.commit().await.unwrap();
+
+    let loaded = repo.load(id).await.unwrap();
+    assert_eq!(loaded.state(), &Total(5));
+    assert_eq!(outbox(&pool).await, ["total-1 added"]);
+}
+
+#[tokio::test]
+async fn save_in_tx_after_rollback_conflicts_instead_of_losing_data() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let repo = Store::new(store)
+        .repository::<Counter>()
+        .codec(OneByte)
+        .build();
+    let id = TotalId("total-1".into());
+
+    let mut root = repo.load(id.clone()).await.unwrap();
+    let mut tx = pool.begin().await.unwrap();
+    repo.save_in_tx(&mut tx, &mut root, &Events::<_, 0>::new(Added(1)))
+        .await
+        .unwrap();
+    tx.rollback().await.unwrap();
+
+    // `root` is now ahead of the store; saving from it conflicts.
+    let err = repo
+        .save(&mut root, &Events::<_, 0>::new(Added(1)))
+        .await
+        .unwrap_err();
+    assert!(err.is_conflict());
+
+    let mut fresh = repo.load(id).await.unwrap();
+    assert_eq!(fresh.version(), None);
+    repo.save(&mut fresh, &Events::<_, 0>::new(Added(1)))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn save_in_tx_conflict_maps_like_save() {
+    let Some((store, pool)) = setup().await else {
+        return;
+    };
+    let repo = Store::new(store)
+        .repository::<Counter>()
+        .codec(OneByte)
+        .build();
+    let id = TotalId("total-1".into());
+    let mut first = repo.load(id.clone()).await.unwrap();
+    let mut second = repo.load(id).await.unwrap();
+    repo.save(&mut first, &Events::<_, 0>::new(Added(1)))
+        .await
+        .unwrap();
+
+    let mut tx = pool.begin().await.unwrap();
+    let err = repo
+        .save_in_tx(&mut tx, &mut second, &Events::<_, 0>::new(Added(1)))
+        .await
+        .unwrap_err();
+    assert!(err.is_conflict());
+    assert_eq!(second.version(), None, "a failed save does not advance");
+}
@@ -0,0 +1,286 @@
+//! Wake strategies: a polling store wakes its subscribers for events written
+//! by other connections with no `LISTEN` at all, a hybrid store keeps waking
+//! while its listener is down, and [`PostgresStore::listener_health`] tracks
+//! the listener through a dropped connection.
+//!
+//! Each test works in its own schema and notify channel, dropped up front.
+//!
+//! # Skip-without-DATABASE_URL
+//!
+//! Every test calls [`pool`] first. If `DATABASE_URL` is unset, `pool`
+//! returns `None` and the test body returns immediately — the test *passes*.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+#![allow(clippy::panic, reason = "tests")]
+
+use std::time::Duration;
+
+use nexus::Version;
+use nexus_postgres::{ListenerHealth, PollInterval, PostgresStore, WakeStrategy};
+use nexus_store::envelope::pending_envelope;
+use nexus_store::store::RawEventStore;
+use nexus_store::wake::{WakeRegistration, WakeSource};
+use nexus_store::{PendingEnvelope, StreamKey};
+use sqlx::PgPool;
+
+/// How long to wait for a wake that should arrive; several poll intervals.
+const WAKE_WAIT: Duration = Duration::from_secs(2);
+
+/// How long to wait for the listener to reach a given health.
+const HEALTH_WAIT: Duration = Duration::from_secs(5);
+
+/// Fast polling, so the tests need not wait out the default back-off.
+const fn fast() -> PollInterval {
+    PollInterval::new(Duration::from_millis(10), Duration::from_millis(100)).unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// Test infrastructure
+// ---------------------------------------------------------------------------
+
+/// Connect and drop `schema` so the test starts from nothing.
+///
+/// Returns `None` (skip) if `DATABASE_URL` is unset.
+async fn pool(schema: &str) -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .connect(&url)
+        .await
+        .expect("connect pool");
+    sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&pool)
+        .await
+        .expect("drop schema");
+    Some(pool)
+}
+
+/// A store in `schema`, notifying on `channel`.
+async fn store(
+    pool: &PgPool,
+    schema: &str,
+    channel: &str,
+    strategy: WakeStrategy,
+) -> PostgresStore {
+    PostgresStore::builder()
+        .schema(schema)
+        .notify_channel(channel)
+        .wake_strategy(strategy)
+        .open(pool.clone())
+        .await
+        .expect("open store")
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+/// Insert one event row with plain SQL — no `NOTIFY`, as a writer outside
+/// nexus would.
+async fn insert_raw(pool: &PgPool, schema: &str, stream: &str, version: i64) {
+    sqlx::query(&format!(
+        "INSERT INTO {schema}.events (stream_id, version, event_type, schema_version, payload) \
+         VALUES ($1, $2, 'TestEvent', 1, $3)"
+    ))
+    .bind(stream.as_bytes())
+    .bind(version)
+    .bind(b"raw".as_slice())
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Wait until `store`'s listener reports `health`.
+async fn await_health(store: &PostgresStore, health: ListenerHealth) {
+    tokio::time::timeout(HEALTH_WAIT, async {
+        while store.listener_health() != health {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("listener never reached {health:?}"));
+}
+
+/// Terminate every backend listening on `channel`.
+async fn kill_listeners(pool: &PgPool, channel: &str) -> i64 {
+    sqlx::query_scalar(
+        "SELECT count(pg_terminate_backend(pid)) FROM pg_stat_activity \
+         WHERE query = 'LISTEN \"' || $1 || '\"' OR query = 'LISTEN ' || $1",
+    )
+    .bind(channel)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// 1. Poll
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn a_polling_store_runs_no_listener() {
+    let Some(pool) = pool("wake_poll_health").await else {
+        return;
+    };
+    let store = store(
+        &pool,
+        "wake_poll_health",
+        "wake_poll_health_events",
+        WakeStrategy::Poll(fast()),
+    )
+    .await;
+    assert_eq!(store.wake_strategy(), WakeStrategy::Poll(fast()));
+    assert_eq!(store.listener_health(), ListenerHealth::Disabled);
+}
+
+#[tokio::test]
+async fn polling_wakes_for_another_stores_append_without_a_shared_channel() {
+    let Some(pool) = pool("wake_poll").await else {
+        return;
+    };
+    let watcher = store(
+        &pool,
+        "wake_poll",
+        "wake_poll_watcher",
+        WakeStrategy::Poll(fast()),
+    )
+    .await;
+    // A different channel: its NOTIFYs never reach the watcher.
+    let writer = store(&pool, "wake_poll", "wake_poll_writer", WakeStrategy::Listen).await;
+    let id = sk("order-1");
+    let stream = watcher.register(Some(id.as_bytes())).unwrap();
+    let all = watcher.register(None).unwrap();
+
+    let (stream_woken, all_woken) = (stream.arm(), all.arm());
+    writer.append(&id, None, &envelopes(1, 2)).await.unwrap();
+    tokio::time::timeout(WAKE_WAIT, stream_woken)
+        .await
+        .expect("the poller wakes the stream");
+    tokio::time::timeout(WAKE_WAIT, all_woken)
+        .await
+        .expect("the poller wakes $all");
+}
+
+#[tokio::test]
+async fn polling_wakes_for_rows_written_outside_nexus() {
+    let Some(pool) = pool("wake_poll_raw").await else {
+        return;
+    };
+    let watcher = store(
+        &pool,
+        "wake_poll_raw",
+        "wake_poll_raw_events",
+        WakeStrategy::Poll(fast()),
+    )
+    .await;
+    let registration = watcher.register(Some(b"external".as_slice())).unwrap();
+
+    for version in 1..=3 {
+        let woken = registration.arm();
+        insert_raw(&pool, "wake_poll_raw", "external", version).await;
+        tokio::time::timeout(WAKE_WAIT, woken)
+            .await
+            .unwrap_or_else(|_| panic!("no wake for version {version}"));
+    }
+}
+
+#[tokio::test]
+async fn a_polling_stores_own_append_wakes_it_at_once() {
+    let Some(pool) = pool("wake_poll_local").await else {
+        return;
+    };
+    // An idle poller backs off to a minute; only the in-process wake is fast.
+    let slow = PollInterval::new(Duration::from_mins(1), Duration::from_mins(1)).unwrap();
+    let store = store(
+        &pool,
+        "wake_poll_local",
+        "wake_poll_local_events",
+        WakeStrategy::Poll(slow),
+    )
+    .await;
+    let id = sk("order-1");
+    let woken = store.register(Some(id.as_bytes())).unwrap().arm();
+    store.append(&id, None, &envelopes(1, 1)).await.unwrap();
+    tokio::time::timeout(Duration::from_millis(500), woken)
+        .await
+        .expect("an append wakes the appending store in-process");
+}
+
+// ---------------------------------------------------------------------------
+// 2. Listener health and Hybrid
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn a_listening_store_reports_its_listener_up() {
+    let Some(pool) = pool("wake_listen_health").await else {
+        return;
+    };
+    let store = store(
+        &pool,
+        "wake_listen_health",
+        "wake_listen_health_events",
+        WakeStrategy::Listen,
+    )
+    .await;
+    assert_eq!(store.wake_strategy(), WakeStrategy::default());
+    await_health(&store, ListenerHealth::Listening).await;
+}
+
+#[tokio::test]
+async fn hybrid_polling_catches_rows_that_send_no_notify() {
+    let Some(pool) = pool("wake_hybrid").await else {
+        return;
+    };
+    let watcher = store(
+        &pool,
+        "wake_hybrid",
+        "wake_hybrid_events",
+        WakeStrategy::Hybrid(fast()),
+    )
+    .await;
+    await_health(&watcher, ListenerHealth::Listening).await;
+    let woken = watcher.register(None).unwrap().arm();
+    insert_raw(&pool, "wake_hybrid", "external", 1).await;
+    tokio::time::timeout(WAKE_WAIT, woken)
+        .await
+        .expect("the safety-net poll wakes $all");
+}
+
+#[tokio::test]
+async fn a_dropped_listener_is_reported_and_reconnected() {
+    let Some(pool) = pool("wake_reconnect").await else {
+        return;
+    };
+    let channel = "wake_reconnect_events";
+    let watcher = store(&pool, "wake_reconnect", channel, WakeStrategy::Listen).await;
+    let writer = store(&pool, "wake_reconnect", channel, WakeStrategy::Listen).await;
+    await_health(&watcher, ListenerHealth::Listening).await;
+    await_health(&writer, ListenerHealth::Listening).await;
+
+    assert!(
+        kill_listeners(&pool, channel).await > 0,
+        "found the listeners"
+    );
+    await_health(&watcher, ListenerHealth::Down).await;
+    await_health(&watcher, ListenerHealth::Listening).await;
+
+    let id = sk("order-1");
+    let woken = watcher.register(Some(id.as_bytes())).unwrap().arm();
+    writer.append(&id, None, &envelopes(1, 1)).await.unwrap();
+    tokio::time::timeout(WAKE_WAIT, woken)
+        .await
+        .expect("the reconnected listener wakes again");
+}
@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use nexus::ErrorId;
+use rusqlite::Connection;
+
+use crate::error::SqliteError;
+use crate::migrate;
+use crate::store::SqliteStore;
+
+/// How long a connection waits on another process's lock before giving up
+/// with `SQLITE_BUSY`, unless [`busy_timeout`](SqliteStoreBuilder::busy_timeout)
+/// says otherwise.
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// When `SQLite` syncs the write-ahead log to disk.
+///
+/// Maps onto `PRAGMA synchronous`. Either way a crash of the *process* never
+/// loses a committed append; the difference is a power loss or OS crash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Synchronous {
+    /// Sync the WAL on every commit: a committed append survives power loss.
+    #[default]
+    Full,
+    /// Sync only at checkpoints. Commits are cheaper — worth it on flash
+    /// storage that wears with every sync — but a power loss may roll back
+    /// the last few commits. The file is never corrupted.
+    Normal,
+}
+
+impl Synchronous {
+    const fn pragma(self) -> &'static str {
+        match self {
+            Self::Full => "FULL",
+            Self::Normal => "NORMAL",
+        }
+    }
+}
+
+/// Builder for [`SqliteStore`].
+///
+/// ```ignore
+/// let store = SqliteStore::builder("/var/lib/app/events.db")
+///     .synchronous(Synchronous::Normal)
+///     .open()?;
+/// ```
+///
+/// Opening creates the file if absent, switches it to WAL mode, and runs its
+/// pending schema migrations.
+#[derive(Debug, Clone)]
+pub struct SqliteStoreBuilder {
+    path: PathBuf,
+    busy_timeout: Duration,
+    synchronous: Synchronous,
+}
+
+impl SqliteStoreBuilder {
+    /// Create a new builder targeting the given database file.
+    pub(crate) fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            busy_timeout: DEFAULT_BUSY_TIMEOUT,
+            synchronous: Synchronous::default(),
+        }
+    }
+
+    /// How long to wait on a lock held by another process sharing the file
+    /// before failing with `SQLITE_BUSY`. Defaults to [`DEFAULT_BUSY_TIMEOUT`].
+    #[must_use]
+    pub const fn busy_timeout(mut self, timeout: Duration) -> Self {
+        self.busy_timeout = timeout;
+        self
+    }
+
+    /// When commits reach the disk. Defaults to [`Synchronous::Full`].
+    #[must_use]
+    pub const fn synchronous(mut self, mode: Synchronous) -> Self {
+        self.synchronous = mode;
+        self
+    }
+
+    /// Open (or create) the database file and bring its schema up to date.
+    ///
+    /// Blocks on file I/O — call it at startup, or from `spawn_blocking`.
+    ///
+    /// # Errors
+    ///
+    /// [`SqliteError::Sqlite`] if the file cannot be opened or a migration
+    /// fails; [`SqliteError::WalUnavailable`] if it cannot run in WAL mode;
+    /// [`SqliteError::SchemaTooNew`] if the file was written by a newer build.
+    pub fn open(self) -> Result<SqliteStore, SqliteError> {
+        let mut writer = Connection::open(&self.path)?;
+        writer.busy_timeout(self.busy_timeout)?;
+        // WAL lets readers run alongside the one writer. `journal_mode`
+        // reports the mode it ended up in rather than failing, and it sticks
+        // to the file, so later connections inherit it.
+        let mode: String =
+            writer.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+        if !mode.eq_ignore_ascii_case("wal") {
+            return Err(SqliteError::WalUnavailable {
+                mode: ErrorId::from_display(&mode),
+            });
+        }
+        writer.pragma_update(None, "synchronous", self.synchronous.pragma())?;
+        migrate::migrate(&mut writer)?;
+        Ok(SqliteStore::assemble(self.path, self.busy_timeout, writer))
+    }
+}
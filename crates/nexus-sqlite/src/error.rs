@@ -0,0 +1,92 @@
+use nexus::ErrorId;
+use nexus_store::envelope::EnvelopeError;
+
+/// Errors produced by the `SQLite` event store adapter.
+///
+/// Distinct failure domains (CLAUDE rule 3); diagnostic fields use
+/// [`ErrorId`] to stay allocation-light on error paths.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SqliteError {
+    /// A `rusqlite` / `SQLite` I/O, locking, or query error.
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// A persisted row decoded into bytes that fail envelope validation.
+    #[error("envelope integrity error in stream '{stream_id}' at version {version}")]
+    EnvelopeCorrupt {
+        stream_id: ErrorId,
+        version: u64,
+        #[source]
+        source: EnvelopeError,
+    },
+
+    /// A stored row has an out-of-range / corrupt scalar (e.g. `global_seq` <= 0,
+    /// version <= 0, `schema_version` == 0).
+    #[error("corrupt row in stream '{stream_id}': {reason}")]
+    CorruptRow {
+        stream_id: ErrorId,
+        reason: ErrorId<128>,
+    },
+
+    /// Invalid input on the write path (e.g. a version past `i64::MAX`, or a
+    /// snapshot that does not cover the last appended event).
+    ///
+    /// Distinct from `CorruptRow`, which indicates already-persisted data is
+    /// unreadable. This error fires before anything is committed.
+    #[error("invalid input for stream '{stream_id}' at version {version}: {reason}")]
+    InvalidInput {
+        stream_id: ErrorId,
+        version: u64,
+        reason: ErrorId<128>,
+    },
+
+    /// Building the canonical wire frame for a row failed.
+    #[error("wire frame build failed in stream '{stream_id}' at version {version}: {reason}")]
+    Frame {
+        stream_id: ErrorId,
+        version: u64,
+        reason: ErrorId<128>,
+    },
+
+    /// The database file was written by a newer build: its schema is past
+    /// the migrations this build knows.
+    #[error("database schema is at migration {found}, this build knows up to {supported}")]
+    SchemaTooNew { found: u32, supported: u32 },
+
+    /// The database could not be switched to WAL mode — an in-memory
+    /// database, or a filesystem without shared-memory support. `mode` is the
+    /// journal mode `SQLite` kept.
+    #[error("cannot switch the database to WAL mode, it stays in '{mode}'")]
+    WalUnavailable { mode: ErrorId },
+
+    /// The blocking task running a `SQLite` call panicked or was cancelled.
+    #[error("sqlite worker task failed: {0}")]
+    Worker(#[source] tokio::task::JoinError),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    /// Verify `SqliteError` satisfies the bounds required by `RawEventStore::Error`.
+    #[test]
+    fn satisfies_error_send_sync_static() {
+        fn assert_bounds<T: std::error::Error + Send + Sync + 'static>() {}
+        assert_bounds::<SqliteError>();
+    }
+
+    /// Defensive boundary: an over-long stream id in the `ErrorId<64>` field
+    /// renders truncated and `…`-suffixed through the error's `Display`.
+    #[test]
+    fn corrupt_row_display_truncates_overlong_stream_id() {
+        let err = SqliteError::CorruptRow {
+            stream_id: ErrorId::from_display(&"s".repeat(200)),
+            reason: ErrorId::from_display(&"bad version"),
+        };
+        let msg = err.to_string();
+        assert!(msg.contains('…'), "display must signal truncation: {msg}");
+        assert!(!msg.contains(&"s".repeat(100)));
+    }
+}
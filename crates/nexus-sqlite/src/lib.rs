@@ -0,0 +1,49 @@
+//! `SQLite`-backed event store adapter for nexus.
+// `SqliteError` and `AppendError<SqliteError>` carry `ErrorId` array-strings
+// like the other adapters' errors. The large-Err lint fires at every return
+// site; suppress it crate-wide.
+#![allow(
+    clippy::result_large_err,
+    reason = "SqliteError is stack-allocated like FjallError and PostgresError"
+)]
+//!
+//! Implements [`RawEventStore`](nexus_store::RawEventStore) +
+//! [`WakeSource`](nexus_store::wake::WakeSource) over `rusqlite`, in one
+//! database file: an embedded store for edge devices and desktop apps that
+//! want a single file any `SQLite` client can open, where fjall's LSM
+//! directory is awkward. Each envelope field is its own column; reads rebuild
+//! the canonical [`wire`](nexus_store::wire) frame, so envelopes are
+//! byte-identical to the other adapters'.
+//!
+//! The file runs in WAL mode: one write connection serializes appends while
+//! pooled read connections page through committed events alongside it. Every
+//! `SQLite` call is blocking, so it runs on tokio's blocking pool. `$all`
+//! positions are the `global_seq` rowid, a plain scalar [`GlobalSeq`] —
+//! `SQLite`'s single writer commits them in order.
+//!
+//! Aggregate snapshots ([`SnapshotStore<Vec<u8>, Version>`](nexus_store::SnapshotStore),
+//! [`SnapshotAppend`](nexus_store::SnapshotAppend)) and projection checkpoints
+//! (`SnapshotStore<Vec<u8>, GlobalSeq>`) live in the same file.
+//!
+//! The schema evolves through embedded, versioned migrations applied on open
+//! and recorded in the file's `user_version`.
+//!
+//! Wakes are in-process, as with fjall: a subscriber is woken by appends made
+//! through its own store, not by another process writing the same file.
+//!
+//! With the `export` / `import` features, [`StreamLister`](nexus_store::export::StreamLister)
+//! pages stream ids by keyset and [`AtomicAppend`](nexus_store::import::AtomicAppend)
+//! applies a whole import batch in one transaction.
+
+mod builder;
+mod error;
+mod migrate;
+mod position;
+mod snapshot;
+mod store;
+
+pub use builder::{DEFAULT_BUSY_TIMEOUT, SqliteStoreBuilder, Synchronous};
+pub use error::SqliteError;
+pub use migrate::SCHEMA_VERSION;
+pub use position::GlobalSeq;
+pub use store::SqliteStore;
@@ -0,0 +1,143 @@
+//! Versioned schema migrations for [`SqliteStore`](crate::SqliteStore).
+//!
+//! The schema is an ordered list of embedded [`Migration`]s, numbered from 1.
+//! The database file records the last one applied in `SQLite`'s own
+//! `PRAGMA user_version` header field, so a file opened by a newer build
+//! gets exactly the migrations it is missing.
+//!
+//! Opening applies every pending migration and the new `user_version` in one
+//! `BEGIN IMMEDIATE` transaction: it takes `SQLite`'s write lock up front, so a
+//! second process opening the same file waits, then finds nothing left to do.
+//! DDL is transactional in `SQLite`, so a failed migration leaves the file as it
+//! was. A file already past [`SCHEMA_VERSION`] was written by a newer build
+//! and is refused with [`SqliteError::SchemaTooNew`].
+//!
+//! Migrations are plain, forward-only DDL and must never be edited once
+//! released — append a new one instead.
+
+use rusqlite::{Connection, TransactionBehavior};
+
+use crate::error::SqliteError;
+
+/// One embedded schema change.
+struct Migration {
+    version: u32,
+    sql: &'static str,
+}
+
+/// Every migration, in the order they apply. Append only.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        sql: EVENTS,
+    },
+    Migration {
+        version: 2,
+        sql: STATE,
+    },
+];
+
+/// The schema version this build requires: the last migration's number.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// The events table.
+///
+/// - `global_seq INTEGER PRIMARY KEY` aliases the rowid, so `$all` scans
+///   walk the table in its own b-tree order. `AUTOINCREMENT` stops a value
+///   from ever being handed out twice (see [`GlobalSeq`](crate::GlobalSeq)).
+/// - `UNIQUE (stream_id, version)` is both the per-stream read index and the
+///   backstop against a duplicate version.
+/// - Every envelope field is its own column, so the file reads with any
+///   `SQLite` client. `schema_version` holds the full `u32` range.
+const EVENTS: &str = "
+CREATE TABLE events (
+    global_seq     INTEGER PRIMARY KEY AUTOINCREMENT,
+    stream_id      BLOB    NOT NULL,
+    version        INTEGER NOT NULL CHECK (version > 0),
+    event_type     TEXT    NOT NULL,
+    schema_version INTEGER NOT NULL CHECK (schema_version > 0),
+    payload        BLOB    NOT NULL,
+    metadata       BLOB,
+    UNIQUE (stream_id, version)
+);
+";
+
+/// Aggregate snapshots and projection checkpoints: one row per id, holding
+/// state, position and schema version together.
+const STATE: &str = "
+CREATE TABLE snapshots (
+    id             BLOB    PRIMARY KEY,
+    schema_version INTEGER NOT NULL,
+    version        INTEGER NOT NULL,
+    state          BLOB    NOT NULL
+) WITHOUT ROWID;
+
+CREATE TABLE checkpoints (
+    id             BLOB    PRIMARY KEY,
+    schema_version INTEGER NOT NULL,
+    global_seq     INTEGER NOT NULL,
+    state          BLOB    NOT NULL
+) WITHOUT ROWID;
+";
+
+/// Apply every migration `conn`'s file is missing.
+///
+/// # Errors
+///
+/// [`SqliteError::SchemaTooNew`] if the file is past [`SCHEMA_VERSION`];
+/// [`SqliteError::Sqlite`] if a migration or the version bump fails, in which
+/// case nothing is applied.
+pub fn migrate(conn: &mut Connection) -> Result<(), SqliteError> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let applied: u32 = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if applied > SCHEMA_VERSION {
+        return Err(SqliteError::SchemaTooNew {
+            found: applied,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.version > applied) {
+        tx.execute_batch(migration.sql)?;
+    }
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    fn user_version(conn: &Connection) -> u32 {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrations_are_numbered_from_one_without_gaps() {
+        for (expected, migration) in (1..).zip(MIGRATIONS) {
+            assert_eq!(migration.version, expected);
+        }
+    }
+
+    #[test]
+    fn migrating_twice_is_a_no_op() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+        migrate(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn a_file_from_a_newer_build_is_refused() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(matches!(
+            migrate(&mut conn),
+            Err(SqliteError::SchemaTooNew { .. })
+        ));
+    }
+}
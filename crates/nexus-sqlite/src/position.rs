@@ -0,0 +1,77 @@
+//! `GlobalSeq` — the `SQLite` adapter's [`AllPosition`](nexus_store::AllPosition).
+//!
+//! The `events.global_seq` column: an `INTEGER PRIMARY KEY AUTOINCREMENT`
+//! `SQLite` assigns inside the append's write transaction. `SQLite` admits one
+//! writer at a time, so sequence numbers commit in the order they are handed
+//! out and a plain scalar is a gap-safe `$all` resume position — the same
+//! shape as fjall's, rather than postgres's commit-ordered composite.
+//!
+//! `AUTOINCREMENT` never reuses a value, even one whose row a rolled-back
+//! append or a manual delete removed. The sequence is **monotonic but not
+//! gapless**: consumers must tolerate gaps and never assume
+//! `next == prev + 1`. The column is a signed 64-bit integer, so positions
+//! stop at `i64::MAX`.
+
+use core::fmt;
+use std::num::NonZeroU64;
+
+/// A store-local global sequence number (always >= 1). See the module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlobalSeq(NonZeroU64);
+
+impl GlobalSeq {
+    /// The first sequence number (1).
+    pub const INITIAL: Self = Self(NonZeroU64::MIN);
+
+    /// The next sequence number, or `None` on overflow at `u64::MAX`.
+    #[must_use]
+    pub const fn next(self) -> Option<Self> {
+        match self.0.checked_add(1) {
+            Some(n) => Some(Self(n)),
+            None => None,
+        }
+    }
+
+    /// The underlying integer value. Always >= 1.
+    #[must_use]
+    pub const fn as_u64(self) -> u64 {
+        self.0.get()
+    }
+
+    /// Construct from a `u64`; `None` if `v` is 0. Mirrors [`NonZeroU64::new`].
+    #[must_use]
+    pub const fn new(v: u64) -> Option<Self> {
+        match NonZeroU64::new(v) {
+            Some(nz) => Some(Self(nz)),
+            None => None,
+        }
+    }
+}
+
+impl fmt::Display for GlobalSeq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The `SQLite` adapter's `$all` resume position is its `global_seq` scalar.
+impl nexus_store::AllPosition for GlobalSeq {}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rejects_zero_and_round_trips_the_rest() {
+        assert_eq!(GlobalSeq::new(0), None);
+        assert_eq!(GlobalSeq::new(1), Some(GlobalSeq::INITIAL));
+        assert_eq!(GlobalSeq::new(u64::MAX).unwrap().as_u64(), u64::MAX);
+    }
+
+    #[test]
+    fn next_overflows_at_max() {
+        assert_eq!(GlobalSeq::INITIAL.next(), GlobalSeq::new(2));
+        assert_eq!(GlobalSeq::new(u64::MAX).unwrap().next(), None);
+    }
+}
@@ -0,0 +1,345 @@
+//! Snapshot and checkpoint persistence for [`SqliteStore`].
+//!
+//! Two [`SnapshotStore`] impls over two tables created by
+//! [migration 2](crate::migrate):
+//!
+//! - `SnapshotStore<Vec<u8>, Version>` — aggregate snapshots, in `snapshots`.
+//! - `SnapshotStore<Vec<u8>, GlobalSeq>` — projection checkpoints, in
+//!   `checkpoints`, so a projection's state lives in the same file as the
+//!   events it folds.
+//!
+//! Each id has one row holding state, position and schema version together,
+//! so a commit is a single upsert — atomic without an explicit transaction.
+//! The upsert is **monotonic**, as in the postgres adapter: its
+//! `ON CONFLICT … WHERE` only replaces a row whose position is at or before
+//! the incoming one, so a late commit of an older position is dropped rather
+//! than rolling a newer snapshot back. An equal position still replaces the
+//! row, which is how a migrated snapshot is rewritten under its new schema
+//! version. Only the latest snapshot per id is kept, so
+//! `hydrate_at_or_before` is the trait's latest-only default.
+//!
+//! [`SnapshotAppend`] runs the snapshot upsert in the append's own
+//! transaction, so an atomic-mode snapshot never lags its events.
+
+use std::num::NonZeroU32;
+
+use nexus::{ErrorId, Id, Version};
+use nexus_store::PendingEnvelope;
+use nexus_store::StreamKey;
+use nexus_store::error::AppendError;
+use nexus_store::state::{PendingSnapshot, SnapshotAppend, SnapshotBytes, SnapshotStore};
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::error::SqliteError;
+use crate::position::GlobalSeq;
+use crate::store::{SqliteStore, begin_write, corrupt, insert_events, store_err};
+
+const READ_SNAPSHOT: &str = "SELECT schema_version, version, state FROM snapshots WHERE id = ?1";
+
+const UPSERT_SNAPSHOT: &str = "INSERT INTO snapshots (id, schema_version, version, state) \
+     VALUES (?1, ?2, ?3, ?4) \
+     ON CONFLICT (id) DO UPDATE SET schema_version = excluded.schema_version, \
+     version = excluded.version, state = excluded.state \
+     WHERE snapshots.version <= excluded.version";
+
+const READ_CHECKPOINT: &str =
+    "SELECT schema_version, global_seq, state FROM checkpoints WHERE id = ?1";
+
+const UPSERT_CHECKPOINT: &str = "INSERT INTO checkpoints (id, schema_version, global_seq, state) \
+     VALUES (?1, ?2, ?3, ?4) \
+     ON CONFLICT (id) DO UPDATE SET schema_version = excluded.schema_version, \
+     global_seq = excluded.global_seq, state = excluded.state \
+     WHERE checkpoints.global_seq <= excluded.global_seq";
+
+/// A `snapshots` or `checkpoints` row as stored: schema version, position,
+/// state. The tables share a shape; only the position column differs.
+struct StateRow {
+    schema_version: i64,
+    position: i64,
+    state: Vec<u8>,
+}
+
+impl StateRow {
+    /// A row to write, rejecting a position past `i64::MAX` before anything
+    /// is written.
+    fn new(
+        schema_version: NonZeroU32,
+        position: u64,
+        state: Vec<u8>,
+        label: ErrorId,
+    ) -> Result<Self, SqliteError> {
+        Ok(Self {
+            schema_version: i64::from(schema_version.get()),
+            position: column_i64(position, label)?,
+            state,
+        })
+    }
+
+    /// Decode with `position` narrowing the stored position column.
+    fn decode<P>(
+        self,
+        label: ErrorId,
+        position: impl FnOnce(u64) -> Option<P>,
+    ) -> Result<(NonZeroU32, P, Vec<u8>), SqliteError> {
+        let schema_version = u32::try_from(self.schema_version)
+            .ok()
+            .and_then(NonZeroU32::new)
+            .ok_or_else(|| corrupt(label, "schema_version == 0 or out of range"))?;
+        let at = u64::try_from(self.position)
+            .ok()
+            .and_then(position)
+            .ok_or_else(|| corrupt(label, "snapshot position <= 0 or out of range"))?;
+        Ok((schema_version, at, self.state))
+    }
+}
+
+/// Narrow a position to its `INTEGER` column, rejecting values past
+/// `i64::MAX` before anything is written.
+fn column_i64(value: u64, label: ErrorId) -> Result<i64, SqliteError> {
+    i64::try_from(value).map_err(|_| SqliteError::InvalidInput {
+        stream_id: label,
+        version: value,
+        reason: ErrorId::from_display(&"position exceeds i64::MAX"),
+    })
+}
+
+fn read_row(conn: &Connection, sql: &str, id: &[u8]) -> Result<Option<StateRow>, SqliteError> {
+    let row = conn
+        .prepare_cached(sql)?
+        .query_row([id], |row| {
+            Ok(StateRow {
+                schema_version: row.get(0)?,
+                position: row.get(1)?,
+                state: row.get(2)?,
+            })
+        })
+        .optional()?;
+    Ok(row)
+}
+
+fn upsert(conn: &Connection, sql: &str, id: &[u8], row: &StateRow) -> rusqlite::Result<()> {
+    conn.prepare_cached(sql)?
+        .execute(params![id, row.schema_version, row.position, row.state])
+        .map(|_| ())
+}
+
+impl SqliteStore {
+    /// Read the row for `id` from the table `sql` selects from.
+    async fn read_state(
+        &self,
+        sql: &'static str,
+        id: &impl Id,
+    ) -> Result<Option<StateRow>, SqliteError> {
+        let key = id.as_ref().to_vec();
+        self.on_reader(move |conn| read_row(conn, sql, &key)).await
+    }
+
+    /// Upsert `row` for `id` through `sql`, on the writer.
+    async fn write_state(
+        &self,
+        sql: &'static str,
+        id: &impl Id,
+        row: StateRow,
+    ) -> Result<(), SqliteError> {
+        let key = id.as_ref().to_vec();
+        self.on_writer(move |conn| upsert(conn, sql, &key, &row))
+            .await?
+            .map_err(SqliteError::Sqlite)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Aggregate snapshots — `SnapshotStore<Vec<u8>, Version>`
+// ---------------------------------------------------------------------------
+
+impl SnapshotStore<Vec<u8>, Version> for SqliteStore {
+    type Error = SqliteError;
+
+    async fn hydrate(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> Result<Option<(Version, Vec<u8>)>, SqliteError> {
+        Ok(
+            <Self as SnapshotStore<Vec<u8>, Version>>::hydrate_with_schema(self, id)
+                .await?
+                .filter(|(stored, _, _)| *stored == schema_version)
+                .map(|(_, version, state)| (version, state)),
+        )
+    }
+
+    async fn commit(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: Version,
+        state: &Vec<u8>,
+    ) -> Result<(), SqliteError> {
+        let row = StateRow::new(
+            schema_version,
+            position.as_u64(),
+            state.clone(),
+            ErrorId::from_display(id),
+        )?;
+        self.write_state(UPSERT_SNAPSHOT, id, row).await
+    }
+
+    async fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> Result<Option<(NonZeroU32, Version, Vec<u8>)>, SqliteError> {
+        let label = ErrorId::from_display(id);
+        self.read_state(READ_SNAPSHOT, id)
+            .await?
+            .map(|row| row.decode(label, Version::new))
+            .transpose()
+    }
+}
+
+/// Defaults to copying the hydrated `Vec` — `SQLite` hands rows back as owned
+/// buffers, so there is nothing to share.
+impl SnapshotBytes<Version> for SqliteStore {}
+
+/// The snapshot upsert runs after the event inserts, in the append's own
+/// transaction: one commit, and no window in which the events are visible
+/// without the snapshot that covers them.
+impl SnapshotAppend for SqliteStore {
+    async fn append_with_snapshot(
+        &self,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+        snapshot: PendingSnapshot<'_>,
+    ) -> Result<(), AppendError<SqliteError>> {
+        let label = ErrorId::from_display(id);
+        let version = snapshot.version().as_u64();
+        let row = StateRow::new(
+            snapshot.schema_version(),
+            version,
+            snapshot.state().to_vec(),
+            label,
+        )
+        .map_err(AppendError::Store)?;
+        let (stream, batch) = (id.clone(), envelopes.to_vec());
+
+        self.on_writer(move |conn| {
+            let tx = begin_write(conn)?;
+            let head = insert_events(&tx, &stream, expected_version, &batch)?;
+            if version != head {
+                // Dropping `tx` rolls the inserts back.
+                return Err(AppendError::Store(SqliteError::InvalidInput {
+                    stream_id: label,
+                    version,
+                    reason: ErrorId::from_display(&"snapshot version is not the last appended"),
+                }));
+            }
+            upsert(&tx, UPSERT_SNAPSHOT, stream.as_bytes(), &row).map_err(store_err)?;
+            tx.commit().map_err(store_err)
+        })
+        .await
+        .map_err(AppendError::Store)??;
+
+        if !envelopes.is_empty() {
+            self.wake_committed(id.as_bytes());
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Projection checkpoints — `SnapshotStore<Vec<u8>, GlobalSeq>`
+// ---------------------------------------------------------------------------
+
+impl SnapshotStore<Vec<u8>, GlobalSeq> for SqliteStore {
+    type Error = SqliteError;
+
+    async fn hydrate(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> Result<Option<(GlobalSeq, Vec<u8>)>, SqliteError> {
+        Ok(
+            <Self as SnapshotStore<Vec<u8>, GlobalSeq>>::hydrate_with_schema(self, id)
+                .await?
+                .filter(|(stored, _, _)| *stored == schema_version)
+                .map(|(_, position, state)| (position, state)),
+        )
+    }
+
+    async fn commit(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: GlobalSeq,
+        state: &Vec<u8>,
+    ) -> Result<(), SqliteError> {
+        let row = StateRow::new(
+            schema_version,
+            position.as_u64(),
+            state.clone(),
+            ErrorId::from_display(id),
+        )?;
+        self.write_state(UPSERT_CHECKPOINT, id, row).await
+    }
+
+    async fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> Result<Option<(NonZeroU32, GlobalSeq, Vec<u8>)>, SqliteError> {
+        let label = ErrorId::from_display(id);
+        self.read_state(READ_CHECKPOINT, id)
+            .await?
+            .map(|row| row.decode(label, GlobalSeq::new))
+            .transpose()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for the column narrowing
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use super::*;
+
+    fn label() -> ErrorId {
+        ErrorId::from_display(&"snap")
+    }
+
+    #[test]
+    fn state_row_decodes_full_schema_range() {
+        let row = StateRow {
+            schema_version: i64::from(u32::MAX),
+            position: 7,
+            state: vec![1],
+        };
+        let (schema_version, version, state) = row.decode(label(), Version::new).unwrap();
+        assert_eq!(schema_version.get(), u32::MAX);
+        assert_eq!(version.as_u64(), 7);
+        assert_eq!(state, [1]);
+    }
+
+    #[test]
+    fn corrupt_state_rows_are_rejected() {
+        for (schema_version, position) in [(0, 1), (i64::from(u32::MAX) + 1, 1), (1, 0), (1, -1)] {
+            let row = StateRow {
+                schema_version,
+                position,
+                state: Vec::new(),
+            };
+            assert!(matches!(
+                row.decode(label(), GlobalSeq::new),
+                Err(SqliteError::CorruptRow { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn positions_past_i64_max_are_invalid_input() {
+        assert!(matches!(
+            column_i64(u64::MAX, label()),
+            Err(SqliteError::InvalidInput { .. })
+        ));
+        assert_eq!(column_i64(42, label()).unwrap(), 42);
+    }
+}
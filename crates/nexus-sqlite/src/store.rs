@@ -0,0 +1,939 @@
+use std::collections::VecDeque;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use nexus::{ErrorId, Version};
+use nexus_store::PendingEnvelope;
+use nexus_store::StreamKey;
+use nexus_store::batch::BatchSize;
+use nexus_store::envelope::PersistedEnvelope;
+use nexus_store::error::AppendError;
+use nexus_store::notify::{NotifyError, StreamNotifiers, WakeReg};
+use nexus_store::store::RawEventStore;
+use nexus_store::value::{EventType, Metadata, Payload, SchemaVersion};
+use nexus_store::wake::WakeSource;
+use nexus_store::wire;
+use parking_lot::Mutex;
+use rusqlite::{Connection, Row, TransactionBehavior, params};
+
+use crate::builder::SqliteStoreBuilder;
+use crate::error::SqliteError;
+use crate::position::GlobalSeq;
+
+/// Idle read connections kept for reuse. Reads beyond this many at once
+/// open a connection of their own and close it when done.
+const MAX_IDLE_READERS: usize = 4;
+
+const CURRENT_VERSION: &str = "SELECT COALESCE(MAX(version), 0) FROM events WHERE stream_id = ?1";
+
+const INSERT_EVENT: &str = "INSERT INTO events \
+     (stream_id, version, event_type, schema_version, payload, metadata) \
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
+
+const STREAM_PAGE: &str = "SELECT version, event_type, schema_version, payload, metadata \
+     FROM events WHERE stream_id = ?1 AND version >= ?2 \
+     ORDER BY version LIMIT ?3";
+
+const ALL_PAGE: &str = "SELECT version, event_type, schema_version, payload, metadata, \
+     global_seq FROM events WHERE global_seq > ?1 AND global_seq <= ?2 \
+     ORDER BY global_seq LIMIT ?3";
+
+const LAST_SEQ: &str = "SELECT COALESCE(MAX(global_seq), 0) FROM events";
+
+/// Shared, `Arc`-owned interior of a [`SqliteStore`].
+///
+/// `SQLite` admits one writer at a time, so the store keeps exactly one write
+/// connection behind a lock; appends queue on it rather than on `SQLite`'s
+/// `SQLITE_BUSY` retry loop. Reads use their own connections — in WAL mode
+/// they run alongside the writer and see the last committed state — pooled
+/// up to [`MAX_IDLE_READERS`].
+struct Inner {
+    path: PathBuf,
+    busy_timeout: Duration,
+    writer: Mutex<Connection>,
+    readers: Mutex<Vec<Connection>>,
+    /// In-process wake registry, woken after every commit.
+    notifiers: Arc<StreamNotifiers>,
+}
+
+impl Inner {
+    /// A read connection: an idle one from the pool, or a new one.
+    fn checkout_reader(&self) -> Result<Connection, SqliteError> {
+        let idle = self.readers.lock().pop();
+        if let Some(conn) = idle {
+            return Ok(conn);
+        }
+        let conn = Connection::open(&self.path)?;
+        conn.busy_timeout(self.busy_timeout)?;
+        // Reads only: a write through this connection is a bug, not a race.
+        conn.pragma_update(None, "query_only", true)?;
+        Ok(conn)
+    }
+
+    fn checkin_reader(&self, conn: Connection) {
+        let mut idle = self.readers.lock();
+        if idle.len() < MAX_IDLE_READERS {
+            idle.push(conn);
+        }
+    }
+
+    /// Run `f` on a read connection, returning it to the pool afterwards.
+    fn with_reader<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, SqliteError>,
+    ) -> Result<T, SqliteError> {
+        let conn = self.checkout_reader()?;
+        let out = f(&conn);
+        self.checkin_reader(conn);
+        out
+    }
+}
+
+/// `SQLite`-backed event store.
+///
+/// Implements [`RawEventStore`] and [`WakeSource`], plus
+/// [`SnapshotStore`](nexus_store::SnapshotStore) for aggregate snapshots and
+/// projection checkpoints, in the same file. Constructed via
+/// [`builder`](Self::builder).
+///
+/// Every `SQLite` call is blocking file I/O, so each runs on tokio's blocking
+/// pool (`spawn_blocking`) and never stalls the async executor.
+///
+/// Clone is cheap: it is a single `Arc` bump over the shared `Inner`. All
+/// clones share one write connection, one reader pool and one wake registry.
+///
+/// Reads page lazily: `read_stream` and `read_all` hold at most
+/// [`batch_size`](Self::batch_size) rows at a time.
+#[derive(Clone)]
+pub struct SqliteStore {
+    inner: Arc<Inner>,
+    batch_size: BatchSize,
+}
+
+impl SqliteStore {
+    /// Start building a store backed by the database file at `path`.
+    pub fn builder(path: impl AsRef<Path>) -> SqliteStoreBuilder {
+        SqliteStoreBuilder::new(path)
+    }
+
+    /// Wrap an opened, migrated write connection. Called by the
+    /// [`builder`](crate::builder).
+    pub(crate) fn assemble(path: PathBuf, busy_timeout: Duration, writer: Connection) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                path,
+                busy_timeout,
+                writer: Mutex::new(writer),
+                readers: Mutex::new(Vec::new()),
+                notifiers: StreamNotifiers::new(),
+            }),
+            batch_size: BatchSize::DEFAULT,
+        }
+    }
+
+    /// Set how many rows `read_stream` and `read_all` fetch per page
+    /// ([`BatchSize::DEFAULT`] unless set). A read holds at most one page in
+    /// memory and fetches the next when it drains.
+    #[must_use]
+    pub const fn with_batch_size(mut self, batch_size: BatchSize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// The configured read page size.
+    #[must_use]
+    pub const fn batch_size(&self) -> BatchSize {
+        self.batch_size
+    }
+
+    /// The database file this store reads and writes.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Run `f` on the write connection, on the blocking pool.
+    ///
+    /// The outer `Err` is a failed worker task; `f`'s own result comes back
+    /// untouched.
+    pub(crate) async fn on_writer<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> T + Send + 'static,
+    ) -> Result<T, SqliteError> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || f(&mut inner.writer.lock()))
+            .await
+            .map_err(SqliteError::Worker)
+    }
+
+    /// Run `f` on a pooled read connection, on the blocking pool.
+    pub(crate) async fn on_reader<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, SqliteError> + Send + 'static,
+    ) -> Result<T, SqliteError> {
+        read_blocking(Arc::clone(&self.inner), f).await
+    }
+
+    /// Wake this process's subscribers to `stream` (and `$all`). Called only
+    /// after a durable commit (`WakeSource` contract).
+    pub(crate) fn wake_committed(&self, stream: &[u8]) {
+        self.inner.notifiers.wake(stream);
+    }
+}
+
+/// [`SqliteStore::on_reader`] for the lazy read streams, which hold the
+/// [`Inner`] rather than a store.
+async fn read_blocking<T: Send + 'static>(
+    inner: Arc<Inner>,
+    f: impl FnOnce(&Connection) -> Result<T, SqliteError> + Send + 'static,
+) -> Result<T, SqliteError> {
+    tokio::task::spawn_blocking(move || inner.with_reader(f))
+        .await
+        .map_err(SqliteError::Worker)?
+}
+
+// ---------------------------------------------------------------------------
+// Per-event columns that reconstitute a `PersistedEnvelope`.
+// ---------------------------------------------------------------------------
+
+/// The per-event columns that reconstitute a [`PersistedEnvelope`].
+///
+/// No `global_seq` — it is an `$all`-position concern, not an envelope one.
+/// The `$all` read pulls it as a sixth column to build the [`GlobalSeq`] tag.
+struct EventRow {
+    version: i64,
+    event_type: String,
+    schema_version: i64,
+    payload: Vec<u8>,
+    metadata: Option<Vec<u8>>,
+}
+
+impl EventRow {
+    /// Read the five envelope columns, in [`STREAM_PAGE`] order.
+    fn read(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            version: row.get(0)?,
+            event_type: row.get(1)?,
+            schema_version: row.get(2)?,
+            payload: row.get(3)?,
+            metadata: row.get(4)?,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pure helpers — no IO
+// ---------------------------------------------------------------------------
+
+/// Rebuild the canonical, 16-byte-aligned V2 wire frame from a row, so a
+/// `SQLite` [`PersistedEnvelope`] is byte-identical to a fjall or postgres one.
+///
+/// `stream_label` is a diagnostic copy of the stream id for error messages.
+fn row_to_envelope(row: EventRow, stream_label: ErrorId) -> Result<PersistedEnvelope, SqliteError> {
+    let version = u64::try_from(row.version)
+        .ok()
+        .and_then(Version::new)
+        .ok_or_else(|| corrupt(stream_label, "version <= 0 or out of range"))?;
+
+    let schema = u32::try_from(row.schema_version)
+        .ok()
+        .and_then(NonZeroU32::new)
+        .map(SchemaVersion::new)
+        .ok_or_else(|| corrupt(stream_label, "schema_version == 0 or out of range"))?;
+
+    let event_type = EventType::from_bytes(Bytes::from(row.event_type))
+        .map_err(|_| corrupt(stream_label, "event_type invalid"))?;
+    let payload = Payload::from_bytes(Bytes::from(row.payload))
+        .map_err(|_| corrupt(stream_label, "payload too large"))?;
+    let metadata = row
+        .metadata
+        .map(|m| Metadata::from_bytes(Bytes::from(m)))
+        .transpose()
+        .map_err(|_| corrupt(stream_label, "metadata too large"))?;
+
+    let frame =
+        wire::encode_frame(schema, &event_type, &payload, metadata.as_ref()).map_err(|e| {
+            SqliteError::Frame {
+                stream_id: stream_label,
+                version: version.as_u64(),
+                reason: ErrorId::from_display(&e),
+            }
+        })?;
+
+    PersistedEnvelope::try_new(
+        version,
+        frame.value,
+        schema,
+        frame.offsets.event_type,
+        frame.offsets.payload,
+        frame.offsets.metadata,
+    )
+    .map_err(|source| SqliteError::EnvelopeCorrupt {
+        stream_id: stream_label,
+        version: version.as_u64(),
+        source,
+    })
+}
+
+/// Build a [`SqliteError::CorruptRow`] from a fixed-string reason.
+pub fn corrupt(stream_id: ErrorId, reason: &str) -> SqliteError {
+    SqliteError::CorruptRow {
+        stream_id,
+        reason: ErrorId::from_display(&reason),
+    }
+}
+
+/// A validated insert row, borrowing its bytes from the pending envelope.
+///
+/// Holding a `PreparedInsert` is proof the batch passed the optimistic +
+/// strict-sequential + range checks; `version` is the narrowed column value.
+#[derive(Debug)]
+pub struct PreparedInsert<'a> {
+    version: i64,
+    env: &'a PendingEnvelope,
+}
+
+/// Pure, IO-free batch validation + narrowing — **unit-testable without a DB.**
+///
+/// Given the stream's `current` (max) version (already read inside the
+/// transaction): enforce the optimistic-concurrency check, then that the
+/// batch versions are strictly sequential from `current + 1`, narrowing each
+/// to the signed `INTEGER` column. Returns the rows ready to `INSERT`, or the
+/// first violation.
+pub fn prepare_inserts<'a>(
+    current: u64,
+    expected: Option<Version>,
+    envelopes: &'a [PendingEnvelope],
+    id: &StreamKey,
+) -> Result<Vec<PreparedInsert<'a>>, AppendError<SqliteError>> {
+    if expected.map_or(0, Version::as_u64) != current {
+        return Err(AppendError::Conflict {
+            stream_id: ErrorId::from_display(id),
+            expected,
+            actual: Version::new(current),
+        });
+    }
+
+    let mut expect = current;
+    envelopes
+        .iter()
+        .map(|env| {
+            expect = expect.checked_add(1).ok_or_else(|| {
+                AppendError::Store(corrupt(ErrorId::from_display(id), "version overflow"))
+            })?;
+            if env.version().as_u64() != expect {
+                return Err(AppendError::Conflict {
+                    stream_id: ErrorId::from_display(id),
+                    expected: Version::new(expect),
+                    actual: Some(env.version()),
+                });
+            }
+            let version = i64::try_from(expect).map_err(|_| {
+                AppendError::Store(SqliteError::InvalidInput {
+                    stream_id: ErrorId::from_display(id),
+                    version: expect,
+                    reason: ErrorId::from_display(&"version exceeds i64::MAX"),
+                })
+            })?;
+            Ok(PreparedInsert { version, env })
+        })
+        .collect()
+}
+
+/// Map a rusqlite error to an `AppendError<SqliteError>` store variant.
+pub const fn store_err(e: rusqlite::Error) -> AppendError<SqliteError> {
+    AppendError::Store(SqliteError::Sqlite(e))
+}
+
+/// Returns `true` if `e` is a `UNIQUE` constraint violation.
+const fn is_unique_violation(e: &rusqlite::Error) -> bool {
+    matches!(
+        e,
+        rusqlite::Error::SqliteFailure(f, _)
+            if f.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Blocking IO helpers — run on the write connection's open transaction
+// ---------------------------------------------------------------------------
+
+/// The stream's current (max) version. Absent stream → 0.
+///
+/// A *negative* stored version is corruption surfaced as an error, NOT a
+/// silent 0.
+pub fn current_version(conn: &Connection, id: &StreamKey) -> Result<u64, SqliteError> {
+    let current: i64 = conn
+        .prepare_cached(CURRENT_VERSION)?
+        .query_row([id.as_bytes()], |row| row.get(0))?;
+    u64::try_from(current)
+        .map_err(|_| corrupt(ErrorId::from_display(id), "stored version is negative"))
+}
+
+/// `INSERT` validated rows on `conn`'s open transaction.
+pub fn insert_rows(
+    conn: &Connection,
+    id: &StreamKey,
+    rows: &[PreparedInsert<'_>],
+) -> Result<(), AppendError<SqliteError>> {
+    let mut insert = conn.prepare_cached(INSERT_EVENT).map_err(store_err)?;
+    for row in rows {
+        let result = insert.execute(params![
+            id.as_bytes(),
+            row.version,
+            row.env.event_type(),
+            row.env.schema_version(),
+            row.env.payload(),
+            row.env.metadata(),
+        ]);
+        if let Err(e) = result {
+            // The write lock makes the head read and these inserts one
+            // serialized step, so a duplicate means a writer that bypassed
+            // the store — still a conflict, not a store failure.
+            if is_unique_violation(&e) {
+                return Err(AppendError::Conflict {
+                    stream_id: ErrorId::from_display(id),
+                    expected: Some(row.env.version()),
+                    actual: None,
+                });
+            }
+            return Err(store_err(e));
+        }
+    }
+    Ok(())
+}
+
+/// Check and insert a batch on `conn`'s open transaction — the whole of
+/// `append` but the commit, so a caller can stage more writes alongside it.
+///
+/// Returns the stream's head version afterwards (unchanged for an empty
+/// batch; 0 for a stream that still has no events).
+pub fn insert_events(
+    conn: &Connection,
+    id: &StreamKey,
+    expected_version: Option<Version>,
+    envelopes: &[PendingEnvelope],
+) -> Result<u64, AppendError<SqliteError>> {
+    let current = current_version(conn, id).map_err(AppendError::Store)?;
+    let rows = prepare_inserts(current, expected_version, envelopes, id)?;
+    let head = rows
+        .last()
+        .map_or(current, |row| row.env.version().as_u64());
+    insert_rows(conn, id, &rows)?;
+    Ok(head)
+}
+
+/// Open a write transaction. `IMMEDIATE` takes `SQLite`'s write lock up front,
+/// so the head read and the inserts see no other writer in between.
+pub fn begin_write(
+    conn: &mut Connection,
+) -> Result<rusqlite::Transaction<'_>, AppendError<SqliteError>> {
+    conn.transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(store_err)
+}
+
+// ---------------------------------------------------------------------------
+// `RawEventStore` impl
+// ---------------------------------------------------------------------------
+
+/// Per-stream stream type: owned, `Send`, `'static` stream of
+/// `Result<PersistedEnvelope, SqliteError>`, paged by [`StreamPages`].
+type Stream = futures::stream::BoxStream<'static, Result<PersistedEnvelope, SqliteError>>;
+
+/// `$all` stream type: owned, `Send`, `'static` stream of
+/// `Result<(GlobalSeq, PersistedEnvelope), SqliteError>`, paged by
+/// [`AllPages`].
+type AllStream =
+    futures::stream::BoxStream<'static, Result<(GlobalSeq, PersistedEnvelope), SqliteError>>;
+
+/// Keyset-paginating state for one `read_stream`: each refill fetches the next
+/// `batch_size` rows at `version >= next`.
+struct StreamPages {
+    inner: Arc<Inner>,
+    stream_id: StreamKey,
+    label: ErrorId,
+    /// Inclusive lower bound of the next page.
+    next: i64,
+    batch_size: usize,
+    limit: i64,
+    buffer: VecDeque<EventRow>,
+    /// Set by a short page (no more rows), a version at `i64::MAX`, or an
+    /// error — the stream then ends rather than skipping rows.
+    done: bool,
+}
+
+impl StreamPages {
+    async fn refill(&mut self) -> Result<(), SqliteError> {
+        let id = self.stream_id.clone();
+        let (next, limit) = (self.next, self.limit);
+        let rows = read_blocking(Arc::clone(&self.inner), move |conn| {
+            let mut page = conn.prepare_cached(STREAM_PAGE)?;
+            let rows = page
+                .query_map(params![id.as_bytes(), next, limit], EventRow::read)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await?;
+
+        self.done = rows.len() < self.batch_size;
+        if let Some(last) = rows.last() {
+            match last.version.checked_add(1) {
+                Some(after) => self.next = after,
+                None => self.done = true,
+            }
+        }
+        self.buffer = rows.into();
+        Ok(())
+    }
+
+    fn into_stream(self) -> Stream {
+        futures::stream::unfold(self, |mut s| async move {
+            loop {
+                if let Some(row) = s.buffer.pop_front() {
+                    let item = row_to_envelope(row, s.label);
+                    if item.is_err() {
+                        s.buffer.clear();
+                        s.done = true;
+                    }
+                    return Some((item, s));
+                }
+                if s.done {
+                    return None;
+                }
+                if let Err(e) = s.refill().await {
+                    s.done = true;
+                    return Some((Err(e), s));
+                }
+                if s.buffer.is_empty() {
+                    return None;
+                }
+            }
+        })
+        .fuse()
+        .boxed()
+    }
+}
+
+/// Keyset-paginating state for one `read_all`: each refill fetches the next
+/// `batch_size` rows strictly after `after` and at or below the last
+/// sequence number committed when the read opened.
+struct AllPages {
+    inner: Arc<Inner>,
+    /// Exclusive resume cursor; 0 = from the very beginning.
+    after: i64,
+    /// `MAX(global_seq)` at open, pinned for every page.
+    until: i64,
+    batch_size: usize,
+    limit: i64,
+    buffer: VecDeque<(i64, EventRow)>,
+    /// Set by a short page or an error — the stream then ends.
+    done: bool,
+}
+
+impl AllPages {
+    async fn refill(&mut self) -> Result<(), SqliteError> {
+        let (after, until, limit) = (self.after, self.until, self.limit);
+        let rows = read_blocking(Arc::clone(&self.inner), move |conn| {
+            let mut page = conn.prepare_cached(ALL_PAGE)?;
+            let rows = page
+                .query_map(params![after, until, limit], |row| {
+                    Ok((row.get(5)?, EventRow::read(row)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await?;
+
+        self.done = rows.len() < self.batch_size;
+        if let Some((last, _)) = rows.last() {
+            self.after = *last;
+        }
+        self.buffer = rows.into();
+        Ok(())
+    }
+
+    fn into_stream(self) -> AllStream {
+        futures::stream::unfold(self, |mut s| async move {
+            loop {
+                if let Some(row) = s.buffer.pop_front() {
+                    let item = tag_all_row(row);
+                    if item.is_err() {
+                        s.buffer.clear();
+                        s.done = true;
+                    }
+                    return Some((item, s));
+                }
+                if s.done {
+                    return None;
+                }
+                if let Err(e) = s.refill().await {
+                    s.done = true;
+                    return Some((Err(e), s));
+                }
+                if s.buffer.is_empty() {
+                    return None;
+                }
+            }
+        })
+        .fuse()
+        .boxed()
+    }
+}
+
+/// Decode one `$all` row into its position tag and envelope.
+fn tag_all_row((seq, row): (i64, EventRow)) -> Result<(GlobalSeq, PersistedEnvelope), SqliteError> {
+    let label = ErrorId::default();
+    let position = u64::try_from(seq)
+        .ok()
+        .and_then(GlobalSeq::new)
+        .ok_or_else(|| corrupt(label, "global_seq <= 0"))?;
+    let env = row_to_envelope(row, label)?;
+    Ok((position, env))
+}
+
+/// A [`BatchSize`] as a SQL `LIMIT`. Infallible in practice — `BatchSize` is
+/// at most [`MAX_BATCH`](nexus_store::MAX_BATCH).
+fn page_limit(batch_size: BatchSize, label: ErrorId) -> Result<i64, SqliteError> {
+    i64::try_from(batch_size.get()).map_err(|_| corrupt(label, "batch size exceeds i64::MAX"))
+}
+
+impl RawEventStore for SqliteStore {
+    type Error = SqliteError;
+    type Stream = Stream;
+    type AllPosition = GlobalSeq;
+    type AllStream = AllStream;
+
+    async fn append(
+        &self,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+    ) -> Result<(), AppendError<Self::Error>> {
+        // The blocking task needs owned inputs; envelope bytes are
+        // ref-counted, so the copies are cheap.
+        let stream = id.clone();
+        let batch = envelopes.to_vec();
+        self.on_writer(move |conn| {
+            let tx = begin_write(conn)?;
+            insert_events(&tx, &stream, expected_version, &batch)?;
+            if batch.is_empty() {
+                return Ok(()); // version checked; nothing to write
+            }
+            tx.commit().map_err(store_err)
+        })
+        .await
+        .map_err(AppendError::Store)??;
+
+        // Wake AFTER durable commit (WakeSource contract: wake post-commit).
+        if !envelopes.is_empty() {
+            self.wake_committed(id.as_bytes());
+        }
+        Ok(())
+    }
+
+    async fn read_stream(
+        &self,
+        id: &StreamKey,
+        from: Version,
+    ) -> Result<Self::Stream, Self::Error> {
+        let label = ErrorId::from_display(id);
+        // Nothing is stored past `i64::MAX`, so a start beyond it reads
+        // nothing rather than failing.
+        let (next, done) = i64::try_from(from.as_u64()).map_or((0, true), |v| (v, false));
+
+        Ok(StreamPages {
+            inner: Arc::clone(&self.inner),
+            stream_id: id.clone(),
+            label,
+            next,
+            batch_size: self.batch_size.get(),
+            limit: page_limit(self.batch_size, label)?,
+            buffer: VecDeque::new(),
+            done,
+        }
+        .into_stream())
+    }
+
+    async fn read_all(&self, from: Option<GlobalSeq>) -> Result<Self::AllStream, Self::Error> {
+        let label = ErrorId::default();
+        // Pin the upper bound once: the read yields exactly the events
+        // committed when it opened, as one unpaged query would, and stays
+        // finite under a steady write load. SQLite commits sequence numbers
+        // in the order it hands them out (one writer at a time), so no
+        // smaller one can commit after this bound is read.
+        let until = self
+            .on_reader(|conn| {
+                conn.query_row(LAST_SEQ, [], |row| row.get::<_, i64>(0))
+                    .map_err(SqliteError::from)
+            })
+            .await?;
+        // A resume position past `i64::MAX` is after every stored event.
+        let (after, done) = from.map_or((0, false), |p| {
+            i64::try_from(p.as_u64()).map_or((0, true), |v| (v, false))
+        });
+
+        Ok(AllPages {
+            inner: Arc::clone(&self.inner),
+            after,
+            until,
+            batch_size: self.batch_size.get(),
+            limit: page_limit(self.batch_size, label)?,
+            buffer: VecDeque::new(),
+            done,
+        }
+        .into_stream())
+    }
+}
+
+/// Wakes are in-process: `append` wakes this store's registry after every
+/// commit, like fjall's. Another process appending to the same file wakes
+/// nothing here — its events appear on this store's next read.
+impl WakeSource for SqliteStore {
+    type Registration = WakeReg;
+    type Error = NotifyError;
+
+    fn register(&self, stream: Option<&[u8]>) -> Result<Self::Registration, Self::Error> {
+        self.inner.notifiers.register(stream)
+    }
+
+    fn wake(&self, stream: &[u8]) {
+        // `StreamNotifiers::wake` bumps BOTH the per-stream and the `$all` wake
+        // paths, so one call rouses per-stream and `$all` registrations alike.
+        self.inner.notifiers.wake(stream);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// `AtomicAppend` — whole-chunk import in one transaction
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "import")]
+mod atomic_append_impl {
+    use std::collections::HashMap;
+
+    use nexus::Version;
+    use nexus_store::error::AppendError;
+    use nexus_store::import::{AtomicAppend, AtomicAppendError, PlannedAppend};
+    use rusqlite::Connection;
+
+    use super::{
+        SqliteError, SqliteStore, begin_write, current_version, insert_rows, prepare_inserts,
+    };
+
+    /// Re-home a per-stream [`AppendError`] onto the batch write at `index`.
+    fn atomic_err(
+        index: usize,
+        actual: Option<Version>,
+        e: AppendError<SqliteError>,
+    ) -> AtomicAppendError<SqliteError> {
+        match e {
+            AppendError::Store(source) => AtomicAppendError::Store(source),
+            _ => AtomicAppendError::Conflict { index, actual },
+        }
+    }
+
+    /// Check every write against its target's *running* head, so a second
+    /// write to one stream conflicts instead of concatenating, then insert
+    /// them all — on `conn`'s open transaction.
+    fn apply(
+        conn: &Connection,
+        writes: &[PlannedAppend],
+    ) -> Result<(), AtomicAppendError<SqliteError>> {
+        let mut heads: HashMap<&[u8], u64> = HashMap::new();
+        let mut planned = Vec::with_capacity(writes.len());
+        for (index, w) in writes.iter().enumerate() {
+            let head = match heads.get(w.target.as_bytes()) {
+                Some(head) => *head,
+                None => current_version(conn, &w.target).map_err(AtomicAppendError::Store)?,
+            };
+            let rows = prepare_inserts(head, w.expected_version, &w.events, &w.target)
+                .map_err(|e| atomic_err(index, Version::new(head), e))?;
+            let advanced = rows.last().map_or(head, |row| row.env.version().as_u64());
+            heads.insert(w.target.as_bytes(), advanced);
+            planned.push(rows);
+        }
+        for (index, (w, rows)) in writes.iter().zip(&planned).enumerate() {
+            insert_rows(conn, &w.target, rows).map_err(|e| atomic_err(index, None, e))?;
+        }
+        Ok(())
+    }
+
+    impl AtomicAppend for SqliteStore {
+        async fn atomic_append_many(
+            &self,
+            writes: &[PlannedAppend],
+        ) -> Result<(), AtomicAppendError<SqliteError>> {
+            // Nothing to commit — don't take the write lock.
+            if writes.is_empty() {
+                return Ok(());
+            }
+            let batch = writes.to_vec();
+            self.on_writer(move |conn| {
+                // Any early Err drops `tx` uncommitted, rolling back the runs
+                // already inserted.
+                let tx = begin_write(conn).map_err(|e| atomic_err(0, None, e))?;
+                apply(&tx, &batch)?;
+                tx.commit()
+                    .map_err(|e| AtomicAppendError::Store(SqliteError::Sqlite(e)))
+            })
+            .await
+            .map_err(AtomicAppendError::Store)??;
+
+            // Wake AFTER the durable commit: once per touched stream.
+            for w in writes {
+                if !w.events.is_empty() {
+                    self.wake_committed(w.target.as_bytes());
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// `StreamLister` — keyset-paginated stream ids for export
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "export")]
+mod stream_lister_impl {
+    use bytes::Bytes;
+    use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+    use nexus_store::StreamKey;
+    use nexus_store::export::StreamLister;
+    use rusqlite::{Connection, params};
+
+    use super::{SqliteError, SqliteStore};
+
+    /// Ids fetched per query.
+    const LIST_PAGE_SIZE: i64 = 512;
+
+    const FIRST_IDS: &str = "SELECT DISTINCT stream_id FROM events ORDER BY stream_id LIMIT ?1";
+
+    const NEXT_IDS: &str = "SELECT DISTINCT stream_id FROM events \
+         WHERE stream_id > ?1 ORDER BY stream_id LIMIT ?2";
+
+    /// Lazily paged stream ids, in ascending byte order.
+    pub type StreamIdPages = BoxStream<'static, Result<StreamKey, SqliteError>>;
+
+    /// The page of distinct ids strictly after `after` (from the start when
+    /// `None`). Served by the `(stream_id, version)` index.
+    fn next_page(conn: &Connection, after: Option<&[u8]>) -> Result<Vec<Vec<u8>>, SqliteError> {
+        let ids = match after {
+            None => conn
+                .prepare_cached(FIRST_IDS)?
+                .query_map([LIST_PAGE_SIZE], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?,
+            Some(last) => conn
+                .prepare_cached(NEXT_IDS)?
+                .query_map(params![last, LIST_PAGE_SIZE], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?,
+        };
+        Ok(ids)
+    }
+
+    /// Each page is its own query keyed on the last id seen, so memory is
+    /// bounded by one page and no connection is held between polls. The
+    /// listing is not one point-in-time view: a stream created mid-listing
+    /// appears iff its id sorts after the cursor. Every id appears at most
+    /// once — the keyset only moves forward.
+    impl StreamLister for SqliteStore {
+        type StreamList = StreamIdPages;
+
+        async fn list_streams(&self) -> Result<Self::StreamList, SqliteError> {
+            let store = self.clone();
+            let pages = stream::try_unfold(None::<Vec<u8>>, move |after| {
+                let reader = store.clone();
+                async move {
+                    let page = reader
+                        .on_reader(move |conn| next_page(conn, after.as_deref()))
+                        .await?;
+                    // An empty page ends the listing.
+                    Ok::<_, SqliteError>(page.last().cloned().map(|last| (page, Some(last))))
+                }
+            });
+            Ok(pages
+                .map_ok(|page| {
+                    stream::iter(
+                        page.into_iter()
+                            .map(|id| Ok(StreamKey::from_bytes(Bytes::from(id)))),
+                    )
+                })
+                .try_flatten()
+                .boxed())
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DB-free unit tests for `prepare_inserts`
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic, reason = "test code")]
+mod tests {
+    use nexus::Version;
+    use nexus_store::PendingEnvelope;
+    use nexus_store::StreamKey;
+    use nexus_store::envelope::pending_envelope;
+    use nexus_store::error::AppendError;
+
+    use super::prepare_inserts;
+
+    fn stream_key() -> StreamKey {
+        StreamKey::from_slice(b"s")
+    }
+
+    fn make_envelope(version: u64) -> PendingEnvelope {
+        pending_envelope(Version::new(version).unwrap())
+            .event_type("E")
+            .payload(vec![1])
+            .unwrap()
+            .build()
+    }
+
+    #[test]
+    fn existing_stream_two_events_ok() {
+        let envs = [make_envelope(4), make_envelope(5)];
+        let rows = prepare_inserts(3, Version::new(3), &envs, &stream_key()).unwrap();
+        assert_eq!(rows.iter().map(|r| r.version).collect::<Vec<_>>(), [4, 5]);
+    }
+
+    #[test]
+    fn stale_expected_version_conflict() {
+        let envs = [make_envelope(3)];
+        let err = prepare_inserts(2, Version::new(1), &envs, &stream_key()).unwrap_err();
+        assert!(matches!(err, AppendError::Conflict { .. }), "got {err:?}");
+    }
+
+    #[test]
+    fn gapped_batch_conflict() {
+        let envs = [make_envelope(1), make_envelope(3)];
+        let err = prepare_inserts(0, None, &envs, &stream_key()).unwrap_err();
+        let AppendError::Conflict {
+            expected, actual, ..
+        } = err
+        else {
+            panic!("expected a conflict, got {err:?}");
+        };
+        assert_eq!(expected, Version::new(2));
+        assert_eq!(actual, Version::new(3));
+    }
+
+    #[test]
+    fn versions_past_i64_max_are_invalid_input() {
+        let past = u64::try_from(i64::MAX).unwrap() + 1;
+        let envs = [make_envelope(past)];
+        let err =
+            prepare_inserts(past - 1, Version::new(past - 1), &envs, &stream_key()).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                AppendError::Store(crate::SqliteError::InvalidInput { .. })
+            ),
+            "got {err:?}"
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! `SqliteStore` behaviour beyond the shared conformance suites: optimistic
+//! concurrency, durability across reopen, paged reads racing appends, wakes,
+//! snapshots, the export/import capabilities, and a file any `SQLite` client
+//! can read.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(clippy::missing_panics_doc, reason = "tests")]
+
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::time::Duration;
+
+use futures::{StreamExt, TryStreamExt};
+use nexus::Version;
+use nexus_sqlite::{GlobalSeq, SqliteError, SqliteStore};
+use nexus_store::batch::BatchSize;
+use nexus_store::envelope::pending_envelope;
+use nexus_store::error::AppendError;
+use nexus_store::export::StreamLister;
+use nexus_store::import::{AtomicAppend, AtomicAppendError, PlannedAppend};
+use nexus_store::state::{PendingSnapshot, SnapshotAppend, SnapshotStore};
+use nexus_store::store::RawEventStore;
+use nexus_store::wake::{WakeRegistration, WakeSource};
+use nexus_store::{PendingEnvelope, StreamKey};
+
+const MUST_WAKE: Duration = Duration::from_secs(5);
+
+fn open(path: &Path) -> SqliteStore {
+    SqliteStore::builder(path).open().expect("open store")
+}
+
+fn sk(s: &str) -> StreamKey {
+    StreamKey::from_slice(s.as_bytes())
+}
+
+fn envelopes(from: u64, to: u64) -> Vec<PendingEnvelope> {
+    (from..=to)
+        .map(|i| {
+            pending_envelope(Version::new(i).unwrap())
+                .event_type("TestEvent")
+                .payload(format!("payload-{i}").into_bytes())
+                .expect("valid payload")
+                .build()
+        })
+        .collect()
+}
+
+async fn stream_versions(store: &SqliteStore, stream: &str) -> Vec<u64> {
+    store
+        .read_stream(&sk(stream), Version::INITIAL)
+        .await
+        .expect("read_stream")
+        .map(|r| r.expect("no error").version().as_u64())
+        .collect()
+        .await
+}
+
+async fn all_seqs(store: &SqliteStore, from: Option<GlobalSeq>) -> Vec<u64> {
+    store
+        .read_all(from)
+        .await
+        .expect("read_all")
+        .map(|r| r.expect("no error").0.as_u64())
+        .collect()
+        .await
+}
+
+const fn schema(v: u32) -> NonZeroU32 {
+    NonZeroU32::new(v).unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// 1. Appends and reads
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn stale_and_gapped_appends_conflict_and_write_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(&dir.path().join("events.db"));
+    store
+        .append(&sk("a"), None, &envelopes(1, 3))
+        .await
+        .unwrap();
+
+    let stale = store
+        .append(&sk("a"), Version::new(2), &envelopes(3, 4))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(stale, AppendError::Conflict { actual, .. } if actual == Version::new(3)),
+        "got {stale:?}"
+    );
+    let gapped = store
+        .append(&sk("a"), Version::new(3), &envelopes(5, 6))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(gapped, AppendError::Conflict { .. }),
+        "got {gapped:?}"
+    );
+    assert_eq!(stream_versions(&store, "a").await, [1, 2, 3]);
+}
+
+#[tokio::test]
+async fn events_and_positions_survive_reopening_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("events.db");
+    {
+        let store = open(&path);
+        store
+            .append(&sk("a"), None, &envelopes(1, 2))
+            .await
+            .unwrap();
+    }
+    let store = open(&path);
+    store
+        .append(&sk("b"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    assert_eq!(stream_versions(&store, "a").await, [1, 2]);
+    assert_eq!(all_seqs(&store, None).await, [1, 2, 3]);
+    assert_eq!(all_seqs(&store, GlobalSeq::new(2)).await, [3]);
+}
+
+#[tokio::test]
+async fn paged_reads_cross_page_boundaries_and_stop_at_the_open_bound() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(&dir.path().join("events.db")).with_batch_size(BatchSize::new(3).unwrap());
+    store
+        .append(&sk("a"), None, &envelopes(1, 10))
+        .await
+        .unwrap();
+    assert_eq!(
+        stream_versions(&store, "a").await,
+        (1..=10).collect::<Vec<_>>()
+    );
+
+    // An append after `read_all` opened is left for the next read.
+    let mut all = store.read_all(None).await.unwrap();
+    let first = all.next().await.unwrap().unwrap().0;
+    store
+        .append(&sk("b"), None, &envelopes(1, 5))
+        .await
+        .unwrap();
+    let rest: Vec<u64> = all.map_ok(|(p, _)| p.as_u64()).try_collect().await.unwrap();
+    assert_eq!(first, GlobalSeq::INITIAL);
+    assert_eq!(rest, (2..=10).collect::<Vec<_>>());
+    assert_eq!(
+        all_seqs(&store, GlobalSeq::new(10)).await,
+        (11..=15).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn a_committed_append_wakes_stream_and_all_registrations() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(&dir.path().join("events.db"));
+    let stream = store.register(Some(b"a")).unwrap();
+    let all = store.register(None).unwrap();
+    let (stream_woken, all_woken) = (stream.arm(), all.arm());
+
+    store
+        .append(&sk("a"), None, &envelopes(1, 1))
+        .await
+        .unwrap();
+    tokio::time::timeout(MUST_WAKE, stream_woken)
+        .await
+        .expect("stream registration woken");
+    tokio::time::timeout(MUST_WAKE, all_woken)
+        .await
+        .expect("$all registration woken");
+}
+
+// ---------------------------------------------------------------------------
+// 2. Snapshots and checkpoints
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn snapshot_commits_are_monotonic_and_schema_checked() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(&dir.path().join("events.db"));
+    let id = sk("agg");
+
+    store
+        .commit(&id, schema(1), Version::new(5).unwrap(), &vec![5])
+        .await
+        .unwrap();
+    // A late commit of an older position is dropped.
+    store
+        .commit(&id, schema(1), Version::new(3).unwrap(), &vec![3])
+        .await
+        .unwrap();
+    let hydrated =
+        <SqliteStore as SnapshotStore<Vec<u8>, Version>>::hydrate(&store, &id, schema(1))
+            .await
+            .unwrap();
+    assert_eq!(hydrated, Some((Version::new(5).unwrap(), vec![5])));
+    let other_schema =
+        <SqliteStore as SnapshotStore<Vec<u8>, Version>>::hydrate(&store, &id, schema(2))
+            .await
+            .unwrap();
+    assert_eq!(other_schema, None);
+
+    let checkpoint = sk("projection");
+    store
+        .commit(&checkpoint, schema(1), GlobalSeq::new(7).unwrap(), &vec![7])
+        .await
+        .unwrap();
+    let resumed =
+        <SqliteStore as SnapshotStore<Vec<u8>, GlobalSeq>>::hydrate(&store, &checkpoint, schema(1))
+            .await
+            .unwrap();
+    assert_eq!(resumed, Some((GlobalSeq::new(7).unwrap(), vec![7])));
+}
+
+#[tokio::test]
+async fn a_mismatched_snapshot_rolls_its_append_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(&dir.path().join("events.db"));
+    let id = sk("agg");
+
+    let err = store
+        .append_with_snapshot(
+            &id,
+            None,
+            &envelopes(1, 3),
+            PendingSnapshot::new(schema(1), Version::new(2).unwrap(), b"two"),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, AppendError::Store(SqliteError::InvalidInput { .. })),
+        "got {err:?}"
+    );
+    assert!(stream_versions(&store, "agg").await.is_empty());
+
+    store
+        .append_with_snapshot(
+            &id,
+            None,
+            &envelopes(1, 3),
+            PendingSnapshot::new(schema(1), Version::new(3).unwrap(), b"three"),
+        )
+        .await
+        .unwrap();
+    let hydrated =
+        <SqliteStore as SnapshotStore<Vec<u8>, Version>>::hydrate(&store, &id, schema(1))
+            .await
+            .unwrap();
+    assert_eq!(
+        hydrated,
+        Some((Version::new(3).unwrap(), b"three".to_vec()))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 3. Export and import
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn streams_list_once_each_in_byte_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(&dir.path().join("events.db"));
+    for id in ["b", "c", "a"] {
+        store.append(&sk(id), None, &envelopes(1, 2)).await.unwrap();
+    }
+    let listed: Vec<StreamKey> = store
+        .list_streams()
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(listed, [sk("a"), sk("b"), sk("c")]);
+}
+
+#[tokio::test]
+async fn an_atomic_batch_with_a_conflict_writes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open(&dir.path().join("events.db"));
+    let write = |id: &str, expected: Option<Version>, from, to| PlannedAppend {
+        target: sk(id),
+        expected_version: expected,
+        events: envelopes(from, to),
+    };
+
+    // The second run to "a" expects the head the first one moves past.
+    let err = store
+        .atomic_append_many(&[write("a", None, 1, 2), write("a", None, 3, 3)])
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, AtomicAppendError::Conflict { index: 1, actual } if actual == Version::new(2)),
+        "got {err:?}"
+    );
+    assert!(stream_versions(&store, "a").await.is_empty());
+
+    store
+        .atomic_append_many(&[
+            write("a", None, 1, 2),
+            write("b", None, 1, 1),
+            write("a", Version::new(2), 3, 3),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(stream_versions(&store, "a").await, [1, 2, 3]);
+    assert_eq!(stream_versions(&store, "b").await, [1]);
+}
+
+// ---------------------------------------------------------------------------
+// 4. The file itself
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn the_file_is_plain_sql_in_wal_mode() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("events.db");
+    let store = open(&path);
+    store
+        .append(&sk("a"), None, &envelopes(1, 2))
+        .await
+        .unwrap();
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let mode: String = conn
+        .pragma_query_value(None, "journal_mode", |row| row.get(0))
+        .unwrap();
+    assert_eq!(mode, "wal");
+    let rows: Vec<(i64, String, Vec<u8>)> = conn
+        .prepare("SELECT version, event_type, payload FROM events ORDER BY global_seq")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        [
+            (1, "TestEvent".to_owned(), b"payload-1".to_vec()),
+            (2, "TestEvent".to_owned(), b"payload-2".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn an_in_memory_database_is_refused() {
+    let err = SqliteStore::builder(":memory:")
+        .open()
+        .err()
+        .expect("no WAL for an in-memory database");
+    assert!(
+        matches!(err, SqliteError::WalUnavailable { .. }),
+        "got {err:?}"
+    );
+}
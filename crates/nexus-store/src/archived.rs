@@ -0,0 +1,329 @@
+//! Zero-copy snapshot hydration for rkyv-archived state.
+//!
+//! [`ArchivedSnapshotStore`] keeps aggregate state as rkyv archives in any
+//! byte-level [`SnapshotBytes`] store and hands it back as an
+//! [`ArchivedSnapshot`]: the state's `&Archived<S>` read in place from the
+//! stored bytes. A command handler decides on the archived state and only
+//! pays for an owned `S` ([`ArchivedSnapshot::into_root`]) when it has
+//! events to apply.
+//!
+//! # Alignment
+//!
+//! Reading `&Archived<S>` in place needs the bytes to start on a
+//! [`PAYLOAD_ALIGN`] boundary — the invariant
+//! [`encode_frame`](crate::wire::encode_frame) gives event payloads.
+//! Adapters keep snapshot state on a 16-byte offset within the stored
+//! value; when the buffer they hand back is aligned, hydration borrows it
+//! as is. Otherwise the bytes are copied once into an aligned buffer —
+//! still never deserialized.
+//!
+//! # Staleness
+//!
+//! An archived snapshot is the state *at its version*, not the stream's
+//! head. Events appended since are not folded in, so a root restored from
+//! it may be behind. That is safe: saving appends at the root's version,
+//! so a stale decision surfaces as an optimistic
+//! [`Conflict`](crate::StoreError::Conflict), never a lost write. For a
+//! caught-up load, put the store behind [`Snapshotting`](crate::Snapshotting)
+//! — it implements [`SnapshotStore<S, Version>`] by deserializing.
+
+use std::marker::PhantomData;
+use std::num::NonZeroU32;
+
+use ::rkyv::{
+    Archive, Deserialize, Serialize,
+    api::high::{HighDeserializer, HighSerializer, HighValidator, to_bytes_in},
+    bytecheck::CheckBytes,
+    rancor,
+    ser::allocator::ArenaHandle,
+    util::AlignedVec,
+};
+use aligned_vec::{AVec, ConstAlign};
+use bytes::Bytes;
+use nexus::{Aggregate, AggregateRoot, Id, Version};
+
+use crate::state::{SnapshotBytes, SnapshotStore};
+use crate::wire::PAYLOAD_ALIGN;
+
+/// A snapshot's state as `&Archived<S>`, borrowed from the stored bytes.
+///
+/// Validated once, when built; [`archived`](Self::archived) is then a
+/// pointer cast. Cheap to clone — a refcount bump on the shared buffer.
+pub struct ArchivedSnapshot<S> {
+    version: Version,
+    bytes: Bytes,
+    _state: PhantomData<fn() -> S>,
+}
+
+impl<S> Clone for ArchivedSnapshot<S> {
+    fn clone(&self) -> Self {
+        Self {
+            version: self.version,
+            bytes: self.bytes.clone(),
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<S> std::fmt::Debug for ArchivedSnapshot<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArchivedSnapshot")
+            .field("version", &self.version)
+            .field("len", &self.bytes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> ArchivedSnapshot<S>
+where
+    S: Archive,
+    S::Archived: for<'a> CheckBytes<HighValidator<'a, rancor::Error>>,
+{
+    /// Validate `bytes` as an archived `S` saved at `version`.
+    ///
+    /// Borrows `bytes` when they start on a [`PAYLOAD_ALIGN`] boundary,
+    /// otherwise copies them once into an aligned buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the rkyv error if `bytes` are not a valid archived `S`.
+    pub fn new(version: Version, bytes: Bytes) -> Result<Self, rancor::Error> {
+        let shared = if bytes.as_ptr().addr().is_multiple_of(PAYLOAD_ALIGN) {
+            bytes
+        } else {
+            let mut aligned: AVec<u8, ConstAlign<PAYLOAD_ALIGN>> =
+                AVec::with_capacity(PAYLOAD_ALIGN, bytes.len());
+            aligned.extend_from_slice(&bytes);
+            Bytes::from_owner(aligned)
+        };
+        ::rkyv::access::<S::Archived, rancor::Error>(&shared)?;
+        Ok(Self {
+            version,
+            bytes: shared,
+            _state: PhantomData,
+        })
+    }
+
+    /// The archived state, read in place.
+    #[must_use]
+    pub fn archived(&self) -> &S::Archived {
+        // SAFETY: `new` validated these exact bytes as an `S::Archived` with
+        // `rkyv::access`, and `bytes` is immutable and private, so the
+        // archive is still valid and aligned here.
+        #[allow(
+            unsafe_code,
+            reason = "bytes validated by rkyv::access at construction; immutable since"
+        )]
+        unsafe {
+            ::rkyv::access_unchecked::<S::Archived>(&self.bytes)
+        }
+    }
+
+    /// Deserialize the archived state into an owned `S`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the rkyv error if deserialization fails.
+    pub fn deserialize(&self) -> Result<S, rancor::Error>
+    where
+        S::Archived: Deserialize<S, HighDeserializer<rancor::Error>>,
+    {
+        ::rkyv::deserialize::<S, rancor::Error>(self.archived())
+    }
+
+    /// Materialize the state into an aggregate root at the snapshot's
+    /// version, ready for commands that apply events.
+    ///
+    /// # Errors
+    ///
+    /// Returns the rkyv error if deserialization fails.
+    pub fn into_root<A>(self, id: A::Id) -> Result<AggregateRoot<A>, rancor::Error>
+    where
+        A: Aggregate<State = S>,
+        S::Archived: Deserialize<S, HighDeserializer<rancor::Error>>,
+    {
+        Ok(AggregateRoot::restore(
+            id,
+            self.deserialize()?,
+            self.version,
+        ))
+    }
+}
+
+impl<S> ArchivedSnapshot<S> {
+    /// The version the snapshot was taken at.
+    #[must_use]
+    pub const fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The archived bytes — shared with the store's buffer when it was
+    /// aligned.
+    #[must_use]
+    pub const fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+}
+
+/// A [`SnapshotStore`] of rkyv-archived state over a byte-level store.
+///
+/// [`hydrate_archived`](Self::hydrate_archived) hands out the state without
+/// deserializing it. The [`SnapshotStore<S, Version>`] impl deserializes, so
+/// the same store also serves [`Snapshotting`](crate::Snapshotting).
+#[derive(Debug, Clone)]
+pub struct ArchivedSnapshotStore<SS> {
+    store: SS,
+}
+
+impl<SS> ArchivedSnapshotStore<SS> {
+    /// Wrap a byte-level snapshot store.
+    pub const fn new(store: SS) -> Self {
+        Self { store }
+    }
+
+    /// The underlying byte-level store.
+    pub const fn inner(&self) -> &SS {
+        &self.store
+    }
+}
+
+impl<SS: SnapshotBytes<Version>> ArchivedSnapshotStore<SS> {
+    /// Load the snapshot for `id` as archived state, if one was saved under
+    /// `schema_version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ArchivedSnapshotError::Store`] if the store fails to read,
+    /// or [`ArchivedSnapshotError::Archive`] if the bytes are not a valid
+    /// archived `S`.
+    pub async fn hydrate_archived<S>(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> Result<Option<ArchivedSnapshot<S>>, ArchivedSnapshotError<SS::Error>>
+    where
+        S: Archive,
+        S::Archived: for<'a> CheckBytes<HighValidator<'a, rancor::Error>>,
+    {
+        let Some((version, bytes)) = self
+            .store
+            .hydrate_bytes(id, schema_version)
+            .await
+            .map_err(ArchivedSnapshotError::Store)?
+        else {
+            return Ok(None);
+        };
+        ArchivedSnapshot::new(version, bytes)
+            .map(Some)
+            .map_err(ArchivedSnapshotError::Archive)
+    }
+}
+
+/// Validate and deserialize an owned snapshot's bytes.
+fn deserialize_owned<S, E>(version: Version, state: Vec<u8>) -> Result<S, ArchivedSnapshotError<E>>
+where
+    S: Archive,
+    S::Archived: for<'a> CheckBytes<HighValidator<'a, rancor::Error>>
+        + Deserialize<S, HighDeserializer<rancor::Error>>,
+{
+    ArchivedSnapshot::<S>::new(version, Bytes::from(state))
+        .and_then(|snapshot| snapshot.deserialize())
+        .map_err(ArchivedSnapshotError::Archive)
+}
+
+impl<S, SS> SnapshotStore<S, Version> for ArchivedSnapshotStore<SS>
+where
+    S: Archive
+        + for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, rancor::Error>>
+        + Send
+        + Sync,
+    S::Archived: for<'a> CheckBytes<HighValidator<'a, rancor::Error>>
+        + Deserialize<S, HighDeserializer<rancor::Error>>,
+    SS: SnapshotBytes<Version>,
+{
+    type Error = ArchivedSnapshotError<SS::Error>;
+
+    async fn hydrate(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> Result<Option<(Version, S)>, Self::Error> {
+        let Some(snapshot) = self.hydrate_archived::<S>(id, schema_version).await? else {
+            return Ok(None);
+        };
+        let state = snapshot
+            .deserialize()
+            .map_err(ArchivedSnapshotError::Archive)?;
+        Ok(Some((snapshot.version(), state)))
+    }
+
+    async fn commit(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: Version,
+        state: &S,
+    ) -> Result<(), Self::Error> {
+        let archived = to_bytes_in::<_, rancor::Error>(state, AlignedVec::new())
+            .map_err(ArchivedSnapshotError::Archive)?;
+        self.store
+            .commit(id, schema_version, position, &archived.into_vec())
+            .await
+            .map_err(ArchivedSnapshotError::Store)
+    }
+
+    async fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> Result<Option<(NonZeroU32, Version, S)>, Self::Error> {
+        let Some((schema_version, version, state)) = self
+            .store
+            .hydrate_with_schema(id)
+            .await
+            .map_err(ArchivedSnapshotError::Store)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some((
+            schema_version,
+            version,
+            deserialize_owned(version, state)?,
+        )))
+    }
+
+    async fn stored_schema_version(&self, id: &impl Id) -> Result<Option<NonZeroU32>, Self::Error> {
+        self.store
+            .stored_schema_version(id)
+            .await
+            .map_err(ArchivedSnapshotError::Store)
+    }
+
+    async fn hydrate_at_or_before(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: Version,
+    ) -> Result<Option<(Version, S)>, Self::Error> {
+        let Some((version, state)) = self
+            .store
+            .hydrate_at_or_before(id, schema_version, position)
+            .await
+            .map_err(ArchivedSnapshotError::Store)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some((version, deserialize_owned(version, state)?)))
+    }
+}
+
+/// Error from [`ArchivedSnapshotStore`] — the underlying store, or rkyv.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ArchivedSnapshotError<E> {
+    /// The underlying byte-level store failed.
+    #[error(transparent)]
+    Store(E),
+    /// The state could not be archived, validated or deserialized.
+    #[error("rkyv snapshot state: {0}")]
+    Archive(#[source] rancor::Error),
+}
@@ -1,5 +1,9 @@
 use std::marker::PhantomData;
 
+use nexus::{Aggregate, DomainEvent, EventOf, Version};
+
+use crate::codec::DecodePolicy;
+use crate::error::{SchemaVersionMismatch, UndeclaredEventNames};
 use crate::repository::EventStore;
 use crate::store::{RawEventStore, Store};
 
@@ -36,12 +40,27 @@ pub struct NoSnapshot;
 ///
 /// `SS` is a typed snapshot store — the codec (if needed) is composed inside
 /// the store adapter (e.g., [`CodecSnapshotStore`](crate::state::CodecSnapshotStore)).
+///
+/// `B` is the commit mode: [`Inline`](crate::snapshot::Inline) by default,
+/// [`BackgroundSnapshots`] after
+/// [`.background()`](RepositoryBuilder::background), or
+/// [`Atomic`](crate::snapshot::Atomic) from
+/// [`.atomic_snapshots()`](RepositoryBuilder::atomic_snapshots).
 #[cfg(feature = "snapshot")]
-pub struct WithSnapshot<SS, T> {
+pub struct WithSnapshot<SS, T, B = crate::snapshot::Inline> {
     store: SS,
     trigger: T,
     schema_version: std::num::NonZeroU32,
     snapshot_on_read: bool,
+    policy: crate::snapshot::SnapshotPolicy,
+    mode: B,
+}
+
+/// Marker: snapshots are committed by a background worker through a queue
+/// of this capacity. Created by [`.background()`](RepositoryBuilder::background).
+#[cfg(feature = "snapshot")]
+pub struct BackgroundSnapshots {
+    capacity: std::num::NonZeroUsize,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -56,10 +75,11 @@ pub struct WithSnapshot<SS, T> {
 /// (requiring an explicit `.codec()` call).
 ///
 /// Upcasting is not configured here — the resulting facade ships with
-/// the no-upcaster [`Repository::load`](crate::Repository::load) /
-/// [`save`](crate::Repository::save) path. For schema evolution, use the
-/// facade's inherent [`load_with`](EventStore::load_with) /
-/// [`save_with`](EventStore::save_with) methods after `build()`.
+/// the no-upcaster [`Repository::load`](crate::Repository::load) path. For
+/// schema evolution, attach the transforms with
+/// [`transforms`](Self::transforms) — `build()` then checks them against
+/// the event's declared schema versions — and use the facade's inherent
+/// [`load_with`](EventStore::load_with).
 ///
 /// # Example
 ///
@@ -73,12 +93,17 @@ pub struct WithSnapshot<SS, T> {
 /// let repo = store.repository().codec(MyCodec).build();
 ///
 /// // With upcasting (drop to the facade after build):
-/// let repo = store.repository().codec(MyCodec).build();
+/// let repo = store
+///     .repository()
+///     .codec(MyCodec)
+///     .transforms(OrderTransforms::current_version)
+///     .build()?;
 /// let root = repo.load_with(id, OrderTransforms::upcast).await?;
 /// ```
 pub struct RepositoryBuilder<S, C, A, Snap = NoSnapshot> {
     store: Store<S>,
     codec: C,
+    decode_policy: DecodePolicy,
     snapshot: Snap,
     /// The aggregate this builder will bind the facade to (named once at
     /// [`Store::repository::<A>()`]). Threaded through every builder step so
@@ -97,12 +122,114 @@ impl<S, C, A, Snap> RepositoryBuilder<S, C, A, Snap> {
         RepositoryBuilder {
             store: self.store,
             codec,
+            decode_policy: self.decode_policy,
             snapshot: self.snapshot,
             aggregate: PhantomData,
         }
     }
 }
 
+impl<S, C, A: Aggregate, Snap> RepositoryBuilder<S, C, A, Snap> {
+    /// Set how the facade treats persisted event types the aggregate's
+    /// event enum does not know. Defaults to [`DecodePolicy::Strict`].
+    ///
+    /// Use [`SkipUnknown`](DecodePolicy::SkipUnknown) or
+    /// [`CaptureUnknown`](DecodePolicy::CaptureUnknown) for forward
+    /// compatibility while a newer service is rolling out.
+    ///
+    /// # Errors
+    ///
+    /// [`UndeclaredEventNames`] for a non-strict policy when the event enum
+    /// declares no names to tell unknown types by — see
+    /// [`DecodePolicy::check`].
+    pub fn decode_policy(mut self, policy: DecodePolicy) -> Result<Self, UndeclaredEventNames> {
+        self.decode_policy = policy.check::<EventOf<A>>()?;
+        Ok(self)
+    }
+}
+
+impl<S, C, A, Snap> RepositoryBuilder<S, C, A, Snap> {
+    /// Attach the upcaster transforms' chain-end lookup.
+    ///
+    /// `current_version` is the lookup the `#[nexus::transforms]` macro
+    /// emits (e.g. `OrderTransforms::current_version`). The returned
+    /// builder's [`build`](WithTransforms::build) checks it against the
+    /// event's declared schema versions before producing the repository,
+    /// so call this last, after any snapshot configuration.
+    #[must_use]
+    pub const fn transforms<F>(self, current_version: F) -> WithTransforms<S, C, A, Snap, F> {
+        WithTransforms {
+            builder: self,
+            current_version,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// WithTransforms — build checks upcaster chain ends
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A [`RepositoryBuilder`] with upcaster transforms attached, created by
+/// [`.transforms()`](RepositoryBuilder::transforms).
+///
+/// Its only terminal is a fallible [`build`](Self::build): a repository
+/// whose upcasters disagree with the event's declared schema versions is
+/// never produced.
+pub struct WithTransforms<S, C, A, Snap, F> {
+    builder: RepositoryBuilder<S, C, A, Snap>,
+    current_version: F,
+}
+
+impl<S, C, A, Snap, F> WithTransforms<S, C, A, Snap, F>
+where
+    A: Aggregate,
+    EventOf<A>: DomainEvent,
+    F: Fn(&str) -> Option<Version>,
+    RepositoryBuilder<S, C, A, Snap>: terminal::Build,
+{
+    /// Check every upcaster chain, then build the repository the
+    /// underlying builder would.
+    ///
+    /// For each variant in [`DomainEvent::SCHEMA_VERSIONS`], a chain that
+    /// exists (`Some`) must end exactly at the variant's declared version —
+    /// the version [`save`](crate::Repository::save) stamps. Variants
+    /// without a chain (`None`) are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SchemaVersionMismatch`] for the first variant whose chain
+    /// ends elsewhere.
+    pub fn build(
+        self,
+    ) -> Result<<RepositoryBuilder<S, C, A, Snap> as terminal::Build>::Output, SchemaVersionMismatch>
+    {
+        for &(event_type, declared) in EventOf::<A>::SCHEMA_VERSIONS {
+            match (self.current_version)(event_type) {
+                Some(chain_end) if chain_end != declared => {
+                    return Err(SchemaVersionMismatch {
+                        event_type,
+                        declared,
+                        chain_end,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(terminal::Build::build(self.builder))
+    }
+}
+
+mod terminal {
+    /// The infallible `build()` of each builder typestate, so
+    /// [`WithTransforms::build`](super::WithTransforms::build) can defer to
+    /// it. Sealed: implemented only for the builders below.
+    pub trait Build {
+        type Output;
+
+        fn build(self) -> Self::Output;
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // NoSnapshot — plain EventStore
 // ═══════════════════════════════════════════════════════════════════════════
@@ -121,7 +248,19 @@ where
     /// excludes [`NeedsCodec`]).
     #[must_use]
     pub fn build(self) -> EventStore<S, C, A> {
-        EventStore::new(self.store, self.codec)
+        EventStore::new(self.store, self.codec, self.decode_policy)
+    }
+}
+
+impl<S, C, A> terminal::Build for RepositoryBuilder<S, C, A, NoSnapshot>
+where
+    S: RawEventStore,
+    C: Send + Sync + 'static,
+{
+    type Output = EventStore<S, C, A>;
+
+    fn build(self) -> Self::Output {
+        Self::build(self)
     }
 }
 
@@ -136,6 +275,15 @@ use crate::state;
 #[cfg(feature = "snapshot")]
 use std::num::NonZeroU64;
 
+/// Snapshot typestate of [`atomic_snapshots`](RepositoryBuilder::atomic_snapshots):
+/// snapshots read back from the event store itself.
+#[cfg(feature = "snapshot")]
+type AtomicSnapshot<S, Cd> = WithSnapshot<
+    state::CodecSnapshotStore<Store<S>, Cd>,
+    state::EveryNEvents,
+    crate::snapshot::Atomic,
+>;
+
 /// Default snapshot interval.
 #[cfg(feature = "snapshot")]
 const DEFAULT_SNAPSHOT_INTERVAL: u64 = 100;
@@ -181,6 +329,7 @@ impl<S, C, A> RepositoryBuilder<S, C, A, NoSnapshot> {
         RepositoryBuilder {
             store: self.store,
             codec: self.codec,
+            decode_policy: self.decode_policy,
             snapshot: WithSnapshot {
                 store: typed_store,
                 trigger: state::EveryNEvents(
@@ -189,6 +338,48 @@ impl<S, C, A> RepositoryBuilder<S, C, A, NoSnapshot> {
                 ),
                 schema_version: DEFAULT_SCHEMA_VERSION,
                 snapshot_on_read: false,
+                policy: crate::snapshot::SnapshotPolicy::Ignore,
+                mode: crate::snapshot::Inline,
+            },
+            aggregate: PhantomData,
+        }
+    }
+
+    /// Keep snapshots in the event store itself and commit each triggered
+    /// snapshot in the same transaction as its events, JSON-encoded.
+    ///
+    /// The store must implement [`SnapshotAppend`](state::SnapshotAppend);
+    /// see [`Atomic`](crate::snapshot::Atomic). Same defaults as
+    /// [`snapshot_store`](Self::snapshot_store).
+    ///
+    /// # Panics
+    ///
+    /// Cannot panic — the internal `expect` is on a compile-time constant.
+    #[must_use]
+    #[allow(
+        clippy::expect_used,
+        reason = "DEFAULT_SNAPSHOT_INTERVAL is non-zero by inspection"
+    )]
+    pub fn atomic_snapshots(self) -> RepositoryBuilder<S, C, A, AtomicSnapshot<S, crate::JsonCodec>>
+    where
+        S: state::SnapshotAppend,
+    {
+        let typed_store =
+            state::CodecSnapshotStore::new(self.store.clone(), crate::JsonCodec::default());
+        RepositoryBuilder {
+            store: self.store,
+            codec: self.codec,
+            decode_policy: self.decode_policy,
+            snapshot: WithSnapshot {
+                store: typed_store,
+                trigger: state::EveryNEvents(
+                    NonZeroU64::new(DEFAULT_SNAPSHOT_INTERVAL)
+                        .expect("DEFAULT_SNAPSHOT_INTERVAL is non-zero"),
+                ),
+                schema_version: DEFAULT_SCHEMA_VERSION,
+                snapshot_on_read: false,
+                policy: crate::snapshot::SnapshotPolicy::Ignore,
+                mode: crate::snapshot::Atomic,
             },
             aggregate: PhantomData,
         }
@@ -225,6 +416,7 @@ impl<S, C, A> RepositoryBuilder<S, C, A, NoSnapshot> {
         RepositoryBuilder {
             store: self.store,
             codec: self.codec,
+            decode_policy: self.decode_policy,
             snapshot: WithSnapshot {
                 store: snapshot_store,
                 trigger: state::EveryNEvents(
@@ -233,6 +425,49 @@ impl<S, C, A> RepositoryBuilder<S, C, A, NoSnapshot> {
                 ),
                 schema_version: DEFAULT_SCHEMA_VERSION,
                 snapshot_on_read: false,
+                policy: crate::snapshot::SnapshotPolicy::Ignore,
+                mode: crate::snapshot::Inline,
+            },
+            aggregate: PhantomData,
+        }
+    }
+
+    /// Keep snapshots in the event store itself and commit each triggered
+    /// snapshot in the same transaction as its events, encoded with `codec`.
+    ///
+    /// The store must implement [`SnapshotAppend`](state::SnapshotAppend);
+    /// see [`Atomic`](crate::snapshot::Atomic). Same defaults as
+    /// [`snapshot_store`](Self::snapshot_store).
+    ///
+    /// # Panics
+    ///
+    /// Cannot panic — the internal `expect` is on a compile-time constant.
+    #[must_use]
+    #[allow(
+        clippy::expect_used,
+        reason = "DEFAULT_SNAPSHOT_INTERVAL is non-zero by inspection"
+    )]
+    pub fn atomic_snapshots<Cd>(
+        self,
+        codec: Cd,
+    ) -> RepositoryBuilder<S, C, A, AtomicSnapshot<S, Cd>>
+    where
+        S: state::SnapshotAppend,
+    {
+        RepositoryBuilder {
+            store: self.store.clone(),
+            codec: self.codec,
+            decode_policy: self.decode_policy,
+            snapshot: WithSnapshot {
+                store: state::CodecSnapshotStore::new(self.store, codec),
+                trigger: state::EveryNEvents(
+                    NonZeroU64::new(DEFAULT_SNAPSHOT_INTERVAL)
+                        .expect("DEFAULT_SNAPSHOT_INTERVAL is non-zero"),
+                ),
+                schema_version: DEFAULT_SCHEMA_VERSION,
+                snapshot_on_read: false,
+                policy: crate::snapshot::SnapshotPolicy::Ignore,
+                mode: crate::snapshot::Atomic,
             },
             aggregate: PhantomData,
         }
@@ -240,21 +475,24 @@ impl<S, C, A> RepositoryBuilder<S, C, A, NoSnapshot> {
 }
 
 #[cfg(feature = "snapshot")]
-impl<S, C, A, SS, T> RepositoryBuilder<S, C, A, WithSnapshot<SS, T>> {
+impl<S, C, A, SS, T, B> RepositoryBuilder<S, C, A, WithSnapshot<SS, T, B>> {
     /// Replace the snapshot trigger.
     #[must_use]
     pub fn snapshot_trigger<NewT: state::PersistTrigger>(
         self,
         trigger: NewT,
-    ) -> RepositoryBuilder<S, C, A, WithSnapshot<SS, NewT>> {
+    ) -> RepositoryBuilder<S, C, A, WithSnapshot<SS, NewT, B>> {
         RepositoryBuilder {
             store: self.store,
             codec: self.codec,
+            decode_policy: self.decode_policy,
             snapshot: WithSnapshot {
                 store: self.snapshot.store,
                 trigger,
                 schema_version: self.snapshot.schema_version,
                 snapshot_on_read: self.snapshot.snapshot_on_read,
+                policy: self.snapshot.policy,
+                mode: self.snapshot.mode,
             },
             aggregate: PhantomData,
         }
@@ -273,6 +511,64 @@ impl<S, C, A, SS, T> RepositoryBuilder<S, C, A, WithSnapshot<SS, T>> {
         self.snapshot.snapshot_on_read = enabled;
         self
     }
+
+    /// Set what the repository does when the snapshot store fails.
+    /// Defaults to [`SnapshotPolicy::Ignore`](crate::snapshot::SnapshotPolicy::Ignore).
+    #[must_use]
+    pub fn snapshot_policy(mut self, policy: crate::snapshot::SnapshotPolicy) -> Self {
+        self.snapshot.policy = policy;
+        self
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl<S, C, A, SS, T> RepositoryBuilder<S, C, A, WithSnapshot<SS, T>> {
+    /// Commit snapshots from a bounded background queue instead of on the
+    /// save path. `build()` then also returns the
+    /// [`SnapshotWorker`](crate::snapshot::SnapshotWorker) to spawn; see
+    /// [`Snapshotting::background`] for the queue's coalescing rules.
+    #[must_use]
+    pub fn background(
+        self,
+        capacity: std::num::NonZeroUsize,
+    ) -> RepositoryBuilder<S, C, A, WithSnapshot<SS, T, BackgroundSnapshots>> {
+        RepositoryBuilder {
+            store: self.store,
+            codec: self.codec,
+            decode_policy: self.decode_policy,
+            snapshot: WithSnapshot {
+                store: self.snapshot.store,
+                trigger: self.snapshot.trigger,
+                schema_version: self.snapshot.schema_version,
+                snapshot_on_read: self.snapshot.snapshot_on_read,
+                policy: self.snapshot.policy,
+                mode: BackgroundSnapshots { capacity },
+            },
+            aggregate: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl<S, C, A, SS, Cd, T, B>
+    RepositoryBuilder<S, C, A, WithSnapshot<state::CodecSnapshotStore<SS, Cd>, T, B>>
+{
+    /// Migrate snapshots stored at an older schema version instead of
+    /// replaying the stream. See
+    /// [`CodecSnapshotStore::with_upcasters`](state::CodecSnapshotStore::with_upcasters).
+    #[must_use]
+    pub fn snapshot_upcasters(mut self, upcasters: crate::upcasting::SnapshotUpcasters) -> Self {
+        self.snapshot.store = self.snapshot.store.with_upcasters(upcasters);
+        self
+    }
+
+    /// Commit migrated snapshots back at the current schema version. See
+    /// [`CodecSnapshotStore::rewrite_migrated`](state::CodecSnapshotStore::rewrite_migrated).
+    #[must_use]
+    pub fn rewrite_migrated_snapshots(mut self, enabled: bool) -> Self {
+        self.snapshot.store = self.snapshot.store.rewrite_migrated(enabled);
+        self
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -288,7 +584,48 @@ where
     /// Build a snapshot-aware [`EventStore`] for aggregate `A` using an owning [`Codec`](crate::Codec).
     #[must_use]
     pub fn build(self) -> Snapshotting<EventStore<S, C, A>, SS, T> {
-        let inner = EventStore::new(self.store, self.codec);
+        let inner = EventStore::new(self.store, self.codec, self.decode_policy);
+        let snap = self.snapshot;
+        Snapshotting::new(
+            inner,
+            snap.store,
+            snap.trigger,
+            snap.schema_version,
+            snap.snapshot_on_read,
+        )
+        .with_policy(snap.policy)
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl<S, C, A, SS, T> terminal::Build for RepositoryBuilder<S, C, A, WithSnapshot<SS, T>>
+where
+    S: RawEventStore,
+    C: Send + Sync + 'static,
+{
+    type Output = Snapshotting<EventStore<S, C, A>, SS, T>;
+
+    fn build(self) -> Self::Output {
+        Self::build(self)
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl<S, C, A, SS, T> RepositoryBuilder<S, C, A, WithSnapshot<SS, T, BackgroundSnapshots>>
+where
+    S: RawEventStore,
+    C: Send + Sync + 'static,
+    A: Aggregate,
+{
+    /// Build a snapshot-aware [`EventStore`] for aggregate `A` whose
+    /// snapshots are committed by the returned worker.
+    ///
+    /// Spawn [`SnapshotWorker::run`](crate::snapshot::SnapshotWorker::run)
+    /// on your runtime; await it after dropping the repository to flush
+    /// pending snapshots.
+    #[must_use]
+    pub fn build(self) -> crate::snapshot::BackgroundParts<EventStore<S, C, A>, SS, T, A> {
+        let inner = EventStore::new(self.store, self.codec, self.decode_policy);
         let snap = self.snapshot;
         Snapshotting::new(
             inner,
@@ -297,6 +634,61 @@ where
             snap.schema_version,
             snap.snapshot_on_read,
         )
+        .with_policy(snap.policy)
+        .background::<A>(snap.mode.capacity)
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl<S, C, A, SS, T> terminal::Build
+    for RepositoryBuilder<S, C, A, WithSnapshot<SS, T, BackgroundSnapshots>>
+where
+    S: RawEventStore,
+    C: Send + Sync + 'static,
+    A: Aggregate,
+{
+    type Output = crate::snapshot::BackgroundParts<EventStore<S, C, A>, SS, T, A>;
+
+    fn build(self) -> Self::Output {
+        Self::build(self)
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl<S, C, A, SS, T> RepositoryBuilder<S, C, A, WithSnapshot<SS, T, crate::snapshot::Atomic>>
+where
+    S: RawEventStore,
+    C: Send + Sync + 'static,
+{
+    /// Build a snapshot-aware [`EventStore`] for aggregate `A` whose
+    /// snapshots commit in the same transaction as their events.
+    #[must_use]
+    pub fn build(self) -> Snapshotting<EventStore<S, C, A>, SS, T, crate::snapshot::Atomic> {
+        let inner = EventStore::new(self.store, self.codec, self.decode_policy);
+        let snap = self.snapshot;
+        Snapshotting::new(
+            inner,
+            snap.store,
+            snap.trigger,
+            snap.schema_version,
+            snap.snapshot_on_read,
+        )
+        .with_policy(snap.policy)
+        .atomic()
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl<S, C, A, SS, T> terminal::Build
+    for RepositoryBuilder<S, C, A, WithSnapshot<SS, T, crate::snapshot::Atomic>>
+where
+    S: RawEventStore,
+    C: Send + Sync + 'static,
+{
+    type Output = Snapshotting<EventStore<S, C, A>, SS, T, crate::snapshot::Atomic>;
+
+    fn build(self) -> Self::Output {
+        Self::build(self)
     }
 }
 
@@ -335,6 +727,7 @@ impl<S: RawEventStore> Store<S> {
         RepositoryBuilder {
             store: self.clone(),
             codec: crate::JsonCodec::default(),
+            decode_policy: DecodePolicy::Strict,
             snapshot: NoSnapshot,
             aggregate: PhantomData,
         }
@@ -363,6 +756,7 @@ impl<S: RawEventStore> Store<S> {
         RepositoryBuilder {
             store: self.clone(),
             codec: NeedsCodec::new(),
+            decode_policy: DecodePolicy::Strict,
             snapshot: NoSnapshot,
             aggregate: PhantomData,
         }
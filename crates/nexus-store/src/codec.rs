@@ -38,7 +38,10 @@
 //! the unsized archived types (`Archived<MyEvent>`, `[u8]`, `str`) are
 //! representable.
 
+use nexus::DomainEvent;
+
 use crate::envelope::PersistedEnvelope;
+use crate::error::UndeclaredEventNames;
 
 // ═══════════════════════════════════════════════════════════════════════════
 // Encode<E> — serialize a typed value to bytes
@@ -127,6 +130,95 @@ pub trait Decode<E: ?Sized>: Send + Sync + 'static {
     fn decode<'a>(&'a self, env: &'a PersistedEnvelope) -> Result<Self::Output<'a>, Self::Error>;
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// DecodePolicy — what to do with event types the reader does not know
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// How a reader treats persisted events whose type its event enum does not
+/// know — a stream written by a newer service, read by an older one during
+/// a rolling deploy.
+///
+/// "Unknown" means absent from the event type's
+/// [`DomainEvent::SCHEMA_VERSIONS`] (see [`DomainEvent::is_known`]);
+/// `#[derive(DomainEvent)]` lists every variant there. A type that lists
+/// nothing cannot tell unknown names apart, so the non-strict policies are
+/// rejected for it ([`check`](Self::check)). Decode failures of known types
+/// are errors under every policy.
+///
+/// Scope: the repository load path, set with
+/// [`RepositoryBuilder::decode_policy`](crate::RepositoryBuilder::decode_policy).
+/// Subscriptions yield raw envelopes and never decode, so no policy applies
+/// there; a consumer that decodes them can apply one itself with
+/// [`decode`](Self::decode), after [`check`](Self::check)ing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum DecodePolicy {
+    /// Hand every envelope to the codec; an unknown type fails the read
+    /// with the codec's error. The default.
+    #[default]
+    Strict,
+    /// Skip unknown events. A repository still advances the aggregate's
+    /// version past them, so optimistic concurrency is unaffected.
+    SkipUnknown,
+    /// Surface unknown events raw. A repository folds them through
+    /// [`AggregateState::apply_unknown`](nexus::AggregateState::apply_unknown).
+    CaptureUnknown,
+}
+
+/// The outcome of [`DecodePolicy::decode`].
+#[derive(Debug)]
+pub enum Decoded<T> {
+    /// A known event, decoded.
+    Event(T),
+    /// An unknown event under [`DecodePolicy::SkipUnknown`].
+    Skipped,
+    /// An unknown event under [`DecodePolicy::CaptureUnknown`]; the caller
+    /// still holds the envelope.
+    Unknown,
+}
+
+impl DecodePolicy {
+    /// This policy, if it can tell `E`'s unknown event types from its known
+    /// ones.
+    ///
+    /// # Errors
+    ///
+    /// [`UndeclaredEventNames`] for a non-strict policy when `E` declares no
+    /// names in [`DomainEvent::SCHEMA_VERSIONS`].
+    pub fn check<E: DomainEvent>(self) -> Result<Self, UndeclaredEventNames> {
+        if self != Self::Strict && E::SCHEMA_VERSIONS.is_empty() {
+            return Err(UndeclaredEventNames {
+                policy: self,
+                event_type: std::any::type_name::<E>(),
+            });
+        }
+        Ok(self)
+    }
+
+    /// Decode `env` with `codec`, unless the policy says to skip or
+    /// capture it.
+    ///
+    /// # Errors
+    ///
+    /// The codec's error for known event types — and, under
+    /// [`Strict`](Self::Strict), for unknown ones.
+    pub fn decode<'a, E, C>(
+        self,
+        codec: &'a C,
+        env: &'a PersistedEnvelope,
+    ) -> Result<Decoded<C::Output<'a>>, C::Error>
+    where
+        E: DomainEvent,
+        C: Decode<E>,
+    {
+        match self {
+            Self::SkipUnknown if !E::is_known(env.event_type()) => Ok(Decoded::Skipped),
+            Self::CaptureUnknown if !E::is_known(env.event_type()) => Ok(Decoded::Unknown),
+            _ => codec.decode(env).map(Decoded::Event),
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Serde adapter — feature-gated Encode/Decode impls driven by a SerdeFormat
 // ═══════════════════════════════════════════════════════════════════════════
@@ -1,7 +1,7 @@
 use std::ops::Range;
 
 use bytes::Bytes;
-use nexus::Version;
+use nexus::{UnknownEvent, Version};
 use thiserror::Error;
 
 use crate::value::{
@@ -546,6 +546,19 @@ impl PersistedEnvelope {
         Version::from(self.schema_version)
     }
 
+    /// A borrowed view for [`AggregateState::apply_unknown`](nexus::AggregateState::apply_unknown)
+    /// — the raw parts of an event whose type the reader does not know.
+    #[must_use]
+    pub fn as_unknown_event(&self) -> UnknownEvent<'_> {
+        UnknownEvent::new(
+            self.version(),
+            self.event_type(),
+            self.schema_version_as_version(),
+            self.payload(),
+            self.metadata(),
+        )
+    }
+
     /// Wrap raw bytes in a synthetic envelope suitable for [`Decode`].
     ///
     /// Builds a fresh wire-format frame via [`crate::wire::encode_frame`] so the
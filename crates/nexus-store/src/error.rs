@@ -1,6 +1,8 @@
 use nexus::{ErrorId, KernelError, Version};
 use thiserror::Error;
 
+use crate::codec::DecodePolicy;
+
 /// Errors from the event store layer.
 ///
 /// Generic over adapter (`A`), encode (`EncErr`), and decode (`DecErr`) error
@@ -133,6 +135,44 @@ impl<A, EncErr, DecErr, UpErr> From<KernelError> for LoadWithError<A, EncErr, De
     }
 }
 
+/// An upcaster chain ends at a different schema version than the event
+/// declares.
+///
+/// Returned by [`WithTransforms::build`](crate::WithTransforms::build)
+/// when, for some variant, the transforms' `current_version` disagrees with
+/// [`DomainEvent::schema_version`](nexus::DomainEvent::schema_version). Left
+/// unchecked, `save` would stamp new events at `declared` while `load_with`
+/// upcasts stored events to `chain_end` — two different shapes under one
+/// event type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error(
+    "upcaster chain for '{event_type}' ends at schema version {chain_end}, but the event declares {declared}"
+)]
+pub struct SchemaVersionMismatch {
+    /// The event variant name.
+    pub event_type: &'static str,
+    /// The version the event declares (what `save` stamps).
+    pub declared: Version,
+    /// The version the upcaster chain migrates stored events to.
+    pub chain_end: Version,
+}
+
+/// A non-strict [`DecodePolicy`] for an event type that declares no names
+/// in [`DomainEvent::SCHEMA_VERSIONS`](nexus::DomainEvent::SCHEMA_VERSIONS).
+///
+/// With nothing declared every event type counts as known, so the policy
+/// would silently behave as [`DecodePolicy::Strict`]. Returned by
+/// [`DecodePolicy::check`] and
+/// [`RepositoryBuilder::decode_policy`](crate::RepositoryBuilder::decode_policy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("decode policy {policy:?} needs '{event_type}' to declare its event names")]
+pub struct UndeclaredEventNames {
+    /// The rejected policy.
+    pub policy: DecodePolicy,
+    /// The event type's Rust type name.
+    pub event_type: &'static str,
+}
+
 /// Structured error from [`RawEventStore::append`](crate::RawEventStore::append).
 ///
 /// Separates concurrency conflicts (a normal, expected condition in
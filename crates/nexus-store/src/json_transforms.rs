@@ -0,0 +1,239 @@
+//! Declarative JSON payload transforms for upcasters.
+//!
+//! Most schema migrations are small edits to a JSON object: rename a
+//! field, add a default, nest a few fields, rewrite an enum string. This
+//! module ships those edits as plain functions over a parsed
+//! [`JsonObject`], so one upcast step parses the payload once, applies any
+//! number of edits in order, and serializes once:
+//!
+//! ```
+//! use nexus_store::json_transforms::{self, JsonTransformError, Value};
+//!
+//! fn v1_to_v2(payload: &[u8]) -> Result<Vec<u8>, JsonTransformError> {
+//!     json_transforms::edit_payload(payload, |object| {
+//!         json_transforms::rename_field(object, "customer", "customer_id")?;
+//!         json_transforms::add_default(object, "currency", Value::from("EUR"));
+//!         json_transforms::move_into(object, &["street", "city"], "address")?;
+//!         json_transforms::map_value(object, "status", |status| {
+//!             Ok::<_, JsonTransformError>(match status.as_str() {
+//!                 Some("open") => Value::from("Pending"),
+//!                 _ => status,
+//!             })
+//!         })
+//!     })
+//! }
+//!
+//! let v2 = v1_to_v2(br#"{"customer":"c-1","street":"Main","city":"Oslo","status":"open"}"#)?;
+//! let v2: Value = serde_json::from_slice(&v2)?;
+//! assert_eq!(v2["customer_id"], "c-1");
+//! assert_eq!(v2["currency"], "EUR");
+//! assert_eq!(v2["address"]["city"], "Oslo");
+//! assert_eq!(v2["status"], "Pending");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Field edits are tolerant of absent fields (an older writer may have
+//! omitted an optional one) and strict about clobbering present ones: a
+//! rename or move onto an existing field is an error, never a silent
+//! overwrite.
+//!
+//! `#[nexus::transforms]` accepts the same edits declaratively inside
+//! `#[transform(...)]`; see its docs.
+
+use std::borrow::Cow;
+
+pub use serde_json::{Map, Value};
+
+use crate::upcasting::EventMorsel;
+
+/// A JSON object payload, as edited by every transform in this module.
+pub type JsonObject = Map<String, Value>;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Error
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A JSON payload transform failed.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum JsonTransformError {
+    /// The payload is not valid JSON, or the edited object failed to
+    /// serialize.
+    #[error("invalid JSON payload: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The payload is valid JSON but not an object.
+    #[error("JSON payload is not an object")]
+    NotAnObject,
+
+    /// A rename or move would overwrite a field that is already present.
+    #[error("field '{field}' already exists")]
+    FieldExists {
+        /// The field that would have been overwritten.
+        field: String,
+    },
+
+    /// `move_into` targets a field that is present but not an object.
+    #[error("field '{field}' is not a JSON object")]
+    FieldNotAnObject {
+        /// The non-object target field.
+        field: String,
+    },
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Payload / morsel entry points
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Parse `payload` as a JSON object, apply `edit`, and serialize the result.
+///
+/// The signature of an upcast step, so a `#[transform]` function body can be
+/// a single `edit_payload` call.
+///
+/// # Errors
+///
+/// [`JsonTransformError::Json`] if `payload` is not valid JSON,
+/// [`JsonTransformError::NotAnObject`] if it is not an object, or whatever
+/// `edit` returns.
+pub fn edit_payload<E, F>(payload: &[u8], edit: F) -> Result<Vec<u8>, E>
+where
+    E: From<JsonTransformError>,
+    F: FnOnce(&mut JsonObject) -> Result<(), E>,
+{
+    let value: Value = serde_json::from_slice(payload).map_err(JsonTransformError::from)?;
+    let Value::Object(mut object) = value else {
+        return Err(JsonTransformError::NotAnObject.into());
+    };
+    edit(&mut object)?;
+    Ok(serde_json::to_vec(&object).map_err(JsonTransformError::from)?)
+}
+
+/// [`edit_payload`] on a morsel's payload, keeping its event type and
+/// schema version.
+///
+/// # Errors
+///
+/// As [`edit_payload`].
+pub fn edit<E, F>(morsel: EventMorsel<'_>, edit: F) -> Result<EventMorsel<'_>, E>
+where
+    E: From<JsonTransformError>,
+    F: FnOnce(&mut JsonObject) -> Result<(), E>,
+{
+    let payload = edit_payload(morsel.payload(), edit)?;
+    Ok(morsel.with_payload(Cow::Owned(payload)))
+}
+
+/// Rename the morsel's event type. The payload is untouched (and stays
+/// borrowed if it was).
+#[must_use]
+pub fn rename_event_type<'a>(
+    morsel: EventMorsel<'a>,
+    event_type: impl Into<Cow<'a, str>>,
+) -> EventMorsel<'a> {
+    morsel.with_event_type(event_type.into())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Object edits
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Rename field `from` to `to`. No-op when `from` is absent.
+///
+/// # Errors
+///
+/// [`JsonTransformError::FieldExists`] if `to` is already present.
+pub fn rename_field(
+    object: &mut JsonObject,
+    from: &str,
+    to: &str,
+) -> Result<(), JsonTransformError> {
+    if from == to || !object.contains_key(from) {
+        return Ok(());
+    }
+    if object.contains_key(to) {
+        return Err(JsonTransformError::FieldExists {
+            field: to.to_owned(),
+        });
+    }
+    if let Some(value) = object.remove(from) {
+        object.insert(to.to_owned(), value);
+    }
+    Ok(())
+}
+
+/// Set `field` to `value` unless it is already present.
+pub fn add_default(object: &mut JsonObject, field: &str, value: Value) {
+    object.entry(field).or_insert(value);
+}
+
+/// Remove `field`. No-op when absent.
+pub fn remove_field(object: &mut JsonObject, field: &str) {
+    object.remove(field);
+}
+
+/// Move each of `fields` into the nested object at `into`, creating it
+/// when absent. Absent fields are skipped; if none is present and `into`
+/// is absent, nothing is created.
+///
+/// # Errors
+///
+/// [`JsonTransformError::FieldNotAnObject`] if `into` is present but not an
+/// object, or [`JsonTransformError::FieldExists`] if a moved field is
+/// already present inside it. The object is left untouched on error.
+pub fn move_into(
+    object: &mut JsonObject,
+    fields: &[&str],
+    into: &str,
+) -> Result<(), JsonTransformError> {
+    let present: Vec<&str> = fields
+        .iter()
+        .copied()
+        .filter(|field| *field != into && object.contains_key(*field))
+        .collect();
+    if present.is_empty() {
+        return Ok(());
+    }
+    match object.get(into) {
+        None => {}
+        Some(Value::Object(nested)) => {
+            if let Some(field) = present.iter().find(|field| nested.contains_key(**field)) {
+                return Err(JsonTransformError::FieldExists {
+                    field: format!("{into}.{field}"),
+                });
+            }
+        }
+        Some(_) => {
+            return Err(JsonTransformError::FieldNotAnObject {
+                field: into.to_owned(),
+            });
+        }
+    }
+    let mut moved = JsonObject::new();
+    for field in present {
+        if let Some(value) = object.remove(field) {
+            moved.insert(field.to_owned(), value);
+        }
+    }
+    if let Value::Object(nested) = object
+        .entry(into)
+        .or_insert_with(|| Value::Object(JsonObject::new()))
+    {
+        nested.append(&mut moved);
+    }
+    Ok(())
+}
+
+/// Replace `field`'s value with `map(value)`. No-op when absent.
+///
+/// # Errors
+///
+/// Whatever `map` returns; the field is left `null` in that case.
+pub fn map_value<E, F>(object: &mut JsonObject, field: &str, map: F) -> Result<(), E>
+where
+    F: FnOnce(Value) -> Result<Value, E>,
+{
+    if let Some(slot) = object.get_mut(field) {
+        *slot = map(std::mem::take(slot))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,319 @@
+//! Leader leases for singleton consumers.
+//!
+//! A projection or saga poller deployed on several replicas must run on
+//! exactly one of them at a time. A [`LeaderLease`] hands out at most one
+//! [`LeaseGuard`] per lease name across every process sharing the backend;
+//! the holder runs the consumer loop, the others wait in
+//! [`acquire`](LeaderLease::acquire) to take over.
+//!
+//! A lease can be lost without the holder letting go — the connection
+//! backing it drops, or an operator revokes it. [`LeaseGuard::lost`] resolves
+//! when that happens, and [`while_held`] ties a subscription to the guard so
+//! delivery stops at once rather than racing the next leader:
+//!
+//! ```ignore
+//! loop {
+//!     let guard = leases.acquire("order-summary").await?;
+//!     let from = checkpoints.load(&"order-summary").await?;
+//!     let events = while_held(guard, Subscription::new(&store).subscribe_all(from)?);
+//!     let mut events = pin!(events);
+//!     while let Some(item) = events.next().await { /* project, checkpoint */ }
+//!     // `None`: the lease is gone. Go back to waiting for it.
+//! }
+//! ```
+//!
+//! A lease only narrows the window in which two replicas act as leader — the
+//! old one notices the loss one heartbeat late at worst. Consumers still
+//! checkpoint idempotently (e.g. [`SnapshotStore`](crate::SnapshotStore)'s
+//! "newer wins" save), so a straggling write cannot move a checkpoint back.
+//!
+//! Like [`wake`](crate::wake), the traits are only ever used as generic
+//! bounds, so their futures are RPITIT — no boxing.
+
+use core::future::Future;
+
+use futures::StreamExt;
+use futures_core::Stream;
+
+/// A named, exclusive lease shared by every process using the same backend.
+pub trait LeaderLease: Send + Sync + 'static {
+    /// Proof of holding a lease; dropping it releases the lease.
+    type Guard: LeaseGuard;
+    /// Failure to reach the backend.
+    type Error: core::error::Error + Send + Sync + 'static;
+
+    /// Take lease `name` if nobody holds it.
+    ///
+    /// Returns `Ok(None)` when another holder has it.
+    ///
+    /// # Errors
+    /// Adapter-specific backend failure.
+    fn try_acquire(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Result<Option<Self::Guard>, Self::Error>> + Send;
+
+    /// Wait until lease `name` is free, then take it.
+    ///
+    /// # Errors
+    /// Adapter-specific backend failure.
+    fn acquire(&self, name: &str) -> impl Future<Output = Result<Self::Guard, Self::Error>> + Send;
+}
+
+/// A held lease. Dropping it releases the lease for the next holder.
+pub trait LeaseGuard: Send + 'static {
+    /// Whether the lease is still held. `false` once it is lost; it never
+    /// becomes `true` again — acquire a new guard instead.
+    fn is_held(&self) -> bool;
+
+    /// Resolves once the lease is lost (immediately if it already is). Never
+    /// resolves while it is held. The future carries no borrow of `self`, so
+    /// it can outlive the guard.
+    fn lost(&self) -> impl Future<Output = ()> + Send + use<Self>;
+}
+
+/// Deliver `stream`'s items only while `guard` holds its lease.
+///
+/// The returned stream takes ownership of the guard. It ends — yields
+/// `None` — as soon as the lease is lost, without delivering another item,
+/// and releases the lease when it is dropped. Subscriptions never end on
+/// their own, so for them `None` means exactly "no longer leader".
+pub fn while_held<G, St>(guard: G, stream: St) -> impl Stream<Item = St::Item> + Send
+where
+    G: LeaseGuard,
+    St: Stream + Send,
+{
+    let lost = guard.lost();
+    stream.take_until(async move {
+        // Owned here so the lease lives exactly as long as the stream.
+        let _guard = guard;
+        lost.await;
+    })
+}
+
+// ---------------------------------------------------------------------------
+// In-memory leases for tests
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "testing")]
+pub use memory::{InMemoryLeaseGuard, InMemoryLeases};
+
+#[cfg(feature = "testing")]
+mod memory {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+    use tokio::sync::{Notify, watch};
+
+    use super::{LeaderLease, LeaseGuard};
+
+    /// Process-local [`LeaderLease`] for tests. Clones share one lease table,
+    /// standing in for replicas sharing a database.
+    ///
+    /// [`revoke`](Self::revoke) takes a lease away from its holder, the way a
+    /// dropped connection would.
+    #[derive(Debug, Clone, Default)]
+    pub struct InMemoryLeases {
+        table: Arc<LeaseTable>,
+    }
+
+    #[derive(Debug, Default)]
+    struct LeaseTable {
+        /// Held leases: the holder's token and its "still held" signal.
+        held: Mutex<HashMap<String, (u64, watch::Sender<bool>)>>,
+        next_token: Mutex<u64>,
+        /// Signalled whenever a lease is released or revoked.
+        released: Notify,
+    }
+
+    impl LeaseTable {
+        fn take(self: &Arc<Self>, name: &str) -> Option<InMemoryLeaseGuard> {
+            let mut held = self.held.lock();
+            if held.contains_key(name) {
+                return None;
+            }
+            let token = {
+                let mut next = self.next_token.lock();
+                *next += 1;
+                *next
+            };
+            let (signal, receiver) = watch::channel(true);
+            held.insert(name.to_owned(), (token, signal));
+            drop(held);
+            Some(InMemoryLeaseGuard {
+                table: Arc::clone(self),
+                name: name.to_owned(),
+                token,
+                held: receiver,
+            })
+        }
+
+        /// Drop `name`'s entry if `token` (or any holder, for `None`) owns it.
+        fn end(&self, name: &str, token: Option<u64>) -> bool {
+            let mut held = self.held.lock();
+            let owned = held
+                .get(name)
+                .is_some_and(|(holder, _)| token.is_none_or(|t| t == *holder));
+            if owned && let Some((_, signal)) = held.remove(name) {
+                signal.send_replace(false);
+            }
+            drop(held);
+            if owned {
+                self.released.notify_waiters();
+            }
+            owned
+        }
+    }
+
+    impl InMemoryLeases {
+        /// An empty lease table.
+        #[must_use]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Take lease `name` from its holder, if any; its guard reports the
+        /// loss and the lease is free again. Returns whether it was held.
+        #[must_use]
+        pub fn revoke(&self, name: &str) -> bool {
+            self.table.end(name, None)
+        }
+
+        /// Whether anyone holds lease `name`.
+        #[must_use]
+        pub fn is_held(&self, name: &str) -> bool {
+            self.table.held.lock().contains_key(name)
+        }
+    }
+
+    impl LeaderLease for InMemoryLeases {
+        type Guard = InMemoryLeaseGuard;
+        type Error = core::convert::Infallible;
+
+        async fn try_acquire(&self, name: &str) -> Result<Option<Self::Guard>, Self::Error> {
+            Ok(self.table.take(name))
+        }
+
+        async fn acquire(&self, name: &str) -> Result<Self::Guard, Self::Error> {
+            loop {
+                // Registered before the attempt, so a release in between is
+                // not missed.
+                let released = self.table.released.notified();
+                tokio::pin!(released);
+                released.as_mut().enable();
+                if let Some(guard) = self.table.take(name) {
+                    return Ok(guard);
+                }
+                released.await;
+            }
+        }
+    }
+
+    /// An [`InMemoryLeases`] lease; dropping it releases the lease.
+    #[derive(Debug)]
+    pub struct InMemoryLeaseGuard {
+        table: Arc<LeaseTable>,
+        name: String,
+        token: u64,
+        held: watch::Receiver<bool>,
+    }
+
+    impl LeaseGuard for InMemoryLeaseGuard {
+        fn is_held(&self) -> bool {
+            *self.held.borrow()
+        }
+
+        fn lost(&self) -> impl Future<Output = ()> + Send + use<> {
+            let mut held = self.held.clone();
+            async move {
+                // A dropped signal means the entry is gone: lost as well.
+                let _ = held.wait_for(|still| !still).await;
+            }
+        }
+    }
+
+    impl Drop for InMemoryLeaseGuard {
+        fn drop(&mut self) {
+            self.table.end(&self.name, Some(self.token));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+#[allow(clippy::unwrap_used, reason = "test code")]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use tokio::time::timeout;
+
+    use super::*;
+
+    const MUST_WAKE: Duration = Duration::from_secs(5);
+
+    #[tokio::test]
+    async fn one_holder_per_name() {
+        let leases = InMemoryLeases::new();
+        let other = leases.clone();
+        let guard = leases.try_acquire("a").await.unwrap().unwrap();
+        assert!(other.try_acquire("a").await.unwrap().is_none());
+        assert!(other.try_acquire("b").await.unwrap().is_some());
+        assert!(guard.is_held());
+        drop(guard);
+        assert!(!leases.is_held("a"));
+        assert!(other.try_acquire("a").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn acquire_waits_for_the_holder_to_let_go() {
+        let leases = InMemoryLeases::new();
+        let guard = leases.acquire("a").await.unwrap();
+        let waiting = tokio::spawn({
+            let replica = leases.clone();
+            async move { replica.acquire("a").await.unwrap() }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        drop(guard);
+        let next = timeout(MUST_WAKE, waiting).await.unwrap().unwrap();
+        assert!(next.is_held());
+    }
+
+    #[tokio::test]
+    async fn revoking_signals_the_holder_and_a_stale_drop_is_harmless() {
+        let leases = InMemoryLeases::new();
+        let old = leases.acquire("a").await.unwrap();
+        let lost = old.lost();
+        assert!(leases.revoke("a"));
+        timeout(MUST_WAKE, lost).await.unwrap();
+        assert!(!old.is_held());
+
+        // The old guard's drop must not release the new holder's lease.
+        let new = leases.acquire("a").await.unwrap();
+        drop(old);
+        assert!(new.is_held());
+        assert!(leases.is_held("a"));
+    }
+
+    #[tokio::test]
+    async fn while_held_ends_the_stream_on_loss_and_releases_on_drop() {
+        let leases = InMemoryLeases::new();
+        let guard = leases.acquire("a").await.unwrap();
+        let (tx, rx) = futures::channel::mpsc::unbounded::<u32>();
+        let stream = while_held(guard, rx);
+        futures::pin_mut!(stream);
+
+        tx.unbounded_send(1).unwrap();
+        assert_eq!(stream.next().await, Some(1));
+
+        // Queued items are not delivered once the lease is gone.
+        tx.unbounded_send(2).unwrap();
+        assert!(leases.revoke("a"));
+        assert_eq!(timeout(MUST_WAKE, stream.next()).await.unwrap(), None);
+
+        let other = leases.acquire("b").await.unwrap();
+        drop(while_held(other, futures::stream::pending::<()>()));
+        assert!(!leases.is_held("b"));
+    }
+}
@@ -17,6 +17,8 @@
 //!   into a single shape that covers both owning serde codecs and
 //!   borrowing codecs (rkyv, bytemuck). Feature-gated codec impls
 //!   (`serde`, `json`, `bytemuck`, `rkyv`) ship with the crate.
+//!   [`DecodePolicy`] decides what a repository does with event types it
+//!   does not know (fail, skip, or capture raw).
 //! - [`envelope`] — [`PendingEnvelope`] (write path, typestate-built) and
 //!   [`PersistedEnvelope`] (read path, owned [`bytes::Bytes`] + cached
 //!   `Range<u32>` offsets). The read envelope is cheap-to-clone (Arc
@@ -25,7 +27,8 @@
 //! - [`store`] — adapter-facing [`RawEventStore`] trait,
 //!   [`Store<S>`](crate::store::Store) shared handle, and [`AllPosition`]
 //!   (the adapter-defined `$all` resume position — the concrete type lives in
-//!   each adapter, only the trait here).
+//!   each adapter, only the trait here), plus [`TransactionalAppend<Tx>`]
+//!   for appends that join a caller-owned transaction.
 //! - [`subscription`] — user-facing [`Subscription<S>`] struct (built
 //!   via `Subscription::new(&store)`). Its `subscribe` / `subscribe_all`
 //!   methods assemble the generic catch-up-then-live-tail loop from
@@ -46,16 +49,29 @@
 //!   trait plus its facade impl ([`EventStore`], one terminal for both
 //!   owning and borrowing codecs), constructed via the
 //!   [`RepositoryBuilder`] typestate.
+//! - [`time_travel`] — [`TimeTravel<A>`] historical loads (at a version, or
+//!   as of a time read from event metadata) returning a [`ReadOnlyRoot`].
 //! - [`state`] — [`SnapshotStore<S, P>`](crate::SnapshotStore) for atomic
 //!   state+position persistence. Powers both aggregate snapshots and
 //!   projection state — same trait, different position type
 //!   ([`Version`] for a single stream vs an adapter's [`AllPosition`] for a
 //!   multi-stream projection).
-//! - [`upcasting`] — schema evolution via the [`Upcaster`] trait and
-//!   [`EventMorsel`] zero-copy-when-possible data unit.
+//! - [`archived`] (feature-gated) — [`ArchivedSnapshotStore`] hands out
+//!   rkyv snapshot state as `&Archived<S>` straight from the stored bytes
+//!   (via [`SnapshotBytes`]), deserializing only when events must be applied.
+//! - [`upcasting`] — schema evolution via plain upcast functions over the
+//!   [`EventMorsel`] zero-copy-when-possible data unit, and the
+//!   [`TransformRegistry`] `#[nexus::transforms]` emits to enumerate them;
+//!   [`SnapshotUpcasters`] do the same for snapshot state bytes, keyed by
+//!   snapshot schema version (`#[nexus::snapshot_transforms]`).
+//! - [`json_transforms`] (feature-gated) — declarative JSON payload edits
+//!   (`rename_field`, `add_default`, `move_into`, …) for upcast steps.
 //! - [`snapshot`] (feature-gated) — decorator that wraps a repository to
 //!   hydrate from a [`SnapshotStore`] on read and commit on write per a
-//!   [`PersistTrigger`].
+//!   [`PersistTrigger`], handling store failures per a [`SnapshotPolicy`].
+//! - [`lease`] — [`LeaderLease`] / [`LeaseGuard`]: named, exclusive leases
+//!   so a singleton consumer runs on one replica at a time, and
+//!   [`while_held`], which stops a subscription the moment its lease is lost.
 //! - [`projection`] (feature-gated) — [`Projector`] trait (pure fallible
 //!   fold). nexus ships no runner; the loop is consumer-owned (see
 //!   `examples/projection-tokio`).
@@ -65,15 +81,15 @@
 //! | Feature | Effect |
 //! |---|---|
 //! | `serde` | Generic serde codec (`SerdeCodec<F>`) |
-//! | `json` | `Json` format + `JsonCodec` alias (implies `serde`) |
+//! | `json` | `Json` format + `JsonCodec` alias, `json_transforms` (implies `serde`) |
 //! | `bytemuck` | `BytemuckCodec` for `#[repr(C)]` POD types (zero-copy `&E`) |
-//! | `rkyv` | `RkyvCodec` for rkyv-archived types (zero-copy `&Archived<E>`) |
-//! | `snapshot` | `Snapshotting<R, SS, T>` repository decorator |
+//! | `rkyv` | `RkyvCodec` for rkyv-archived types (zero-copy `&Archived<E>`), `ArchivedSnapshotStore` |
+//! | `snapshot` | `Snapshotting<R, SS, T, M>` repository decorator, inline, background or atomic commits |
 //! | `snapshot-json` | `snapshot` + `json` |
 //! | `projection` | `Projector` trait |
 //! | `projection-json` | `projection` + `json` |
 //! | `subscription` | [`StreamNotifiers`](crate::notify) per-stream wake registry (pulls `tokio`, `foldhash`, `parking_lot`) |
-//! | `testing` | `InMemoryStore`, `InMemorySnapshotStore` for tests (implies `subscription`) |
+//! | `testing` | `InMemoryStore`, `InMemorySnapshotStore`, `InMemoryLeases` for tests (implies `subscription`) |
 //!
 //! # Design notes
 //!
@@ -84,6 +100,8 @@
 //! (kernel-pure → store-persistence → adapters); the boundary that
 //! didn't matter was inside `nexus-store`.
 
+#[cfg(feature = "rkyv")]
+pub mod archived;
 pub mod batch;
 pub mod builder;
 #[cfg(feature = "subscription")]
@@ -97,6 +115,9 @@ pub mod error;
 pub mod export;
 #[cfg(feature = "import")]
 pub mod import;
+#[cfg(feature = "json")]
+pub mod json_transforms;
+pub mod lease;
 #[cfg(feature = "subscription")]
 pub mod notify;
 #[cfg(feature = "projection")]
@@ -115,16 +136,19 @@ pub mod subscription;
 pub(crate) mod subscription_cursor;
 #[cfg(feature = "testing")]
 pub mod testing;
+pub mod time_travel;
 pub mod upcasting;
 pub mod value;
 #[cfg(feature = "subscription")]
 pub mod wake;
 pub mod wire;
 
+#[cfg(feature = "rkyv")]
+pub use archived::{ArchivedSnapshot, ArchivedSnapshotError, ArchivedSnapshotStore};
 pub use batch::{BatchSize, BatchSizeError, DEFAULT_BATCH, MAX_BATCH};
 #[cfg(feature = "snapshot")]
-pub use builder::WithSnapshot;
-pub use builder::{NeedsCodec, NoSnapshot, RepositoryBuilder};
+pub use builder::{BackgroundSnapshots, WithSnapshot};
+pub use builder::{NeedsCodec, NoSnapshot, RepositoryBuilder, WithTransforms};
 // Re-export `bytes` so downstreams name `nexus_store::bytes::Bytes` to feed
 // `Encode` / the value newtypes, sharing *our* version rather than coupling to
 // theirs. Additive (non-breaking).
@@ -138,12 +162,12 @@ pub use cbor::{
 pub use codec::serde::json::{Json, JsonCodec};
 #[cfg(feature = "serde")]
 pub use codec::serde::{SerdeCodec, SerdeFormat};
-pub use codec::{Decode, Encode};
+pub use codec::{Decode, DecodePolicy, Decoded, Encode};
 pub use envelope::{
     EnvelopeError, ForDecodeError, PendingEnvelope, PersistedEnvelope, pending_envelope,
 };
 pub use error::LoadWithError;
-pub use error::{AppendError, StoreError};
+pub use error::{AppendError, SchemaVersionMismatch, StoreError, UndeclaredEventNames};
 #[cfg(feature = "export")]
 pub use export::{EventExporter, StreamLister};
 #[cfg(feature = "import")]
@@ -151,6 +175,11 @@ pub use import::{
     AbortReason, Atomicity, EventImporter, ImportBlock, ImportError, ImportReport, StreamOutcome,
     StreamReport, StreamSection,
 };
+#[cfg(feature = "json")]
+pub use json_transforms::JsonTransformError;
+#[cfg(feature = "testing")]
+pub use lease::InMemoryLeases;
+pub use lease::{LeaderLease, LeaseGuard, while_held};
 pub use nexus::Version;
 #[cfg(feature = "projection")]
 pub use projection::Projector;
@@ -160,16 +189,22 @@ pub use saga::{
     SagaError, SagaRepository,
 };
 #[cfg(feature = "snapshot")]
-pub use snapshot::Snapshotting;
+pub use snapshot::{
+    Atomic, Background, BackgroundParts, CommitMode, Inline, SnapshotFailure, SnapshotObserver,
+    SnapshotOperation, SnapshotPolicy, SnapshotStats, SnapshotWorker, Snapshotting,
+    SnapshottingError,
+};
 #[cfg(feature = "testing")]
 pub use state::InMemorySnapshotStore;
 pub use state::{
-    AfterEventTypes, CodecSnapshotStore, CodecSnapshotStoreError, EveryNEvents, PersistTrigger,
-    SnapshotStore,
+    AfterEventTypes, AllOf, AnyOf, CodecSnapshotStore, CodecSnapshotStoreError, EveryNEvents,
+    MinInterval, Not, PayloadBytes, PendingSnapshot, PersistContext, PersistTrigger, ReplayCost,
+    SnapshotAppend, SnapshotBytes, SnapshotRetention, SnapshotStore,
 };
-pub use store::{AllPosition, RawEventStore, Store};
+pub use store::{AllPosition, RawEventStore, Store, TransactionalAppend};
 pub use stream::EventStream;
 pub use stream_id::StreamKey;
+pub use time_travel::{ReadOnlyRoot, TimeTravel};
 // Re-export the `Stream` trait from `futures-core` (the small, near-frozen
 // definitional crate) rather than `futures`. `futures::Stream` *is* this trait,
 // so our public `EventStream` / `subscribe*` surface is married to
@@ -179,7 +214,7 @@ pub use futures_core::Stream;
 pub use subscription::Subscription;
 #[cfg(feature = "testing")]
 pub use testing::InMemoryStoreError;
-pub use upcasting::EventMorsel;
+pub use upcasting::{EventMorsel, SnapshotUpcastError, SnapshotUpcasters, TransformRegistry};
 pub use value::{EventType, Metadata, Payload, SchemaVersion, ValueError};
 #[cfg(feature = "subscription")]
 pub use wake::{WakeRegistration, WakeSource};
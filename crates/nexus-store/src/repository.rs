@@ -8,20 +8,25 @@
 )]
 
 use std::borrow::Borrow;
-use std::future::Future;
+use std::future::{self, Future};
 use std::marker::PhantomData;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 
-use nexus::{Aggregate, AggregateRoot, DomainEvent, EventOf, Events, Version};
+use nexus::{Aggregate, AggregateRoot, DomainEvent, EventOf, Events, UnknownEvent, Version};
 
+#[cfg(feature = "snapshot")]
+use bytes::Bytes;
 use futures::TryStreamExt;
 
-use crate::codec::{Decode, Encode};
-use crate::envelope::{PersistedEnvelope, pending_envelope};
+use crate::codec::{Decode, DecodePolicy, Decoded, Encode};
+use crate::envelope::{PendingEnvelope, PersistedEnvelope, pending_envelope};
 use crate::error::{AppendError, LoadWithError, StoreError};
-use crate::store::{RawEventStore, Store};
+#[cfg(feature = "snapshot")]
+use crate::state::{PendingSnapshot, SnapshotAppend};
+use crate::store::{RawEventStore, Store, TransactionalAppend};
 use crate::stream_id::StreamKey;
+use crate::time_travel::{ReadOnlyRoot, TimeTravel};
 use crate::upcasting::EventMorsel;
 use crate::value::SchemaVersion;
 
@@ -64,7 +69,8 @@ use crate::value::SchemaVersion;
 ///
 /// The trait surface does not carry an upcaster — `load()` reads events
 /// at their stored schema version and decodes them directly, while `save()`
-/// stamps `Version::INITIAL` as the schema version on each new event. For
+/// stamps each new event with its declared
+/// [`DomainEvent::schema_version`](nexus::DomainEvent::schema_version). For
 /// schema evolution, drop to the concrete facade and call its inherent
 /// [`load_with`](EventStore::load_with) /
 /// [`save_with`](EventStore::save_with) methods (or compose the
@@ -125,6 +131,19 @@ pub(crate) trait ReplayFrom<A: Aggregate>: Send + Sync {
     /// The error type for replay operations.
     type Error: std::error::Error + Send + Sync + 'static;
 
+    /// Replay events starting from `from` version (inclusive) into `root`,
+    /// stopping before the first event `keep` rejects.
+    ///
+    /// Returns the updated aggregate with the kept events applied.
+    fn replay_while<P>(
+        &self,
+        root: AggregateRoot<A>,
+        from: Version,
+        keep: P,
+    ) -> impl Future<Output = Result<AggregateRoot<A>, Self::Error>> + Send
+    where
+        P: FnMut(&PersistedEnvelope) -> bool + Send + 'static;
+
     /// Replay events starting from `from` version (inclusive) into `root`.
     ///
     /// Returns the updated aggregate with all events applied.
@@ -132,7 +151,9 @@ pub(crate) trait ReplayFrom<A: Aggregate>: Send + Sync {
         &self,
         root: AggregateRoot<A>,
         from: Version,
-    ) -> impl Future<Output = Result<AggregateRoot<A>, Self::Error>> + Send;
+    ) -> impl Future<Output = Result<AggregateRoot<A>, Self::Error>> + Send {
+        self.replay_while(root, from, |_| true)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -161,6 +182,64 @@ pub(crate) const fn first_persisted_version(current: Option<Version>) -> Option<
     }
 }
 
+/// The [`Version`] an append of `count` events will end at, given the
+/// stream's current version. Returns `None` for `count == 0` or on overflow.
+#[cfg(feature = "snapshot")]
+pub(crate) fn last_persisted_version(current: Option<Version>, count: usize) -> Option<Version> {
+    let first = first_persisted_version(current)?;
+    let extra = u64::try_from(count.checked_sub(1)?).ok()?;
+    Version::new(first.as_u64().checked_add(extra)?)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SaveWithSnapshot<A> — pub(crate) trait for the atomic snapshot mode
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Internal trait for saving events together with an encoded snapshot of the
+/// state they produce, in one store transaction.
+///
+/// [`EventStore`] implements this over a [`SnapshotAppend`] store so the
+/// [`Snapshotting`](crate::snapshot::Snapshotting) decorator's
+/// [`Atomic`](crate::snapshot::Atomic) mode can use it. Not public API.
+#[cfg(feature = "snapshot")]
+pub(crate) trait SaveWithSnapshot<A: Aggregate>: Repository<A> {
+    /// Save `events` as [`Repository::save`] does. Once they are encoded,
+    /// `snapshot` is handed the aggregate and their payload bytes; the
+    /// encoded state it returns — the state after `events` — is committed
+    /// under `schema_version` in the same transaction, and `None` appends
+    /// the events alone.
+    fn save_with_snapshot<const N: usize, F>(
+        &self,
+        aggregate: &mut AggregateRoot<A>,
+        events: &Events<EventOf<A>, N>,
+        schema_version: NonZeroU32,
+        snapshot: F,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send
+    where
+        F: FnOnce(&AggregateRoot<A>, u64) -> Option<Bytes> + Send;
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SaveMeasured<A> — pub(crate) trait for the context-driven snapshot triggers
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Internal trait for saving events and reporting their encoded payload
+/// bytes.
+///
+/// [`EventStore`] implements this so the
+/// [`Snapshotting`](crate::snapshot::Snapshotting) decorator can feed
+/// [`PayloadBytes`](crate::state::PayloadBytes). Not public API.
+#[cfg(feature = "snapshot")]
+pub(crate) trait SaveMeasured<A: Aggregate>: Repository<A> {
+    /// Save `events` as [`Repository::save`] does, returning their total
+    /// encoded payload bytes.
+    fn save_measured<const N: usize>(
+        &self,
+        aggregate: &mut AggregateRoot<A>,
+        events: &Events<EventOf<A>, N>,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // EventStore — one facade for any codec (owning or borrowing)
 // ═══════════════════════════════════════════════════════════════════════════
@@ -203,21 +282,38 @@ pub(crate) const fn first_persisted_version(current: Option<Version>) -> Option<
 ///
 /// # Schema evolution
 ///
-/// The plain [`load`](Repository::load) / [`save`](Repository::save) path
-/// performs no upcasting. For schema evolution, call
-/// [`load_with`](Self::load_with) with the macro-generated function
-/// (e.g. `OrderTransforms::upcast`) on the read path, and
-/// [`save_with`](Self::save_with) with `OrderTransforms::current_version`
-/// on the write path:
+/// The plain [`load`](Repository::load) path performs no upcasting. For
+/// schema evolution, call [`load_with`](Self::load_with) with the
+/// macro-generated function (e.g. `OrderTransforms::upcast`) on the read
+/// path. The write path needs nothing extra: [`save`](Repository::save)
+/// stamps each event's declared
+/// [`schema_version`](nexus::DomainEvent::schema_version), and
+/// [`RepositoryBuilder::transforms`](crate::RepositoryBuilder::transforms)
+/// makes `build()` check that every upcaster chain ends there:
 ///
 /// ```ignore
+/// let es = store
+///     .repository::<Order>()
+///     .transforms(OrderTransforms::current_version)
+///     .build()?;
+///
 /// // Read path:
 /// let root = es.load_with(id, OrderTransforms::upcast).await?;
 ///
-/// // Write path:
-/// es.save_with(&mut root, &events, OrderTransforms::current_version).await?;
+/// // Write path — stamps `OrderEvent::schema_version()`:
+/// es.save(&mut root, &events).await?;
 /// ```
 ///
+/// # Unknown event types
+///
+/// A stream written by a newer service may hold event types this build's
+/// event enum does not know. By default ([`DecodePolicy::Strict`]) they fail
+/// the load with the codec's error;
+/// [`RepositoryBuilder::decode_policy`](crate::RepositoryBuilder::decode_policy)
+/// can skip them or fold them through
+/// [`AggregateState::apply_unknown`](nexus::AggregateState::apply_unknown)
+/// instead. Either way the aggregate's version advances past them.
+///
 /// # Internal ownership
 ///
 /// Owns the codec as `Arc<C>` so async load paths can clone the handle
@@ -232,18 +328,26 @@ pub(crate) const fn first_persisted_version(current: Option<Version>) -> Option<
 pub struct EventStore<S, C, A> {
     store: Store<S>,
     codec: Arc<C>,
+    decode_policy: DecodePolicy,
     _aggregate: PhantomData<fn() -> A>,
 }
 
 impl<S, C, A> EventStore<S, C, A> {
     /// Create an event store bound to a shared store and codec for aggregate `A`.
-    pub(crate) fn new(store: Store<S>, codec: C) -> Self {
+    pub(crate) fn new(store: Store<S>, codec: C, decode_policy: DecodePolicy) -> Self {
         Self {
             store,
             codec: Arc::new(codec),
+            decode_policy,
             _aggregate: PhantomData,
         }
     }
+
+    /// How this facade treats event types the aggregate does not know.
+    #[must_use]
+    pub const fn decode_policy(&self) -> DecodePolicy {
+        self.decode_policy
+    }
 }
 
 impl<S, C, A> ReplayFrom<A> for EventStore<S, C, A>
@@ -257,17 +361,22 @@ where
     type Error =
         StoreError<S::Error, <C as Encode<EventOf<A>>>::Error, <C as Decode<EventOf<A>>>::Error>;
 
-    async fn replay_from(
+    async fn replay_while<P>(
         &self,
         root: AggregateRoot<A>,
         from: Version,
-    ) -> Result<AggregateRoot<A>, Self::Error> {
+        mut keep: P,
+    ) -> Result<AggregateRoot<A>, Self::Error>
+    where
+        P: FnMut(&PersistedEnvelope) -> bool + Send + 'static,
+    {
         // Clone everything into function-local owned values. The
         // combinator closure captures the locals (Arc clones), with no
         // borrow of `&self`. See the doc comment on `EventStore` for the
         // full Rust 2024 capture-rules rationale.
         let store = self.store.clone();
         let codec = Arc::<C>::clone(&self.codec);
+        let policy = self.decode_policy;
 
         let raw_stream = store
             .raw()
@@ -277,6 +386,7 @@ where
 
         raw_stream
             .map_err(StoreError::Adapter)
+            .try_take_while(move |env| future::ready(Ok(keep(env))))
             .try_fold(root, move |mut r, env| {
                 let codec = Arc::<C>::clone(&codec);
                 async move {
@@ -286,9 +396,14 @@ where
                     // `&EventOf<A>` in both arms (std Borrow blanket impls),
                     // and is consumed in-place by `replay` so it never
                     // escapes (avoids the GAT `'static` implication).
-                    let out = <C as Decode<EventOf<A>>>::decode(&codec, &env)
-                        .map_err(StoreError::Decode)?;
-                    r.replay(version, out.borrow())?;
+                    match policy
+                        .decode::<EventOf<A>, C>(&codec, &env)
+                        .map_err(StoreError::Decode)?
+                    {
+                        Decoded::Event(out) => r.replay(version, out.borrow())?,
+                        Decoded::Skipped => r.replay_skipped(version)?,
+                        Decoded::Unknown => r.replay_unknown(&env.as_unknown_event())?,
+                    }
                     Ok(r)
                 }
             })
@@ -317,13 +432,112 @@ where
         aggregate: &mut AggregateRoot<A>,
         events: &Events<EventOf<A>, N>,
     ) -> Result<(), Self::Error> {
-        // The no-upcaster save stamps Version::INITIAL as the schema
-        // version on every event — the schema-version-lookup function
-        // is only needed when an upcaster is in play. See `save_with`.
+        // Stamps each event's declared `DomainEvent::schema_version()` —
+        // no lookup override. See `save_with`.
+        save_events::<A, S, C, _, N>(&self.store, &self.codec, aggregate, events, |_| None)
+            .await
+            .map(drop)
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl<S, C, A> SaveMeasured<A> for EventStore<S, C, A>
+where
+    A: Aggregate,
+    S: RawEventStore + 'static,
+    for<'a> C: Encode<EventOf<A>> + Decode<EventOf<A>, Output<'a>: Borrow<EventOf<A>>> + 'static,
+    EventOf<A>: DomainEvent,
+    S::Stream: Send,
+{
+    async fn save_measured<const N: usize>(
+        &self,
+        aggregate: &mut AggregateRoot<A>,
+        events: &Events<EventOf<A>, N>,
+    ) -> Result<u64, Self::Error> {
         save_events::<A, S, C, _, N>(&self.store, &self.codec, aggregate, events, |_| None).await
     }
 }
 
+#[cfg(feature = "snapshot")]
+impl<S, C, A> SaveWithSnapshot<A> for EventStore<S, C, A>
+where
+    A: Aggregate,
+    S: SnapshotAppend + 'static,
+    for<'a> C: Encode<EventOf<A>> + Decode<EventOf<A>, Output<'a>: Borrow<EventOf<A>>> + 'static,
+    EventOf<A>: DomainEvent,
+    <S as RawEventStore>::Stream: Send,
+{
+    async fn save_with_snapshot<const N: usize, F>(
+        &self,
+        aggregate: &mut AggregateRoot<A>,
+        events: &Events<EventOf<A>, N>,
+        schema_version: NonZeroU32,
+        snapshot: F,
+    ) -> Result<(), Self::Error>
+    where
+        F: FnOnce(&AggregateRoot<A>, u64) -> Option<Bytes> + Send,
+    {
+        let expected_version = aggregate.version();
+        let (envelopes, last_version) =
+            encode_events::<A, _, C, _, N>(&self.codec, expected_version, events, |_| None)?;
+        let stream = StreamKey::from_slice(aggregate.id().as_ref());
+        let raw = self.store.raw();
+        match snapshot(aggregate, payload_bytes(&envelopes)) {
+            Some(state) => raw
+                .append_with_snapshot(
+                    &stream,
+                    expected_version,
+                    &envelopes,
+                    PendingSnapshot::new(schema_version, last_version, &state),
+                )
+                .await
+                .map_err(from_append_error)?,
+            None => raw
+                .append(&stream, expected_version, &envelopes)
+                .await
+                .map_err(from_append_error)?,
+        }
+        aggregate.commit_persisted(last_version, events);
+        Ok(())
+    }
+}
+
+impl<S, C, A> TimeTravel<A> for EventStore<S, C, A>
+where
+    A: Aggregate,
+    S: RawEventStore + 'static,
+    for<'a> C: Encode<EventOf<A>> + Decode<EventOf<A>, Output<'a>: Borrow<EventOf<A>>> + 'static,
+    EventOf<A>: DomainEvent,
+    S::Stream: Send,
+{
+    async fn load_at(&self, id: A::Id, version: Version) -> Result<ReadOnlyRoot<A>, Self::Error> {
+        let root = AggregateRoot::<A>::new(id);
+        self.replay_while(root, Version::INITIAL, move |env| env.version() <= version)
+            .await
+            .map(ReadOnlyRoot::new)
+    }
+
+    async fn load_as_of<T, F>(
+        &self,
+        id: A::Id,
+        as_of: T,
+        timestamp: F,
+    ) -> Result<ReadOnlyRoot<A>, Self::Error>
+    where
+        T: PartialOrd + Send + 'static,
+        F: Fn(&[u8]) -> Option<T> + Send + 'static,
+    {
+        let root = AggregateRoot::<A>::new(id);
+        self.replay_while(root, Version::INITIAL, move |env| {
+            env.metadata()
+                .and_then(&timestamp)
+                .is_none_or(|at| at <= as_of)
+        })
+        .await
+        .map(ReadOnlyRoot::new)
+    }
+}
+
 impl<S, C, A> EventStore<S, C, A> {
     /// Load an aggregate, running `upcast` over each persisted event
     /// before decoding it.
@@ -366,6 +580,7 @@ impl<S, C, A> EventStore<S, C, A> {
     {
         let store = self.store.clone();
         let codec = Arc::<C>::clone(&self.codec);
+        let policy = self.decode_policy;
         let root = AggregateRoot::<A>::new(id);
 
         let raw_stream = store
@@ -396,10 +611,23 @@ impl<S, C, A> EventStore<S, C, A> {
                         transformed.payload(),
                     )
                     .map_err(|e| LoadWithError::Store(StoreError::EnvelopeSynthesis(e)))?;
-                    let out = <C as Decode<EventOf<A>>>::decode(&codec, &upcast_env)
-                        .map_err(|e| LoadWithError::Store(StoreError::Decode(e)))?;
-                    r.replay(version, out.borrow())
-                        .map_err(|e| LoadWithError::Store(StoreError::Kernel(e)))?;
+                    // The policy judges the upcast type: a renamed-away
+                    // old type is known once upcast.
+                    let replayed = match policy
+                        .decode::<EventOf<A>, C>(&codec, &upcast_env)
+                        .map_err(|e| LoadWithError::Store(StoreError::Decode(e)))?
+                    {
+                        Decoded::Event(out) => r.replay(version, out.borrow()),
+                        Decoded::Skipped => r.replay_skipped(version),
+                        Decoded::Unknown => r.replay_unknown(&UnknownEvent::new(
+                            version,
+                            transformed.event_type(),
+                            transformed.schema_version(),
+                            transformed.payload(),
+                            env.metadata(),
+                        )),
+                    };
+                    replayed.map_err(|e| LoadWithError::Store(StoreError::Kernel(e)))?;
                     Ok(r)
                 }
             })
@@ -413,8 +641,11 @@ impl<S, C, A> EventStore<S, C, A> {
     /// `#[nexus::transforms]` macro emits (e.g.
     /// `OrderTransforms::current_version`). For event types it doesn't
     /// know about, it returns `None` and the schema version falls back
-    /// to [`Version::INITIAL`] (the same default as the no-upcaster
-    /// [`save`](Repository::save)).
+    /// to the event's declared
+    /// [`schema_version`](nexus::DomainEvent::schema_version) (what the
+    /// plain [`save`](Repository::save) stamps). Prefer declaring versions
+    /// on the event and calling `save`; this override exists for event
+    /// types whose `DomainEvent` impl declares none.
     ///
     /// # Errors
     ///
@@ -438,13 +669,68 @@ impl<S, C, A> EventStore<S, C, A> {
     {
         save_events::<A, S, C, _, N>(&self.store, &self.codec, aggregate, events, current_version)
             .await
+            .map(drop)
+    }
+}
+
+impl<S, C, A> EventStore<S, C, A>
+where
+    A: Aggregate,
+    C: Encode<EventOf<A>> + Decode<EventOf<A>> + 'static,
+    EventOf<A>: DomainEvent,
+{
+    /// Persist decided events inside a transaction the caller owns, as
+    /// [`save`](Repository::save) does with its own.
+    ///
+    /// The events join `tx` alongside the caller's other writes and commit
+    /// or roll back with them (see [`TransactionalAppend`]). Conflicts are
+    /// reported exactly as `save` reports them.
+    ///
+    /// On success the root is advanced immediately, as by `save`. If `tx`
+    /// is then rolled back the root is ahead of the store: reload it. A
+    /// save from the stale root cannot lose data — its expected version no
+    /// longer matches and it fails with a
+    /// [`Conflict`](StoreError::Conflict).
+    ///
+    /// # Errors
+    ///
+    /// The same set of errors [`save`](Repository::save) can produce.
+    pub async fn save_in_tx<Tx, const N: usize>(
+        &self,
+        tx: &mut Tx,
+        aggregate: &mut AggregateRoot<A>,
+        events: &Events<EventOf<A>, N>,
+    ) -> Result<
+        (),
+        StoreError<S::Error, <C as Encode<EventOf<A>>>::Error, <C as Decode<EventOf<A>>>::Error>,
+    >
+    where
+        Tx: Send + ?Sized,
+        S: TransactionalAppend<Tx>,
+    {
+        let expected_version = aggregate.version();
+        let (envelopes, last_version) =
+            encode_events::<A, _, C, _, N>(&self.codec, expected_version, events, |_| None)?;
+        self.store
+            .raw()
+            .append_in_tx(
+                tx,
+                &StreamKey::from_slice(aggregate.id().as_ref()),
+                expected_version,
+                &envelopes,
+            )
+            .await
+            .map_err(from_append_error)?;
+        aggregate.commit_persisted(last_version, events);
+        Ok(())
     }
 }
 
-// Single save path shared between Repository::save (no upcaster, always stamps
-// Version::INITIAL) and EventStore::save_with (uses the user's current_version
-// fn). Encode-only — the decode shape is irrelevant on the write path, so this
-// serves owning and borrowing codecs alike.
+// Single save path shared between Repository::save (stamps the declared
+// `DomainEvent::schema_version`) and EventStore::save_with (the user's
+// current_version fn, falling back to the declared version). Encode-only —
+// the decode shape is irrelevant on the write path, so this serves owning
+// and borrowing codecs alike.
 async fn save_events<A, S, C, F, const N: usize>(
     store: &Store<S>,
     codec: &Arc<C>,
@@ -452,7 +738,7 @@ async fn save_events<A, S, C, F, const N: usize>(
     events: &Events<EventOf<A>, N>,
     current_version: F,
 ) -> Result<
-    (),
+    u64,
     StoreError<S::Error, <C as Encode<EventOf<A>>>::Error, <C as Decode<EventOf<A>>>::Error>,
 >
 where
@@ -463,7 +749,51 @@ where
     EventOf<A>: DomainEvent,
 {
     let expected_version = aggregate.version();
+    let (envelopes, last_version) =
+        encode_events::<A, _, C, F, N>(codec, expected_version, events, current_version)?;
 
+    store
+        .raw()
+        .append(
+            &StreamKey::from_slice(aggregate.id().as_ref()),
+            expected_version,
+            &envelopes,
+        )
+        .await
+        .map_err(from_append_error)?;
+
+    aggregate.commit_persisted(last_version, events);
+    Ok(payload_bytes(&envelopes))
+}
+
+/// Total payload bytes of `envelopes`.
+fn payload_bytes(envelopes: &[PendingEnvelope]) -> u64 {
+    envelopes
+        .iter()
+        .map(|envelope| u64::try_from(envelope.payload().len()).unwrap_or(u64::MAX))
+        .fold(0, u64::saturating_add)
+}
+
+/// Envelopes ready to append, with the version of the last one.
+type EncodedEvents<E, C, A> = Result<
+    (Vec<PendingEnvelope>, Version),
+    StoreError<E, <C as Encode<EventOf<A>>>::Error, <C as Decode<EventOf<A>>>::Error>,
+>;
+
+// Encode `events` into envelopes numbered from after `expected_version`,
+// returning them with the version of the last one.
+fn encode_events<A, E, C, F, const N: usize>(
+    codec: &Arc<C>,
+    expected_version: Option<Version>,
+    events: &Events<EventOf<A>, N>,
+    current_version: F,
+) -> EncodedEvents<E, C, A>
+where
+    A: Aggregate,
+    C: Encode<EventOf<A>> + Decode<EventOf<A>>,
+    F: Fn(&str) -> Option<Version>,
+    EventOf<A>: DomainEvent,
+{
     let mut next_version =
         first_persisted_version(expected_version).ok_or(StoreError::VersionOverflow)?;
 
@@ -477,7 +807,7 @@ where
             <C as Encode<EventOf<A>>>::encode(codec, event).map_err(StoreError::Encode)?;
 
         let event_name = event.name();
-        let schema_version = current_version(event_name).unwrap_or(Version::INITIAL);
+        let schema_version = current_version(event_name).unwrap_or_else(|| event.schema_version());
         let schema_nz32 = version_to_nz32(schema_version).ok_or(StoreError::VersionOverflow)?;
 
         let envelope = pending_envelope(next_version)
@@ -494,29 +824,22 @@ where
         }
     }
 
-    store
-        .raw()
-        .append(
-            &StreamKey::from_slice(aggregate.id().as_ref()),
-            expected_version,
-            &envelopes,
-        )
-        .await
-        .map_err(|err| match err {
-            AppendError::Conflict {
-                stream_id,
-                expected,
-                actual,
-            } => StoreError::Conflict {
-                stream_id,
-                expected,
-                actual,
-            },
-            AppendError::Store(e) => StoreError::Adapter(e),
-        })?;
+    Ok((envelopes, last_version))
+}
 
-    aggregate.commit_persisted(last_version, events);
-    Ok(())
+fn from_append_error<E, EncErr, DecErr>(err: AppendError<E>) -> StoreError<E, EncErr, DecErr> {
+    match err {
+        AppendError::Conflict {
+            stream_id,
+            expected,
+            actual,
+        } => StoreError::Conflict {
+            stream_id,
+            expected,
+            actual,
+        },
+        AppendError::Store(e) => StoreError::Adapter(e),
+    }
 }
 
 #[cfg(test)]
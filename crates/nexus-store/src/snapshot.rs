@@ -1,9 +1,24 @@
-use std::num::NonZeroU32;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
 
-use nexus::{Aggregate, AggregateRoot, DomainEvent, EventOf, Events, KernelError, Version};
+use nexus::{
+    Aggregate, AggregateRoot, AggregateState, DomainEvent, ErrorId, EventOf, Events, Id,
+    KernelError, Version,
+};
 
-use crate::repository::{ReplayFrom, Repository};
+use parking_lot::Mutex;
+
+use crate::codec::{Decode, Encode};
+use crate::repository::{
+    ReplayFrom, Repository, SaveMeasured, SaveWithSnapshot, last_persisted_version,
+};
 use crate::state;
+use crate::time_travel::{ReadOnlyRoot, TimeTravel};
 
 /// Snapshot-aware repository decorator.
 ///
@@ -19,32 +34,53 @@ use crate::state;
 ///   version, restores state from the snapshot and replays only subsequent
 ///   events. On miss or schema mismatch, falls back to full event replay via
 ///   the inner repository. Optionally creates a snapshot after a full replay
-///   (lazy/on-read snapshotting) if `snapshot_on_read` is enabled.
+///   (lazy/on-read snapshotting) if `snapshot_on_read` is enabled. A store
+///   configured with snapshot upcasters (see
+///   [`CodecSnapshotStore::with_upcasters`](crate::state::CodecSnapshotStore::with_upcasters))
+///   migrates an older-schema snapshot instead of reporting a mismatch.
+///   The replay is timed, and a trigger that
+///   [uses replay time](state::PersistTrigger::uses_replay_time) (e.g.
+///   [`ReplayCost`](state::ReplayCost)) is consulted with it, snapshotting
+///   aggregates that have grown slow to load.
 ///
 /// - **Save:** delegates event persistence to the inner repository, then
 ///   checks the trigger to optionally persist a snapshot of the current state.
-///   Snapshot save is best-effort — failures are silently ignored so they
-///   never block event persistence.
+///   The commit mode `M` decides where that snapshot is committed: inline
+///   on the save path ([`Inline`], the default), or handed to a
+///   [`SnapshotWorker`] ([`Background`]) so the state encode and store
+///   write stay off the command's latency. [`Atomic`] instead writes the
+///   snapshot in the same store transaction as the events.
+///
+/// Snapshot store failures on either path are handled per the
+/// [`SnapshotPolicy`] (default [`Ignore`](SnapshotPolicy::Ignore): fall back
+/// to full replay / skip the snapshot). Hits, misses, stale schema versions
+/// and failures are counted either way — see [`stats`](Self::stats).
 ///
 /// The trigger type `T` is a generic parameter (not `Box<dyn>`) for
-/// zero-cost monomorphization — the compiler inlines `should_persist()`
-/// calls entirely.
+/// zero-cost monomorphization — the compiler inlines `should_persist_with()`
+/// calls entirely. Time and payload bytes since each aggregate's last
+/// snapshot are tracked only for a trigger that
+/// [reads them](state::PersistTrigger::uses_persist_history).
 ///
 /// The snapshot store `SS` is generic over the aggregate's state type at the
 /// `Repository` impl level — the struct itself is agnostic of the state type.
 /// Codec responsibility lives in the snapshot store adapter (e.g.,
 /// [`CodecSnapshotStore`](crate::state::CodecSnapshotStore)).
-pub struct Snapshotting<R, SS, T> {
+pub struct Snapshotting<R, SS, T, M = Inline> {
     inner: R,
     snapshot_store: SS,
     trigger: T,
     schema_version: NonZeroU32,
     snapshot_on_read: bool,
+    policy: SnapshotPolicy,
+    stats: Arc<Counters>,
+    history: History,
+    mode: M,
 }
 
 impl<R, SS, T> Snapshotting<R, SS, T> {
     /// Create a new snapshot-aware repository.
-    pub const fn new(
+    pub fn new(
         inner: R,
         snapshot_store: SS,
         trigger: T,
@@ -57,33 +93,115 @@ impl<R, SS, T> Snapshotting<R, SS, T> {
             trigger,
             schema_version,
             snapshot_on_read,
+            policy: SnapshotPolicy::Ignore,
+            stats: Arc::new(Counters::new()),
+            history: History::default(),
+            mode: Inline,
         }
     }
+
+    /// Commit snapshots through a bounded background queue instead of on
+    /// the save path.
+    ///
+    /// Returns the repository and the [`SnapshotWorker`] draining its queue.
+    /// nexus ships no runtime: spawn [`SnapshotWorker::run`] on yours. The
+    /// two share the snapshot store through an `Arc`.
+    ///
+    /// At most `capacity` aggregates wait in the queue at once; a request
+    /// for an aggregate already queued replaces it when newer and is dropped
+    /// otherwise, and a request for a new aggregate while the queue is full
+    /// is dropped. Either way it counts in [`SnapshotStats::dropped`].
+    #[must_use]
+    pub fn background<A>(self, capacity: NonZeroUsize) -> BackgroundParts<R, SS, T, A>
+    where
+        A: Aggregate,
+    {
+        let store = Arc::new(self.snapshot_store);
+        let queue = Arc::new(Queue::new(capacity));
+        let worker = SnapshotWorker {
+            store: Arc::clone(&store),
+            queue: Arc::clone(&queue),
+            schema_version: self.schema_version,
+            policy: self.policy.clone(),
+            stats: Arc::clone(&self.stats),
+        };
+        let repo = Snapshotting {
+            inner: self.inner,
+            snapshot_store: store,
+            trigger: self.trigger,
+            schema_version: self.schema_version,
+            snapshot_on_read: self.snapshot_on_read,
+            policy: self.policy,
+            stats: self.stats,
+            history: self.history,
+            mode: Background { queue },
+        };
+        (repo, worker)
+    }
+
+    /// Commit triggered snapshots in the same transaction as the events they
+    /// cover. See [`Atomic`].
+    ///
+    /// The snapshot store must read from the inner repository's own event
+    /// store — the one the atomic append writes to.
+    #[must_use]
+    pub fn atomic(self) -> Snapshotting<R, SS, T, Atomic> {
+        Snapshotting {
+            inner: self.inner,
+            snapshot_store: self.snapshot_store,
+            trigger: self.trigger,
+            schema_version: self.schema_version,
+            snapshot_on_read: self.snapshot_on_read,
+            policy: self.policy,
+            stats: self.stats,
+            history: self.history,
+            mode: Atomic,
+        }
+    }
+}
+
+impl<R, SS, T, M> Snapshotting<R, SS, T, M> {
+    /// Replace the snapshot failure policy.
+    #[must_use]
+    pub fn with_policy(mut self, policy: SnapshotPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Snapshot counters accumulated since construction.
+    #[must_use]
+    pub fn stats(&self) -> SnapshotStats {
+        self.stats.read()
+    }
+
+    /// The snapshot store — e.g. to read a
+    /// [`CodecSnapshotStore`](state::CodecSnapshotStore)'s
+    /// [`rewrite_failures`](state::CodecSnapshotStore::rewrite_failures).
+    #[must_use]
+    pub const fn snapshot_store(&self) -> &SS {
+        &self.snapshot_store
+    }
 }
 
-impl<A, R, SS, T> Repository<A> for Snapshotting<R, SS, T>
+impl<A, R, SS, T, M> Repository<A> for Snapshotting<R, SS, T, M>
 where
     A: Aggregate,
-    R: Repository<A> + ReplayFrom<A, Error = <R as Repository<A>>::Error>,
+    R: SaveMeasured<A> + ReplayFrom<A, Error = <R as Repository<A>>::Error>,
     <R as Repository<A>>::Error: From<KernelError>,
     SS: state::SnapshotStore<A::State, Version>,
     T: state::PersistTrigger,
+    M: CommitMode<A>,
     EventOf<A>: DomainEvent,
 {
-    type Error = <R as Repository<A>>::Error;
+    type Error = SnapshottingError<<R as Repository<A>>::Error, SS::Error>;
 
     async fn load(&self, id: A::Id) -> Result<AggregateRoot<A>, Self::Error> {
-        // Snapshot hit → partial replay from snapshot version.
-        if let Some((root, from)) = self.try_load_from_snapshot::<A>(&id).await {
-            return self.inner.replay_from(root, from).await;
-        }
+        let (root, replay) = self.load_root::<A>(id).await?;
 
-        // Fallback: full replay.
-        let root = self.inner.load(id).await?;
-
-        // Lazy snapshot on full replay when enabled.
-        if let (true, Some(version)) = (self.snapshot_on_read, root.version()) {
-            self.try_save_snapshot::<A>(&root, version).await;
+        // Lazy snapshot: full replay when enabled, or a replay the trigger
+        // finds too slow.
+        if let Some(version) = self.snapshot_after_load(&root, replay) {
+            self.try_save_snapshot::<A, _>(&root, version).await?;
         }
 
         Ok(root)
@@ -97,55 +215,343 @@ where
         let old_version = aggregate.version();
 
         // Delegate event persistence to inner.
-        self.inner.save(aggregate, events).await?;
+        let bytes = self
+            .inner
+            .save_measured(aggregate, events)
+            .await
+            .map_err(SnapshottingError::Repository)?;
 
         // Snapshot after save when the trigger fires. `events` is non-empty
         // (`&Events<_, N>`), so a successful save always advances the version.
         let Some(new_version) = aggregate.version() else {
             return Ok(());
         };
-        if self.trigger.should_persist(
+        let context = self.record_save(aggregate.id(), bytes);
+        if self.trigger.should_persist_with(
+            &context,
             old_version,
             new_version,
             events.iter().map(DomainEvent::name),
         ) {
-            self.try_save_snapshot::<A>(aggregate, new_version).await;
+            self.try_save_snapshot::<A, _>(aggregate, new_version)
+                .await?;
         }
 
         Ok(())
     }
 }
 
-impl<R, SS, T> Snapshotting<R, SS, T>
+impl<A, R, SS, C, T> Repository<A> for Snapshotting<R, state::CodecSnapshotStore<SS, C>, T, Atomic>
+where
+    A: Aggregate,
+    A::State: Clone,
+    R: SaveWithSnapshot<A> + ReplayFrom<A, Error = <R as Repository<A>>::Error>,
+    <R as Repository<A>>::Error: From<KernelError>,
+    SS: state::SnapshotStore<Vec<u8>, Version>,
+    for<'a> C: Encode<A::State> + Decode<A::State, Output<'a> = A::State>,
+    T: state::PersistTrigger,
+    EventOf<A>: DomainEvent,
+{
+    type Error = SnapshottingError<
+        <R as Repository<A>>::Error,
+        <state::CodecSnapshotStore<SS, C> as state::SnapshotStore<A::State, Version>>::Error,
+    >;
+
+    async fn load(&self, id: A::Id) -> Result<AggregateRoot<A>, Self::Error> {
+        let (root, replay) = self.load_root::<A>(id).await?;
+
+        // A lazy snapshot has no events to ride with: commit it on its own.
+        if let Some(version) = self.snapshot_after_load(&root, replay) {
+            self.commit_snapshot::<A, _>(&root, version).await?;
+        }
+
+        Ok(root)
+    }
+
+    async fn save<const N: usize>(
+        &self,
+        aggregate: &mut AggregateRoot<A>,
+        events: &Events<EventOf<A>, N>,
+    ) -> Result<(), Self::Error> {
+        // The trigger runs once the events are encoded, so it sees their
+        // payload bytes; the state it snapshots is folded and encoded then,
+        // to ride in the events' transaction.
+        let mut snapshotted = false;
+        let mut saved_bytes = 0;
+        let mut failed = None;
+        self.inner
+            .save_with_snapshot(aggregate, events, self.schema_version, |root, bytes| {
+                saved_bytes = bytes;
+                let old_version = root.version();
+                let new_version = last_persisted_version(old_version, events.len())?;
+                let context = self.history_context(root.id(), bytes);
+                if !self.trigger.should_persist_with(
+                    &context,
+                    old_version,
+                    new_version,
+                    events.iter().map(DomainEvent::name),
+                ) {
+                    return None;
+                }
+                let next = events
+                    .iter()
+                    .fold(root.state().clone(), AggregateState::apply);
+                match self.snapshot_store.encode_state(&next) {
+                    Ok(state) => {
+                        snapshotted = true;
+                        Some(state)
+                    }
+                    Err(error) => {
+                        failed = Some(error);
+                        None
+                    }
+                }
+            })
+            .await
+            .map_err(SnapshottingError::Repository)?;
+
+        if snapshotted {
+            self.record_snapshot(aggregate.id());
+        } else {
+            self.record_save(aggregate.id(), saved_bytes);
+        }
+        failed.map_or(Ok(()), |error| {
+            self.on_failure(
+                SnapshotOperation::Save,
+                aggregate.id(),
+                aggregate.version(),
+                state::CodecSnapshotStoreError::Encode(error),
+            )
+        })
+    }
+}
+
+impl<A, R, SS, T, M> TimeTravel<A> for Snapshotting<R, SS, T, M>
+where
+    A: Aggregate,
+    R: TimeTravel<A> + ReplayFrom<A, Error = <R as Repository<A>>::Error>,
+    SS: state::SnapshotStore<A::State, Version>,
+    T: Send + Sync,
+    M: Send + Sync,
+    Self: Repository<A, Error = SnapshottingError<<R as Repository<A>>::Error, SS::Error>>,
+{
+    /// Starts from the newest snapshot at or before `version` the store
+    /// still holds (see [`SnapshotRetention`](crate::SnapshotRetention)),
+    /// else replays from the first event.
+    async fn load_at(&self, id: A::Id, version: Version) -> Result<ReadOnlyRoot<A>, Self::Error> {
+        if let Some((root, from)) = self.try_load_at_or_before::<A, _>(&id, version).await? {
+            return self
+                .inner
+                .replay_while(root, from, move |env| env.version() <= version)
+                .await
+                .map(ReadOnlyRoot::new)
+                .map_err(SnapshottingError::Repository);
+        }
+        self.inner
+            .load_at(id, version)
+            .await
+            .map_err(SnapshottingError::Repository)
+    }
+
+    /// Always replays from the first event. Snapshots record no time, and
+    /// replay stops at the *first* event stamped after `as_of` — which may
+    /// sit before any snapshot, since stamps need not rise with versions.
+    /// Starting from a snapshot would skip reading the stamps of the events
+    /// it covers, and reading them is the replay it would save.
+    async fn load_as_of<U, F>(
+        &self,
+        id: A::Id,
+        as_of: U,
+        timestamp: F,
+    ) -> Result<ReadOnlyRoot<A>, Self::Error>
+    where
+        U: PartialOrd + Send + 'static,
+        F: Fn(&[u8]) -> Option<U> + Send + 'static,
+    {
+        self.inner
+            .load_as_of(id, as_of, timestamp)
+            .await
+            .map_err(SnapshottingError::Repository)
+    }
+}
+
+impl<R, SS, T, M> Snapshotting<R, SS, T, M>
 where
     R: Send + Sync,
     SS: Send + Sync,
     T: Send + Sync,
+    M: Send + Sync,
 {
-    /// Try to load a snapshot. Returns `(root, next_version)` on hit.
-    /// Returns `None` on miss, schema mismatch, or any error (best-effort).
-    async fn try_load_from_snapshot<A>(&self, id: &A::Id) -> Option<(AggregateRoot<A>, Version)>
+    /// Load from the snapshot plus the events after it, or by full replay
+    /// on a miss, timing the replay.
+    async fn load_root<A>(
+        &self,
+        id: A::Id,
+    ) -> Result<(AggregateRoot<A>, Replay), SnapshottingError<<R as Repository<A>>::Error, SS::Error>>
     where
         A: Aggregate,
+        R: Repository<A> + ReplayFrom<A, Error = <R as Repository<A>>::Error>,
         SS: state::SnapshotStore<A::State, Version>,
     {
-        let (version, typed_state) = self
-            .snapshot_store
-            .hydrate(id, self.schema_version)
+        // Snapshot hit → partial replay from snapshot version.
+        if let Some((root, from)) = self.try_load_from_snapshot::<A, _>(&id).await? {
+            let snapshot = root.version();
+            let started = Instant::now();
+            return self
+                .inner
+                .replay_from(root, from)
+                .await
+                .map(|replayed| (replayed, Replay::since(snapshot, started)))
+                .map_err(SnapshottingError::Repository);
+        }
+
+        // Fallback: full replay.
+        let started = Instant::now();
+        self.inner
+            .load(id)
             .await
-            .ok()??;
+            .map(|replayed| (replayed, Replay::since(None, started)))
+            .map_err(SnapshottingError::Repository)
+    }
+
+    /// The version to snapshot after a load, if any: a full replay under
+    /// `snapshot_on_read`, or a replay the trigger finds too slow (see
+    /// [`PersistTrigger::uses_replay_time`](state::PersistTrigger::uses_replay_time)).
+    fn snapshot_after_load<A>(&self, root: &AggregateRoot<A>, replay: Replay) -> Option<Version>
+    where
+        A: Aggregate,
+        T: state::PersistTrigger,
+    {
+        let version = root.version()?;
+        if replay.snapshot == Some(version) {
+            return None;
+        }
+        if self.snapshot_on_read && replay.snapshot.is_none() {
+            return Some(version);
+        }
+        let context = self
+            .history_context(root.id(), 0)
+            .with_replay_time(replay.took);
+        (self.trigger.uses_replay_time()
+            && self.trigger.should_persist_with(
+                &context,
+                replay.snapshot,
+                version,
+                std::iter::empty::<&str>(),
+            ))
+        .then_some(version)
+    }
+
+    /// Try to load a snapshot. Returns `(root, next_version)` on hit and
+    /// `None` on miss or stale schema version. A store failure is `None`
+    /// too unless the policy propagates it.
+    async fn try_load_from_snapshot<A, E>(
+        &self,
+        id: &A::Id,
+    ) -> Result<Option<(AggregateRoot<A>, Version)>, SnapshottingError<E, SS::Error>>
+    where
+        A: Aggregate,
+        SS: state::SnapshotStore<A::State, Version>,
+    {
+        let hydrated = match self.snapshot_store.hydrate(id, self.schema_version).await {
+            Ok(hydrated) => hydrated,
+            Err(error) => {
+                return self
+                    .on_failure(SnapshotOperation::Load, id, None, error)
+                    .map(|()| None);
+            }
+        };
+        let Some((version, typed_state)) = hydrated else {
+            // Tell a stale snapshot from a plain miss. The probe only runs
+            // on the replay path, which is about to read the whole stream.
+            match self.snapshot_store.stored_schema_version(id).await {
+                Ok(Some(stored)) if stored != self.schema_version => {
+                    self.stats.stale.fetch_add(1, Ordering::Relaxed);
+                }
+                Ok(_) => {
+                    self.stats.misses.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(error) => self.on_failure(SnapshotOperation::Load, id, None, error)?,
+            }
+            return Ok(None);
+        };
+        self.stats.hits.fetch_add(1, Ordering::Relaxed);
         let root = AggregateRoot::<A>::restore(id.clone(), typed_state, version);
-        let next = version.next()?;
-        Some((root, next))
+        Ok(version.next().map(|next| (root, next)))
+    }
+
+    /// Try to load the newest snapshot at or before `version`. Returns
+    /// `(root, next_version)` on hit, `None` on miss; a store failure is
+    /// `None` too unless the policy propagates it.
+    async fn try_load_at_or_before<A, E>(
+        &self,
+        id: &A::Id,
+        version: Version,
+    ) -> Result<Option<(AggregateRoot<A>, Version)>, SnapshottingError<E, SS::Error>>
+    where
+        A: Aggregate,
+        SS: state::SnapshotStore<A::State, Version>,
+    {
+        match self
+            .snapshot_store
+            .hydrate_at_or_before(id, self.schema_version, version)
+            .await
+        {
+            Ok(Some((at, typed_state))) => {
+                self.stats.historical_hits.fetch_add(1, Ordering::Relaxed);
+                let root = AggregateRoot::<A>::restore(id.clone(), typed_state, at);
+                Ok(at.next().map(|next| (root, next)))
+            }
+            Ok(None) => {
+                self.stats.historical_misses.fetch_add(1, Ordering::Relaxed);
+                Ok(None)
+            }
+            Err(error) => self
+                .on_failure(SnapshotOperation::Load, id, None, error)
+                .map(|()| None),
+        }
+    }
+
+    /// Snapshot save, or hand-off to the background queue. A store failure
+    /// is ignored unless the policy propagates it.
+    async fn try_save_snapshot<A, E>(
+        &self,
+        aggregate: &AggregateRoot<A>,
+        version: Version,
+    ) -> Result<(), SnapshottingError<E, SS::Error>>
+    where
+        A: Aggregate,
+        SS: state::SnapshotStore<A::State, Version>,
+        T: state::PersistTrigger,
+        M: CommitMode<A>,
+    {
+        match self.mode.hand_off(aggregate, version) {
+            HandOff::Inline => {}
+            HandOff::Queued => {
+                self.record_snapshot(aggregate.id());
+                return Ok(());
+            }
+            HandOff::Dropped => {
+                self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            }
+        }
+        self.commit_snapshot(aggregate, version).await
     }
 
-    /// Best-effort snapshot save. Errors are silently ignored.
-    async fn try_save_snapshot<A>(&self, aggregate: &AggregateRoot<A>, version: Version)
+    /// Snapshot save on the calling task. A store failure is ignored unless
+    /// the policy propagates it.
+    async fn commit_snapshot<A, E>(
+        &self,
+        aggregate: &AggregateRoot<A>,
+        version: Version,
+    ) -> Result<(), SnapshottingError<E, SS::Error>>
     where
         A: Aggregate,
         SS: state::SnapshotStore<A::State, Version>,
+        T: state::PersistTrigger,
     {
-        let _ = self
+        match self
             .snapshot_store
             .commit(
                 aggregate.id(),
@@ -153,6 +559,637 @@ where
                 version,
                 aggregate.state(),
             )
-            .await;
+            .await
+        {
+            Ok(()) => {
+                self.record_snapshot(aggregate.id());
+                Ok(())
+            }
+            Err(error) => self.on_failure(
+                SnapshotOperation::Save,
+                aggregate.id(),
+                Some(version),
+                error,
+            ),
+        }
+    }
+
+    /// What the trigger's context knows about `id` since its last snapshot,
+    /// with `pending` payload bytes not yet recorded. Empty unless the
+    /// trigger [reads it](state::PersistTrigger::uses_persist_history).
+    fn history_context(&self, id: &impl Id, pending: u64) -> state::PersistContext
+    where
+        T: state::PersistTrigger,
+    {
+        if self.trigger.uses_persist_history() {
+            self.history.context(id.as_ref(), pending)
+        } else {
+            state::PersistContext::new()
+        }
+    }
+
+    /// Count `bytes` saved for `id`, returning the context after them.
+    fn record_save(&self, id: &impl Id, bytes: u64) -> state::PersistContext
+    where
+        T: state::PersistTrigger,
+    {
+        if self.trigger.uses_persist_history() {
+            self.history.saved(id.as_ref(), bytes);
+        }
+        self.history_context(id, 0)
+    }
+
+    /// Restart `id`'s history: a snapshot of it was committed or queued.
+    fn record_snapshot(&self, id: &impl Id)
+    where
+        T: state::PersistTrigger,
+    {
+        if self.trigger.uses_persist_history() {
+            self.history.snapshotted(id.as_ref());
+        }
+    }
+
+    /// Count a snapshot store failure and apply the policy to it.
+    fn on_failure<E, SE>(
+        &self,
+        operation: SnapshotOperation,
+        id: &impl Id,
+        version: Option<Version>,
+        error: SE,
+    ) -> Result<(), SnapshottingError<E, SE>>
+    where
+        SE: std::error::Error + Send + Sync + 'static,
+    {
+        self.stats.failures.fetch_add(1, Ordering::Relaxed);
+        match &self.policy {
+            SnapshotPolicy::Ignore => Ok(()),
+            SnapshotPolicy::Observe(observer) => {
+                observer.on_failure(&SnapshotFailure {
+                    operation,
+                    stream_id: id.to_label(),
+                    version,
+                    error: &error,
+                });
+                Ok(())
+            }
+            SnapshotPolicy::Propagate => Err(SnapshottingError::Snapshot {
+                operation,
+                stream_id: id.to_label(),
+                version,
+                source: error,
+            }),
+        }
+    }
+}
+
+/// How a load rebuilt its state: from which snapshot version (`None` for a
+/// full replay), and how long the replay took.
+#[derive(Debug, Clone, Copy)]
+struct Replay {
+    snapshot: Option<Version>,
+    took: Duration,
+}
+
+impl Replay {
+    fn since(snapshot: Option<Version>, started: Instant) -> Self {
+        Self {
+            snapshot,
+            took: started.elapsed(),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// History — time and payload bytes since each aggregate's last snapshot
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// How many aggregates a [`History`] tracks at once.
+const HISTORY_CAPACITY: usize = 4096;
+
+/// Time and payload bytes since each aggregate's last snapshot, for the
+/// triggers that read them ([`MinInterval`](state::MinInterval),
+/// [`PayloadBytes`](state::PayloadBytes)).
+///
+/// Counting starts when this repository first saves an aggregate. At most
+/// [`HISTORY_CAPACITY`] aggregates are tracked; when full, the half saved
+/// least recently is forgotten in one pass, so making room stays O(1)
+/// amortized. Once anything has been forgotten an untracked aggregate may
+/// have been snapshotted a moment ago, so it reads as just snapshotted —
+/// a [`MinInterval`](state::MinInterval) waits out a full interval rather
+/// than firing early.
+#[derive(Default)]
+struct History(Mutex<Tracked>);
+
+#[derive(Default)]
+struct Tracked {
+    entries: HashMap<Box<[u8]>, Since>,
+    /// Whether an entry has ever been evicted.
+    forgotten: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Since {
+    /// When a snapshot was last committed or queued, if ever.
+    snapshot: Option<Instant>,
+    /// Payload bytes saved after it.
+    bytes: u64,
+    /// When the aggregate was last saved or snapshotted — for eviction.
+    touched: Instant,
+}
+
+impl History {
+    fn context(&self, id: &[u8], pending: u64) -> state::PersistContext {
+        let (since, forgotten) = Self::lookup(&self.0.lock(), id);
+        let bytes = since.map_or(0, |s| s.bytes).saturating_add(pending);
+        let context = state::PersistContext::new().with_bytes(bytes);
+        match since {
+            Some(Since {
+                snapshot: Some(at), ..
+            }) => context.with_elapsed(at.elapsed()),
+            None if forgotten => context.with_elapsed(Duration::ZERO),
+            _ => context,
+        }
+    }
+
+    fn lookup(tracked: &Tracked, id: &[u8]) -> (Option<Since>, bool) {
+        (tracked.entries.get(id).copied(), tracked.forgotten)
+    }
+
+    fn saved(&self, id: &[u8], bytes: u64) {
+        self.update(id, |since| since.bytes = since.bytes.saturating_add(bytes));
+    }
+
+    fn snapshotted(&self, id: &[u8]) {
+        self.update(id, |since| {
+            since.snapshot = Some(Instant::now());
+            since.bytes = 0;
+        });
+    }
+
+    fn update(&self, id: &[u8], change: impl FnOnce(&mut Since)) {
+        change(Self::track(&mut self.0.lock(), id));
+    }
+
+    /// `id`'s entry, touched now — made room for and created if new.
+    fn track<'a>(tracked: &'a mut Tracked, id: &[u8]) -> &'a mut Since {
+        let now = Instant::now();
+        if tracked.entries.len() >= HISTORY_CAPACITY && !tracked.entries.contains_key(id) {
+            Self::forget_stalest_half(tracked);
+        }
+        // After an eviction a new entry may be one that was forgotten:
+        // start its interval now rather than read it as never snapshotted.
+        let snapshot = tracked.forgotten.then_some(now);
+        let since = tracked.entries.entry(id.into()).or_insert(Since {
+            snapshot,
+            bytes: 0,
+            touched: now,
+        });
+        since.touched = now;
+        since
+    }
+
+    fn forget_stalest_half(tracked: &mut Tracked) {
+        let mut touched: Vec<Instant> = tracked.entries.values().map(|s| s.touched).collect();
+        let middle = touched.len() / 2;
+        let (_, &mut cutoff, _) = touched.select_nth_unstable(middle);
+        tracked.entries.retain(|_, since| since.touched > cutoff);
+        tracked.forgotten = true;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SnapshotPolicy — what to do with snapshot store failures
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// What [`Snapshotting`] does when the snapshot store fails.
+///
+/// Snapshots are an optimisation, so the default is to carry on: a failed
+/// load falls back to full replay and a failed save is skipped. That hides
+/// a broken store behind slower loads — observe or propagate to see it.
+#[derive(Clone, Default)]
+pub enum SnapshotPolicy {
+    /// Fall back silently (only [`SnapshotStats::failures`] records it).
+    #[default]
+    Ignore,
+    /// Fall back, and report each failure to the observer.
+    Observe(Arc<dyn SnapshotObserver>),
+    /// Return [`SnapshottingError::Snapshot`] from `load` / `save`.
+    ///
+    /// On `save` the events are already committed when the snapshot
+    /// fails — the error reports only the snapshot, and the aggregate has
+    /// advanced. Do not retry the save.
+    Propagate,
+}
+
+impl fmt::Debug for SnapshotPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ignore => f.write_str("Ignore"),
+            Self::Observe(_) => f.write_str("Observe(..)"),
+            Self::Propagate => f.write_str("Propagate"),
+        }
+    }
+}
+
+/// Callback for snapshot store failures under [`SnapshotPolicy::Observe`].
+///
+/// Implemented for any `Fn(&SnapshotFailure<'_>) + Send + Sync` closure.
+/// Runs inline on the load/save path — keep it cheap (log, bump a metric).
+pub trait SnapshotObserver: Send + Sync {
+    /// A snapshot load or save failed.
+    fn on_failure(&self, failure: &SnapshotFailure<'_>);
+}
+
+impl<F> SnapshotObserver for F
+where
+    F: Fn(&SnapshotFailure<'_>) + Send + Sync,
+{
+    fn on_failure(&self, failure: &SnapshotFailure<'_>) {
+        self(failure);
+    }
+}
+
+/// One snapshot store failure, as handed to a [`SnapshotObserver`].
+#[derive(Debug)]
+pub struct SnapshotFailure<'a> {
+    /// Whether the snapshot was being loaded or saved.
+    pub operation: SnapshotOperation,
+    /// The aggregate's id.
+    pub stream_id: ErrorId,
+    /// The version being saved; `None` for a load.
+    pub version: Option<Version>,
+    /// The snapshot store's error.
+    pub error: &'a (dyn std::error::Error + Send + Sync + 'static),
+}
+
+/// The snapshot operation that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotOperation {
+    /// Hydrating state from the snapshot store.
+    Load,
+    /// Committing state to the snapshot store.
+    Save,
+}
+
+impl fmt::Display for SnapshotOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Load => "load",
+            Self::Save => "save",
+        })
+    }
+}
+
+/// Error from a [`Snapshotting`] repository.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SnapshottingError<E, S> {
+    /// The inner repository failed.
+    #[error(transparent)]
+    Repository(E),
+    /// The snapshot store failed under [`SnapshotPolicy::Propagate`].
+    #[error("snapshot {operation} failed for stream '{stream_id}': {source}")]
+    Snapshot {
+        /// Whether the snapshot was being loaded or saved.
+        operation: SnapshotOperation,
+        /// The aggregate's id.
+        stream_id: ErrorId,
+        /// The version being saved; `None` for a load.
+        version: Option<Version>,
+        /// The snapshot store's error.
+        #[source]
+        source: S,
+    },
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SnapshotStats — hit / miss / stale / failure counters
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Snapshot counters, read with [`Snapshotting::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotStats {
+    /// Loads that restored from a snapshot.
+    pub hits: u64,
+    /// Loads with no snapshot stored.
+    pub misses: u64,
+    /// [`load_at`](TimeTravel::load_at) loads that started from a retained
+    /// snapshot at or before the requested version. Counted apart from
+    /// [`hits`](Self::hits): retention, not the trigger, decides them.
+    pub historical_hits: u64,
+    /// [`load_at`](TimeTravel::load_at) loads with no retained snapshot at
+    /// or before the requested version.
+    pub historical_misses: u64,
+    /// Loads whose stored snapshot had another schema version (and no
+    /// upcaster path to the current one).
+    pub stale: u64,
+    /// Snapshot store failures, on load or save, under any policy.
+    pub failures: u64,
+    /// Background snapshot requests dropped: superseded by a newer one for
+    /// the same aggregate, older than one already queued, or refused by a
+    /// full queue.
+    pub dropped: u64,
+}
+
+/// Relaxed atomics: the counters are independent tallies, never used to
+/// order other memory.
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    historical_hits: AtomicU64,
+    historical_misses: AtomicU64,
+    stale: AtomicU64,
+    failures: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            historical_hits: AtomicU64::new(0),
+            historical_misses: AtomicU64::new(0),
+            stale: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn read(&self) -> SnapshotStats {
+        SnapshotStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            historical_hits: self.historical_hits.load(Ordering::Relaxed),
+            historical_misses: self.historical_misses.load(Ordering::Relaxed),
+            stale: self.stale.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Commit modes — inline vs background
+// ═══════════════════════════════════════════════════════════════════════════
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Where [`Snapshotting`] commits a triggered snapshot. Sealed: the modes
+/// are [`Inline`] and [`Background`].
+pub trait CommitMode<A: Aggregate>: sealed::Sealed + Send + Sync {
+    /// Take the snapshot off the save path, if this mode does.
+    #[doc(hidden)]
+    fn hand_off(&self, aggregate: &AggregateRoot<A>, version: Version) -> HandOff;
+}
+
+/// Outcome of [`CommitMode::hand_off`].
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandOff {
+    /// Not handed off: commit on the save path.
+    Inline,
+    /// Queued for the worker.
+    Queued,
+    /// Queued, but a request was dropped doing so (or this one was).
+    Dropped,
+}
+
+/// Commit mode: snapshots are committed inline on the save path (default).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Inline;
+
+impl sealed::Sealed for Inline {}
+
+impl<A: Aggregate> CommitMode<A> for Inline {
+    fn hand_off(&self, _: &AggregateRoot<A>, _: Version) -> HandOff {
+        HandOff::Inline
+    }
+}
+
+/// Snapshot mode: a triggered snapshot is committed in the same store
+/// transaction as the events it covers.
+///
+/// Built by [`Snapshotting::atomic`]. Needs an event store that also holds
+/// the snapshots ([`SnapshotAppend`](crate::SnapshotAppend)): the decorator
+/// folds and encodes the post-save state before the append, and the store
+/// writes both at once — no window where the events are durable and their
+/// snapshot is not. The fold clones the state, and only when the trigger
+/// fires.
+///
+/// Not a [`CommitMode`]: it replaces the save path rather than deciding where
+/// a separate snapshot commit runs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Atomic;
+
+/// What [`Snapshotting::background`] returns: the repository and the
+/// worker draining its queue.
+pub type BackgroundParts<R, SS, T, A> = (
+    Snapshotting<R, Arc<SS>, T, Background<<A as Aggregate>::Id, <A as Aggregate>::State>>,
+    SnapshotWorker<Arc<SS>, <A as Aggregate>::Id, <A as Aggregate>::State>,
+);
+
+/// Commit mode: snapshots are queued for a [`SnapshotWorker`].
+///
+/// Built by [`Snapshotting::background`]. Queuing clones the state; the
+/// encode and store write happen on the worker. Dropping the repository
+/// closes the queue, and the worker finishes once it has drained it.
+pub struct Background<I, S> {
+    queue: Arc<Queue<I, S>>,
+}
+
+impl<I, S> sealed::Sealed for Background<I, S> {}
+
+impl<A> CommitMode<A> for Background<A::Id, A::State>
+where
+    A: Aggregate,
+    A::State: Clone + Send,
+{
+    fn hand_off(&self, aggregate: &AggregateRoot<A>, version: Version) -> HandOff {
+        // Cloned before the queue lock, which then only guards the swap.
+        self.queue
+            .push(aggregate.id(), version, aggregate.state().clone())
+    }
+}
+
+impl<I, S> Drop for Background<I, S> {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+impl<I, S> fmt::Debug for Background<I, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Background")
+            .field("capacity", &self.queue.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SnapshotWorker — drains the background queue
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Commits the snapshots a [`Background`]-mode [`Snapshotting`] queues.
+///
+/// Run it on your runtime (`tokio::spawn(worker.run())`). It commits one
+/// snapshot at a time, oldest-queued aggregate first, and completes once
+/// the repository is dropped and the queue is drained — await it on
+/// shutdown to flush pending snapshots.
+///
+/// Store failures are counted in the repository's
+/// [`stats`](Snapshotting::stats) and reported to a
+/// [`SnapshotPolicy::Observe`] observer. There is no caller to return them
+/// to, so [`Propagate`](SnapshotPolicy::Propagate) only counts them here.
+pub struct SnapshotWorker<SS, I, S> {
+    store: SS,
+    queue: Arc<Queue<I, S>>,
+    schema_version: NonZeroU32,
+    policy: SnapshotPolicy,
+    stats: Arc<Counters>,
+}
+
+impl<SS, I, S> SnapshotWorker<SS, I, S>
+where
+    SS: state::SnapshotStore<S, Version>,
+    I: Id,
+    S: Send + Sync,
+{
+    /// Commit queued snapshots until the repository is dropped and the
+    /// queue is empty.
+    pub async fn run(self) {
+        while let Some((id, version, state)) = self.queue.next().await {
+            let committed = self
+                .store
+                .commit(&id, self.schema_version, version, &state)
+                .await;
+            self.queue.finish();
+            if let Err(error) = committed {
+                self.stats.failures.fetch_add(1, Ordering::Relaxed);
+                if let SnapshotPolicy::Observe(observer) = &self.policy {
+                    observer.on_failure(&SnapshotFailure {
+                        operation: SnapshotOperation::Save,
+                        stream_id: id.to_label(),
+                        version: Some(version),
+                        error: &error,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<SS, I, S> fmt::Debug for SnapshotWorker<SS, I, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotWorker")
+            .field("capacity", &self.queue.capacity)
+            .field("schema_version", &self.schema_version)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Pending snapshots, at most one per aggregate, in first-queued order.
+struct Queue<I, S> {
+    capacity: NonZeroUsize,
+    inner: Mutex<QueueState<I, S>>,
+}
+
+struct QueueState<I, S> {
+    pending: HashMap<I, (Version, S)>,
+    order: VecDeque<I>,
+    /// The snapshot the worker is committing: a request for it at or below
+    /// this version is already stale.
+    in_flight: Option<(I, Version)>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl<I, S> Queue<I, S> {
+    fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(QueueState {
+                pending: HashMap::new(),
+                order: VecDeque::new(),
+                in_flight: None,
+                closed: false,
+                waker: None,
+            }),
+        }
+    }
+
+    fn close(&self) {
+        let mut inner = self.inner.lock();
+        inner.closed = true;
+        let pending_waker = inner.waker.take();
+        drop(inner);
+        if let Some(waker) = pending_waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<I: Id, S> Queue<I, S> {
+    /// Queue `state` at `version`, coalescing with a pending request for
+    /// the same id. A superseded or refused state is dropped after the lock
+    /// is released.
+    fn push(&self, id: &I, version: Version, state: S) -> HandOff {
+        let mut inner = self.inner.lock();
+        if inner.closed
+            || matches!(&inner.in_flight, Some((busy, at)) if busy == id && *at >= version)
+        {
+            return HandOff::Dropped;
+        }
+        let (outcome, superseded) =
+            if let Some((pending, pending_state)) = inner.pending.get_mut(id) {
+                if *pending >= version {
+                    return HandOff::Dropped;
+                }
+                // Keeps its place in line; the older request is superseded.
+                *pending = version;
+                (
+                    HandOff::Dropped,
+                    Some(std::mem::replace(pending_state, state)),
+                )
+            } else if inner.pending.len() >= self.capacity.get() {
+                return HandOff::Dropped;
+            } else {
+                inner.pending.insert(id.clone(), (version, state));
+                inner.order.push_back(id.clone());
+                (HandOff::Queued, None)
+            };
+        let pending_waker = inner.waker.take();
+        drop(inner);
+        drop(superseded);
+        if let Some(waker) = pending_waker {
+            waker.wake();
+        }
+        outcome
+    }
+
+    /// The next snapshot to commit; `None` once closed and drained.
+    async fn next(&self) -> Option<(I, Version, S)> {
+        std::future::poll_fn(|cx| {
+            let mut inner = self.inner.lock();
+            while let Some(id) = inner.order.pop_front() {
+                if let Some((version, state)) = inner.pending.remove(&id) {
+                    inner.in_flight = Some((id.clone(), version));
+                    return Poll::Ready(Some((id, version, state)));
+                }
+            }
+            if inner.closed {
+                return Poll::Ready(None);
+            }
+            inner.waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// The in-flight snapshot is committed (or failed).
+    fn finish(&self) {
+        self.inner.lock().in_flight = None;
     }
 }
@@ -1,9 +1,21 @@
+use std::convert::Infallible;
 use std::future::Future;
-use std::num::{NonZeroU32, NonZeroU64};
+use std::iter;
+use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use bytes::Bytes;
 
 use nexus::{Id, Version};
 
 use crate::codec::{Decode, Encode};
+use crate::envelope::PendingEnvelope;
+use crate::error::AppendError;
+use crate::store::{RawEventStore, Store};
+use crate::stream_id::StreamKey;
+use crate::upcasting::{SnapshotUpcastError, SnapshotUpcasters};
 
 // ═══════════════════════════════════════════════════════════════════════════
 // SnapshotStore<S, P> — atomic state + position persistence
@@ -58,6 +70,69 @@ pub trait SnapshotStore<S, P>: Send + Sync {
         position: P,
         state: &S,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Load the saved snapshot whatever its schema version, along with that
+    /// version.
+    ///
+    /// Backs snapshot schema migration: a caller that can upcast an older
+    /// state shape reads past the [`hydrate`](Self::hydrate) schema check.
+    /// The default returns `Ok(None)` — no migration, so a stale snapshot
+    /// still costs a full replay. Adapters that store the schema version
+    /// alongside the state should override it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Self::Error` if the underlying store fails to read.
+    fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> impl Future<Output = Result<Option<(NonZeroU32, P, S)>, Self::Error>> + Send {
+        let _ = id;
+        async { Ok(None) }
+    }
+
+    /// The schema version of the saved snapshot, if any.
+    ///
+    /// Lets a caller tell a stale snapshot (saved under another schema
+    /// version) from a plain miss after [`hydrate`](Self::hydrate) returns
+    /// `None`. The default reads it through
+    /// [`hydrate_with_schema`](Self::hydrate_with_schema), so stores that
+    /// don't override either report `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Self::Error` if the underlying store fails to read.
+    fn stored_schema_version(
+        &self,
+        id: &impl Id,
+    ) -> impl Future<Output = Result<Option<NonZeroU32>, Self::Error>> + Send {
+        let stored = self.hydrate_with_schema(id);
+        async move { Ok(stored.await?.map(|(schema_version, _, _)| schema_version)) }
+    }
+
+    /// Load the newest saved snapshot whose position is at or before
+    /// `position`, saved under `schema_version`.
+    ///
+    /// Backs loading as of an older position, and falling back past a
+    /// snapshot that can't be used. Stores that keep more than the latest
+    /// snapshot per id (see [`SnapshotRetention`]) should override it. The
+    /// default only considers the latest one, via [`hydrate`](Self::hydrate).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Self::Error` if the underlying store fails to read.
+    fn hydrate_at_or_before(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: P,
+    ) -> impl Future<Output = Result<Option<(P, S)>, Self::Error>> + Send
+    where
+        P: PartialOrd + Send,
+    {
+        let latest = self.hydrate(id, schema_version);
+        async move { Ok(latest.await?.filter(|(stored, _)| *stored <= position)) }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -72,89 +147,877 @@ where
 {
     type Error = T::Error;
 
-    fn hydrate(
+    fn hydrate(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> impl Future<Output = Result<Option<(P, S)>, Self::Error>> + Send {
+        (**self).hydrate(id, schema_version)
+    }
+
+    fn commit(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: P,
+        state: &S,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        (**self).commit(id, schema_version, position, state)
+    }
+
+    fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> impl Future<Output = Result<Option<(NonZeroU32, P, S)>, Self::Error>> + Send {
+        (**self).hydrate_with_schema(id)
+    }
+
+    fn stored_schema_version(
+        &self,
+        id: &impl Id,
+    ) -> impl Future<Output = Result<Option<NonZeroU32>, Self::Error>> + Send {
+        (**self).stored_schema_version(id)
+    }
+
+    fn hydrate_at_or_before(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: P,
+    ) -> impl Future<Output = Result<Option<(P, S)>, Self::Error>> + Send
+    where
+        P: PartialOrd + Send,
+    {
+        (**self).hydrate_at_or_before(id, schema_version, position)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Delegation implementation — share via Arc
+// ═══════════════════════════════════════════════════════════════════════════
+
+impl<S, P, T> SnapshotStore<S, P> for Arc<T>
+where
+    S: Send + Sync,
+    P: Send,
+    T: SnapshotStore<S, P>,
+{
+    type Error = T::Error;
+
+    fn hydrate(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> impl Future<Output = Result<Option<(P, S)>, Self::Error>> + Send {
+        (**self).hydrate(id, schema_version)
+    }
+
+    fn commit(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: P,
+        state: &S,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        (**self).commit(id, schema_version, position, state)
+    }
+
+    fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> impl Future<Output = Result<Option<(NonZeroU32, P, S)>, Self::Error>> + Send {
+        (**self).hydrate_with_schema(id)
+    }
+
+    fn stored_schema_version(
+        &self,
+        id: &impl Id,
+    ) -> impl Future<Output = Result<Option<NonZeroU32>, Self::Error>> + Send {
+        (**self).stored_schema_version(id)
+    }
+
+    fn hydrate_at_or_before(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: P,
+    ) -> impl Future<Output = Result<Option<(P, S)>, Self::Error>> + Send
+    where
+        P: PartialOrd + Send,
+    {
+        (**self).hydrate_at_or_before(id, schema_version, position)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Delegation implementation — share via the Store handle
+// ═══════════════════════════════════════════════════════════════════════════
+
+impl<S, P, T> SnapshotStore<S, P> for Store<T>
+where
+    S: Send + Sync,
+    P: Send,
+    T: SnapshotStore<S, P>,
+{
+    type Error = T::Error;
+
+    fn hydrate(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> impl Future<Output = Result<Option<(P, S)>, Self::Error>> + Send {
+        self.raw().hydrate(id, schema_version)
+    }
+
+    fn commit(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: P,
+        state: &S,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.raw().commit(id, schema_version, position, state)
+    }
+
+    fn hydrate_with_schema(
+        &self,
+        id: &impl Id,
+    ) -> impl Future<Output = Result<Option<(NonZeroU32, P, S)>, Self::Error>> + Send {
+        self.raw().hydrate_with_schema(id)
+    }
+
+    fn stored_schema_version(
+        &self,
+        id: &impl Id,
+    ) -> impl Future<Output = Result<Option<NonZeroU32>, Self::Error>> + Send {
+        self.raw().stored_schema_version(id)
+    }
+
+    fn hydrate_at_or_before(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: P,
+    ) -> impl Future<Output = Result<Option<(P, S)>, Self::Error>> + Send
+    where
+        P: PartialOrd + Send,
+    {
+        self.raw()
+            .hydrate_at_or_before(id, schema_version, position)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SnapshotBytes — a byte-level store's snapshot as a shared buffer
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A byte-level snapshot store that can hand out a snapshot's state bytes
+/// as a shared [`Bytes`] buffer rather than a fresh `Vec<u8>`.
+///
+/// Backs zero-copy hydration: a store whose reads already come back as
+/// refcounted buffers (fjall) shares the state bytes without copying them.
+/// The default copies the [`hydrate`](SnapshotStore::hydrate)d `Vec` into a
+/// `Bytes`, so any byte store can opt in with an empty impl.
+pub trait SnapshotBytes<P>: SnapshotStore<Vec<u8>, P> {
+    /// [`hydrate`](SnapshotStore::hydrate), returning the state bytes as a
+    /// shared buffer.
+    ///
+    /// Stores that can should return bytes starting on a
+    /// [`PAYLOAD_ALIGN`](crate::wire::PAYLOAD_ALIGN) boundary — the same
+    /// invariant [`encode_frame`](crate::wire::encode_frame) gives event
+    /// payloads — so archived-state readers can borrow them in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Self::Error` if the underlying store fails to read.
+    fn hydrate_bytes(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> impl Future<Output = Result<Option<(P, Bytes)>, Self::Error>> + Send {
+        let owned = self.hydrate(id, schema_version);
+        async move {
+            Ok(owned
+                .await?
+                .map(|(position, state)| (position, Bytes::from(state))))
+        }
+    }
+}
+
+impl<P, T> SnapshotBytes<P> for &T
+where
+    P: Send,
+    T: SnapshotBytes<P>,
+{
+    fn hydrate_bytes(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> impl Future<Output = Result<Option<(P, Bytes)>, Self::Error>> + Send {
+        (**self).hydrate_bytes(id, schema_version)
+    }
+}
+
+impl<P, T> SnapshotBytes<P> for Arc<T>
+where
+    P: Send,
+    T: SnapshotBytes<P>,
+{
+    fn hydrate_bytes(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> impl Future<Output = Result<Option<(P, Bytes)>, Self::Error>> + Send {
+        (**self).hydrate_bytes(id, schema_version)
+    }
+}
+
+impl<P, T> SnapshotBytes<P> for Store<T>
+where
+    P: Send,
+    T: SnapshotBytes<P>,
+{
+    fn hydrate_bytes(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> impl Future<Output = Result<Option<(P, Bytes)>, Self::Error>> + Send {
+        self.raw().hydrate_bytes(id, schema_version)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SnapshotAppend — a snapshot committed with the events it covers
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A snapshot blob to commit alongside an append: the encoded state, the
+/// stream version it was folded up to, and its schema version.
+#[derive(Debug, Clone, Copy)]
+pub struct PendingSnapshot<'a> {
+    schema_version: NonZeroU32,
+    version: Version,
+    state: &'a [u8],
+}
+
+impl<'a> PendingSnapshot<'a> {
+    /// A snapshot of `state` at `version`.
+    #[must_use]
+    pub const fn new(schema_version: NonZeroU32, version: Version, state: &'a [u8]) -> Self {
+        Self {
+            schema_version,
+            version,
+            state,
+        }
+    }
+
+    /// The snapshot's schema version.
+    #[must_use]
+    pub const fn schema_version(&self) -> NonZeroU32 {
+        self.schema_version
+    }
+
+    /// The stream version the state was folded up to.
+    #[must_use]
+    pub const fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The encoded state.
+    #[must_use]
+    pub const fn state(&self) -> &'a [u8] {
+        self.state
+    }
+}
+
+/// An event store that keeps snapshots in the same database as the events
+/// and can commit one in the same transaction as an append.
+///
+/// Two separate writes — events, then the snapshot — can leave a crash
+/// between them; a snapshot then lags its stream, harmlessly, but the write
+/// still costs a second commit. Committing both together removes the gap and
+/// the extra sync. The
+/// [`Atomic`](crate::snapshot::Atomic) snapshot mode drives this.
+pub trait SnapshotAppend: RawEventStore + SnapshotStore<Vec<u8>, Version> {
+    /// Append `envelopes` exactly as [`RawEventStore::append`] does, and
+    /// commit `snapshot` in the same transaction — both, or neither.
+    ///
+    /// `snapshot.version()` must be the version of the last envelope;
+    /// adapters reject anything else rather than store a snapshot ahead of or
+    /// behind its events. An empty `envelopes` writes nothing.
+    ///
+    /// # Errors
+    ///
+    /// The errors [`append`](RawEventStore::append) returns, plus an adapter
+    /// error for a snapshot version that doesn't match.
+    fn append_with_snapshot(
+        &self,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+        snapshot: PendingSnapshot<'_>,
+    ) -> impl Future<Output = Result<(), AppendError<<Self as RawEventStore>::Error>>> + Send;
+}
+
+impl<S: SnapshotAppend> SnapshotAppend for Store<S> {
+    async fn append_with_snapshot(
+        &self,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+        snapshot: PendingSnapshot<'_>,
+    ) -> Result<(), AppendError<<Self as RawEventStore>::Error>> {
+        self.raw()
+            .append_with_snapshot(id, expected_version, envelopes, snapshot)
+            .await
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SnapshotRetention — how many snapshots to keep per id
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// How many snapshots a store keeps per id.
+///
+/// The default, [`LATEST`](Self::LATEST), keeps only the newest — every
+/// commit replaces the previous one. Keeping more lets
+/// [`hydrate_at_or_before`](SnapshotStore::hydrate_at_or_before) start
+/// from an older snapshot instead of replaying from the first event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SnapshotRetention {
+    keep: NonZeroUsize,
+}
+
+impl SnapshotRetention {
+    /// Keep only the newest snapshot.
+    pub const LATEST: Self = Self {
+        keep: NonZeroUsize::MIN,
+    };
+
+    /// Keep the newest `count` snapshots; older ones are pruned on commit.
+    #[must_use]
+    pub const fn keep_last(count: NonZeroUsize) -> Self {
+        Self { keep: count }
+    }
+
+    /// The number of snapshots kept per id.
+    #[must_use]
+    pub const fn keep(self) -> NonZeroUsize {
+        self.keep
+    }
+}
+
+impl Default for SnapshotRetention {
+    fn default() -> Self {
+        Self::LATEST
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// PersistTrigger — when-to-persist policy
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Strategy for deciding when to persist state.
+///
+/// Used by both projection runners (when to checkpoint projection state)
+/// and snapshot decorators (when to snapshot aggregate state).
+///
+/// Triggers compose: [`or`](Self::or) / [`AnyOf`], [`and`](Self::and) /
+/// [`AllOf`], and [`Not`]. Triggers that need more than versions and event
+/// names — [`MinInterval`], [`PayloadBytes`], [`ReplayCost`] — read a
+/// [`PersistContext`] the caller fills in through
+/// [`should_persist_with`](Self::should_persist_with).
+pub trait PersistTrigger: Send + Sync {
+    /// Whether state should be persisted now.
+    ///
+    /// - `old_version`: version before the operation (`None` for first run)
+    /// - `new_version`: version after the operation
+    /// - `event_names`: names of events just processed
+    fn should_persist(
+        &self,
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool;
+
+    /// [`should_persist`](Self::should_persist), given what the caller
+    /// knows about time, bytes and replay cost since the last persist.
+    ///
+    /// Defaults to ignoring the context; the context-driven triggers and the
+    /// combinators override it.
+    fn should_persist_with(
+        &self,
+        context: &PersistContext,
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        let _ = context;
+        self.should_persist(old_version, new_version, event_names)
+    }
+
+    /// Whether this trigger reads [`PersistContext::replay_time`].
+    ///
+    /// A caller that can measure replay only on load — the `Snapshotting`
+    /// decorator — consults such a trigger after each load too, not just on
+    /// save.
+    fn uses_replay_time(&self) -> bool {
+        false
+    }
+
+    /// Whether this trigger reads [`PersistContext::elapsed`] or
+    /// [`PersistContext::bytes`].
+    ///
+    /// The `Snapshotting` decorator tracks time and payload bytes since each
+    /// aggregate's last snapshot only for a trigger that reads them.
+    fn uses_persist_history(&self) -> bool {
+        false
+    }
+
+    /// Whether this trigger reads the event names it is given.
+    ///
+    /// [`AnyOf`] and [`AllOf`] hand the names to the operand that reads
+    /// them, collecting them only when both do. Defaults to `true`; a
+    /// trigger that ignores the names can say so to spare the copy.
+    fn uses_event_names(&self) -> bool {
+        true
+    }
+
+    /// Persist when either trigger fires.
+    #[must_use]
+    fn or<T: PersistTrigger>(self, other: T) -> AnyOf<Self, T>
+    where
+        Self: Sized,
+    {
+        AnyOf(self, other)
+    }
+
+    /// Persist when both triggers fire.
+    #[must_use]
+    fn and<T: PersistTrigger>(self, other: T) -> AllOf<Self, T>
+    where
+        Self: Sized,
+    {
+        AllOf(self, other)
+    }
+}
+
+/// What a caller knows about the state since it was last persisted.
+///
+/// Every field is optional: a trigger reading one the caller left unset
+/// decides as documented on the trigger.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PersistContext {
+    elapsed: Option<Duration>,
+    bytes: Option<u64>,
+    replay_time: Option<Duration>,
+}
+
+impl PersistContext {
+    /// A context that knows nothing.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            elapsed: None,
+            bytes: None,
+            replay_time: None,
+        }
+    }
+
+    /// Wall-clock time since the state was last persisted.
+    #[must_use]
+    pub const fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed = Some(elapsed);
+        self
+    }
+
+    /// Event payload bytes folded since the state was last persisted.
+    #[must_use]
+    pub const fn with_bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    /// How long rebuilding the state by replay took.
+    #[must_use]
+    pub const fn with_replay_time(mut self, replay_time: Duration) -> Self {
+        self.replay_time = Some(replay_time);
+        self
+    }
+
+    /// Wall-clock time since the last persist, if known.
+    #[must_use]
+    pub const fn elapsed(&self) -> Option<Duration> {
+        self.elapsed
+    }
+
+    /// Payload bytes since the last persist, if known.
+    #[must_use]
+    pub const fn bytes(&self) -> Option<u64> {
+        self.bytes
+    }
+
+    /// Measured replay time, if known.
+    #[must_use]
+    pub const fn replay_time(&self) -> Option<Duration> {
+        self.replay_time
+    }
+}
+
+/// Persist every N events (bucket-crossing algorithm).
+#[derive(Debug, Clone, Copy)]
+pub struct EveryNEvents(pub NonZeroU64);
+
+impl PersistTrigger for EveryNEvents {
+    fn should_persist(
+        &self,
+        old_version: Option<Version>,
+        new_version: Version,
+        _event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        let n = self.0.get();
+        let old_bucket = old_version.map_or(0, |v| v.as_u64() / n);
+        let new_bucket = new_version.as_u64() / n;
+        new_bucket > old_bucket
+    }
+
+    fn uses_event_names(&self) -> bool {
+        false
+    }
+}
+
+/// Persist after specific event types.
+#[derive(Debug, Clone)]
+pub struct AfterEventTypes {
+    types: Vec<&'static str>,
+}
+
+impl AfterEventTypes {
+    /// Create a trigger that fires when any of the given event types is persisted.
+    #[must_use]
+    pub fn new(types: &[&'static str]) -> Self {
+        Self {
+            types: types.to_vec(),
+        }
+    }
+}
+
+impl PersistTrigger for AfterEventTypes {
+    fn should_persist(
+        &self,
+        _old_version: Option<Version>,
+        _new_version: Version,
+        mut event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        event_names.any(|name| self.types.iter().any(|t| *t == name.as_ref()))
+    }
+}
+
+/// Persist once at least this much wall-clock time has passed since the
+/// last persist ([`PersistContext::elapsed`]).
+///
+/// With no elapsed time in the context the interval counts as passed —
+/// there is no known persist to wait on. Compose it to throttle another
+/// trigger: `EveryNEvents(n).and(MinInterval(d))`.
+#[derive(Debug, Clone, Copy)]
+pub struct MinInterval(pub Duration);
+
+impl PersistTrigger for MinInterval {
+    fn should_persist(
+        &self,
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        self.should_persist_with(
+            &PersistContext::new(),
+            old_version,
+            new_version,
+            event_names,
+        )
+    }
+
+    fn should_persist_with(
+        &self,
+        context: &PersistContext,
+        _old_version: Option<Version>,
+        _new_version: Version,
+        _event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        context.elapsed.is_none_or(|elapsed| elapsed >= self.0)
+    }
+
+    fn uses_persist_history(&self) -> bool {
+        true
+    }
+
+    fn uses_event_names(&self) -> bool {
+        false
+    }
+}
+
+/// Persist once at least this many event payload bytes have been folded
+/// since the last persist ([`PersistContext::bytes`]).
+///
+/// Never fires without a byte count in the context.
+#[derive(Debug, Clone, Copy)]
+pub struct PayloadBytes(pub NonZeroU64);
+
+impl PersistTrigger for PayloadBytes {
+    fn should_persist(
         &self,
-        id: &impl Id,
-        schema_version: NonZeroU32,
-    ) -> impl Future<Output = Result<Option<(P, S)>, Self::Error>> + Send {
-        (**self).hydrate(id, schema_version)
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        self.should_persist_with(
+            &PersistContext::new(),
+            old_version,
+            new_version,
+            event_names,
+        )
     }
 
-    fn commit(
+    fn should_persist_with(
         &self,
-        id: &impl Id,
-        schema_version: NonZeroU32,
-        position: P,
-        state: &S,
-    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
-        (**self).commit(id, schema_version, position, state)
+        context: &PersistContext,
+        _old_version: Option<Version>,
+        _new_version: Version,
+        _event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        context.bytes.is_some_and(|bytes| bytes >= self.0.get())
     }
-}
 
-// ═══════════════════════════════════════════════════════════════════════════
-// PersistTrigger — when-to-persist policy
-// ═══════════════════════════════════════════════════════════════════════════
+    fn uses_persist_history(&self) -> bool {
+        true
+    }
 
-/// Strategy for deciding when to persist state.
+    fn uses_event_names(&self) -> bool {
+        false
+    }
+}
+
+/// Persist once rebuilding the state by replay took at least this long
+/// ([`PersistContext::replay_time`]) — snapshot what is slow to load.
 ///
-/// Used by both projection runners (when to checkpoint projection state)
-/// and snapshot decorators (when to snapshot aggregate state).
-pub trait PersistTrigger: Send + Sync {
-    /// Whether state should be persisted now.
-    ///
-    /// - `old_version`: version before the operation (`None` for first run)
-    /// - `new_version`: version after the operation
-    /// - `event_names`: names of events just processed
+/// Never fires without a replay time in the context. `Snapshotting`
+/// measures the replay on each load and consults this trigger then.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayCost(pub Duration);
+
+impl PersistTrigger for ReplayCost {
     fn should_persist(
         &self,
         old_version: Option<Version>,
         new_version: Version,
         event_names: impl Iterator<Item: AsRef<str>>,
-    ) -> bool;
+    ) -> bool {
+        self.should_persist_with(
+            &PersistContext::new(),
+            old_version,
+            new_version,
+            event_names,
+        )
+    }
+
+    fn should_persist_with(
+        &self,
+        context: &PersistContext,
+        _old_version: Option<Version>,
+        _new_version: Version,
+        _event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        context.replay_time.is_some_and(|took| took >= self.0)
+    }
+
+    fn uses_replay_time(&self) -> bool {
+        true
+    }
+
+    fn uses_event_names(&self) -> bool {
+        false
+    }
 }
 
-/// Persist every N events (bucket-crossing algorithm).
+/// Persist when either trigger fires. Built by [`PersistTrigger::or`];
+/// nest for more than two.
 #[derive(Debug, Clone, Copy)]
-pub struct EveryNEvents(pub NonZeroU64);
+pub struct AnyOf<A, B>(pub A, pub B);
 
-impl PersistTrigger for EveryNEvents {
+impl<A: PersistTrigger, B: PersistTrigger> PersistTrigger for AnyOf<A, B> {
     fn should_persist(
         &self,
         old_version: Option<Version>,
         new_version: Version,
-        _event_names: impl Iterator<Item: AsRef<str>>,
+        event_names: impl Iterator<Item: AsRef<str>>,
     ) -> bool {
-        let n = self.0.get();
-        let old_bucket = old_version.map_or(0, |v| v.as_u64() / n);
-        let new_bucket = new_version.as_u64() / n;
-        new_bucket > old_bucket
+        self.should_persist_with(
+            &PersistContext::new(),
+            old_version,
+            new_version,
+            event_names,
+        )
     }
-}
 
-/// Persist after specific event types.
-#[derive(Debug, Clone)]
-pub struct AfterEventTypes {
-    types: Vec<&'static str>,
+    fn should_persist_with(
+        &self,
+        context: &PersistContext,
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        match (self.0.uses_event_names(), self.1.uses_event_names()) {
+            (true, true) => {
+                let names: Vec<_> = event_names.collect();
+                self.0
+                    .should_persist_with(context, old_version, new_version, names.iter())
+                    || self
+                        .1
+                        .should_persist_with(context, old_version, new_version, names.iter())
+            }
+            (true, false) => {
+                self.0
+                    .should_persist_with(context, old_version, new_version, event_names)
+                    || self.1.should_persist_with(
+                        context,
+                        old_version,
+                        new_version,
+                        iter::empty::<&str>(),
+                    )
+            }
+            (false, _) => {
+                self.0
+                    .should_persist_with(context, old_version, new_version, iter::empty::<&str>())
+                    || self
+                        .1
+                        .should_persist_with(context, old_version, new_version, event_names)
+            }
+        }
+    }
+
+    fn uses_replay_time(&self) -> bool {
+        self.0.uses_replay_time() || self.1.uses_replay_time()
+    }
+
+    fn uses_persist_history(&self) -> bool {
+        self.0.uses_persist_history() || self.1.uses_persist_history()
+    }
+
+    fn uses_event_names(&self) -> bool {
+        self.0.uses_event_names() || self.1.uses_event_names()
+    }
 }
 
-impl AfterEventTypes {
-    /// Create a trigger that fires when any of the given event types is persisted.
-    #[must_use]
-    pub fn new(types: &[&'static str]) -> Self {
-        Self {
-            types: types.to_vec(),
+/// Persist when both triggers fire. Built by [`PersistTrigger::and`];
+/// nest for more than two.
+#[derive(Debug, Clone, Copy)]
+pub struct AllOf<A, B>(pub A, pub B);
+
+impl<A: PersistTrigger, B: PersistTrigger> PersistTrigger for AllOf<A, B> {
+    fn should_persist(
+        &self,
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        self.should_persist_with(
+            &PersistContext::new(),
+            old_version,
+            new_version,
+            event_names,
+        )
+    }
+
+    fn should_persist_with(
+        &self,
+        context: &PersistContext,
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        match (self.0.uses_event_names(), self.1.uses_event_names()) {
+            (true, true) => {
+                let names: Vec<_> = event_names.collect();
+                self.0
+                    .should_persist_with(context, old_version, new_version, names.iter())
+                    && self
+                        .1
+                        .should_persist_with(context, old_version, new_version, names.iter())
+            }
+            (true, false) => {
+                self.0
+                    .should_persist_with(context, old_version, new_version, event_names)
+                    && self.1.should_persist_with(
+                        context,
+                        old_version,
+                        new_version,
+                        iter::empty::<&str>(),
+                    )
+            }
+            (false, _) => {
+                self.0
+                    .should_persist_with(context, old_version, new_version, iter::empty::<&str>())
+                    && self
+                        .1
+                        .should_persist_with(context, old_version, new_version, event_names)
+            }
         }
     }
+
+    fn uses_replay_time(&self) -> bool {
+        self.0.uses_replay_time() || self.1.uses_replay_time()
+    }
+
+    fn uses_persist_history(&self) -> bool {
+        self.0.uses_persist_history() || self.1.uses_persist_history()
+    }
+
+    fn uses_event_names(&self) -> bool {
+        self.0.uses_event_names() || self.1.uses_event_names()
+    }
 }
 
-impl PersistTrigger for AfterEventTypes {
+/// Persist when the inner trigger does not — e.g.
+/// `EveryNEvents(n).and(Not(AfterEventTypes::new(&["Closed"])))`.
+#[derive(Debug, Clone, Copy)]
+pub struct Not<T>(pub T);
+
+impl<T: PersistTrigger> PersistTrigger for Not<T> {
     fn should_persist(
         &self,
-        _old_version: Option<Version>,
-        _new_version: Version,
-        mut event_names: impl Iterator<Item: AsRef<str>>,
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
     ) -> bool {
-        event_names.any(|name| self.types.iter().any(|t| *t == name.as_ref()))
+        self.should_persist_with(
+            &PersistContext::new(),
+            old_version,
+            new_version,
+            event_names,
+        )
+    }
+
+    fn should_persist_with(
+        &self,
+        context: &PersistContext,
+        old_version: Option<Version>,
+        new_version: Version,
+        event_names: impl Iterator<Item: AsRef<str>>,
+    ) -> bool {
+        !self
+            .0
+            .should_persist_with(context, old_version, new_version, event_names)
+    }
+
+    fn uses_replay_time(&self) -> bool {
+        self.0.uses_replay_time()
+    }
+
+    fn uses_persist_history(&self) -> bool {
+        self.0.uses_persist_history()
+    }
+
+    fn uses_event_names(&self) -> bool {
+        self.0.uses_event_names()
     }
 }
 
@@ -169,23 +1032,124 @@ impl PersistTrigger for AfterEventTypes {
 /// Use this when your storage backend works with raw bytes (e.g., fjall)
 /// but consumers need typed state. The position `P` is opaque to the
 /// bridge — it passes through untouched.
+///
+/// # Schema migration
+///
+/// A snapshot written under an older state shape is normally a miss — the
+/// caller replays the whole stream. With
+/// [`with_upcasters`](Self::with_upcasters), a miss at the requested
+/// schema version falls back to
+/// [`hydrate_with_schema`](SnapshotStore::hydrate_with_schema) and runs the
+/// stored bytes through the chain before decoding, and
+/// [`rewrite_migrated`](Self::rewrite_migrated) commits the migrated bytes
+/// back (best-effort) so the next load hits directly.
 pub struct CodecSnapshotStore<SS, C> {
     store: SS,
     codec: C,
+    upcasters: SnapshotUpcasters,
+    rewrite_migrated: bool,
+    /// Relaxed: an independent tally, never used to order other memory.
+    rewrite_failures: AtomicU64,
 }
 
 impl<SS, C> CodecSnapshotStore<SS, C> {
     /// Create a new codec-bridged snapshot store.
     #[must_use]
     pub const fn new(store: SS, codec: C) -> Self {
-        Self { store, codec }
+        Self {
+            store,
+            codec,
+            upcasters: SnapshotUpcasters::NONE,
+            rewrite_migrated: false,
+            rewrite_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Migrate snapshots stored at an older schema version through
+    /// `upcasters` (typically `<Name>::UPCASTERS` from
+    /// `#[nexus::snapshot_transforms]`).
+    #[must_use]
+    pub const fn with_upcasters(mut self, upcasters: SnapshotUpcasters) -> Self {
+        self.upcasters = upcasters;
+        self
+    }
+
+    /// Commit migrated snapshots back at the requested schema version.
+    ///
+    /// Best-effort: a failed rewrite does not fail the load, the migrated
+    /// state is still returned; it counts in
+    /// [`rewrite_failures`](Self::rewrite_failures). The rewrite keeps the
+    /// stored position, so it never claims more than the original snapshot
+    /// did.
+    #[must_use]
+    pub const fn rewrite_migrated(mut self, enabled: bool) -> Self {
+        self.rewrite_migrated = enabled;
+        self
+    }
+
+    /// The configured snapshot upcasters.
+    #[must_use]
+    pub const fn upcasters(&self) -> SnapshotUpcasters {
+        self.upcasters
+    }
+
+    /// Migrated snapshots whose rewrite the inner store refused, since
+    /// construction.
+    #[must_use]
+    pub fn rewrite_failures(&self) -> u64 {
+        self.rewrite_failures.load(Ordering::Relaxed)
+    }
+}
+
+impl<SS, C> CodecSnapshotStore<SS, C> {
+    /// Read an older-schema snapshot and migrate it to `schema_version`.
+    /// `None` when nothing is stored or the chain has no path.
+    async fn hydrate_migrated<P>(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+    ) -> Result<Option<(P, Vec<u8>)>, CodecSnapshotStoreError<SS::Error>>
+    where
+        P: Clone + Send,
+        SS: SnapshotStore<Vec<u8>, P>,
+        C: Sync,
+    {
+        if self.upcasters.steps().is_empty() {
+            return Ok(None);
+        }
+        let Some((stored_schema, position, bytes)) = self
+            .store
+            .hydrate_with_schema(id)
+            .await
+            .map_err(CodecSnapshotStoreError::Store)?
+        else {
+            return Ok(None);
+        };
+        let Some(migrated) = self
+            .upcasters
+            .migrate(stored_schema, schema_version, &bytes)
+            .map_err(CodecSnapshotStoreError::Upcast)?
+        else {
+            return Ok(None);
+        };
+        if self.rewrite_migrated
+            && stored_schema != schema_version
+            && self
+                .store
+                .commit(id, schema_version, position.clone(), &migrated)
+                .await
+                .is_err()
+        {
+            self.rewrite_failures.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(Some((position, migrated)))
     }
 }
 
 impl<S, P, SS, C> SnapshotStore<S, P> for CodecSnapshotStore<SS, C>
 where
     S: Send + Sync + 'static,
-    P: Send,
+    P: Clone + Send,
     SS: SnapshotStore<Vec<u8>, P>,
     for<'a> C: Encode<S> + Decode<S, Output<'a> = S>,
 {
@@ -197,26 +1161,20 @@ where
         id: &impl Id,
         schema_version: NonZeroU32,
     ) -> Result<Option<(P, S)>, Self::Error> {
-        let Some((position, bytes)) = self
+        let hit = match self
             .store
             .hydrate(id, schema_version)
             .await
             .map_err(CodecSnapshotStoreError::Store)?
-        else {
-            return Ok(None);
+        {
+            Some(hit) => Some(hit),
+            None => self
+                .hydrate_migrated(id, schema_version)
+                .await
+                .map_err(CodecSnapshotStoreError::widen)?,
         };
-
-        let label = id.to_label();
-        // Wrap the snapshot's raw bytes in a synthetic envelope so the
-        // codec's envelope-based decode trait can be called. The
-        // snapshot wire format is *not* the event wire format; this
-        // synthesis only carries the bytes through to `decode()`.
-        let env = crate::envelope::PersistedEnvelope::for_decode(label.as_str(), &bytes)
-            .map_err(CodecSnapshotStoreError::EnvelopeSynthesis)?;
-        let state =
-            <C as Decode<S>>::decode(&self.codec, &env).map_err(CodecSnapshotStoreError::Decode)?;
-
-        Ok(Some((position, state)))
+        hit.map(|(position, bytes)| Ok((position, self.decode_state(id, &bytes)?)))
+            .transpose()
     }
 
     async fn commit(
@@ -238,13 +1196,68 @@ where
             .await
             .map_err(CodecSnapshotStoreError::Store)
     }
+
+    async fn stored_schema_version(&self, id: &impl Id) -> Result<Option<NonZeroU32>, Self::Error> {
+        self.store
+            .stored_schema_version(id)
+            .await
+            .map_err(CodecSnapshotStoreError::Store)
+    }
+
+    async fn hydrate_at_or_before(
+        &self,
+        id: &impl Id,
+        schema_version: NonZeroU32,
+        position: P,
+    ) -> Result<Option<(P, S)>, Self::Error>
+    where
+        P: PartialOrd + Send,
+    {
+        self.store
+            .hydrate_at_or_before(id, schema_version, position)
+            .await
+            .map_err(CodecSnapshotStoreError::Store)?
+            .map(|(stored, bytes)| Ok((stored, self.decode_state(id, &bytes)?)))
+            .transpose()
+    }
+}
+
+impl<SS, C> CodecSnapshotStore<SS, C> {
+    /// Encode typed state to the bytes [`commit`](SnapshotStore::commit)
+    /// would store.
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn encode_state<S>(&self, state: &S) -> Result<bytes::Bytes, <C as Encode<S>>::Error>
+    where
+        C: Encode<S>,
+    {
+        <C as Encode<S>>::encode(&self.codec, state)
+    }
+
+    /// Decode stored snapshot bytes into typed state.
+    fn decode_state<S, StoreErr, EncErr>(
+        &self,
+        id: &impl Id,
+        bytes: &[u8],
+    ) -> Result<S, CodecSnapshotStoreError<StoreErr, EncErr, <C as Decode<S>>::Error>>
+    where
+        for<'a> C: Decode<S, Output<'a> = S>,
+    {
+        let label = id.to_label();
+        // Wrap the snapshot's raw bytes in a synthetic envelope so the
+        // codec's envelope-based decode trait can be called. The
+        // snapshot wire format is *not* the event wire format; this
+        // synthesis only carries the bytes through to `decode()`.
+        let env = crate::envelope::PersistedEnvelope::for_decode(label.as_str(), bytes)
+            .map_err(CodecSnapshotStoreError::EnvelopeSynthesis)?;
+        <C as Decode<S>>::decode(&self.codec, &env).map_err(CodecSnapshotStoreError::Decode)
+    }
 }
 
 /// Error from [`CodecSnapshotStore`] — the underlying store, the encoder, the decoder,
 /// or the wire-format synthesis used to call the envelope-based decode trait.
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
-pub enum CodecSnapshotStoreError<S, EncErr, DecErr> {
+pub enum CodecSnapshotStoreError<S, EncErr = Infallible, DecErr = Infallible> {
     /// The underlying byte-level store failed.
     #[error(transparent)]
     Store(S),
@@ -259,6 +1272,21 @@ pub enum CodecSnapshotStoreError<S, EncErr, DecErr> {
     /// labels (≤64 bytes via `Id::to_label`) and snapshot bytes ≤ 4 GiB.
     #[error("envelope synthesis error: {0}")]
     EnvelopeSynthesis(#[source] crate::envelope::ForDecodeError),
+    /// A snapshot upcaster failed while migrating an older-schema snapshot.
+    #[error(transparent)]
+    Upcast(SnapshotUpcastError),
+}
+
+impl<S> CodecSnapshotStoreError<S> {
+    /// Re-type a store/upcast-only error for any codec error types.
+    fn widen<EncErr, DecErr>(self) -> CodecSnapshotStoreError<S, EncErr, DecErr> {
+        match self {
+            Self::Store(e) => CodecSnapshotStoreError::Store(e),
+            Self::Upcast(e) => CodecSnapshotStoreError::Upcast(e),
+            Self::Encode(never) | Self::Decode(never) => match never {},
+            Self::EnvelopeSynthesis(e) => CodecSnapshotStoreError::EnvelopeSynthesis(e),
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -267,19 +1295,25 @@ pub enum CodecSnapshotStoreError<S, EncErr, DecErr> {
 
 #[cfg(feature = "testing")]
 mod testing {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, VecDeque};
     use std::convert::Infallible;
     use std::num::NonZeroU32;
 
     use nexus::Id;
     use tokio::sync::RwLock;
 
-    use super::SnapshotStore;
+    use super::{SnapshotBytes, SnapshotRetention, SnapshotStore};
+
+    /// Saved snapshots for one id, oldest first: `(schema_version, position, state)`.
+    type History<S, P> = VecDeque<(NonZeroU32, P, S)>;
 
     /// In-memory snapshot store for tests.
+    ///
+    /// Keeps the last [`SnapshotRetention::keep`] commits per id.
     #[derive(Debug, Default)]
     pub struct InMemorySnapshotStore<S, P> {
-        snapshots: RwLock<HashMap<String, (NonZeroU32, P, S)>>,
+        snapshots: RwLock<HashMap<String, History<S, P>>>,
+        retention: SnapshotRetention,
     }
 
     impl<S, P> InMemorySnapshotStore<S, P> {
@@ -287,8 +1321,16 @@ mod testing {
         pub fn new() -> Self {
             Self {
                 snapshots: RwLock::new(HashMap::new()),
+                retention: SnapshotRetention::LATEST,
             }
         }
+
+        /// Keep more than the latest snapshot per id.
+        #[must_use]
+        pub const fn with_retention(mut self, retention: SnapshotRetention) -> Self {
+            self.retention = retention;
+            self
+        }
     }
 
     impl<S, P> SnapshotStore<S, P> for InMemorySnapshotStore<S, P>
@@ -306,6 +1348,7 @@ mod testing {
             let snapshots = self.snapshots.read().await;
             Ok(snapshots
                 .get(&id.to_string())
+                .and_then(VecDeque::back)
                 .filter(|(stored_schema, _, _)| *stored_schema == schema_version)
                 .map(|(_, position, state)| (position.clone(), state.clone())))
         }
@@ -317,13 +1360,51 @@ mod testing {
             position: P,
             state: &S,
         ) -> Result<(), Infallible> {
-            self.snapshots
-                .write()
-                .await
-                .insert(id.to_string(), (schema_version, position, state.clone()));
+            let mut snapshots = self.snapshots.write().await;
+            let history = snapshots.entry(id.to_string()).or_default();
+            history.push_back((schema_version, position, state.clone()));
+            while history.len() > self.retention.keep().get() {
+                history.pop_front();
+            }
+            drop(snapshots);
             Ok(())
         }
+
+        async fn hydrate_with_schema(
+            &self,
+            id: &impl Id,
+        ) -> Result<Option<(NonZeroU32, P, S)>, Infallible> {
+            let snapshots = self.snapshots.read().await;
+            Ok(snapshots
+                .get(&id.to_string())
+                .and_then(VecDeque::back)
+                .cloned())
+        }
+
+        async fn hydrate_at_or_before(
+            &self,
+            id: &impl Id,
+            schema_version: NonZeroU32,
+            position: P,
+        ) -> Result<Option<(P, S)>, Infallible>
+        where
+            P: PartialOrd + Send,
+        {
+            let snapshots = self.snapshots.read().await;
+            Ok(snapshots.get(&id.to_string()).and_then(|history| {
+                history
+                    .iter()
+                    .rev()
+                    .find(|(stored_schema, stored, _)| {
+                        *stored_schema == schema_version && *stored <= position
+                    })
+                    .map(|(_, stored, state)| (stored.clone(), state.clone()))
+            }))
+        }
     }
+
+    impl<P> SnapshotBytes<P> for InMemorySnapshotStore<Vec<u8>, P> where P: Clone + Send + Sync + 'static
+    {}
 }
 
 #[cfg(feature = "testing")]
@@ -323,6 +323,52 @@ impl<S: RawEventStore> RawEventStore for Store<S> {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// TransactionalAppend<Tx> — append inside a caller-owned transaction
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// An event store whose appends can join a transaction the caller owns.
+///
+/// `Tx` is the adapter's transaction handle (postgres: `sqlx::Transaction`).
+/// The append is staged in `tx` next to whatever else the caller writes
+/// there — read models, outbox rows, uniqueness reservations — and becomes
+/// durable, and visible to readers and subscribers, only when the caller
+/// commits. Rolling `tx` back discards the events with everything else.
+pub trait TransactionalAppend<Tx: Send + ?Sized>: RawEventStore {
+    /// Append `envelopes` inside `tx`, with the same optimistic-concurrency
+    /// check and the same errors as [`RawEventStore::append`].
+    ///
+    /// Success means the events are staged, not committed. Wake-ups for
+    /// subscribers fire on commit, never for a rolled-back transaction.
+    ///
+    /// # Errors
+    ///
+    /// The errors [`append`](RawEventStore::append) returns. A failed append
+    /// stages nothing; whether `tx` stays usable afterwards is up to the
+    /// adapter.
+    fn append_in_tx(
+        &self,
+        tx: &mut Tx,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+    ) -> impl std::future::Future<Output = Result<(), AppendError<Self::Error>>> + Send;
+}
+
+impl<Tx: Send + ?Sized, S: TransactionalAppend<Tx>> TransactionalAppend<Tx> for Store<S> {
+    async fn append_in_tx(
+        &self,
+        tx: &mut Tx,
+        id: &StreamKey,
+        expected_version: Option<Version>,
+        envelopes: &[PendingEnvelope],
+    ) -> Result<(), AppendError<Self::Error>> {
+        self.raw()
+            .append_in_tx(tx, id, expected_version, envelopes)
+            .await
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // AllPosition — adapter-defined `$all` resume position
 // ═══════════════════════════════════════════════════════════════════════════
@@ -0,0 +1,91 @@
+//! Loading an aggregate as it was at a past version or time.
+//!
+//! [`TimeTravel`] extends [`Repository`] with two historical loads that
+//! stop replay early. Both return a [`ReadOnlyRoot`]: a past state is for
+//! looking at, and `save` takes `&mut AggregateRoot`, so a historical root
+//! can never be used to append on top of history it doesn't reflect.
+
+use std::fmt;
+use std::future::Future;
+
+use nexus::{Aggregate, AggregateRoot, Version};
+
+use crate::repository::Repository;
+
+/// A historical aggregate: id, state and version, with no way back to a
+/// savable [`AggregateRoot`].
+pub struct ReadOnlyRoot<A: Aggregate> {
+    root: AggregateRoot<A>,
+}
+
+impl<A: Aggregate> ReadOnlyRoot<A> {
+    pub(crate) const fn new(root: AggregateRoot<A>) -> Self {
+        Self { root }
+    }
+
+    /// The aggregate's identity.
+    #[must_use]
+    pub const fn id(&self) -> &A::Id {
+        self.root.id()
+    }
+
+    /// The state as of the requested point.
+    #[must_use]
+    pub const fn state(&self) -> &A::State {
+        self.root.state()
+    }
+
+    /// The version of the last event replayed, or `None` if none was.
+    ///
+    /// Lower than requested when the stream was shorter.
+    #[must_use]
+    pub const fn version(&self) -> Option<Version> {
+        self.root.version()
+    }
+}
+
+impl<A: Aggregate> fmt::Debug for ReadOnlyRoot<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadOnlyRoot")
+            .field("id", self.id())
+            .field("version", &self.version())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Historical loads on top of [`Repository`].
+///
+/// Implemented by [`EventStore`](crate::EventStore) and by the
+/// `Snapshotting` decorator, which starts `load_at` from the nearest
+/// retained snapshot at or before the requested version.
+pub trait TimeTravel<A: Aggregate>: Repository<A> {
+    /// Load the aggregate as of `version`: replay stops after the event at
+    /// `version`.
+    ///
+    /// A stream shorter than `version` loads to its end — check
+    /// [`ReadOnlyRoot::version`].
+    fn load_at(
+        &self,
+        id: A::Id,
+        version: Version,
+    ) -> impl Future<Output = Result<ReadOnlyRoot<A>, Self::Error>> + Send;
+
+    /// Load the aggregate as of `as_of`, reading each event's time from its
+    /// metadata with `timestamp`.
+    ///
+    /// The frame carries no time of its own; the metadata format is the
+    /// application's, so `timestamp` decodes it (e.g. an RFC 3339 field, or
+    /// a little-endian epoch). Replay stops at the first event stamped after
+    /// `as_of`. An event whose time can't be read — no metadata, or
+    /// `timestamp` returns `None` — doesn't stop it: streams are append
+    /// ordered, so it sits between its neighbours.
+    fn load_as_of<T, F>(
+        &self,
+        id: A::Id,
+        as_of: T,
+        timestamp: F,
+    ) -> impl Future<Output = Result<ReadOnlyRoot<A>, Self::Error>> + Send
+    where
+        T: PartialOrd + Send + 'static,
+        F: Fn(&[u8]) -> Option<T> + Send + 'static;
+}
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
+use std::num::NonZeroU32;
 
-use nexus::Version;
+use nexus::{DomainEvent, Version};
 
 // ═══════════════════════════════════════════════════════════════════════════
 // EventMorsel — data unit flowing through the transform pipeline
@@ -95,3 +96,369 @@ impl<'a> EventMorsel<'a> {
         Self { event_type, ..self }
     }
 }
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TransformRegistry — enumerable description of every upcaster chain
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Every upcaster chain of one `#[nexus::transforms]` block, as data.
+///
+/// The macro emits it as `<Name>::REGISTRY`, one [`TransformPath`] per
+/// origin event type (a type that is never the target of a rename). A path
+/// follows its steps across renames, so `OrderCancelled` renamed to
+/// `OrderVoided` at 1 → 2 and migrated again at 2 → 3 is a single path
+/// ending at `("OrderVoided", 3)`.
+///
+/// The macro has already rejected gaps, overlaps and rename cycles, so
+/// every path is contiguous from version 1. Tooling and tests enumerate
+/// the registry to exercise each migration step without a store.
+pub struct TransformRegistry<E: 'static> {
+    paths: &'static [TransformPath<E>],
+}
+
+impl<E> TransformRegistry<E> {
+    /// Build a registry. Called by `#[nexus::transforms]`.
+    #[must_use]
+    pub const fn new(paths: &'static [TransformPath<E>]) -> Self {
+        Self { paths }
+    }
+
+    /// Every path, in declaration order of their first step.
+    #[must_use]
+    pub const fn paths(&self) -> &'static [TransformPath<E>] {
+        self.paths
+    }
+
+    /// The path starting at `event_type`, if it is an origin.
+    #[must_use]
+    pub fn path(&self, event_type: &str) -> Option<&'static TransformPath<E>> {
+        self.paths.iter().find(|path| path.origin() == event_type)
+    }
+
+    /// Every step across every path.
+    pub fn steps(&self) -> impl Iterator<Item = &'static TransformStep<E>> {
+        self.paths.iter().flat_map(|path| path.steps.iter())
+    }
+}
+
+/// The version path of one origin event type, from version 1 to its
+/// current `(event_type, version)`.
+pub struct TransformPath<E: 'static> {
+    origin: &'static str,
+    steps: &'static [TransformStep<E>],
+}
+
+impl<E> TransformPath<E> {
+    /// Build a path. Called by `#[nexus::transforms]`; `steps` must be
+    /// non-empty and contiguous.
+    #[must_use]
+    pub const fn new(origin: &'static str, steps: &'static [TransformStep<E>]) -> Self {
+        Self { origin, steps }
+    }
+
+    /// The event type stored at version 1.
+    #[must_use]
+    pub const fn origin(&self) -> &'static str {
+        self.origin
+    }
+
+    /// The steps in order, each starting where the previous one ended.
+    #[must_use]
+    pub const fn steps(&self) -> &'static [TransformStep<E>] {
+        self.steps
+    }
+
+    /// The event type the path ends at (differs from
+    /// [`origin`](Self::origin) when a step renames).
+    #[must_use]
+    pub const fn current_event_type(&self) -> &'static str {
+        match self.steps.last() {
+            Some(step) => step.output_event_type,
+            None => self.origin,
+        }
+    }
+
+    /// The schema version the path ends at.
+    #[must_use]
+    pub const fn current_version(&self) -> Version {
+        match self.steps.last() {
+            Some(step) => step.to,
+            None => Version::INITIAL,
+        }
+    }
+
+    /// Run every step on a version-1 payload of [`origin`](Self::origin).
+    ///
+    /// # Errors
+    ///
+    /// Returns the first step's error.
+    pub fn migrate(&self, payload: &[u8]) -> Result<Vec<u8>, E> {
+        self.steps
+            .iter()
+            .try_fold(payload.to_vec(), |bytes, step| step.apply(&bytes))
+    }
+}
+
+/// One `#[transform]` step: `event_type` at `from` becomes
+/// `output_event_type` at `to` (`from + 1`).
+pub struct TransformStep<E: 'static> {
+    name: &'static str,
+    event_type: &'static str,
+    output_event_type: &'static str,
+    from: Version,
+    to: Version,
+    transform: fn(&[u8]) -> Result<Vec<u8>, E>,
+}
+
+impl<E> TransformStep<E> {
+    /// Build a step. Called by `#[nexus::transforms]`.
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        (event_type, from): (&'static str, Version),
+        (output_event_type, to): (&'static str, Version),
+        transform: fn(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Self {
+        Self {
+            name,
+            event_type,
+            output_event_type,
+            from,
+            to,
+            transform,
+        }
+    }
+
+    /// The transform function's name.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The event type this step reads.
+    #[must_use]
+    pub const fn event_type(&self) -> &'static str {
+        self.event_type
+    }
+
+    /// The event type this step writes (the rename target, if any).
+    #[must_use]
+    pub const fn output_event_type(&self) -> &'static str {
+        self.output_event_type
+    }
+
+    /// Source schema version.
+    #[must_use]
+    pub const fn from(&self) -> Version {
+        self.from
+    }
+
+    /// Target schema version.
+    #[must_use]
+    pub const fn to(&self) -> Version {
+        self.to
+    }
+
+    /// Run the transform on a payload at [`from`](Self::from).
+    ///
+    /// # Errors
+    ///
+    /// Returns the transform function's error.
+    pub fn apply(&self, payload: &[u8]) -> Result<Vec<u8>, E> {
+        (self.transform)(payload)
+    }
+}
+
+/// The schema version `E` declares for `event_type`, as a `const fn`.
+///
+/// Reads [`DomainEvent::SCHEMA_VERSIONS`]; unlisted names are at
+/// [`Version::INITIAL`], matching [`DomainEvent::schema_version`].
+/// `#[nexus::transforms]` calls this in `const` assertions so a chain that
+/// does not end at the declared version fails to compile.
+#[must_use]
+pub const fn declared_schema_version<E: DomainEvent>(event_type: &str) -> Version {
+    let mut i = 0;
+    while i < E::SCHEMA_VERSIONS.len() {
+        let (name, version) = E::SCHEMA_VERSIONS[i];
+        if bytes_eq(name.as_bytes(), event_type.as_bytes()) {
+            return version;
+        }
+        i += 1;
+    }
+    Version::INITIAL
+}
+
+/// `a == b` for byte slices, usable in `const fn` (slice `PartialEq` is not).
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SnapshotUpcasters — schema migration for snapshot bytes
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Boxed error from a snapshot transform function.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// One snapshot transform: state bytes at schema version `from` become
+/// bytes at `to` (`from + 1`).
+///
+/// Same function shape as an event `#[transform]`; the error is boxed so
+/// a [`CodecSnapshotStore`](crate::state::CodecSnapshotStore) carries
+/// chains from any error type without another type parameter.
+pub struct SnapshotTransform {
+    name: &'static str,
+    from: NonZeroU32,
+    to: NonZeroU32,
+    transform: fn(&[u8]) -> Result<Vec<u8>, BoxError>,
+}
+
+impl SnapshotTransform {
+    /// Build a step. Called by `#[nexus::snapshot_transforms]`.
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        from: NonZeroU32,
+        to: NonZeroU32,
+        transform: fn(&[u8]) -> Result<Vec<u8>, BoxError>,
+    ) -> Self {
+        Self {
+            name,
+            from,
+            to,
+            transform,
+        }
+    }
+
+    /// The transform function's name.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Source snapshot schema version.
+    #[must_use]
+    pub const fn from(&self) -> NonZeroU32 {
+        self.from
+    }
+
+    /// Target snapshot schema version.
+    #[must_use]
+    pub const fn to(&self) -> NonZeroU32 {
+        self.to
+    }
+
+    /// Run the transform on state bytes at [`from`](Self::from).
+    ///
+    /// # Errors
+    ///
+    /// Returns the transform function's error, boxed.
+    pub fn apply(&self, state: &[u8]) -> Result<Vec<u8>, BoxError> {
+        (self.transform)(state)
+    }
+}
+
+/// A contiguous chain of [`SnapshotTransform`]s, keyed by snapshot schema
+/// version.
+///
+/// `#[nexus::snapshot_transforms]` emits one as `<Name>::UPCASTERS`, having
+/// rejected gaps and duplicate steps at compile time. Hand to
+/// [`CodecSnapshotStore::with_upcasters`](crate::state::CodecSnapshotStore::with_upcasters)
+/// so a snapshot written under an older state shape is migrated on load
+/// instead of discarded for a full replay.
+#[derive(Clone, Copy)]
+pub struct SnapshotUpcasters {
+    steps: &'static [SnapshotTransform],
+}
+
+impl SnapshotUpcasters {
+    /// No transforms: only snapshots at the requested schema version load.
+    pub const NONE: Self = Self { steps: &[] };
+
+    /// Build a chain. Called by `#[nexus::snapshot_transforms]`; `steps`
+    /// must be contiguous and ordered by [`from`](SnapshotTransform::from).
+    #[must_use]
+    pub const fn new(steps: &'static [SnapshotTransform]) -> Self {
+        Self { steps }
+    }
+
+    /// The steps in order.
+    #[must_use]
+    pub const fn steps(&self) -> &'static [SnapshotTransform] {
+        self.steps
+    }
+
+    /// The schema version the chain ends at, or `None` when empty.
+    #[must_use]
+    pub const fn current_version(&self) -> Option<NonZeroU32> {
+        match self.steps.last() {
+            Some(step) => Some(step.to),
+            None => None,
+        }
+    }
+
+    /// Migrate state bytes from schema version `from` to `to`.
+    ///
+    /// Returns `None` when the chain has no path — `from` is newer than
+    /// `to`, or older than the chain's first step.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SnapshotUpcastError`] naming the first failing step.
+    pub fn migrate(
+        &self,
+        from: NonZeroU32,
+        to: NonZeroU32,
+        state: &[u8],
+    ) -> Result<Option<Vec<u8>>, SnapshotUpcastError> {
+        if from == to {
+            return Ok(Some(state.to_vec()));
+        }
+        let Some(start) = self.steps.iter().position(|step| step.from == from) else {
+            return Ok(None);
+        };
+        let mut bytes = state.to_vec();
+        for step in &self.steps[start..] {
+            bytes = step.apply(&bytes).map_err(|source| SnapshotUpcastError {
+                from: step.from,
+                to: step.to,
+                source,
+            })?;
+            if step.to == to {
+                return Ok(Some(bytes));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl std::fmt::Debug for SnapshotUpcasters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.steps.iter().map(|step| (step.from, step.to)))
+            .finish()
+    }
+}
+
+/// A snapshot transform failed.
+#[derive(Debug, thiserror::Error)]
+#[error("snapshot transform from schema version {from} to {to} failed: {source}")]
+pub struct SnapshotUpcastError {
+    /// Source schema version of the failing step.
+    pub from: NonZeroU32,
+    /// Target schema version of the failing step.
+    pub to: NonZeroU32,
+    /// The transform function's error.
+    #[source]
+    pub source: BoxError,
+}
@@ -0,0 +1,306 @@
+//! `ArchivedSnapshotStore` — rkyv snapshot state read in place, and
+//! materialized only when events must be applied.
+
+#![cfg(all(
+    feature = "rkyv",
+    feature = "snapshot",
+    feature = "json",
+    feature = "testing"
+))]
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::missing_const_for_fn,
+    reason = "test harness — relaxed lints for test code"
+)]
+
+use std::fmt;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::sync::Arc;
+
+use nexus::*;
+use nexus_store::bytes::Bytes;
+use nexus_store::state::{EveryNEvents, InMemorySnapshotStore, SnapshotStore};
+use nexus_store::testing::InMemoryStore;
+use nexus_store::{
+    ArchivedSnapshot, ArchivedSnapshotError, ArchivedSnapshotStore, Repository, Snapshotting, Store,
+};
+
+const SV1: NonZeroU32 = NonZeroU32::MIN;
+
+// ── Test domain ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+enum LedgerEvent {
+    Credited(u64),
+}
+
+impl Message for LedgerEvent {}
+impl DomainEvent for LedgerEvent {
+    fn name(&self) -> &'static str {
+        "Credited"
+    }
+}
+
+#[derive(
+    Default, Debug, Clone, PartialEq, Eq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize,
+)]
+struct LedgerState {
+    balance: u64,
+    entries: Vec<u64>,
+}
+
+impl AggregateState for LedgerState {
+    type Event = LedgerEvent;
+    fn initial() -> Self {
+        Self::default()
+    }
+    fn apply(mut self, event: &LedgerEvent) -> Self {
+        let LedgerEvent::Credited(amount) = event;
+        self.balance += amount;
+        self.entries.push(*amount);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct LedgerId(String);
+
+impl fmt::Display for LedgerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<[u8]> for LedgerId {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+impl Id for LedgerId {
+    const BYTE_LEN: usize = 0;
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("ledger error")]
+struct LedgerError;
+
+struct Ledger;
+impl Aggregate for Ledger {
+    type State = LedgerState;
+    type Error = LedgerError;
+    type Id = LedgerId;
+}
+
+type Bytestore = Arc<InMemorySnapshotStore<Vec<u8>, Version>>;
+
+fn id() -> LedgerId {
+    LedgerId("ledger-1".into())
+}
+
+fn version(n: u64) -> Version {
+    Version::new(n).unwrap()
+}
+
+fn state(amounts: &[u64]) -> LedgerState {
+    amounts
+        .iter()
+        .map(|amount| LedgerEvent::Credited(*amount))
+        .fold(LedgerState::initial(), |state, event| state.apply(&event))
+}
+
+fn snapshotting(
+    events: &Store<InMemoryStore>,
+    bytes: &Bytestore,
+) -> impl Repository<Ledger, Error = impl core::error::Error> {
+    Snapshotting::new(
+        events.repository().build(),
+        ArchivedSnapshotStore::new(Arc::clone(bytes)),
+        EveryNEvents(NonZeroU64::MIN),
+        SV1,
+        false,
+    )
+}
+
+// ── Tests ──────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn archived_state_reads_in_place_after_commit() {
+    let store = ArchivedSnapshotStore::new(InMemorySnapshotStore::<Vec<u8>, Version>::new());
+    store
+        .commit(&id(), SV1, version(3), &state(&[5, 7, 11]))
+        .await
+        .unwrap();
+
+    let snapshot = store
+        .hydrate_archived::<LedgerState>(&id(), SV1)
+        .await
+        .unwrap()
+        .expect("snapshot committed");
+
+    assert_eq!(snapshot.version(), version(3));
+    let archived = snapshot.archived();
+    assert_eq!(archived.balance, 23);
+    assert_eq!(archived.entries.as_slice(), [5, 7, 11]);
+}
+
+#[tokio::test]
+async fn schema_mismatch_and_missing_id_hydrate_as_none() {
+    let store = ArchivedSnapshotStore::new(InMemorySnapshotStore::<Vec<u8>, Version>::new());
+    store
+        .commit(&id(), SV1, version(1), &state(&[1]))
+        .await
+        .unwrap();
+
+    let other_schema = NonZeroU32::new(2).unwrap();
+    assert!(
+        store
+            .hydrate_archived::<LedgerState>(&id(), other_schema)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        store
+            .hydrate_archived::<LedgerState>(&LedgerId("other".into()), SV1)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn aligned_bytes_are_shared_not_copied() {
+    let archived = rkyv::to_bytes::<rkyv::rancor::Error>(&state(&[9])).unwrap();
+    let bytes = Bytes::from_owner(archived);
+    let snapshot = ArchivedSnapshot::<LedgerState>::new(version(1), bytes.clone()).unwrap();
+
+    assert_eq!(snapshot.bytes().as_ptr(), bytes.as_ptr());
+    assert_eq!(snapshot.archived().balance, 9);
+}
+
+#[test]
+fn misaligned_bytes_are_realigned_once() {
+    let archived = rkyv::to_bytes::<rkyv::rancor::Error>(&state(&[2, 3])).unwrap();
+    let mut shifted = vec![0u8];
+    shifted.extend_from_slice(&archived);
+    let misaligned = Bytes::from(shifted).slice(1..);
+    assert_ne!(
+        misaligned.as_ptr().addr() % nexus_store::wire::PAYLOAD_ALIGN,
+        0
+    );
+
+    let snapshot = ArchivedSnapshot::<LedgerState>::new(version(2), misaligned).unwrap();
+
+    assert_eq!(
+        snapshot.bytes().as_ptr().addr() % nexus_store::wire::PAYLOAD_ALIGN,
+        0
+    );
+    assert_eq!(snapshot.archived().balance, 5);
+    assert_eq!(snapshot.deserialize().unwrap(), state(&[2, 3]));
+}
+
+#[tokio::test]
+async fn invalid_archive_is_an_archive_error() {
+    let bytes = InMemorySnapshotStore::<Vec<u8>, Version>::new();
+    bytes
+        .commit(&id(), SV1, version(1), &vec![0xFF; 3])
+        .await
+        .unwrap();
+    let store = ArchivedSnapshotStore::new(bytes);
+
+    let err = store
+        .hydrate_archived::<LedgerState>(&id(), SV1)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ArchivedSnapshotError::Archive(_)));
+}
+
+#[tokio::test]
+async fn snapshotting_commits_archives_that_handlers_read_in_place() {
+    let events = Store::new(InMemoryStore::new());
+    let bytes: Bytestore = Arc::new(InMemorySnapshotStore::new());
+    let repo = snapshotting(&events, &bytes);
+
+    let mut root = repo.load(id()).await.unwrap();
+    let mut credits = Events::<_, 1>::new(LedgerEvent::Credited(40));
+    credits.add(LedgerEvent::Credited(2));
+    repo.save(&mut root, &credits).await.unwrap();
+
+    let reader = ArchivedSnapshotStore::new(Arc::clone(&bytes));
+    let snapshot = reader
+        .hydrate_archived::<LedgerState>(&id(), SV1)
+        .await
+        .unwrap()
+        .expect("trigger fires on every event");
+    assert_eq!(snapshot.version(), version(2));
+    assert_eq!(snapshot.archived().balance, 42);
+
+    // The owned path the decorator uses deserializes the same bytes.
+    let loaded = repo.load(id()).await.unwrap();
+    assert_eq!(loaded.state(), &state(&[40, 2]));
+}
+
+#[tokio::test]
+async fn materialized_root_saves_on_top_of_a_current_snapshot() {
+    let events = Store::new(InMemoryStore::new());
+    let bytes: Bytestore = Arc::new(InMemorySnapshotStore::new());
+    let repo = snapshotting(&events, &bytes);
+    let mut root = repo.load(id()).await.unwrap();
+    repo.save(&mut root, &Events::<_, 0>::new(LedgerEvent::Credited(10)))
+        .await
+        .unwrap();
+
+    let reader = ArchivedSnapshotStore::new(Arc::clone(&bytes));
+    let snapshot = reader
+        .hydrate_archived::<LedgerState>(&id(), SV1)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut materialized = snapshot.into_root::<Ledger>(id()).unwrap();
+    repo.save(
+        &mut materialized,
+        &Events::<_, 0>::new(LedgerEvent::Credited(5)),
+    )
+    .await
+    .unwrap();
+
+    let loaded = repo.load(id()).await.unwrap();
+    assert_eq!(loaded.state().balance, 15);
+    assert_eq!(loaded.version(), Some(version(2)));
+}
+
+#[tokio::test]
+async fn stale_snapshot_surfaces_as_a_conflict_not_a_lost_write() {
+    let events = Store::new(InMemoryStore::new());
+    let bytes: Bytestore = Arc::new(InMemorySnapshotStore::new());
+    let plain = events.repository::<Ledger>().build();
+    let repo = snapshotting(&events, &bytes);
+
+    let mut root = repo.load(id()).await.unwrap();
+    repo.save(&mut root, &Events::<_, 0>::new(LedgerEvent::Credited(1)))
+        .await
+        .unwrap();
+    // Appended without a snapshot: the archived one is now a version behind.
+    plain
+        .save(&mut root, &Events::<_, 0>::new(LedgerEvent::Credited(1)))
+        .await
+        .unwrap();
+
+    let reader = ArchivedSnapshotStore::new(Arc::clone(&bytes));
+    let snapshot = reader
+        .hydrate_archived::<LedgerState>(&id(), SV1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(snapshot.version(), version(1));
+    let mut stale = snapshot.into_root::<Ledger>(id()).unwrap();
+
+    let err = plain
+        .save(&mut stale, &Events::<_, 0>::new(LedgerEvent::Credited(100)))
+        .await
+        .unwrap_err();
+    assert!(err.is_conflict());
+    assert_eq!(repo.load(id()).await.unwrap().state().balance, 2);
+}
@@ -0,0 +1,353 @@
+//! Integration tests for `DecodePolicy`: an older reader loading a stream
+//! written by a newer service that added an event type.
+
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+#![allow(
+    clippy::unnecessary_wraps,
+    reason = "plain-function upcasters keep Result<_, E> so they can be passed to load_with"
+)]
+
+use std::convert::Infallible;
+use std::fmt;
+
+use nexus::*;
+use nexus_store::testing::InMemoryStore;
+use nexus_store::upcasting::EventMorsel;
+use nexus_store::{Decode, DecodePolicy, Decoded, Encode, PersistedEnvelope, Repository, Store};
+
+// -- Shared id / error --
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct TodoId(String);
+impl fmt::Display for TodoId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+impl AsRef<[u8]> for TodoId {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+impl Id for TodoId {
+    const BYTE_LEN: usize = 0;
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("todo error")]
+struct TodoError;
+
+// -- Newer writer: knows `Archived` --
+
+#[derive(Debug, Clone, PartialEq)]
+enum NewEvent {
+    Created(String),
+    Archived,
+}
+impl Message for NewEvent {}
+impl DomainEvent for NewEvent {
+    const SCHEMA_VERSIONS: &'static [(&'static str, Version)] = &[
+        ("Created", Version::INITIAL),
+        ("Archived", Version::INITIAL),
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Created(_) => "Created",
+            Self::Archived => "Archived",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct NewState;
+impl AggregateState for NewState {
+    type Event = NewEvent;
+    fn initial() -> Self {
+        Self
+    }
+    fn apply(self, _: &NewEvent) -> Self {
+        self
+    }
+}
+
+struct NewAggregate;
+impl Aggregate for NewAggregate {
+    type State = NewState;
+    type Error = TodoError;
+    type Id = TodoId;
+}
+
+// -- Older reader: only knows `Created` --
+
+#[derive(Debug, Clone, PartialEq)]
+enum OldEvent {
+    Created(String),
+}
+impl Message for OldEvent {}
+impl DomainEvent for OldEvent {
+    const SCHEMA_VERSIONS: &'static [(&'static str, Version)] = &[("Created", Version::INITIAL)];
+
+    fn name(&self) -> &'static str {
+        "Created"
+    }
+}
+
+#[derive(Debug, Default)]
+struct OldState {
+    titles: Vec<String>,
+    unknown: Vec<(u64, String, Vec<u8>)>,
+}
+impl AggregateState for OldState {
+    type Event = OldEvent;
+    fn initial() -> Self {
+        Self::default()
+    }
+    fn apply(mut self, event: &OldEvent) -> Self {
+        let OldEvent::Created(title) = event;
+        self.titles.push(title.clone());
+        self
+    }
+    fn apply_unknown(mut self, event: &UnknownEvent<'_>) -> Self {
+        self.unknown.push((
+            event.version().as_u64(),
+            event.event_type().to_owned(),
+            event.payload().to_vec(),
+        ));
+        self
+    }
+}
+
+struct OldAggregate;
+impl Aggregate for OldAggregate {
+    type State = OldState;
+    type Error = TodoError;
+    type Id = TodoId;
+}
+
+// -- Codec keyed by event type --
+
+struct TestCodec;
+
+fn invalid(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+impl Encode<NewEvent> for TestCodec {
+    type Error = std::io::Error;
+
+    fn encode(&self, event: &NewEvent) -> Result<bytes::Bytes, Self::Error> {
+        match event {
+            NewEvent::Created(t) => Ok(bytes::Bytes::from(t.clone().into_bytes())),
+            NewEvent::Archived => Ok(bytes::Bytes::from_static(b"archived")),
+        }
+    }
+}
+
+impl Decode<NewEvent> for TestCodec {
+    type Output<'a> = NewEvent;
+    type Error = std::io::Error;
+
+    fn decode<'a>(&'a self, env: &'a PersistedEnvelope) -> Result<NewEvent, Self::Error> {
+        match env.event_type() {
+            "Created" => Ok(NewEvent::Created(
+                String::from_utf8_lossy(env.payload()).into_owned(),
+            )),
+            "Archived" => Ok(NewEvent::Archived),
+            other => Err(invalid(format!("unknown event type: {other}"))),
+        }
+    }
+}
+
+impl Encode<OldEvent> for TestCodec {
+    type Error = std::io::Error;
+
+    fn encode(&self, event: &OldEvent) -> Result<bytes::Bytes, Self::Error> {
+        let OldEvent::Created(t) = event;
+        Ok(bytes::Bytes::from(t.clone().into_bytes()))
+    }
+}
+
+impl Decode<OldEvent> for TestCodec {
+    type Output<'a> = OldEvent;
+    type Error = std::io::Error;
+
+    fn decode<'a>(&'a self, env: &'a PersistedEnvelope) -> Result<OldEvent, Self::Error> {
+        match env.event_type() {
+            "Created" => Ok(OldEvent::Created(
+                String::from_utf8_lossy(env.payload()).into_owned(),
+            )),
+            other => Err(invalid(format!("unknown event type: {other}"))),
+        }
+    }
+}
+
+/// Write `Created("a"), Archived, Created("b")` as the newer service.
+async fn seed(store: &Store<InMemoryStore>) -> TodoId {
+    let id = TodoId("todo-1".into());
+    let writer = store.repository::<NewAggregate>().codec(TestCodec).build();
+    let mut root = AggregateRoot::<NewAggregate>::new(id.clone());
+    let events: Events<NewEvent, 3> = events![
+        NewEvent::Created("a".into()),
+        NewEvent::Archived,
+        NewEvent::Created("b".into())
+    ];
+    writer.save(&mut root, &events).await.unwrap();
+    id
+}
+
+// -- Repository path --
+
+#[tokio::test]
+async fn strict_policy_fails_on_unknown_event_type() {
+    let store = Store::new(InMemoryStore::new());
+    let id = seed(&store).await;
+
+    let reader = store.repository::<OldAggregate>().codec(TestCodec).build();
+    assert_eq!(reader.decode_policy(), DecodePolicy::Strict);
+    let err = reader.load(id).await.unwrap_err();
+    assert!(matches!(err, nexus_store::StoreError::Decode(_)));
+}
+
+#[tokio::test]
+async fn skip_unknown_advances_version_without_applying() {
+    let store = Store::new(InMemoryStore::new());
+    let id = seed(&store).await;
+
+    let reader = store
+        .repository::<OldAggregate>()
+        .codec(TestCodec)
+        .decode_policy(DecodePolicy::SkipUnknown)
+        .unwrap()
+        .build();
+    let mut root = reader.load(id).await.unwrap();
+    assert_eq!(root.state().titles, ["a", "b"]);
+    assert!(root.state().unknown.is_empty());
+    assert_eq!(root.version(), Version::new(3));
+
+    // The version counts the skipped event, so the next save appends at 4
+    // instead of conflicting.
+    let events: Events<OldEvent, 1> = events![OldEvent::Created("c".into())];
+    reader.save(&mut root, &events).await.unwrap();
+    assert_eq!(root.version(), Version::new(4));
+}
+
+#[tokio::test]
+async fn capture_unknown_hands_raw_event_to_state() {
+    let store = Store::new(InMemoryStore::new());
+    let id = seed(&store).await;
+
+    let reader = store
+        .repository::<OldAggregate>()
+        .codec(TestCodec)
+        .decode_policy(DecodePolicy::CaptureUnknown)
+        .unwrap()
+        .build();
+    let root = reader.load(id).await.unwrap();
+    assert_eq!(root.state().titles, ["a", "b"]);
+    assert_eq!(
+        root.state().unknown,
+        [(2, "Archived".to_owned(), b"archived".to_vec())]
+    );
+    assert_eq!(root.version(), Version::new(3));
+}
+
+#[tokio::test]
+async fn load_with_applies_policy_after_upcasting() {
+    fn identity(morsel: EventMorsel<'_>) -> Result<EventMorsel<'_>, Infallible> {
+        Ok(morsel)
+    }
+
+    let store = Store::new(InMemoryStore::new());
+    let id = seed(&store).await;
+
+    let reader = store
+        .repository::<OldAggregate>()
+        .codec(TestCodec)
+        .decode_policy(DecodePolicy::CaptureUnknown)
+        .unwrap()
+        .build();
+    let root = reader.load_with(id, identity).await.unwrap();
+    assert_eq!(root.state().titles, ["a", "b"]);
+    assert_eq!(root.state().unknown.len(), 1);
+    assert_eq!(root.version(), Version::new(3));
+}
+
+// -- Subscription consumers --
+
+#[test]
+fn decode_reports_skipped_and_unknown_without_calling_the_codec() {
+    let archived = PersistedEnvelope::for_decode("Archived", b"archived").unwrap();
+    let created = PersistedEnvelope::for_decode("Created", b"a").unwrap();
+
+    let strict = DecodePolicy::Strict.decode::<OldEvent, _>(&TestCodec, &archived);
+    assert!(strict.is_err());
+    assert!(matches!(
+        DecodePolicy::SkipUnknown.decode::<OldEvent, _>(&TestCodec, &archived),
+        Ok(Decoded::Skipped)
+    ));
+    assert!(matches!(
+        DecodePolicy::CaptureUnknown.decode::<OldEvent, _>(&TestCodec, &archived),
+        Ok(Decoded::Unknown)
+    ));
+    assert!(matches!(
+        DecodePolicy::SkipUnknown.decode::<OldEvent, _>(&TestCodec, &created),
+        Ok(Decoded::Event(OldEvent::Created(ref t))) if t == "a"
+    ));
+}
+
+#[derive(Debug)]
+struct Undeclared;
+impl Message for Undeclared {}
+impl DomainEvent for Undeclared {
+    fn name(&self) -> &'static str {
+        "Undeclared"
+    }
+}
+
+#[test]
+fn event_types_without_declared_names_are_all_known() {
+    assert!(Undeclared::is_known("Anything"));
+    assert!(OldEvent::is_known("Created"));
+    assert!(!OldEvent::is_known("Archived"));
+}
+
+#[test]
+fn non_strict_policies_are_rejected_without_declared_names() {
+    assert_eq!(
+        DecodePolicy::Strict.check::<Undeclared>(),
+        Ok(DecodePolicy::Strict)
+    );
+    for policy in [DecodePolicy::SkipUnknown, DecodePolicy::CaptureUnknown] {
+        let err = policy.check::<Undeclared>().unwrap_err();
+        assert_eq!(err.policy, policy);
+        assert!(err.event_type.ends_with("Undeclared"));
+        assert_eq!(policy.check::<OldEvent>(), Ok(policy));
+    }
+}
+
+#[test]
+fn builder_rejects_non_strict_policy_without_declared_names() {
+    #[derive(Debug, Default)]
+    struct UndeclaredState;
+    impl AggregateState for UndeclaredState {
+        type Event = Undeclared;
+        fn initial() -> Self {
+            Self
+        }
+        fn apply(self, _: &Undeclared) -> Self {
+            self
+        }
+    }
+    struct UndeclaredAggregate;
+    impl Aggregate for UndeclaredAggregate {
+        type State = UndeclaredState;
+        type Error = TodoError;
+        type Id = TodoId;
+    }
+
+    let store = Store::new(InMemoryStore::new());
+    let builder = store.repository::<UndeclaredAggregate>();
+    assert!(builder.decode_policy(DecodePolicy::SkipUnknown).is_err());
+}
@@ -11,11 +11,11 @@ use std::convert::Infallible;
 use std::fmt;
 
 use nexus::*;
-use nexus_store::Repository;
 use nexus_store::Store;
 use nexus_store::testing::InMemoryStore;
 use nexus_store::upcasting::EventMorsel;
 use nexus_store::{Decode, Encode};
+use nexus_store::{Repository, TimeTravel};
 
 // -- Test domain --
 
@@ -290,6 +290,144 @@ async fn load_with_transform_transforms_events() {
     assert_eq!(loaded.version(), Some(Version::new(1).unwrap()));
 }
 
+// =============================================================================
+// Declared schema versions
+// =============================================================================
+
+/// `TodoEvent` with `Created` declared at schema v2 — the shape a
+/// `#[schema_version = 2]` variant attribute derives.
+#[derive(Debug, Clone, PartialEq)]
+struct VersionedTodoEvent(TodoEvent);
+impl Message for VersionedTodoEvent {}
+impl DomainEvent for VersionedTodoEvent {
+    const SCHEMA_VERSIONS: &'static [(&'static str, Version)] = &[
+        ("Created", Version::new(2).unwrap()),
+        ("Done", Version::INITIAL),
+    ];
+
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+struct VersionedTodoState(TodoState);
+impl AggregateState for VersionedTodoState {
+    type Event = VersionedTodoEvent;
+    fn initial() -> Self {
+        Self::default()
+    }
+    fn apply(self, event: &VersionedTodoEvent) -> Self {
+        Self(self.0.apply(&event.0))
+    }
+}
+
+struct VersionedTodoAggregate;
+impl Aggregate for VersionedTodoAggregate {
+    type State = VersionedTodoState;
+    type Error = TodoError;
+    type Id = TodoId;
+}
+
+struct VersionedTestCodec;
+
+impl Encode<VersionedTodoEvent> for VersionedTestCodec {
+    type Error = std::io::Error;
+
+    fn encode(&self, event: &VersionedTodoEvent) -> Result<bytes::Bytes, Self::Error> {
+        TestCodec.encode(&event.0)
+    }
+}
+
+impl Decode<VersionedTodoEvent> for VersionedTestCodec {
+    type Output<'a> = VersionedTodoEvent;
+    type Error = std::io::Error;
+
+    fn decode<'a>(
+        &'a self,
+        env: &'a nexus_store::PersistedEnvelope,
+    ) -> Result<VersionedTodoEvent, Self::Error> {
+        TestCodec.decode(env).map(VersionedTodoEvent)
+    }
+}
+
+#[tokio::test]
+async fn save_stamps_declared_schema_version() {
+    use futures::TryStreamExt;
+    use nexus_store::StreamKey;
+    use nexus_store::store::RawEventStore;
+
+    let store = Store::new(InMemoryStore::new());
+    let es = store
+        .repository::<VersionedTodoAggregate>()
+        .codec(VersionedTestCodec)
+        .build();
+
+    let mut agg = AggregateRoot::<VersionedTodoAggregate>::new(TodoId("todo-1".into()));
+    es.save(
+        &mut agg,
+        &save_events(&[
+            VersionedTodoEvent(TodoEvent::Created("Task".into())),
+            VersionedTodoEvent(TodoEvent::Done),
+        ]),
+    )
+    .await
+    .unwrap();
+
+    let stored: Vec<_> = store
+        .raw()
+        .read_stream(&StreamKey::from_slice(b"todo-1"), Version::INITIAL)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let stamped: Vec<_> = stored
+        .iter()
+        .map(|env| (env.event_type().to_owned(), env.schema_version()))
+        .collect();
+    assert_eq!(
+        stamped,
+        vec![("Created".to_owned(), 2), ("Done".to_owned(), 1)]
+    );
+}
+
+#[test]
+fn build_accepts_transforms_ending_at_declared_version() {
+    let store = Store::new(InMemoryStore::new());
+    // Created's chain ends at v2 (declared); Done has no chain.
+    let built = store
+        .repository::<VersionedTodoAggregate>()
+        .codec(VersionedTestCodec)
+        .transforms(v1_to_v2_current_version)
+        .build();
+    assert!(built.is_ok());
+}
+
+#[test]
+fn build_rejects_transforms_ending_elsewhere() {
+    let store = Store::new(InMemoryStore::new());
+    // Done is declared at v1, but its chain migrates stored events to v3.
+    let err = store
+        .repository::<VersionedTodoAggregate>()
+        .codec(VersionedTestCodec)
+        .transforms(|event_type: &str| match event_type {
+            "Done" => Some(Version::new(3).unwrap()),
+            _ => None,
+        })
+        .build()
+        .err()
+        .expect("chain ending at v3 must be rejected for Done (declared v1)");
+    assert_eq!(
+        err,
+        nexus_store::SchemaVersionMismatch {
+            event_type: "Done",
+            declared: Version::INITIAL,
+            chain_end: Version::new(3).unwrap(),
+        }
+    );
+}
+
 #[tokio::test]
 async fn event_store_with_no_transforms_is_zero_sized_chain() {
     assert_eq!(std::mem::size_of::<()>(), 0);
@@ -297,6 +435,88 @@ async fn event_store_with_no_transforms_is_zero_sized_chain() {
     let _es = store.repository::<TodoAggregate>().codec(TestCodec).build();
 }
 
+// =============================================================================
+// Time travel
+// =============================================================================
+
+#[tokio::test]
+async fn load_at_stops_replay_at_the_version() {
+    let store = Store::new(InMemoryStore::new());
+    let es = store.repository().codec(TestCodec).build();
+
+    let mut agg = AggregateRoot::<TodoAggregate>::new(TodoId("todo-1".into()));
+    let events = [TodoEvent::Created("Buy milk".into()), TodoEvent::Done];
+    es.save(&mut agg, &save_events(&events)).await.unwrap();
+
+    let past = es
+        .load_at(TodoId("todo-1".into()), Version::INITIAL)
+        .await
+        .unwrap();
+    assert_eq!(past.state().title, "Buy milk");
+    assert!(!past.state().done);
+    assert_eq!(past.version(), Some(Version::INITIAL));
+
+    // Past the end of the stream: the latest state, at the stream's version.
+    let beyond = es
+        .load_at(TodoId("todo-1".into()), Version::new(9).unwrap())
+        .await
+        .unwrap();
+    assert!(beyond.state().done);
+    assert_eq!(beyond.version(), Some(Version::new(2).unwrap()));
+}
+
+#[tokio::test]
+async fn load_as_of_stops_at_the_first_later_event() {
+    use nexus_store::RawEventStore;
+    use nexus_store::envelope::pending_envelope;
+
+    let store = Store::new(InMemoryStore::new());
+    let es = store.repository::<TodoAggregate>().codec(TestCodec).build();
+
+    // Timestamps as little-endian u64 metadata; the second event has none.
+    let stamped = |version: u64, payload: &'static str, at: Option<u64>| {
+        let envelope = pending_envelope(Version::new(version).unwrap())
+            .event_type(if payload == "done" { "Done" } else { "Created" })
+            .payload(payload.as_bytes().to_vec())
+            .unwrap();
+        match at {
+            Some(millis) => envelope
+                .with_metadata(millis.to_le_bytes().to_vec())
+                .unwrap(),
+            None => envelope.build(),
+        }
+    };
+    let envelopes = [
+        stamped(1, "created:first", Some(100)),
+        stamped(2, "created:second", None),
+        stamped(3, "created:third", Some(300)),
+        stamped(4, "done", Some(400)),
+    ];
+    store
+        .raw()
+        .append(
+            &nexus_store::StreamKey::from_slice(b"todo-1"),
+            None,
+            &envelopes,
+        )
+        .await
+        .unwrap();
+
+    let timestamp = |metadata: &[u8]| metadata.try_into().ok().map(u64::from_le_bytes);
+    let past = es
+        .load_as_of(TodoId("todo-1".into()), 250, timestamp)
+        .await
+        .unwrap();
+    assert_eq!(past.state().title, "second");
+    assert_eq!(past.version(), Some(Version::new(2).unwrap()));
+
+    let before_all = es
+        .load_as_of(TodoId("todo-1".into()), 50, timestamp)
+        .await
+        .unwrap();
+    assert_eq!(before_all.version(), None);
+}
+
 // ─── #207 test helper ──────────────────────────────────────────────────────
 // `Repository::save` takes `&Events<E, N>` (non-empty, compile-time capacity).
 // These tests build batches from runtime-length slices/proptest vectors, so we
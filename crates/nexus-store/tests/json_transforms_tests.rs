@@ -0,0 +1,195 @@
+//! Tests for the declarative `json_transforms` payload edits.
+
+#![cfg(feature = "json")]
+#![allow(clippy::unwrap_used, reason = "tests")]
+#![allow(clippy::expect_used, reason = "tests")]
+
+use nexus::Version;
+use nexus_store::json_transforms::{self, JsonObject, JsonTransformError, Value};
+use nexus_store::upcasting::EventMorsel;
+use serde_json::json;
+
+fn object(value: &Value) -> JsonObject {
+    value.as_object().cloned().expect("test value is an object")
+}
+
+// -- rename_field --
+
+#[test]
+fn rename_field_moves_the_value() {
+    let mut obj = object(&json!({ "customer": "c-1", "total": 3 }));
+    json_transforms::rename_field(&mut obj, "customer", "customer_id").unwrap();
+    assert_eq!(
+        Value::Object(obj),
+        json!({ "customer_id": "c-1", "total": 3 })
+    );
+}
+
+#[test]
+fn rename_field_absent_source_is_noop() {
+    let mut obj = object(&json!({ "total": 3 }));
+    json_transforms::rename_field(&mut obj, "customer", "customer_id").unwrap();
+    assert_eq!(Value::Object(obj), json!({ "total": 3 }));
+}
+
+#[test]
+fn rename_field_refuses_to_overwrite() {
+    let mut obj = object(&json!({ "a": 1, "b": 2 }));
+    let err = json_transforms::rename_field(&mut obj, "a", "b").unwrap_err();
+    assert!(matches!(err, JsonTransformError::FieldExists { ref field } if field == "b"));
+    assert_eq!(Value::Object(obj), json!({ "a": 1, "b": 2 }));
+}
+
+#[test]
+fn rename_field_onto_itself_is_noop() {
+    let mut obj = object(&json!({ "a": 1 }));
+    json_transforms::rename_field(&mut obj, "a", "a").unwrap();
+    assert_eq!(Value::Object(obj), json!({ "a": 1 }));
+}
+
+// -- add_default / remove_field --
+
+#[test]
+fn add_default_only_fills_absent_fields() {
+    let mut obj = object(&json!({ "currency": "USD" }));
+    json_transforms::add_default(&mut obj, "currency", json!("EUR"));
+    json_transforms::add_default(&mut obj, "tags", json!([]));
+    assert_eq!(Value::Object(obj), json!({ "currency": "USD", "tags": [] }));
+}
+
+#[test]
+fn remove_field_drops_present_and_ignores_absent() {
+    let mut obj = object(&json!({ "a": 1, "b": 2 }));
+    json_transforms::remove_field(&mut obj, "a");
+    json_transforms::remove_field(&mut obj, "missing");
+    assert_eq!(Value::Object(obj), json!({ "b": 2 }));
+}
+
+// -- move_into --
+
+#[test]
+fn move_into_creates_the_nested_object() {
+    let mut obj = object(&json!({ "street": "Main", "city": "Oslo", "id": 7 }));
+    json_transforms::move_into(&mut obj, &["street", "city", "zip"], "address").unwrap();
+    assert_eq!(
+        Value::Object(obj),
+        json!({ "id": 7, "address": { "street": "Main", "city": "Oslo" } })
+    );
+}
+
+#[test]
+fn move_into_merges_into_an_existing_object() {
+    let mut obj = object(&json!({ "city": "Oslo", "address": { "street": "Main" } }));
+    json_transforms::move_into(&mut obj, &["city"], "address").unwrap();
+    assert_eq!(
+        Value::Object(obj),
+        json!({ "address": { "street": "Main", "city": "Oslo" } })
+    );
+}
+
+#[test]
+fn move_into_with_nothing_to_move_creates_nothing() {
+    let mut obj = object(&json!({ "id": 7 }));
+    json_transforms::move_into(&mut obj, &["street"], "address").unwrap();
+    assert_eq!(Value::Object(obj), json!({ "id": 7 }));
+}
+
+#[test]
+fn move_into_rejects_non_object_target_untouched() {
+    let mut obj = object(&json!({ "city": "Oslo", "address": "Main St" }));
+    let err = json_transforms::move_into(&mut obj, &["city"], "address").unwrap_err();
+    assert!(
+        matches!(err, JsonTransformError::FieldNotAnObject { ref field } if field == "address")
+    );
+    assert_eq!(
+        Value::Object(obj),
+        json!({ "city": "Oslo", "address": "Main St" })
+    );
+}
+
+#[test]
+fn move_into_rejects_nested_collision_untouched() {
+    let mut obj = object(&json!({ "city": "Oslo", "address": { "city": "Bergen" } }));
+    let err = json_transforms::move_into(&mut obj, &["city"], "address").unwrap_err();
+    assert!(
+        matches!(err, JsonTransformError::FieldExists { ref field } if field == "address.city")
+    );
+    assert_eq!(
+        Value::Object(obj),
+        json!({ "city": "Oslo", "address": { "city": "Bergen" } })
+    );
+}
+
+// -- map_value --
+
+#[test]
+fn map_value_rewrites_present_field() {
+    let mut obj = object(&json!({ "status": "open" }));
+    json_transforms::map_value(&mut obj, "status", |v| {
+        Ok::<_, JsonTransformError>(json!(v.as_str().unwrap().to_uppercase()))
+    })
+    .unwrap();
+    assert_eq!(Value::Object(obj), json!({ "status": "OPEN" }));
+}
+
+#[test]
+fn map_value_skips_absent_field_and_propagates_errors() {
+    let mut obj = object(&json!({ "status": "open" }));
+    json_transforms::map_value(&mut obj, "missing", |_| Err("never called")).unwrap();
+    let err = json_transforms::map_value(&mut obj, "status", |_| Err("bad status")).unwrap_err();
+    assert_eq!(err, "bad status");
+}
+
+// -- edit_payload / edit / rename_event_type --
+
+#[test]
+fn edit_payload_composes_edits_over_one_parse() {
+    let out = json_transforms::edit_payload::<JsonTransformError, _>(
+        br#"{"name":"Ada","street":"Main"}"#,
+        |obj| {
+            json_transforms::rename_field(obj, "name", "full_name")?;
+            json_transforms::move_into(obj, &["street"], "address")?;
+            json_transforms::add_default(obj, "tier", json!("basic"));
+            Ok(())
+        },
+    )
+    .unwrap();
+    let value: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(
+        value,
+        json!({ "full_name": "Ada", "address": { "street": "Main" }, "tier": "basic" })
+    );
+}
+
+#[test]
+fn edit_payload_rejects_invalid_json_and_non_objects() {
+    let invalid = json_transforms::edit_payload::<JsonTransformError, _>(b"{not json", |_| Ok(()))
+        .unwrap_err();
+    assert!(matches!(invalid, JsonTransformError::Json(_)));
+
+    let array =
+        json_transforms::edit_payload::<JsonTransformError, _>(b"[1,2]", |_| Ok(())).unwrap_err();
+    assert!(matches!(array, JsonTransformError::NotAnObject));
+}
+
+#[test]
+fn edit_keeps_morsel_metadata() {
+    let v2 = Version::new(2).unwrap();
+    let morsel = EventMorsel::borrowed("OrderCreated", v2, br#"{"a":1}"#);
+    let edited = json_transforms::edit::<JsonTransformError, _>(morsel, |obj| {
+        json_transforms::rename_field(obj, "a", "b")
+    })
+    .unwrap();
+    assert_eq!(edited.event_type(), "OrderCreated");
+    assert_eq!(edited.schema_version(), v2);
+    assert_eq!(edited.payload(), br#"{"b":1}"#);
+}
+
+#[test]
+fn rename_event_type_leaves_payload_borrowed() {
+    let morsel = EventMorsel::borrowed("OrderCancelled", Version::INITIAL, b"{}");
+    let renamed = json_transforms::rename_event_type(morsel, "OrderVoided");
+    assert_eq!(renamed.event_type(), "OrderVoided");
+    assert_eq!(renamed.payload(), b"{}");
+    assert!(renamed.is_borrowed());
+}
@@ -768,7 +768,865 @@ async fn defensive_after_event_types_empty_names_no_trigger() {
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
-// 4. Linearizability/Isolation Tests
+// 4. Snapshot Schema Migration Tests
+// ═══════════════════════════════════════════════════════════════════════════
+
+mod migration {
+    use super::*;
+    use nexus_store::state::{CodecSnapshotStore, CodecSnapshotStoreError};
+    use nexus_store::upcasting::{BoxError, SnapshotTransform, SnapshotUpcasters};
+
+    /// v1 state was `{"count": n}`; v2 renamed it to `value`.
+    fn count_to_value(state: &[u8]) -> Result<Vec<u8>, BoxError> {
+        let mut object: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(state)?;
+        let count = object.remove("count").ok_or("missing `count`")?;
+        object.insert("value".into(), count);
+        Ok(serde_json::to_vec(&object)?)
+    }
+
+    fn always_fails(_: &[u8]) -> Result<Vec<u8>, BoxError> {
+        Err("corrupt v1 snapshot".into())
+    }
+
+    const UPCASTERS: SnapshotUpcasters = SnapshotUpcasters::new(&[SnapshotTransform::new(
+        "count_to_value",
+        NonZeroU32::MIN,
+        NonZeroU32::new(2).unwrap(),
+        count_to_value,
+    )]);
+
+    const FAILING: SnapshotUpcasters = SnapshotUpcasters::new(&[SnapshotTransform::new(
+        "always_fails",
+        NonZeroU32::MIN,
+        NonZeroU32::new(2).unwrap(),
+        always_fails,
+    )]);
+
+    /// Three `Incremented` events, plus a schema-1 snapshot at version 3
+    /// claiming a count of 100 — a loaded value of 100 proves the snapshot
+    /// was used, 3 proves a full replay.
+    async fn seeded() -> (
+        Store<InMemoryStore>,
+        InMemorySnapshotStore<Vec<u8>, Version>,
+    ) {
+        let store = Store::new(InMemoryStore::new());
+        let repo = store.repository().build();
+        let id = CounterId("counter-1".into());
+        let mut agg: AggregateRoot<CounterAggregate> = repo.load(id.clone()).await.unwrap();
+        repo.save(
+            &mut agg,
+            &save_events(&[
+                CounterEvent::Incremented,
+                CounterEvent::Incremented,
+                CounterEvent::Incremented,
+            ]),
+        )
+        .await
+        .unwrap();
+
+        let byte_store = InMemorySnapshotStore::<Vec<u8>, Version>::new();
+        byte_store
+            .commit(
+                &id,
+                SV1,
+                Version::new(3).unwrap(),
+                &br#"{"count":100}"#.to_vec(),
+            )
+            .await
+            .unwrap();
+        (store, byte_store)
+    }
+
+    #[tokio::test]
+    async fn old_schema_snapshot_is_migrated_on_load() {
+        let (store, byte_store) = seeded().await;
+        let snapshots = CodecSnapshotStore::new(&byte_store, nexus_store::JsonCodec::default())
+            .with_upcasters(UPCASTERS);
+        let repo = Snapshotting::new(
+            store.repository().build(),
+            snapshots,
+            EveryNEvents(NonZeroU64::new(100).unwrap()),
+            sv2(),
+            false,
+        );
+
+        let loaded: AggregateRoot<CounterAggregate> =
+            repo.load(CounterId("counter-1".into())).await.unwrap();
+        assert_eq!(loaded.state().value, 100);
+        assert_eq!(loaded.version(), Some(Version::new(3).unwrap()));
+
+        // Not rewritten by default: the stored snapshot is still schema 1.
+        let id = CounterId("counter-1".into());
+        assert!(byte_store.hydrate(&id, sv2()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn old_schema_snapshot_without_upcasters_falls_back_to_full_replay() {
+        let (store, byte_store) = seeded().await;
+        let snapshots = CodecSnapshotStore::new(&byte_store, nexus_store::JsonCodec::default());
+        let repo = Snapshotting::new(
+            store.repository().build(),
+            snapshots,
+            EveryNEvents(NonZeroU64::new(100).unwrap()),
+            sv2(),
+            false,
+        );
+
+        let loaded: AggregateRoot<CounterAggregate> =
+            repo.load(CounterId("counter-1".into())).await.unwrap();
+        assert_eq!(loaded.state().value, 3);
+    }
+
+    #[tokio::test]
+    async fn rewrite_migrated_commits_the_new_schema_at_the_stored_position() {
+        let (store, byte_store) = seeded().await;
+        let repo = store
+            .repository()
+            .snapshot_store(&byte_store)
+            .snapshot_schema_version(sv2())
+            .snapshot_upcasters(UPCASTERS)
+            .rewrite_migrated_snapshots(true)
+            .build();
+
+        let id = CounterId("counter-1".into());
+        let loaded: AggregateRoot<CounterAggregate> = repo.load(id.clone()).await.unwrap();
+        assert_eq!(loaded.state().value, 100);
+
+        let (version, bytes) = byte_store.hydrate(&id, sv2()).await.unwrap().unwrap();
+        assert_eq!(version, Version::new(3).unwrap());
+        assert_eq!(bytes, br#"{"value":100}"#);
+    }
+
+    /// Reads through to the seeded store; refuses every commit.
+    struct ReadOnly<'a>(&'a InMemorySnapshotStore<Vec<u8>, Version>);
+
+    impl SnapshotStore<Vec<u8>, Version> for ReadOnly<'_> {
+        type Error = std::io::Error;
+
+        async fn hydrate(
+            &self,
+            id: &impl nexus::Id,
+            schema_version: NonZeroU32,
+        ) -> Result<Option<(Version, Vec<u8>)>, Self::Error> {
+            Ok(self.0.hydrate(id, schema_version).await.unwrap())
+        }
+
+        async fn hydrate_with_schema(
+            &self,
+            id: &impl nexus::Id,
+        ) -> Result<Option<(NonZeroU32, Version, Vec<u8>)>, Self::Error> {
+            Ok(self.0.hydrate_with_schema(id).await.unwrap())
+        }
+
+        async fn commit(
+            &self,
+            _id: &impl nexus::Id,
+            _schema_version: NonZeroU32,
+            _position: Version,
+            _state: &Vec<u8>,
+        ) -> Result<(), Self::Error> {
+            Err(std::io::Error::other("read-only snapshot store"))
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_rewrite_is_counted_and_the_migrated_state_still_loads() {
+        let (store, byte_store) = seeded().await;
+        let repo = store
+            .repository()
+            .snapshot_store(ReadOnly(&byte_store))
+            .snapshot_schema_version(sv2())
+            .snapshot_upcasters(UPCASTERS)
+            .rewrite_migrated_snapshots(true)
+            .build();
+        assert_eq!(repo.snapshot_store().rewrite_failures(), 0);
+
+        let id = CounterId("counter-1".into());
+        let loaded: AggregateRoot<CounterAggregate> = repo.load(id.clone()).await.unwrap();
+        assert_eq!(loaded.state().value, 100);
+        assert_eq!(repo.snapshot_store().rewrite_failures(), 1);
+        assert!(byte_store.hydrate(&id, sv2()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn failing_upcaster_surfaces_from_store_and_falls_back_in_repository() {
+        let (store, byte_store) = seeded().await;
+        let snapshots = CodecSnapshotStore::new(&byte_store, nexus_store::JsonCodec::default())
+            .with_upcasters(FAILING);
+        let id = CounterId("counter-1".into());
+
+        let err = SnapshotStore::<CounterState, Version>::hydrate(&snapshots, &id, sv2())
+            .await
+            .unwrap_err();
+        let CodecSnapshotStoreError::Upcast(err) = err else {
+            panic!("expected an upcast error, got {err:?}");
+        };
+        assert_eq!(err.from, SV1);
+        assert_eq!(err.to, sv2());
+
+        let repo = Snapshotting::new(
+            store.repository().build(),
+            snapshots,
+            EveryNEvents(NonZeroU64::new(100).unwrap()),
+            sv2(),
+            false,
+        );
+        let loaded: AggregateRoot<CounterAggregate> = repo.load(id).await.unwrap();
+        assert_eq!(loaded.state().value, 3);
+    }
+
+    #[test]
+    fn migrate_has_no_path_down_or_below_the_chain() {
+        let state = br#"{"count":1}"#;
+        let sv3 = NonZeroU32::new(3).unwrap();
+        assert!(UPCASTERS.migrate(sv2(), SV1, state).unwrap().is_none());
+        assert!(UPCASTERS.migrate(SV1, sv3, state).unwrap().is_none());
+        assert_eq!(
+            UPCASTERS.migrate(SV1, sv2(), state).unwrap().unwrap(),
+            br#"{"value":1}"#,
+        );
+        assert_eq!(UPCASTERS.current_version(), Some(sv2()));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 5. Failure Policy and Stats Tests
+// ═══════════════════════════════════════════════════════════════════════════
+
+mod policy {
+    use super::*;
+    use std::sync::{Arc, mpsc};
+
+    use nexus_store::{
+        SnapshotFailure, SnapshotOperation, SnapshotPolicy, SnapshotStats, SnapshottingError,
+    };
+
+    /// Fails every hydrate and commit.
+    struct BrokenStore;
+    impl SnapshotStore<CounterState, Version> for BrokenStore {
+        type Error = std::io::Error;
+        async fn hydrate(
+            &self,
+            _id: &impl nexus::Id,
+            _schema_version: NonZeroU32,
+        ) -> Result<Option<(Version, CounterState)>, Self::Error> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "disk on fire",
+            ))
+        }
+        async fn commit(
+            &self,
+            _id: &impl nexus::Id,
+            _schema_version: NonZeroU32,
+            _position: Version,
+            _state: &CounterState,
+        ) -> Result<(), Self::Error> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "disk on fire",
+            ))
+        }
+    }
+
+    fn broken_repo(
+        store: &Store<InMemoryStore>,
+        policy: SnapshotPolicy,
+    ) -> impl Repository<
+        CounterAggregate,
+        Error = SnapshottingError<impl std::error::Error, std::io::Error>,
+    > {
+        Snapshotting::new(
+            store.repository().build(),
+            BrokenStore,
+            EveryNEvents(NonZeroU64::new(1).unwrap()),
+            SV1,
+            false,
+        )
+        .with_policy(policy)
+    }
+
+    #[tokio::test]
+    async fn stats_count_hits_misses_and_stale_schema_versions() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        let repo = Snapshotting::new(
+            store.repository().build(),
+            &snapshots,
+            EveryNEvents(NonZeroU64::new(1).unwrap()),
+            SV1,
+            false,
+        );
+        let id = CounterId("counter-1".into());
+
+        let mut agg: AggregateRoot<CounterAggregate> = repo.load(id.clone()).await.unwrap();
+        repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+            .await
+            .unwrap();
+        let _: AggregateRoot<CounterAggregate> = repo.load(id.clone()).await.unwrap();
+        assert_eq!(
+            repo.stats(),
+            SnapshotStats {
+                hits: 1,
+                misses: 1,
+                historical_hits: 0,
+                historical_misses: 0,
+                stale: 0,
+                failures: 0,
+                dropped: 0,
+            }
+        );
+
+        // Same snapshots, newer schema: the schema-1 snapshot is stale.
+        let repo_v2 = Snapshotting::new(
+            store.repository().build(),
+            &snapshots,
+            EveryNEvents(NonZeroU64::new(100).unwrap()),
+            sv2(),
+            false,
+        );
+        let loaded: AggregateRoot<CounterAggregate> = repo_v2.load(id).await.unwrap();
+        assert_eq!(loaded.state().value, 1);
+        assert_eq!(repo_v2.stats().stale, 1);
+        assert_eq!(repo_v2.stats().misses, 0);
+    }
+
+    #[tokio::test]
+    async fn ignore_policy_counts_failures_and_falls_back() {
+        let store = Store::new(InMemoryStore::new());
+        let repo = Snapshotting::new(
+            store.repository().build(),
+            BrokenStore,
+            EveryNEvents(NonZeroU64::new(1).unwrap()),
+            SV1,
+            false,
+        );
+        let id = CounterId("counter-1".into());
+
+        let mut agg: AggregateRoot<CounterAggregate> = repo.load(id.clone()).await.unwrap();
+        repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+            .await
+            .unwrap();
+        assert_eq!(repo.stats().failures, 2);
+        assert_eq!(repo.stats().hits, 0);
+    }
+
+    #[tokio::test]
+    async fn observe_policy_reports_operation_id_and_version() {
+        let (sink, seen) = mpsc::channel();
+        let observer = move |failure: &SnapshotFailure<'_>| {
+            sink.send((
+                failure.operation,
+                failure.stream_id.to_string(),
+                failure.version,
+                failure.error.to_string(),
+            ))
+            .unwrap();
+        };
+        let store = Store::new(InMemoryStore::new());
+        let repo = broken_repo(&store, SnapshotPolicy::Observe(Arc::new(observer)));
+        let id = CounterId("counter-1".into());
+
+        let mut agg = repo.load(id.clone()).await.unwrap();
+        repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+            .await
+            .unwrap();
+        assert_eq!(agg.state().value, 1);
+
+        let seen: Vec<_> = seen.try_iter().collect();
+        assert_eq!(
+            seen,
+            [
+                (
+                    SnapshotOperation::Load,
+                    "counter-1".to_owned(),
+                    None,
+                    "disk on fire".to_owned(),
+                ),
+                (
+                    SnapshotOperation::Save,
+                    "counter-1".to_owned(),
+                    Some(Version::new(1).unwrap()),
+                    "disk on fire".to_owned(),
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn propagate_policy_returns_typed_snapshot_errors() {
+        let store = Store::new(InMemoryStore::new());
+        let id = CounterId("counter-1".into());
+
+        // Seed one event through the plain facade so the aggregate exists.
+        let plain = store.repository().build();
+        let mut agg: AggregateRoot<CounterAggregate> = plain.load(id.clone()).await.unwrap();
+        plain
+            .save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+            .await
+            .unwrap();
+
+        let repo = broken_repo(&store, SnapshotPolicy::Propagate);
+        let err = repo.load(id.clone()).await.unwrap_err();
+        let SnapshottingError::Snapshot {
+            operation,
+            stream_id,
+            version,
+            ..
+        } = err
+        else {
+            panic!("expected a snapshot error, got {err:?}");
+        };
+        assert_eq!(operation, SnapshotOperation::Load);
+        assert_eq!(stream_id.to_string(), "counter-1");
+        assert_eq!(version, None);
+
+        // The save error reports only the snapshot: the event is committed.
+        let err = repo
+            .save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SnapshottingError::Snapshot {
+                operation: SnapshotOperation::Save,
+                version: Some(v),
+                ..
+            } if v == Version::new(2).unwrap()
+        ));
+        let reloaded: AggregateRoot<CounterAggregate> = plain.load(id).await.unwrap();
+        assert_eq!(reloaded.state().value, 2);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 6. Background Snapshotting Tests
+// ═══════════════════════════════════════════════════════════════════════════
+
+mod background {
+    use super::*;
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+
+    fn every_save() -> EveryNEvents {
+        EveryNEvents(NonZeroU64::new(1).unwrap())
+    }
+
+    #[tokio::test]
+    async fn queued_requests_coalesce_to_the_newest_version() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        let (repo, worker) = Snapshotting::new(
+            store.repository::<CounterAggregate>().build(),
+            &snapshots,
+            every_save(),
+            SV1,
+            false,
+        )
+        .background::<CounterAggregate>(NonZeroUsize::new(4).unwrap());
+        let id = CounterId("counter-1".into());
+
+        let mut agg = repo.load(id.clone()).await.unwrap();
+        for _ in 0..3 {
+            repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+                .await
+                .unwrap();
+        }
+        // Nothing committed on the save path.
+        assert!(snapshots.hydrate(&id, SV1).await.unwrap().is_none());
+        assert_eq!(repo.stats().dropped, 2);
+
+        drop(repo);
+        worker.run().await;
+        let (version, state) = snapshots.hydrate(&id, SV1).await.unwrap().unwrap();
+        assert_eq!(version, Version::new(3).unwrap());
+        assert_eq!(state.value, 3);
+    }
+
+    #[tokio::test]
+    async fn full_queue_drops_new_aggregates() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        let (repo, worker) = Snapshotting::new(
+            store.repository::<CounterAggregate>().build(),
+            &snapshots,
+            every_save(),
+            SV1,
+            false,
+        )
+        .background::<CounterAggregate>(NonZeroUsize::MIN);
+        let first = CounterId("counter-1".into());
+        let second = CounterId("counter-2".into());
+
+        for id in [&first, &second] {
+            let mut agg = repo.load(id.clone()).await.unwrap();
+            repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+                .await
+                .unwrap();
+        }
+        assert_eq!(repo.stats().dropped, 1);
+
+        drop(repo);
+        worker.run().await;
+        assert!(snapshots.hydrate(&first, SV1).await.unwrap().is_some());
+        assert!(snapshots.hydrate(&second, SV1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn spawned_worker_flushes_pending_snapshots_on_shutdown() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = Arc::new(InMemorySnapshotStore::<Vec<u8>, Version>::new());
+        let (repo, worker) = store
+            .repository()
+            .snapshot_store(Arc::clone(&snapshots))
+            .snapshot_trigger(every_save())
+            .background(NonZeroUsize::new(16).unwrap())
+            .build();
+        let worker = tokio::spawn(worker.run());
+
+        let ids: Vec<_> = (0..8).map(|i| CounterId(format!("counter-{i}"))).collect();
+        for id in &ids {
+            let mut agg: AggregateRoot<CounterAggregate> = repo.load(id.clone()).await.unwrap();
+            repo.save(
+                &mut agg,
+                &save_events(&[CounterEvent::Incremented, CounterEvent::Incremented]),
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(repo.stats().dropped, 0);
+
+        drop(repo);
+        worker.await.unwrap();
+        for id in &ids {
+            let (version, bytes) = snapshots.hydrate(id, SV1).await.unwrap().unwrap();
+            assert_eq!(version, Version::new(2).unwrap());
+            assert_eq!(bytes, br#"{"value":2}"#);
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 7. Snapshot History Tests
+// ═══════════════════════════════════════════════════════════════════════════
+
+mod history {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    use nexus_store::state::CodecSnapshotStore;
+    use nexus_store::{SnapshotRetention, TimeTravel};
+
+    fn v(n: u64) -> Version {
+        Version::new(n).unwrap()
+    }
+
+    fn state(value: i64) -> CounterState {
+        CounterState { value }
+    }
+
+    #[tokio::test]
+    async fn retained_snapshots_serve_loads_at_or_before_a_version() {
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new()
+            .with_retention(SnapshotRetention::keep_last(NonZeroUsize::new(2).unwrap()));
+        let id = CounterId("counter-1".into());
+        for n in 1..=3 {
+            snapshots
+                .commit(&id, SV1, v(n * 10), &state(n.cast_signed()))
+                .await
+                .unwrap();
+        }
+
+        let at = |n| snapshots.hydrate_at_or_before(&id, SV1, v(n));
+        assert_eq!(at(35).await.unwrap(), Some((v(30), state(3))));
+        assert_eq!(at(29).await.unwrap(), Some((v(20), state(2))));
+        // The snapshot at 10 fell out of the two retained.
+        assert_eq!(at(19).await.unwrap(), None);
+        assert_eq!(
+            snapshots.hydrate(&id, SV1).await.unwrap(),
+            Some((v(30), state(3)))
+        );
+    }
+
+    #[tokio::test]
+    async fn latest_retention_only_answers_from_the_latest() {
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        let id = CounterId("counter-1".into());
+        snapshots.commit(&id, SV1, v(10), &state(1)).await.unwrap();
+        snapshots.commit(&id, SV1, v(20), &state(2)).await.unwrap();
+
+        assert_eq!(
+            snapshots
+                .hydrate_at_or_before(&id, SV1, v(20))
+                .await
+                .unwrap(),
+            Some((v(20), state(2)))
+        );
+        assert_eq!(
+            snapshots
+                .hydrate_at_or_before(&id, SV1, v(19))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn load_at_starts_from_the_nearest_retained_snapshot() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new()
+            .with_retention(SnapshotRetention::keep_last(NonZeroUsize::new(3).unwrap()));
+        let repo = Snapshotting::new(
+            store.repository::<CounterAggregate>().build(),
+            &snapshots,
+            EveryNEvents(NonZeroU64::new(100).unwrap()),
+            SV1,
+            false,
+        );
+        let id = CounterId("counter-1".into());
+        let mut agg = repo.load(id.clone()).await.unwrap();
+        for _ in 0..6 {
+            repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+                .await
+                .unwrap();
+        }
+        // Marked states: a loaded 401 proves the v4 snapshot was used.
+        snapshots.commit(&id, SV1, v(4), &state(400)).await.unwrap();
+        snapshots.commit(&id, SV1, v(6), &state(600)).await.unwrap();
+
+        let past = repo.load_at(id.clone(), v(5)).await.unwrap();
+        assert_eq!(past.state().value, 401);
+        assert_eq!(past.version(), Some(v(5)));
+
+        // Nothing retained at or before v3: full replay.
+        let early = repo.load_at(id.clone(), v(3)).await.unwrap();
+        assert_eq!(early.state().value, 3);
+        assert_eq!(early.version(), Some(v(3)));
+
+        // Historical loads are counted apart from the initial load's miss.
+        let stats = repo.stats();
+        assert_eq!((stats.hits, stats.misses), (0, 1));
+        assert_eq!((stats.historical_hits, stats.historical_misses), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn codec_store_decodes_retained_snapshots() {
+        let byte_store = InMemorySnapshotStore::<Vec<u8>, Version>::new()
+            .with_retention(SnapshotRetention::keep_last(NonZeroUsize::new(3).unwrap()));
+        let snapshots = CodecSnapshotStore::new(&byte_store, nexus_store::JsonCodec::default());
+        let id = CounterId("counter-1".into());
+        snapshots.commit(&id, SV1, v(5), &state(5)).await.unwrap();
+        snapshots.commit(&id, SV1, v(9), &state(9)).await.unwrap();
+
+        assert_eq!(
+            snapshots
+                .hydrate_at_or_before(&id, SV1, v(8))
+                .await
+                .unwrap(),
+            Some((v(5), state(5)))
+        );
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 8. Replay-Cost Trigger Tests
+// ═══════════════════════════════════════════════════════════════════════════
+
+mod replay_cost {
+    use super::*;
+    use std::time::Duration;
+
+    use nexus_store::state::ReplayCost;
+
+    fn v(n: u64) -> Version {
+        Version::new(n).unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_slow_enough_replay_snapshots_on_load() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        // Any replay counts as slow.
+        let repo = Snapshotting::new(
+            store.repository::<CounterAggregate>().build(),
+            &snapshots,
+            ReplayCost(Duration::ZERO),
+            SV1,
+            false,
+        );
+        let id = CounterId("counter-1".into());
+        let mut agg = repo.load(id.clone()).await.unwrap();
+        for _ in 0..3 {
+            repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+                .await
+                .unwrap();
+        }
+        // Saves carry no replay time: nothing yet.
+        assert!(snapshots.hydrate(&id, SV1).await.unwrap().is_none());
+
+        repo.load(id.clone()).await.unwrap();
+        let (at, state) = snapshots.hydrate(&id, SV1).await.unwrap().unwrap();
+        assert_eq!((at, state.value), (v(3), 3));
+
+        // Nothing past the snapshot to replay: nothing to snapshot.
+        snapshots
+            .commit(&id, SV1, v(3), &CounterState { value: 30 })
+            .await
+            .unwrap();
+        repo.load(id.clone()).await.unwrap();
+        let (_, state) = snapshots.hydrate(&id, SV1).await.unwrap().unwrap();
+        assert_eq!(state.value, 30);
+
+        // A partial replay past the snapshot is timed too.
+        repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+            .await
+            .unwrap();
+        repo.load(id.clone()).await.unwrap();
+        let (at, state) = snapshots.hydrate(&id, SV1).await.unwrap().unwrap();
+        assert_eq!((at, state.value), (v(4), 31));
+    }
+
+    #[tokio::test]
+    async fn a_fast_replay_does_not_snapshot() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        let repo = Snapshotting::new(
+            store.repository::<CounterAggregate>().build(),
+            &snapshots,
+            ReplayCost(Duration::from_hours(1)).or(EveryNEvents(NonZeroU64::new(100).unwrap())),
+            SV1,
+            false,
+        );
+        let id = CounterId("counter-1".into());
+        let mut agg = repo.load(id.clone()).await.unwrap();
+        repo.save(&mut agg, &save_events(&[CounterEvent::Incremented]))
+            .await
+            .unwrap();
+
+        repo.load(id.clone()).await.unwrap();
+        assert!(snapshots.hydrate(&id, SV1).await.unwrap().is_none());
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 9. Persist-History Trigger Tests
+// ═══════════════════════════════════════════════════════════════════════════
+
+mod persist_history {
+    use super::*;
+    use std::time::Duration;
+
+    use nexus_store::state::{MinInterval, PayloadBytes};
+
+    /// `CounterEvent::Incremented` as JSON: `"Incremented"`.
+    const INCREMENTED_BYTES: u64 = 13;
+
+    fn v(n: u64) -> Version {
+        Version::new(n).unwrap()
+    }
+
+    async fn snapshot_version(
+        snapshots: &InMemorySnapshotStore<CounterState, Version>,
+        id: &CounterId,
+    ) -> Option<Version> {
+        snapshots.hydrate(id, SV1).await.unwrap().map(|(at, _)| at)
+    }
+
+    #[tokio::test]
+    async fn min_interval_throttles_every_n_events() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        let interval = Duration::from_millis(200);
+        let repo = Snapshotting::new(
+            store.repository::<CounterAggregate>().build(),
+            &snapshots,
+            EveryNEvents(NonZeroU64::new(2).unwrap()).and(MinInterval(interval)),
+            SV1,
+            false,
+        );
+        let id = CounterId("counter-1".into());
+        let mut agg = repo.load(id.clone()).await.unwrap();
+        let two = || save_events(&[CounterEvent::Incremented, CounterEvent::Incremented]);
+
+        // Never snapshotted: the interval counts as passed.
+        repo.save(&mut agg, &two()).await.unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, Some(v(2)));
+
+        // A bucket is crossed again, but too soon after the last snapshot.
+        repo.save(&mut agg, &two()).await.unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, Some(v(2)));
+
+        tokio::time::sleep(interval).await;
+        repo.save(&mut agg, &two()).await.unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, Some(v(6)));
+    }
+
+    #[tokio::test]
+    async fn payload_bytes_snapshots_once_enough_bytes_are_saved() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        let repo = Snapshotting::new(
+            store.repository::<CounterAggregate>().build(),
+            &snapshots,
+            PayloadBytes(NonZeroU64::new(3 * INCREMENTED_BYTES).unwrap()),
+            SV1,
+            false,
+        );
+        let id = CounterId("counter-1".into());
+        let mut agg = repo.load(id.clone()).await.unwrap();
+        let one = || save_events(&[CounterEvent::Incremented]);
+
+        repo.save(&mut agg, &one()).await.unwrap();
+        repo.save(&mut agg, &one()).await.unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, None);
+
+        repo.save(&mut agg, &one()).await.unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, Some(v(3)));
+
+        // The count restarts at the snapshot.
+        repo.save(
+            &mut agg,
+            &save_events(&[CounterEvent::Incremented, CounterEvent::Incremented]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, Some(v(3)));
+        repo.save(&mut agg, &one()).await.unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, Some(v(6)));
+    }
+
+    #[tokio::test]
+    async fn min_interval_keeps_throttling_an_aggregate_pushed_out_of_the_history() {
+        let store = Store::new(InMemoryStore::new());
+        let snapshots = InMemorySnapshotStore::<CounterState, Version>::new();
+        let repo = Snapshotting::new(
+            store.repository::<CounterAggregate>().build(),
+            &snapshots,
+            EveryNEvents(NonZeroU64::new(2).unwrap()).and(MinInterval(Duration::from_hours(1))),
+            SV1,
+            false,
+        );
+        let two = || save_events(&[CounterEvent::Incremented, CounterEvent::Incremented]);
+        let id = CounterId("counter-1".into());
+        let mut agg = repo.load(id.clone()).await.unwrap();
+        repo.save(&mut agg, &two()).await.unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, Some(v(2)));
+
+        // More aggregates than the history holds: counter-1, the stalest,
+        // is forgotten.
+        for n in 0..5000 {
+            let mut other = repo.load(CounterId(format!("other-{n}"))).await.unwrap();
+            repo.save(&mut other, &two()).await.unwrap();
+        }
+
+        // Forgotten is not "never snapshotted": the hour has not passed.
+        repo.save(&mut agg, &two()).await.unwrap();
+        assert_eq!(snapshot_version(&snapshots, &id).await, Some(v(2)));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 10. Linearizability/Isolation Tests
 // ═══════════════════════════════════════════════════════════════════════════
 
 #[tokio::test]
@@ -21,7 +21,10 @@ use std::num::{NonZeroU32, NonZeroU64};
 use nexus::{Id, Version};
 
 const SV1: NonZeroU32 = NonZeroU32::MIN;
-use nexus_store::state::{AfterEventTypes, EveryNEvents, PersistTrigger, SnapshotStore};
+use nexus_store::state::{
+    AfterEventTypes, EveryNEvents, MinInterval, Not, PayloadBytes, PersistContext, PersistTrigger,
+    ReplayCost, SnapshotStore,
+};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct TestId(String);
@@ -128,6 +131,104 @@ fn after_event_types_does_not_trigger_on_empty_events() {
     assert!(!trigger.should_persist(None, Version::new(5).unwrap(), std::iter::empty::<&str>()));
 }
 
+// ── Context triggers and combinators ────────────────────────────────
+
+const fn v(n: u64) -> Version {
+    Version::new(n).unwrap()
+}
+
+#[test]
+fn context_triggers_read_their_context_field() {
+    let second = std::time::Duration::from_secs(1);
+    let none = PersistContext::new();
+    let names = || std::iter::empty::<&str>();
+
+    // No known last persist: the interval counts as passed.
+    assert!(MinInterval(second).should_persist_with(&none, None, v(1), names()));
+    let soon = PersistContext::new().with_elapsed(second / 2);
+    assert!(!MinInterval(second).should_persist_with(&soon, None, v(1), names()));
+
+    let bytes = PayloadBytes(NonZeroU64::new(1024).unwrap());
+    assert!(!bytes.should_persist_with(&none, None, v(1), names()));
+    assert!(!bytes.should_persist_with(
+        &PersistContext::new().with_bytes(1023),
+        None,
+        v(1),
+        names()
+    ));
+    assert!(bytes.should_persist_with(
+        &PersistContext::new().with_bytes(1024),
+        None,
+        v(1),
+        names()
+    ));
+
+    let cost = ReplayCost(second);
+    assert!(!cost.should_persist(None, v(1), names()));
+    let slow = PersistContext::new().with_replay_time(second * 2);
+    assert!(cost.should_persist_with(&slow, None, v(1), names()));
+    assert!(cost.uses_replay_time());
+    assert!(!EveryNEvents(NonZeroU64::MIN).uses_replay_time());
+}
+
+#[test]
+fn combinators_compose_and_share_event_names() {
+    let every_10 = EveryNEvents(NonZeroU64::new(10).unwrap());
+    let closed = || AfterEventTypes::new(&["Closed"]);
+
+    let any = every_10.or(closed());
+    assert!(any.should_persist(Some(v(3)), v(4), ["Closed"].into_iter()));
+    assert!(any.should_persist(Some(v(9)), v(10), ["Opened"].into_iter()));
+    assert!(!any.should_persist(Some(v(3)), v(4), ["Opened"].into_iter()));
+
+    // Both sides see the names, not just the first.
+    let all = closed().and(AfterEventTypes::new(&["Archived"]));
+    assert!(all.should_persist(None, v(2), ["Closed", "Archived"].into_iter()));
+    assert!(!all.should_persist(None, v(2), ["Closed"].into_iter()));
+
+    let unless_closed = every_10.and(Not(closed()));
+    assert!(unless_closed.should_persist(Some(v(9)), v(10), ["Opened"].into_iter()));
+    assert!(!unless_closed.should_persist(Some(v(9)), v(10), ["Closed"].into_iter()));
+}
+
+#[test]
+fn combinators_hand_event_names_to_the_operand_that_reads_them() {
+    let every_10 = EveryNEvents(NonZeroU64::new(10).unwrap());
+    let closed = AfterEventTypes::new(&["Closed"]);
+    assert!(closed.uses_event_names());
+    assert!(!every_10.uses_event_names());
+    assert!(
+        !every_10
+            .and(MinInterval(std::time::Duration::ZERO))
+            .uses_event_names()
+    );
+    assert!(every_10.or(Not(closed.clone())).uses_event_names());
+
+    // Whichever side reads the names gets them, first or second.
+    let first = closed.clone().or(every_10);
+    let second = every_10.or(closed);
+    assert!(first.should_persist(Some(v(3)), v(4), ["Closed"].into_iter()));
+    assert!(second.should_persist(Some(v(3)), v(4), ["Closed"].into_iter()));
+    assert!(!second.should_persist(Some(v(3)), v(4), ["Opened"].into_iter()));
+}
+
+#[test]
+fn combinators_forward_the_context() {
+    let second = std::time::Duration::from_secs(1);
+    let throttled = EveryNEvents(NonZeroU64::MIN).and(MinInterval(second));
+    let names = || std::iter::empty::<&str>();
+
+    let soon = PersistContext::new().with_elapsed(second / 2);
+    let late = PersistContext::new().with_elapsed(second);
+    assert!(!throttled.should_persist_with(&soon, Some(v(1)), v(2), names()));
+    assert!(throttled.should_persist_with(&late, Some(v(1)), v(2), names()));
+
+    let either = AfterEventTypes::new(&["Closed"]).or(ReplayCost(second));
+    assert!(either.uses_replay_time());
+    let slow = PersistContext::new().with_replay_time(second);
+    assert!(either.should_persist_with(&slow, None, v(2), names()));
+}
+
 // ── InMemorySnapshotStore ────────────────────────────────────────
 
 #[cfg(feature = "testing")]
@@ -270,6 +371,7 @@ mod builder_tests {
             .snapshot_trigger(AfterEventTypes::new(&["Done"]))
             .snapshot_schema_version(NonZeroU32::new(2).unwrap())
             .snapshot_on_read(true)
+            .snapshot_policy(nexus_store::SnapshotPolicy::Propagate)
             .build();
     }
 }
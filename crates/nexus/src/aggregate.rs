@@ -1,5 +1,5 @@
 use crate::error::KernelError;
-use crate::event::DomainEvent;
+use crate::event::{DomainEvent, UnknownEvent};
 use crate::events::Events;
 use crate::id::Id;
 use crate::version::Version;
@@ -77,6 +77,21 @@ pub trait AggregateState: Send + Sync + Debug + 'static {
     /// panics, it indicates a bug in the state machine.
     #[must_use]
     fn apply(self, event: &Self::Event) -> Self;
+
+    /// Fold an event whose type [`Event`](Self::Event) does not know.
+    ///
+    /// Only called by a repository configured to capture unknown events
+    /// (a stream written by a newer service). The default ignores it, so
+    /// the event still advances the version but leaves the state as is.
+    /// Override to record or interpret the raw event.
+    #[must_use]
+    fn apply_unknown(self, event: &UnknownEvent<'_>) -> Self
+    where
+        Self: Sized,
+    {
+        let _ = event;
+        self
+    }
 }
 
 /// Type-level specification binding state, error, and ID types.
@@ -332,11 +347,55 @@ impl<A: Aggregate> AggregateRoot<A> {
     ///
     /// Panics if `MAX_REHYDRATION_EVENTS` exceeds `u64::MAX` on the
     /// current platform (impossible on 32/64-bit systems).
+    pub fn replay(&mut self, version: Version, event: &EventOf<A>) -> Result<(), KernelError> {
+        self.check_replay_version(version)?;
+        // Move the state out and fold it through `apply` — no clone. If
+        // `apply` panics (a state-machine bug), the state is left at
+        // `initial()` (valid, never partially mutated).
+        let taken = mem::replace(&mut self.state, A::State::initial());
+        self.state = taken.apply(event);
+        self.version = Some(version);
+        Ok(())
+    }
+
+    /// Replay an event the aggregate's event type does not know, folding
+    /// it through [`AggregateState::apply_unknown`].
+    ///
+    /// Same version checks as [`replay`](Self::replay), at
+    /// [`event.version()`](UnknownEvent::version).
+    ///
+    /// # Errors
+    ///
+    /// As [`replay`](Self::replay).
+    pub fn replay_unknown(&mut self, event: &UnknownEvent<'_>) -> Result<(), KernelError> {
+        self.check_replay_version(event.version())?;
+        let taken = mem::replace(&mut self.state, A::State::initial());
+        self.state = taken.apply_unknown(event);
+        self.version = Some(event.version());
+        Ok(())
+    }
+
+    /// Advance past a persisted event without applying it.
+    ///
+    /// For events a repository was configured to skip (unknown types
+    /// written by a newer service). Same version checks as
+    /// [`replay`](Self::replay); the state is untouched.
+    ///
+    /// # Errors
+    ///
+    /// As [`replay`](Self::replay).
+    pub fn replay_skipped(&mut self, version: Version) -> Result<(), KernelError> {
+        self.check_replay_version(version)?;
+        self.version = Some(version);
+        Ok(())
+    }
+
+    /// `version` must be the next one and within the rehydration limit.
     #[allow(
         clippy::expect_used,
         reason = "u64::try_from(usize) cannot fail on supported platforms (max 64-bit)"
     )]
-    pub fn replay(&mut self, version: Version, event: &EventOf<A>) -> Result<(), KernelError> {
+    fn check_replay_version(&self, version: Version) -> Result<(), KernelError> {
         let expected = match self.version {
             None => Version::INITIAL,
             Some(v) => v.next().ok_or(KernelError::VersionOverflow)?,
@@ -355,12 +414,6 @@ impl<A: Aggregate> AggregateRoot<A> {
                 max: A::MAX_REHYDRATION_EVENTS.get(),
             });
         }
-        // Move the state out and fold it through `apply` — no clone. If
-        // `apply` panics (a state-machine bug), the state is left at
-        // `initial()` (valid, never partially mutated).
-        let taken = mem::replace(&mut self.state, A::State::initial());
-        self.state = taken.apply(event);
-        self.version = Some(version);
         Ok(())
     }
 
@@ -433,7 +486,7 @@ impl<A: Aggregate> AggregateRoot<A> {
 )]
 mod purist_dispatch_tests {
     use super::{Aggregate, AggregateRoot, AggregateState, Handle};
-    use crate::event::DomainEvent;
+    use crate::event::{DomainEvent, UnknownEvent};
     use crate::events;
     use crate::events::Events;
     use crate::id::Id;
@@ -685,4 +738,32 @@ mod purist_dispatch_tests {
         assert_eq!(root.state().total, 15);
         assert_eq!(root.version(), Version::new(2));
     }
+
+    #[test]
+    fn unknown_and_skipped_events_advance_version_only() {
+        // `CtrState` keeps the default `apply_unknown`, which ignores the
+        // event: both paths move the version on and leave the state as is.
+        let mut root = AggregateRoot::<Counter>::new(CtrId::new(7));
+        root.replay(Version::INITIAL, &CtrEvent::Added(10))
+            .expect("replay v1");
+        let v2 = Version::new(2).expect("nonzero");
+        let unknown = UnknownEvent::new(v2, "Reset", Version::INITIAL, b"{}", None);
+        root.replay_unknown(&unknown).expect("replay_unknown v2");
+        root.replay_skipped(Version::new(3).expect("nonzero"))
+            .expect("replay_skipped v3");
+        root.replay(Version::new(4).expect("nonzero"), &CtrEvent::Added(5))
+            .expect("replay v4");
+        assert_eq!(root.state().total, 15);
+        assert_eq!(root.version(), Version::new(4));
+    }
+
+    #[test]
+    fn skipped_event_out_of_order_is_rejected() {
+        let mut root = AggregateRoot::<Counter>::new(CtrId::new(7));
+        let v2 = Version::new(2).expect("nonzero");
+        assert!(root.replay_skipped(v2).is_err());
+        let unknown = UnknownEvent::new(v2, "Reset", Version::INITIAL, b"{}", None);
+        assert!(root.replay_unknown(&unknown).is_err());
+        assert_eq!(root.version(), None);
+    }
 }
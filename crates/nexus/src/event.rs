@@ -1,5 +1,112 @@
 use crate::message::Message;
+use crate::version::Version;
 
 pub trait DomainEvent: Message {
+    /// Every variant's event name paired with its current schema version.
+    ///
+    /// `#[derive(DomainEvent)]` emits one entry per variant (from the
+    /// variant's `#[schema_version = N]` attribute, default 1). Hand-written
+    /// impls may leave the empty default, in which case every variant is at
+    /// [`Version::INITIAL`] and nothing can be checked against upcaster
+    /// chains.
+    const SCHEMA_VERSIONS: &'static [(&'static str, Version)] = &[];
+
     fn name(&self) -> &'static str;
+
+    /// The schema version new events of this variant are written at.
+    ///
+    /// Looks [`name`](Self::name) up in
+    /// [`SCHEMA_VERSIONS`](Self::SCHEMA_VERSIONS), falling back to
+    /// [`Version::INITIAL`] for unlisted names. The derive overrides this
+    /// with a direct `match`.
+    fn schema_version(&self) -> Version {
+        let name = self.name();
+        Self::SCHEMA_VERSIONS
+            .iter()
+            .find(|(variant, _)| *variant == name)
+            .map_or(Version::INITIAL, |&(_, version)| version)
+    }
+
+    /// Whether `name` is one of this type's event names.
+    ///
+    /// Reads [`SCHEMA_VERSIONS`](Self::SCHEMA_VERSIONS). When it is empty
+    /// (a hand-written impl that lists nothing) every name counts as known
+    /// — there is nothing to tell them apart by — so a reader that skips or
+    /// captures unknown events must refuse such a type rather than treat
+    /// every event as known.
+    #[must_use]
+    fn is_known(name: &str) -> bool {
+        Self::SCHEMA_VERSIONS.is_empty()
+            || Self::SCHEMA_VERSIONS
+                .iter()
+                .any(|(variant, _)| *variant == name)
+    }
+}
+
+/// A persisted event whose type the aggregate's event type does not know.
+///
+/// Written by a newer service and read by an older one during a rolling
+/// deploy. A repository configured to capture unknown events hands it to
+/// [`AggregateState::apply_unknown`](crate::AggregateState::apply_unknown)
+/// instead of failing the load. Borrows the raw persisted parts: nothing
+/// has been decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownEvent<'a> {
+    version: Version,
+    event_type: &'a str,
+    schema_version: Version,
+    payload: &'a [u8],
+    metadata: Option<&'a [u8]>,
+}
+
+impl<'a> UnknownEvent<'a> {
+    /// Create from the raw persisted parts.
+    ///
+    /// Public because store crates build it from their envelope type.
+    #[must_use]
+    pub const fn new(
+        version: Version,
+        event_type: &'a str,
+        schema_version: Version,
+        payload: &'a [u8],
+        metadata: Option<&'a [u8]>,
+    ) -> Self {
+        Self {
+            version,
+            event_type,
+            schema_version,
+            payload,
+            metadata,
+        }
+    }
+
+    /// The event's version (sequence number) in the aggregate's history.
+    #[must_use]
+    pub const fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The persisted event type name.
+    #[must_use]
+    pub const fn event_type(&self) -> &'a str {
+        self.event_type
+    }
+
+    /// The schema version the payload was written at.
+    #[must_use]
+    pub const fn schema_version(&self) -> Version {
+        self.schema_version
+    }
+
+    /// The serialized payload bytes.
+    #[must_use]
+    pub const fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// The persisted metadata bytes, if any.
+    #[must_use]
+    pub const fn metadata(&self) -> Option<&'a [u8]> {
+        self.metadata
+    }
 }
@@ -18,7 +18,7 @@ pub use aggregate::{
 };
 pub use error::KernelError;
 pub use error_id::{DEFAULT_ERROR_ID_CAP, ErrorId};
-pub use event::DomainEvent;
+pub use event::{DomainEvent, UnknownEvent};
 pub use events::{Events, EventsIntoIter};
 pub use id::Id;
 pub use message::Message;
@@ -13,10 +13,11 @@
 use std::future::Future;
 use std::iter;
 use std::num::NonZeroU32;
+use std::time::Instant;
 
 use futures::StreamExt;
 use nexus::{DomainEvent, Id, Version};
-use nexus_store::state::{PersistTrigger, SnapshotStore};
+use nexus_store::state::{PersistContext, PersistTrigger, SnapshotStore};
 use nexus_store::store::RawEventStore;
 use nexus_store::wake::WakeSource;
 use nexus_store::{Decode, Projector, Subscription};
@@ -27,8 +28,10 @@ use nexus_store::{Decode, Projector, Subscription};
 ///    `checkpoint` atomically; if nothing is persisted, start from
 ///    `projector.initial()`.
 /// 2. Subscribe from `checkpoint` (the cursor never returns `None`).
-/// 3. For each event: fold via `Projector`; if `PersistTrigger` fires,
-///    `commit` state and position together and advance `checkpoint`.
+/// 3. For each event: fold via `Projector`; if `PersistTrigger` fires —
+///    given the time and payload bytes since the last commit as its
+///    [`PersistContext`] — `commit` state and position together and advance
+///    `checkpoint`.
 /// 4. On shutdown, commit any unpersisted trailing state once.
 ///
 /// # Errors
@@ -72,6 +75,8 @@ where
 
     // 3. Drive until shutdown or stream end.
     let mut pending: Option<Version> = None;
+    let mut since_commit = Instant::now();
+    let mut bytes_since_commit: u64 = 0;
     tokio::pin!(shutdown);
     loop {
         tokio::select! {
@@ -82,10 +87,17 @@ where
                 let version = env.version();
                 let event = event_codec.decode(&env)?;
                 state = projector.apply(state, &event)?;
-                if trigger.should_persist(checkpoint, version, iter::once(event.name())) {
+                let payload_len = u64::try_from(env.payload().len()).unwrap_or(u64::MAX);
+                bytes_since_commit = bytes_since_commit.saturating_add(payload_len);
+                let context = PersistContext::new()
+                    .with_elapsed(since_commit.elapsed())
+                    .with_bytes(bytes_since_commit);
+                if trigger.should_persist_with(&context, checkpoint, version, iter::once(event.name())) {
                     snapshot_store.commit(&id, schema_version, version, &state).await?;
                     checkpoint = Some(version);
                     pending = None;
+                    since_commit = Instant::now();
+                    bytes_since_commit = 0;
                 } else {
                     pending = Some(version);
                 }